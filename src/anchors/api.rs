@@ -1,11 +1,16 @@
-//! Anchor API - list, get operations
+//! Anchor API - list, get, grep operations
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::HashSet;
 use std::path::Path;
+use std::process::Command;
 
-use crate::anchors::parse::{parse_file, Anchor};
+use crate::anchors::parse::{parse_file, Anchor, BEGIN_RE, END_RE};
 use crate::backends::scan::{scan_files, ScanOptions};
-use crate::core::model::ResultSet;
+use crate::cache::reader::get_all_anchors_parsed;
+use crate::core::model::{Range, ResultItem, ResultSet, SourceMode};
+use crate::core::paths::{make_relative, normalize_path};
 use crate::core::render::{RenderConfig, Renderer};
 
 fn file_scan_options() -> ScanOptions {
@@ -16,11 +21,63 @@ fn file_scan_options() -> ScanOptions {
     }
 }
 
+/// Files under `root` that differ from `git_ref` (`git diff --name-only`)
+///
+/// Paths are relative to `root`; changes outside it are ignored.
+pub fn changed_files_since(root: &Path, git_ref: &str) -> Result<HashSet<String>> {
+    // Outside a work tree git diff falls back to --no-index usage errors
+    let in_repo = Command::new("git")
+        .current_dir(root)
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .output()
+        .context("Failed to run git")?;
+    if !in_repo.status.success() {
+        anyhow::bail!("--since requires a git repository: {}", root.display());
+    }
+
+    let output = Command::new("git")
+        .current_dir(root)
+        // Unquoted, NUL-separated names so non-ASCII and odd paths match
+        // verbatim; --end-of-options keeps a ref like "-x" from being an option
+        .args([
+            "-c",
+            "core.quotePath=false",
+            "diff",
+            "--name-only",
+            "-z",
+            "--relative",
+        ])
+        .args(["--end-of-options", git_ref, "--"])
+        .output()
+        .context("Failed to run git")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git diff against '{}' failed: {}", git_ref, stderr.trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|l| !l.is_empty())
+        .map(|l| l.to_string())
+        .collect())
+}
+
 /// List all anchors in the workspace
-/// When brief=true, only emit metadata without content (saves tokens)
-pub fn list_anchors(root: &Path, tag_filter: Option<&str>, brief: bool) -> Result<ResultSet> {
+/// When brief=true, only emit metadata without content (saves tokens).
+/// With `since`, only files changed relative to that git ref are parsed.
+pub fn list_anchors(
+    root: &Path,
+    tag_filter: Option<&str>,
+    since: Option<&str>,
+    brief: bool,
+) -> Result<ResultSet> {
     let mut result_set = ResultSet::new();
 
+    let changed = since
+        .map(|git_ref| changed_files_since(root, git_ref))
+        .transpose()?;
+
     // Scan all files
     let files = scan_files(root, &file_scan_options())?;
 
@@ -33,6 +90,10 @@ pub fn list_anchors(root: &Path, tag_filter: Option<&str>, brief: bool) -> Resul
                 continue;
             }
 
+            if changed.as_ref().is_some_and(|c| !c.contains(path)) {
+                continue;
+            }
+
             let anchors = parse_file(&full_path, path);
 
             for anchor in anchors {
@@ -56,6 +117,19 @@ pub fn list_anchors(root: &Path, tag_filter: Option<&str>, brief: bool) -> Resul
     Ok(result_set)
 }
 
+/// Anchor items of a single file, in the full form cached in anchors.jsonl
+pub fn file_anchor_items(root: &Path, path: &str) -> Vec<ResultItem> {
+    let full_path = root.join(path);
+    if !is_anchor_candidate(&full_path) {
+        return Vec::new();
+    }
+
+    parse_file(&full_path, path)
+        .iter()
+        .map(|anchor| anchor.to_result_item())
+        .collect()
+}
+
 /// Get a specific anchor by ID
 pub fn get_anchor(root: &Path, id: &str, with_neighbors: Option<usize>) -> Result<ResultSet> {
     let mut result_set = ResultSet::new();
@@ -117,6 +191,74 @@ pub fn get_anchor(root: &Path, id: &str, with_neighbors: Option<usize>) -> Resul
     Ok(result_set)
 }
 
+/// Search for a regex only inside anchor bodies
+///
+/// Each matching line becomes a match item at its file line, with the owning
+/// anchor's id, tags and the 1-based line offset within the anchor body in
+/// `data`. A line inside nested anchors is reported once per anchor.
+pub fn grep_anchors(
+    root: &Path,
+    pattern: &str,
+    tag_filter: Option<&str>,
+    scope: Option<&Path>,
+    explain: bool,
+) -> Result<ResultSet> {
+    let regex =
+        Regex::new(pattern).with_context(|| format!("Invalid search pattern: {}", pattern))?;
+    let scope = scope.map(|s| {
+        let relative = if s.is_absolute() {
+            make_relative(s, root).unwrap_or_else(|| normalize_path(s))
+        } else {
+            normalize_path(s)
+        };
+        relative
+            .trim_start_matches("./")
+            .trim_end_matches('/')
+            .to_string()
+    });
+
+    let mut result_set = ResultSet::new();
+
+    for (path, anchor) in get_all_anchors_parsed(root)? {
+        if let Some(scope) = scope.as_deref().filter(|s| !s.is_empty() && *s != ".") {
+            if path != scope && !path.starts_with(&format!("{}/", scope)) {
+                continue;
+            }
+        }
+        if let Some(tag) = tag_filter {
+            if !anchor.tags.iter().any(|t| t == tag) {
+                continue;
+            }
+        }
+
+        let Some(content) = &anchor.content else {
+            continue;
+        };
+
+        for (offset, line) in content.lines().enumerate() {
+            if !regex.is_match(line) {
+                continue;
+            }
+
+            // Body starts on the line after the begin marker
+            let line_num = anchor.range.start + 1 + offset as u32;
+            let mut item =
+                ResultItem::match_result(path.clone(), Range::lines(line_num, line_num), line)
+                    .with_source_mode(SourceMode::Anchor)
+                    .with_data(serde_json::json!({
+                        "id": anchor.id,
+                        "tags": anchor.tags,
+                        "offset": offset + 1,
+                    }));
+            item.explain(explain, format!("matched inside anchor '{}'", anchor.id));
+            result_set.push(item);
+        }
+    }
+
+    result_set.sort();
+    Ok(result_set)
+}
+
 /// Check if a file might contain anchors
 fn is_anchor_candidate(path: &Path) -> bool {
     let text_extensions = [
@@ -132,26 +274,89 @@ fn is_anchor_candidate(path: &Path) -> bool {
 }
 
 /// Run anchor list command
-pub fn run_list(root: &Path, tag: Option<&str>, brief: bool, config: RenderConfig) -> Result<()> {
-    let result_set = list_anchors(root, tag, brief)?;
+pub fn run_list(
+    root: &Path,
+    tag: Option<&str>,
+    since: Option<&str>,
+    brief: bool,
+    config: RenderConfig,
+) -> Result<()> {
+    let result_set = list_anchors(root, tag, since, brief)?;
 
     let renderer = Renderer::with_config(config);
-    println!("{}", renderer.render(&result_set));
+    print!("{}", renderer.render(&result_set));
 
     Ok(())
 }
 
+/// Get every anchor carrying `tag`, with full content, sorted by path then line
+///
+/// Same as `list_anchors` with a tag filter; `anchor get-by-tag` is an alias
+/// of `anchor list --tag`.
+pub fn get_anchors_by_tag(root: &Path, tag: &str) -> Result<ResultSet> {
+    list_anchors(root, Some(tag), None, false)
+}
+
+/// Drop begin/end marker lines from an anchor excerpt, keeping only its content
+///
+/// An anchor's own markers are never part of its excerpt, but the markers of
+/// anchors nested inside it are.
+pub fn strip_marker_lines(excerpt: &str) -> String {
+    excerpt
+        .lines()
+        .filter(|line| !BEGIN_RE.is_match(line) && !END_RE.is_match(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Run anchor get command
 pub fn run_get(
     root: &Path,
     id: &str,
     with_neighbors: Option<usize>,
+    body_only: bool,
     config: RenderConfig,
 ) -> Result<()> {
-    let result_set = get_anchor(root, id, with_neighbors)?;
+    let mut result_set = get_anchor(root, id, with_neighbors)?;
+
+    // Ranges keep covering the full marked region
+    if body_only {
+        for item in &mut result_set.items {
+            if let Some(excerpt) = &item.excerpt {
+                item.excerpt = Some(strip_marker_lines(excerpt));
+            }
+        }
+    }
+
+    let renderer = Renderer::with_config(config);
+    print!("{}", renderer.render(&result_set));
+
+    Ok(())
+}
+
+/// Run anchor get-by-tag command
+pub fn run_get_by_tag(root: &Path, tag: &str, config: RenderConfig) -> Result<()> {
+    let result_set = get_anchors_by_tag(root, tag)?;
 
     let renderer = Renderer::with_config(config);
-    println!("{}", renderer.render(&result_set));
+    print!("{}", renderer.render(&result_set));
+
+    Ok(())
+}
+
+/// Run anchor grep command
+pub fn run_grep(
+    root: &Path,
+    pattern: &str,
+    tag: Option<&str>,
+    scope: Option<&Path>,
+    explain: bool,
+    config: RenderConfig,
+) -> Result<()> {
+    let result_set = grep_anchors(root, pattern, tag, scope, explain)?;
+
+    let renderer = Renderer::with_config(config);
+    print!("{}", renderer.render(&result_set));
 
     Ok(())
 }
@@ -213,7 +418,7 @@ mod tests {
     #[test]
     fn test_list_anchors_empty_dir() {
         let temp = tempfile::tempdir().unwrap();
-        let result = list_anchors(temp.path(), None, false);
+        let result = list_anchors(temp.path(), None, None, false);
         assert!(result.is_ok());
         assert!(result.unwrap().items.is_empty());
     }
@@ -225,7 +430,7 @@ mod tests {
             "# Test\n<!--Q:begin id=test1 tags=a,b v=1-->\nContent\n<!--Q:end id=test1-->\n";
         std::fs::write(temp.path().join("test.md"), content).unwrap();
 
-        let result = list_anchors(temp.path(), None, false).unwrap();
+        let result = list_anchors(temp.path(), None, None, false).unwrap();
         assert_eq!(result.items.len(), 1);
     }
 
@@ -235,10 +440,69 @@ mod tests {
         let content = "<!--Q:begin id=a tags=foo v=1-->\nA\n<!--Q:end id=a-->\n<!--Q:begin id=b tags=bar v=1-->\nB\n<!--Q:end id=b-->\n";
         std::fs::write(temp.path().join("test.md"), content).unwrap();
 
-        let result = list_anchors(temp.path(), Some("foo"), false).unwrap();
+        let result = list_anchors(temp.path(), Some("foo"), None, false).unwrap();
         assert_eq!(result.items.len(), 1);
     }
 
+    #[test]
+    fn test_list_anchors_since() {
+        let temp = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(args)
+                .current_dir(temp.path())
+                .output()
+                .unwrap()
+        };
+        let anchor = |id: &str, tags: &str| {
+            format!(
+                "<!--Q:begin id={} tags={} v=1-->\nx\n<!--Q:end id={}-->\n",
+                id, tags, id
+            )
+        };
+
+        // Not a repository yet: a clear error, not the full listing
+        std::fs::write(temp.path().join("a.md"), anchor("a", "foo")).unwrap();
+        let err = list_anchors(temp.path(), None, Some("HEAD"), false).unwrap_err();
+        assert!(err.to_string().contains("requires a git repository"));
+
+        git(&["init", "-q"]);
+        std::fs::write(temp.path().join("b.md"), anchor("b", "foo")).unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "init"]);
+
+        let b = anchor("b", "foo") + &anchor("b2", "bar");
+        std::fs::write(temp.path().join("b.md"), b).unwrap();
+
+        let ids = |result: ResultSet| -> Vec<String> {
+            result
+                .items
+                .iter()
+                .map(|i| i.data.as_ref().unwrap()["id"].as_str().unwrap().to_string())
+                .collect()
+        };
+        let result = list_anchors(temp.path(), None, Some("HEAD"), true).unwrap();
+        assert_eq!(ids(result), ["b", "b2"]);
+        let result = list_anchors(temp.path(), Some("foo"), Some("HEAD"), true).unwrap();
+        assert_eq!(ids(result), ["b"]);
+
+        // Paths git would quote (non-ASCII, spaces) still match
+        std::fs::write(temp.path().join("文档 c.md"), anchor("c", "foo")).unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "c"]);
+        std::fs::write(
+            temp.path().join("文档 c.md"),
+            anchor("c", "foo") + &anchor("c2", "foo"),
+        )
+        .unwrap();
+        let changed = changed_files_since(temp.path(), "HEAD").unwrap();
+        assert!(changed.contains("文档 c.md"));
+
+        assert!(list_anchors(temp.path(), None, Some("no-such-ref"), true).is_err());
+        assert!(changed_files_since(temp.path(), "--output=x").is_err());
+    }
+
     #[test]
     fn test_list_anchors_brief_mode() {
         let temp = tempfile::tempdir().unwrap();
@@ -247,13 +511,13 @@ mod tests {
         std::fs::write(temp.path().join("test.md"), content).unwrap();
 
         // Brief mode should not have excerpt
-        let result = list_anchors(temp.path(), None, true).unwrap();
+        let result = list_anchors(temp.path(), None, None, true).unwrap();
         assert_eq!(result.items.len(), 1);
         assert!(result.items[0].excerpt.is_none());
         assert!(result.items[0].data.is_some());
 
         // Full mode should have excerpt
-        let result_full = list_anchors(temp.path(), None, false).unwrap();
+        let result_full = list_anchors(temp.path(), None, None, false).unwrap();
         assert_eq!(result_full.items.len(), 1);
         assert!(result_full.items[0].excerpt.is_some());
     }
@@ -268,6 +532,43 @@ mod tests {
         assert!(result.items.is_empty());
     }
 
+    #[test]
+    fn test_get_anchors_by_tag() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp.path().join("b.md"),
+            "<!--Q:begin id=b2 tags=chapter-->\nB2\n<!--Q:end id=b2-->\n\
+<!--Q:begin id=b1 tags=note,chapter-->\nB1\n<!--Q:end id=b1-->\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp.path().join("a.md"),
+            "<!--Q:begin id=a1 tags=chapter-->\nA1\n<!--Q:end id=a1-->\n\
+<!--Q:begin id=a2 tags=note-->\nA2\n<!--Q:end id=a2-->\n",
+        )
+        .unwrap();
+
+        let result = get_anchors_by_tag(temp.path(), "chapter").unwrap();
+        let excerpts: Vec<_> = result
+            .items
+            .iter()
+            .map(|i| i.excerpt.as_deref().unwrap())
+            .collect();
+        assert_eq!(excerpts, ["A1", "B2", "B1"]);
+
+        assert!(get_anchors_by_tag(temp.path(), "missing")
+            .unwrap()
+            .items
+            .is_empty());
+    }
+
+    #[test]
+    fn test_strip_marker_lines() {
+        let excerpt = "one\n<!--Q:begin id=inner tags=a-->\ntwo\n  // Q:end id=inner\nthree";
+        assert_eq!(strip_marker_lines(excerpt), "one\ntwo\nthree");
+        assert_eq!(strip_marker_lines("plain"), "plain");
+    }
+
     #[test]
     fn test_get_anchor_found() {
         let temp = tempfile::tempdir().unwrap();
@@ -299,4 +600,33 @@ mod tests {
         assert!(!is_anchor_candidate(Path::new("test.dll")));
         assert!(!is_anchor_candidate(Path::new("test.so")));
     }
+
+    #[test]
+    fn test_grep_anchors() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(temp.path().join("src")).unwrap();
+        let content = "unsafe outside\n<!--Q:begin id=api.io tags=api v=1-->\nfn read() {}\nunsafe { ptr.read() }\n<!--Q:end id=api.io-->\n<!--Q:begin id=misc tags=util v=1-->\nunsafe { other() }\n<!--Q:end id=misc-->\n";
+        std::fs::write(temp.path().join("src/lib.rs"), content).unwrap();
+
+        let result = grep_anchors(temp.path(), "unsafe", None, None, false).unwrap();
+        assert_eq!(result.len(), 2);
+
+        let result = grep_anchors(temp.path(), "unsafe", Some("api"), None, false).unwrap();
+        assert_eq!(result.len(), 1);
+        let item = &result.items[0];
+        assert_eq!(item.range, Some(Range::lines(4, 4)));
+        let data = item.data.as_ref().unwrap();
+        assert_eq!(data["id"], "api.io");
+        assert_eq!(data["offset"], 2);
+        assert!(data.get("reason").is_none());
+
+        let result = grep_anchors(temp.path(), "unsafe", Some("api"), None, true).unwrap();
+        let data = result.items[0].data.as_ref().unwrap();
+        assert_eq!(data["reason"], "matched inside anchor 'api.io'");
+
+        let result =
+            grep_anchors(temp.path(), "unsafe", None, Some(Path::new("docs")), false).unwrap();
+        assert!(result.is_empty());
+        assert!(grep_anchors(temp.path(), "(", None, None, false).is_err());
+    }
 }
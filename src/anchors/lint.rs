@@ -4,22 +4,32 @@
 //! - begin/end pairing
 //! - Duplicate IDs
 //! - Empty/oversized ranges
-//! - Semantic drift (version unchanged but hash changed significantly)
+//! - Partially overlapping anchors (intersecting but not nested)
+//! - Content changed since marking (body no longer matches the marker's `hash=`)
+//!
+//! Optional checks (id pattern, token budget) and
+//! per-rule severities can be configured with a TOML/JSON rules file.
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::json;
 use std::collections::HashMap;
 use std::path::Path;
 
+use crate::anchors::mark::rewrite_hashes;
 use crate::anchors::parse::{parse_content, Anchor};
 use crate::backends::scan::{scan_files, ScanOptions};
 use crate::core::model::{Confidence, Kind, MiseError, ResultItem, ResultSet, SourceMode};
 use crate::core::render::{RenderConfig, Renderer};
+use crate::core::tokenizer::{count_tokens, TokenModel};
 
 /// Lint issue severity
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LintSeverity {
     Error,
     Warning,
+    Info,
 }
 
 /// A lint issue
@@ -30,6 +40,11 @@ pub struct LintIssue {
     pub message: String,
     pub path: String,
     pub line: Option<u32>,
+    /// Older codes for the same finding, reported after `code` so rules
+    /// written against them keep matching
+    pub aliases: Vec<String>,
+    /// Structured details for the result item's `data`
+    pub data: Option<serde_json::Value>,
 }
 
 impl LintIssue {
@@ -40,6 +55,8 @@ impl LintIssue {
             message: message.to_string(),
             path: path.to_string(),
             line,
+            aliases: Vec::new(),
+            data: None,
         }
     }
 
@@ -50,23 +67,63 @@ impl LintIssue {
             message: message.to_string(),
             path: path.to_string(),
             line,
+            aliases: Vec::new(),
+            data: None,
         }
     }
 
+    /// Create an issue with an explicit severity
+    pub fn with_severity(
+        severity: LintSeverity,
+        code: &str,
+        message: &str,
+        path: &str,
+        line: Option<u32>,
+    ) -> Self {
+        Self {
+            severity,
+            code: code.to_string(),
+            message: message.to_string(),
+            path: path.to_string(),
+            line,
+            aliases: Vec::new(),
+            data: None,
+        }
+    }
+
+    /// Also report the issue under an older code
+    pub fn with_alias(mut self, code: &str) -> Self {
+        self.aliases.push(code.to_string());
+        self
+    }
+
+    pub fn with_data(mut self, data: serde_json::Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
     pub fn to_result_item(&self) -> ResultItem {
         ResultItem {
-            kind: Kind::Error,
+            // Informational findings are not errors
+            kind: match self.severity {
+                LintSeverity::Info => Kind::Anchor,
+                _ => Kind::Error,
+            },
             path: Some(self.path.clone()),
             range: self.line.map(|l| crate::core::model::Range::lines(l, l)),
             excerpt: Some(self.message.clone()),
-            data: None,
+            data: self.data.clone(),
             confidence: match self.severity {
                 LintSeverity::Error => Confidence::High,
                 LintSeverity::Warning => Confidence::Medium,
+                LintSeverity::Info => Confidence::Low,
             },
             source_mode: SourceMode::Anchor,
             meta: Default::default(),
-            errors: vec![MiseError::new(&self.code, &self.message)],
+            errors: std::iter::once(&self.code)
+                .chain(&self.aliases)
+                .map(|code| MiseError::new(code, &self.message))
+                .collect(),
         }
     }
 }
@@ -74,6 +131,150 @@ impl LintIssue {
 /// Maximum recommended anchor size (in lines)
 const MAX_ANCHOR_LINES: u32 = 500;
 
+/// Configured severity of a lint rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleSeverity {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl RuleSeverity {
+    /// Severity of issues produced by this rule, or None when disabled
+    fn issue_severity(self) -> Option<LintSeverity> {
+        match self {
+            RuleSeverity::Off => None,
+            RuleSeverity::Info => Some(LintSeverity::Info),
+            RuleSeverity::Warning => Some(LintSeverity::Warning),
+            RuleSeverity::Error => Some(LintSeverity::Error),
+        }
+    }
+}
+
+/// Setting for a single rule: severity plus rule-specific parameters
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleSetting {
+    pub severity: RuleSeverity,
+    /// `oversized`: maximum content lines
+    #[serde(default)]
+    pub max_lines: Option<u32>,
+    /// `id-pattern`: regex every anchor id must match
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// `token-budget`: maximum content tokens
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
+}
+
+impl RuleSetting {
+    fn new(severity: RuleSeverity) -> Self {
+        Self {
+            severity,
+            max_lines: None,
+            pattern: None,
+            max_tokens: None,
+        }
+    }
+}
+
+/// Lint rule configuration
+///
+/// Rules missing from a rules file keep their defaults, which reproduce the
+/// built-in rule set (id-pattern and token-budget are off).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct LintRules {
+    #[serde(default = "LintRules::default_duplicate_id")]
+    pub duplicate_id: RuleSetting,
+    #[serde(default = "LintRules::default_empty")]
+    pub empty: RuleSetting,
+    #[serde(default = "LintRules::default_oversized")]
+    pub oversized: RuleSetting,
+    #[serde(default = "LintRules::default_overlapping")]
+    pub overlapping: RuleSetting,
+    #[serde(default = "LintRules::default_off")]
+    pub id_pattern: RuleSetting,
+    #[serde(default = "LintRules::default_off")]
+    pub token_budget: RuleSetting,
+    #[serde(default = "LintRules::default_content_changed")]
+    pub content_changed: RuleSetting,
+}
+
+impl Default for LintRules {
+    fn default() -> Self {
+        Self {
+            duplicate_id: Self::default_duplicate_id(),
+            empty: Self::default_empty(),
+            oversized: Self::default_oversized(),
+            overlapping: Self::default_overlapping(),
+            id_pattern: Self::default_off(),
+            token_budget: Self::default_off(),
+            content_changed: Self::default_content_changed(),
+        }
+    }
+}
+
+impl LintRules {
+    fn default_duplicate_id() -> RuleSetting {
+        RuleSetting::new(RuleSeverity::Error)
+    }
+
+    fn default_empty() -> RuleSetting {
+        RuleSetting::new(RuleSeverity::Warning)
+    }
+
+    fn default_oversized() -> RuleSetting {
+        RuleSetting::new(RuleSeverity::Warning)
+    }
+
+    fn default_overlapping() -> RuleSetting {
+        RuleSetting::new(RuleSeverity::Error)
+    }
+
+    fn default_content_changed() -> RuleSetting {
+        RuleSetting::new(RuleSeverity::Warning)
+    }
+
+    fn default_off() -> RuleSetting {
+        RuleSetting::new(RuleSeverity::Off)
+    }
+
+    /// Load rules from a `.toml` or `.json` file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read lint rules: {}", path.display()))?;
+        let is_json = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+        let rules = if is_json {
+            serde_json::from_str(&content)
+                .with_context(|| format!("Invalid lint rules JSON: {}", path.display()))?
+        } else {
+            toml::from_str(&content)
+                .with_context(|| format!("Invalid lint rules TOML: {}", path.display()))?
+        };
+        Ok(rules)
+    }
+
+    /// Compile the id-pattern regex, if that rule is enabled
+    fn id_regex(&self) -> Result<Option<Regex>> {
+        if self.id_pattern.severity == RuleSeverity::Off {
+            return Ok(None);
+        }
+        let pattern = self
+            .id_pattern
+            .pattern
+            .as_deref()
+            .context("Rule 'id-pattern' requires a 'pattern'")?;
+        let regex = Regex::new(pattern)
+            .with_context(|| format!("Invalid id-pattern regex: {}", pattern))?;
+        Ok(Some(regex))
+    }
+}
+
 /// Result from processing a single file
 struct FileProcessResult {
     issues: Vec<LintIssue>,
@@ -81,7 +282,12 @@ struct FileProcessResult {
 }
 
 /// Process a single file for anchor linting
-fn process_file(root: &Path, path: &str) -> Option<FileProcessResult> {
+fn process_file(
+    root: &Path,
+    path: &str,
+    rules: &LintRules,
+    id_regex: Option<&Regex>,
+) -> Option<FileProcessResult> {
     use crate::core::file_reader::read_file_safe;
 
     let full_path = root.join(path);
@@ -115,38 +321,154 @@ fn process_file(root: &Path, path: &str) -> Option<FileProcessResult> {
     let anchors = parse_content(&content, path);
 
     for anchor in &anchors {
-        // Check for empty/oversized content (use content lines, not marker lines)
-        let content_lines: u32 = anchor
-            .content
-            .as_ref()
-            .map(|c| c.lines().count() as u32)
-            .unwrap_or(0);
+        issues.extend(check_anchor(anchor, path, rules, id_regex));
+    }
+
+    if let Some(severity) = rules.overlapping.severity.issue_severity() {
+        issues.extend(check_overlapping(&anchors, path, severity));
+    }
 
-        if content_lines == 0 {
-            issues.push(LintIssue::warning(
+    Some(FileProcessResult { issues, anchors })
+}
+
+/// Per-anchor checks: empty, oversized, id pattern, token budget and content hash
+fn check_anchor(
+    anchor: &Anchor,
+    path: &str,
+    rules: &LintRules,
+    id_regex: Option<&Regex>,
+) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    let line = Some(anchor.range.start);
+
+    // Check for empty/oversized content (use content lines, not marker lines)
+    let content_lines: u32 = anchor
+        .content
+        .as_ref()
+        .map(|c| c.lines().count() as u32)
+        .unwrap_or(0);
+    let max_lines = rules.oversized.max_lines.unwrap_or(MAX_ANCHOR_LINES);
+
+    if content_lines == 0 {
+        if let Some(severity) = rules.empty.severity.issue_severity() {
+            issues.push(LintIssue::with_severity(
+                severity,
                 "EMPTY_ANCHOR",
                 &format!("Anchor '{}' has empty content", anchor.id),
                 path,
-                Some(anchor.range.start),
+                line,
             ));
-        } else if content_lines > MAX_ANCHOR_LINES {
-            issues.push(LintIssue::warning(
+        }
+    } else if content_lines > max_lines {
+        if let Some(severity) = rules.oversized.severity.issue_severity() {
+            issues.push(LintIssue::with_severity(
+                severity,
                 "LARGE_ANCHOR",
                 &format!(
                     "Anchor '{}' is very large ({} lines), consider splitting",
                     anchor.id, content_lines
                 ),
                 path,
-                Some(anchor.range.start),
+                line,
             ));
         }
     }
 
-    Some(FileProcessResult { issues, anchors })
+    if let (Some(regex), Some(severity)) = (id_regex, rules.id_pattern.severity.issue_severity()) {
+        if !regex.is_match(&anchor.id) {
+            issues.push(LintIssue::with_severity(
+                severity,
+                "ID_PATTERN",
+                &format!(
+                    "Anchor ID '{}' does not match pattern '{}'",
+                    anchor.id,
+                    regex.as_str()
+                ),
+                path,
+                line,
+            ));
+        }
+    }
+
+    if let (Some(severity), Some(max_tokens)) = (
+        rules.token_budget.severity.issue_severity(),
+        rules.token_budget.max_tokens,
+    ) {
+        let tokens = anchor
+            .content
+            .as_deref()
+            .map(|c| count_tokens(c, TokenModel::default()))
+            .unwrap_or(0);
+        if tokens > max_tokens {
+            issues.push(LintIssue::with_severity(
+                severity,
+                "TOKEN_BUDGET",
+                &format!(
+                    "Anchor '{}' has {} tokens, exceeding budget of {}",
+                    anchor.id, tokens, max_tokens
+                ),
+                path,
+                line,
+            ));
+        }
+    }
+
+    // Anchors marked without a hash have nothing to compare against
+    if let (Some(recorded), Some(severity)) = (
+        anchor.content_hash.as_deref(),
+        rules.content_changed.severity.issue_severity(),
+    ) {
+        if recorded != anchor.hash {
+            issues.push(LintIssue::with_severity(
+                severity,
+                "ANCHOR_CONTENT_CHANGED",
+                &format!(
+                    "Anchor '{}' content changed since it was marked (recorded hash {}, now {})",
+                    anchor.id, recorded, anchor.hash
+                ),
+                path,
+                line,
+            ));
+        }
+    }
+
+    issues
+}
+
+/// Report anchors in the same file whose ranges partially overlap
+///
+/// Fully nested anchors are not considered overlapping.
+fn check_overlapping(anchors: &[Anchor], path: &str, severity: LintSeverity) -> Vec<LintIssue> {
+    let mut sorted: Vec<&Anchor> = anchors.iter().collect();
+    sorted.sort_by_key(|a| (a.range.start, a.range.end));
+
+    let mut issues = Vec::new();
+    for (i, a) in sorted.iter().enumerate() {
+        for b in &sorted[i + 1..] {
+            if b.range.start > a.range.end {
+                break;
+            }
+            if b.range.end > a.range.end {
+                issues.push(LintIssue::with_severity(
+                    severity,
+                    "OVERLAPPING_ANCHORS",
+                    &format!(
+                        "Anchor '{}' (lines {}-{}) partially overlaps anchor '{}' (lines {}-{})",
+                        b.id, b.range.start, b.range.end, a.id, a.range.start, a.range.end
+                    ),
+                    path,
+                    Some(b.range.start),
+                ));
+            }
+        }
+    }
+
+    issues
 }
 
-/// Lint all anchors in the workspace
-pub fn lint_anchors(root: &Path) -> Result<Vec<LintIssue>> {
+/// Lint all anchors in the workspace using a custom rule configuration
+pub fn lint_anchors_with_rules(root: &Path, rules: &LintRules) -> Result<Vec<LintIssue>> {
+    let id_regex = rules.id_regex()?;
     let mut issues = Vec::new();
     let mut all_anchors: HashMap<String, Vec<Anchor>> = HashMap::new();
 
@@ -171,14 +493,14 @@ pub fn lint_anchors(root: &Path) -> Result<Vec<LintIssue>> {
         use rayon::prelude::*;
         paths
             .par_iter()
-            .filter_map(|path| process_file(root, path))
+            .filter_map(|path| process_file(root, path, rules, id_regex.as_ref()))
             .collect()
     };
 
     #[cfg(not(feature = "parallel"))]
     let results: Vec<FileProcessResult> = paths
         .iter()
-        .filter_map(|path| process_file(root, path))
+        .filter_map(|path| process_file(root, path, rules, id_regex.as_ref()))
         .collect();
 
     // Aggregate results
@@ -193,10 +515,12 @@ pub fn lint_anchors(root: &Path) -> Result<Vec<LintIssue>> {
     }
 
     // Check for duplicate IDs
+    let duplicate_severity = rules.duplicate_id.severity.issue_severity();
     for (id, anchors) in &all_anchors {
-        if anchors.len() > 1 {
+        if let (Some(severity), true) = (duplicate_severity, anchors.len() > 1) {
             for anchor in anchors {
-                issues.push(LintIssue::error(
+                issues.push(LintIssue::with_severity(
+                    severity,
                     "DUPLICATE_ID",
                     &format!("Anchor ID '{}' is used {} times", id, anchors.len()),
                     &anchor.path,
@@ -209,6 +533,71 @@ pub fn lint_anchors(root: &Path) -> Result<Vec<LintIssue>> {
     Ok(issues)
 }
 
+/// Re-record `hash=` for every anchor whose body changed since it was marked
+///
+/// Meant for after a review: current bodies become the new baseline, so they
+/// no longer raise `ANCHOR_CONTENT_CHANGED`. Returns one info issue
+/// (`ANCHOR_HASH_UPDATED`) per rewritten anchor.
+pub fn update_content_hashes(root: &Path) -> Result<Vec<LintIssue>> {
+    let options = ScanOptions {
+        file_type: Some("file".to_string()),
+        ignore: true,
+        ..Default::default()
+    };
+    let mut issues = Vec::new();
+
+    for path in scan_files(root, &options)?
+        .items
+        .into_iter()
+        .filter_map(|item| item.path)
+    {
+        let full_path = root.join(&path);
+        if !is_text_file(&full_path) {
+            continue;
+        }
+        // Only UTF-8 files are rewritten, as with the other marker edits
+        let Ok(content) = std::fs::read_to_string(&full_path) else {
+            continue;
+        };
+        if !content.contains(" hash=") {
+            continue;
+        }
+
+        let (new_content, updated) = rewrite_hashes(&content, |_| true);
+        if updated.is_empty() {
+            continue;
+        }
+        std::fs::write(&full_path, new_content)
+            .with_context(|| format!("Failed to write file: {}", path))?;
+        for anchor in updated {
+            issues.push(LintIssue::with_severity(
+                LintSeverity::Info,
+                "ANCHOR_HASH_UPDATED",
+                &format!("Anchor '{}' hash updated to its current content", anchor.id),
+                &path,
+                Some(anchor.range.start),
+            ));
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Stray marker issue: `ORPHANED_END_MARKER` / `DANGLING_BEGIN_MARKER`, aliased
+/// to the older `UNPAIRED_END` / `UNPAIRED_BEGIN`, with the marker's id and line
+fn stray_marker(
+    code: &str,
+    alias: &str,
+    message: &str,
+    id: &str,
+    path: &str,
+    line: u32,
+) -> LintIssue {
+    LintIssue::error(code, message, path, Some(line))
+        .with_alias(alias)
+        .with_data(json!({ "id": id, "line": line }))
+}
+
 /// Check for unpaired begin/end markers
 fn check_pairing(content: &str, path: &str) -> Vec<LintIssue> {
     use crate::anchors::parse::{BEGIN_RE, END_RE};
@@ -233,11 +622,13 @@ fn check_pairing(content: &str, path: &str) -> Vec<LintIssue> {
             if let Some(pos) = open_ids.iter().rposition(|(id, _)| id == end_id) {
                 open_ids.remove(pos);
             } else {
-                issues.push(LintIssue::error(
+                issues.push(stray_marker(
+                    "ORPHANED_END_MARKER",
                     "UNPAIRED_END",
                     &format!("End marker for '{}' has no matching begin", end_id),
+                    end_id,
                     path,
-                    Some(line_num),
+                    line_num,
                 ));
             }
         }
@@ -245,11 +636,13 @@ fn check_pairing(content: &str, path: &str) -> Vec<LintIssue> {
 
     // Report unclosed markers
     for (id, line) in open_ids {
-        issues.push(LintIssue::error(
+        issues.push(stray_marker(
+            "DANGLING_BEGIN_MARKER",
             "UNPAIRED_BEGIN",
             &format!("Begin marker for '{}' has no matching end", id),
+            &id,
             path,
-            Some(line),
+            line,
         ));
     }
 
@@ -271,30 +664,53 @@ fn is_text_file(path: &Path) -> bool {
 }
 
 /// Run the lint command
-pub fn run_lint(root: &Path, config: RenderConfig) -> Result<()> {
-    let result_set = lint_to_result_set(root)?;
+///
+/// Every issue is printed; any error-severity issue then fails the command so
+/// it exits non-zero, with or without a rules file. With `update_hashes`, stale
+/// content hashes are re-recorded before linting.
+pub fn run_lint(
+    root: &Path,
+    rules_file: Option<&Path>,
+    update_hashes: bool,
+    config: RenderConfig,
+) -> Result<()> {
+    let rules = match rules_file {
+        Some(path) => LintRules::from_file(path)?,
+        None => LintRules::default(),
+    };
+    let mut issues = if update_hashes {
+        update_content_hashes(root)?
+    } else {
+        Vec::new()
+    };
+    issues.extend(lint_anchors_with_rules(root, &rules)?);
+    let errors = issues
+        .iter()
+        .filter(|i| i.severity == LintSeverity::Error)
+        .count();
+    let result_set = issues_to_result_set(issues);
 
     let renderer = Renderer::with_config(config);
-    println!("{}", renderer.render(&result_set));
+    print!("{}", renderer.render(&result_set));
 
+    if errors > 0 {
+        bail!("anchor lint found {} error(s)", errors);
+    }
     Ok(())
 }
 
-/// Public API for MCP: lint anchors and return ResultSet
-pub fn lint_to_result_set(root: &Path) -> Result<ResultSet> {
-    let issues = lint_anchors(root)?;
-
+fn issues_to_result_set(issues: Vec<LintIssue>) -> ResultSet {
     let mut result_set = ResultSet::new();
     for issue in issues {
         result_set.push(issue.to_result_item());
     }
-
-    Ok(result_set)
+    result_set
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::util::{hash_bytes, HashAlgorithm};
 
     #[test]
     fn test_check_pairing_valid() {
@@ -327,7 +743,7 @@ content
 "#;
         let issues = check_pairing(content, "test.md");
         assert_eq!(issues.len(), 1);
-        assert_eq!(issues[0].code, "UNPAIRED_BEGIN");
+        assert_eq!(issues[0].code, "DANGLING_BEGIN_MARKER");
     }
 
     #[test]
@@ -338,7 +754,168 @@ content
 "#;
         let issues = check_pairing(content, "test.md");
         assert_eq!(issues.len(), 1);
-        assert_eq!(issues[0].code, "UNPAIRED_END");
+        assert_eq!(issues[0].code, "ORPHANED_END_MARKER");
+    }
+
+    fn anchor(id: &str, start: u32, end: u32, content: &str) -> Anchor {
+        let content = format!(
+            "<!--Q:begin id={}-->\n{}\n<!--Q:end id={}-->\n",
+            id, content, id
+        );
+        let mut anchor = parse_content(&content, "doc.md").remove(0);
+        anchor.range.start = start;
+        anchor.range.end = end;
+        anchor
+    }
+
+    #[test]
+    fn test_lint_rules_from_toml_and_json() {
+        let temp = tempfile::tempdir().unwrap();
+        let toml_path = temp.path().join("rules.toml");
+        std::fs::write(
+            &toml_path,
+            "empty = { severity = \"off\" }\n[oversized]\nseverity = \"error\"\nmax_lines = 10\n",
+        )
+        .unwrap();
+        let rules = LintRules::from_file(&toml_path).unwrap();
+        assert_eq!(rules.empty.severity, RuleSeverity::Off);
+        assert_eq!(rules.oversized.max_lines, Some(10));
+        assert_eq!(rules.duplicate_id.severity, RuleSeverity::Error);
+        assert_eq!(rules.overlapping.severity, RuleSeverity::Error);
+
+        let json_path = temp.path().join("rules.json");
+        std::fs::write(
+            &json_path,
+            r#"{"id-pattern": {"severity": "warning", "pattern": "^[a-z.]+$"}}"#,
+        )
+        .unwrap();
+        let rules = LintRules::from_file(&json_path).unwrap();
+        assert_eq!(rules.id_pattern.severity, RuleSeverity::Warning);
+
+        std::fs::write(&json_path, r#"{"bogus": {"severity": "error"}}"#).unwrap();
+        assert!(LintRules::from_file(&json_path).is_err());
+    }
+
+    #[test]
+    fn test_check_anchor_with_rules() {
+        let mut rules = LintRules {
+            id_pattern: RuleSetting {
+                pattern: Some("^[a-z.]+$".to_string()),
+                ..RuleSetting::new(RuleSeverity::Error)
+            },
+            token_budget: RuleSetting {
+                max_tokens: Some(1),
+                ..RuleSetting::new(RuleSeverity::Info)
+            },
+            ..Default::default()
+        };
+        let regex = rules.id_regex().unwrap();
+
+        let issues = check_anchor(
+            &anchor("Bad_ID", 1, 3, "several words of content"),
+            "doc.md",
+            &rules,
+            regex.as_ref(),
+        );
+        let codes: Vec<_> = issues.iter().map(|i| i.code.as_str()).collect();
+        assert_eq!(codes, vec!["ID_PATTERN", "TOKEN_BUDGET"]);
+        assert_eq!(issues[0].severity, LintSeverity::Error);
+
+        let item = issues[1].to_result_item();
+        assert_eq!(item.kind, Kind::Anchor);
+        assert_eq!(item.confidence, Confidence::Low);
+
+        rules.id_pattern.pattern = None;
+        assert!(rules.id_regex().is_err());
+    }
+
+    #[test]
+    fn test_check_anchor_content_changed() {
+        let rules = LintRules::default();
+        let hash = hash_bytes(b"original", HashAlgorithm::Xxh3);
+        let marked = |body: &str| {
+            let content = format!(
+                "<!--Q:begin id=a v=1 hash={}-->\n{}\n<!--Q:end id=a-->\n",
+                hash, body
+            );
+            parse_content(&content, "doc.md").remove(0)
+        };
+
+        assert!(check_anchor(&marked("original"), "doc.md", &rules, None).is_empty());
+        let issues = check_anchor(&marked("edited"), "doc.md", &rules, None);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code, "ANCHOR_CONTENT_CHANGED");
+        assert_eq!(issues[0].severity, LintSeverity::Warning);
+
+        // Hashless anchors are never reported
+        assert!(check_anchor(&anchor("b", 1, 3, "edited"), "doc.md", &rules, None).is_empty());
+    }
+
+    #[test]
+    fn test_run_lint_fails_on_errors_with_or_without_rules() {
+        use crate::core::render::OutputFormat;
+
+        let temp = tempfile::tempdir().unwrap();
+        let config = || RenderConfig::new(OutputFormat::Jsonl);
+        std::fs::write(
+            temp.path().join("ok.md"),
+            "<!--Q:begin id=a v=1-->\nA\n<!--Q:end id=a-->\n",
+        )
+        .unwrap();
+        assert!(run_lint(temp.path(), None, false, config()).is_ok());
+
+        std::fs::write(temp.path().join("bad.md"), "<!--Q:begin id=b v=1-->\n").unwrap();
+        let err = run_lint(temp.path(), None, false, config()).unwrap_err();
+        assert!(err.to_string().contains("1 error(s)"));
+
+        let rules = temp.path().join("rules.toml");
+        std::fs::write(&rules, "empty = { severity = \"off\" }\n").unwrap();
+        assert!(run_lint(temp.path(), Some(&rules), false, config()).is_err());
+    }
+
+    #[test]
+    fn test_update_content_hashes() {
+        let temp = tempfile::tempdir().unwrap();
+        let hash = hash_bytes(b"original", HashAlgorithm::Xxh3);
+        std::fs::write(
+            temp.path().join("doc.md"),
+            format!(
+                "<!--Q:begin id=a v=1 hash={}-->\nedited\n<!--Q:end id=a-->\n",
+                hash
+            ),
+        )
+        .unwrap();
+
+        let codes = |issues: &[LintIssue]| -> Vec<String> {
+            issues.iter().map(|i| i.code.clone()).collect()
+        };
+        assert_eq!(
+            codes(&lint_anchors_with_rules(temp.path(), &LintRules::default()).unwrap()),
+            ["ANCHOR_CONTENT_CHANGED"]
+        );
+
+        let updated = update_content_hashes(temp.path()).unwrap();
+        assert_eq!(codes(&updated), ["ANCHOR_HASH_UPDATED"]);
+        assert_eq!(updated[0].line, Some(1));
+        assert!(lint_anchors_with_rules(temp.path(), &LintRules::default())
+            .unwrap()
+            .is_empty());
+        assert!(update_content_hashes(temp.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_check_overlapping() {
+        let anchors = vec![
+            anchor("outer", 1, 10, "x"),
+            anchor("nested", 2, 5, "x"),
+            anchor("crossing", 8, 15, "x"),
+        ];
+        let issues = check_overlapping(&anchors, "doc.md", LintSeverity::Warning);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code, "OVERLAPPING_ANCHORS");
+        assert_eq!(issues[0].line, Some(8));
+        assert!(issues[0].message.contains("'crossing' (lines 8-15)"));
+        assert!(issues[0].message.contains("'outer' (lines 1-10)"));
     }
 
     #[test]
@@ -470,7 +1047,7 @@ content
 "#;
         let issues = check_pairing(content, "test.md");
         assert_eq!(issues.len(), 2);
-        assert!(issues.iter().all(|i| i.code == "UNPAIRED_BEGIN"));
+        assert!(issues.iter().all(|i| i.code == "DANGLING_BEGIN_MARKER"));
     }
 
     #[test]
@@ -484,8 +1061,8 @@ content
         // Should have unpaired begin for test1 and unpaired end for test2
         assert_eq!(issues.len(), 2);
         let codes: Vec<&str> = issues.iter().map(|i| i.code.as_str()).collect();
-        assert!(codes.contains(&"UNPAIRED_BEGIN"));
-        assert!(codes.contains(&"UNPAIRED_END"));
+        assert!(codes.contains(&"DANGLING_BEGIN_MARKER"));
+        assert!(codes.contains(&"ORPHANED_END_MARKER"));
     }
 
     #[test]
@@ -526,4 +1103,55 @@ content
         let cloned = error;
         assert_eq!(error, cloned);
     }
+
+    #[test]
+    fn test_lint_anchors_reports_overlap_by_default() {
+        let temp = tempfile::tempdir().unwrap();
+        let content = "<!--Q:begin id=a-->\n1\n<!--Q:begin id=b-->\n2\n<!--Q:end id=a-->\n3\n<!--Q:end id=b-->\n";
+        std::fs::write(temp.path().join("doc.md"), content).unwrap();
+
+        let issues = lint_anchors_with_rules(temp.path(), &LintRules::default()).unwrap();
+        let overlap: Vec<_> = issues
+            .iter()
+            .filter(|i| i.code == "OVERLAPPING_ANCHORS")
+            .collect();
+        assert_eq!(overlap.len(), 1);
+        assert_eq!(overlap[0].severity, LintSeverity::Error);
+        assert_eq!(overlap[0].line, Some(3));
+    }
+
+    #[test]
+    fn test_lint_anchors_reports_stray_markers_as_errors() {
+        let temp = tempfile::tempdir().unwrap();
+        // The begin of `gone` was deleted; `open` never got an end marker
+        let content = "<!--Q:begin id=ok-->\nbody\n<!--Q:end id=ok-->\ntext\n<!--Q:end id=gone-->\n<!--Q:begin id=open-->\n";
+        std::fs::write(temp.path().join("doc.md"), content).unwrap();
+
+        let result_set = issues_to_result_set(
+            lint_anchors_with_rules(temp.path(), &LintRules::default()).unwrap(),
+        );
+        let stray: Vec<_> = result_set
+            .items
+            .iter()
+            .filter(|i| i.errors[0].code.ends_with("_MARKER"))
+            .collect();
+        assert_eq!(stray.len(), 2);
+        assert!(stray.iter().all(|i| i.kind == Kind::Error));
+
+        let end = stray
+            .iter()
+            .find(|i| i.errors[0].code == "ORPHANED_END_MARKER")
+            .unwrap();
+        assert_eq!(end.range, Some(crate::core::model::Range::lines(5, 5)));
+        assert!(end.excerpt.as_deref().unwrap().contains("'gone'"));
+        assert_eq!(end.errors[1].code, "UNPAIRED_END");
+        assert_eq!(end.data, Some(json!({ "id": "gone", "line": 5 })));
+
+        let begin = stray
+            .iter()
+            .find(|i| i.errors[0].code == "DANGLING_BEGIN_MARKER")
+            .unwrap();
+        assert_eq!(begin.errors[1].code, "UNPAIRED_BEGIN");
+        assert_eq!(begin.data, Some(json!({ "id": "open", "line": 6 })));
+    }
 }
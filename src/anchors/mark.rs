@@ -1,18 +1,26 @@
 //! Anchor marking module - Insert anchor markers into files
 //!
 //! Provides functionality to quickly mark text blocks with anchor markers:
-//! <!--Q:begin id=xxx tags=a,b v=1-->
+//! <!--Q:begin id=xxx tags=a,b v=1 hash=<xxh3 of the body>-->
 //! ...content...
 //! <!--Q:end id=xxx-->
 //!
+//! The comment syntax follows the file type (see [`CommentStyle::for_path`]):
+//! `# Q:begin ...` for Python/shell/YAML, `// Q:begin ...` for Rust/JS/C-like code.
+//!
 //! Supports single and batch marking operations for AI agents.
 
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
+use crate::anchors::parse::{body_hash, parse_content, parse_file, Anchor, BEGIN_RE, END_RE};
+use crate::backends::scan::{scan_files, ScanOptions};
+use crate::cache::reader::get_all_anchors_parsed;
 use crate::core::model::ResultSet;
+use crate::core::policy::{is_path_denied, PATH_DENIED};
 use crate::core::render::{RenderConfig, Renderer};
 
 /// A single mark operation specification
@@ -27,7 +35,8 @@ pub struct MarkSpec {
     /// End line (1-indexed, inclusive)
     pub end_line: u32,
 
-    /// Anchor ID
+    /// Anchor ID (when omitted, `--auto-id` slugs or a `path.Lstart-end` id is generated)
+    #[serde(default)]
     pub id: String,
 
     /// Tags (optional)
@@ -64,36 +73,260 @@ pub struct MarkResult {
     pub lines_affected: Option<(u32, u32)>,
 }
 
+/// Error code prefix for marks that would interleave with an existing anchor
+pub const ANCHOR_CONFLICT: &str = "ANCHOR_CONFLICT";
+
 /// Batch mark specification (for JSON input)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatchMarkSpec {
     pub marks: Vec<MarkSpec>,
 }
 
+/// Comment syntax used to write anchor markers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CommentStyle {
+    /// `<!--Q:begin id=xxx-->` (Markdown, HTML and unknown file types)
+    #[default]
+    Html,
+    /// `# Q:begin id=xxx`
+    Hash,
+    /// `// Q:begin id=xxx`
+    Slash,
+}
+
+/// Comment style by file extension; anything not listed uses HTML comments
+const COMMENT_STYLES: &[(&str, CommentStyle)] = &[
+    ("py", CommentStyle::Hash),
+    ("sh", CommentStyle::Hash),
+    ("bash", CommentStyle::Hash),
+    ("zsh", CommentStyle::Hash),
+    ("rb", CommentStyle::Hash),
+    ("pl", CommentStyle::Hash),
+    ("r", CommentStyle::Hash),
+    ("yaml", CommentStyle::Hash),
+    ("yml", CommentStyle::Hash),
+    ("toml", CommentStyle::Hash),
+    ("rs", CommentStyle::Slash),
+    ("js", CommentStyle::Slash),
+    ("jsx", CommentStyle::Slash),
+    ("ts", CommentStyle::Slash),
+    ("tsx", CommentStyle::Slash),
+    ("go", CommentStyle::Slash),
+    ("c", CommentStyle::Slash),
+    ("h", CommentStyle::Slash),
+    ("cpp", CommentStyle::Slash),
+    ("hpp", CommentStyle::Slash),
+    ("cs", CommentStyle::Slash),
+    ("java", CommentStyle::Slash),
+    ("kt", CommentStyle::Slash),
+    ("scala", CommentStyle::Slash),
+    ("swift", CommentStyle::Slash),
+    ("php", CommentStyle::Slash),
+];
+
+impl CommentStyle {
+    /// Pick the comment style for a file path by its extension
+    pub fn for_path(path: &str) -> Self {
+        let ext = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+
+        COMMENT_STYLES
+            .iter()
+            .find(|(e, _)| *e == ext)
+            .map(|(_, style)| *style)
+            .unwrap_or_default()
+    }
+
+    /// Wrap a marker body (`Q:begin id=...`) in this comment syntax
+    fn wrap(self, body: &str) -> String {
+        match self {
+            CommentStyle::Html => format!("<!--{}-->", body),
+            CommentStyle::Hash => format!("# {}", body),
+            CommentStyle::Slash => format!("// {}", body),
+        }
+    }
+}
+
 /// Generate the begin marker line
-fn generate_begin_marker(id: &str, tags: &[String], version: u32) -> String {
-    let mut marker = format!("<!--Q:begin id={}", id);
+///
+/// `content_hash` is the body hash recorded for `anchor lint` to detect edits.
+fn generate_begin_marker(
+    id: &str,
+    tags: &[String],
+    version: u32,
+    content_hash: Option<&str>,
+    style: CommentStyle,
+) -> String {
+    let mut marker = format!("Q:begin id={}", id);
 
     if !tags.is_empty() {
         marker.push_str(&format!(" tags={}", tags.join(",")));
     }
 
-    marker.push_str(&format!(" v={}-->", version));
-    marker
+    marker.push_str(&format!(" v={}", version));
+    if let Some(hash) = content_hash {
+        marker.push_str(&format!(" hash={}", hash));
+    }
+    style.wrap(&marker)
 }
 
 /// Generate the end marker line
-fn generate_end_marker(id: &str) -> String {
-    format!("<!--Q:end id={}-->", id)
+fn generate_end_marker(id: &str, style: CommentStyle) -> String {
+    style.wrap(&format!("Q:end id={}", id))
+}
+
+/// Turn text into a lowercase, hyphen-separated id fragment
+fn slugify(text: &str, max_len: usize) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.chars().count() >= max_len {
+            break;
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Derive an anchor id from the file stem and the first non-empty line of the range
+///
+/// e.g. `src/main.rs` with `// Entry point` on the first marked line -> `main.entry-point`.
+/// A `-2`, `-3`, ... suffix is appended until the id is not in `taken`.
+pub fn generate_auto_id(
+    path: &str,
+    content: &str,
+    start_line: u32,
+    end_line: u32,
+    taken: &HashSet<String>,
+) -> String {
+    let stem = Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .map(|s| slugify(s, 32))
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "anchor".to_string());
+
+    let slug = content
+        .lines()
+        .skip(start_line.saturating_sub(1) as usize)
+        .take((end_line.saturating_sub(start_line) + 1) as usize)
+        .map(|line| slugify(line, 32))
+        .find(|s| !s.is_empty());
+
+    let base = match slug {
+        Some(slug) => format!("{}.{}", stem, slug),
+        None => stem,
+    };
+
+    let mut id = base.clone();
+    let mut counter = 2;
+    while taken.contains(&id) {
+        id = format!("{}-{}", base, counter);
+        counter += 1;
+    }
+    id
+}
+
+/// Fill in missing ids on the given specs, unique against existing anchors and each other
+pub fn assign_auto_ids(root: &Path, specs: &mut [MarkSpec]) -> Result<()> {
+    if specs.iter().all(|s| !s.id.is_empty()) {
+        return Ok(());
+    }
+
+    let mut taken: HashSet<String> = get_all_anchors_parsed(root)?
+        .into_iter()
+        .map(|(_, anchor)| anchor.id)
+        .collect();
+    taken.extend(specs.iter().map(|s| s.id.clone()));
+
+    let mut contents: HashMap<String, String> = HashMap::new();
+    for spec in specs.iter_mut().filter(|s| s.id.is_empty()) {
+        let content = contents
+            .entry(spec.path.clone())
+            .or_insert_with(|| fs::read_to_string(root.join(&spec.path)).unwrap_or_default());
+        spec.id = generate_auto_id(&spec.path, content, spec.start_line, spec.end_line, &taken);
+        taken.insert(spec.id.clone());
+    }
+
+    Ok(())
+}
+
+/// Derive a stable anchor id from the normalized path and line range
+///
+/// e.g. `src/main.rs` lines 10-25 -> `src_main_rs.L10-25`.
+pub fn range_id(path: &str, start_line: u32, end_line: u32) -> String {
+    let stem: String = path
+        .trim_start_matches("./")
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{}.L{}-{}", stem, start_line, end_line)
+}
+
+/// Fill in missing ids on specs for one file with [`range_id`]
+///
+/// Ids are checked against anchors already in `content` and against each other;
+/// on a clash a `-2`, `-3`, ... suffix is appended, in spec order.
+fn assign_range_ids(content: &str, specs: &mut [MarkSpec]) {
+    if specs.iter().all(|s| !s.id.is_empty()) {
+        return;
+    }
+
+    let mut taken: HashSet<String> = parse_content(content, "")
+        .into_iter()
+        .map(|anchor| anchor.id)
+        .collect();
+    taken.extend(specs.iter().map(|s| s.id.clone()));
+
+    for spec in specs.iter_mut().filter(|s| s.id.is_empty()) {
+        let base = range_id(&spec.path, spec.start_line, spec.end_line);
+        let mut id = base.clone();
+        let mut counter = 2;
+        while taken.contains(&id) {
+            id = format!("{}-{}", base, counter);
+            counter += 1;
+        }
+        taken.insert(id.clone());
+        spec.id = id;
+    }
+}
+
+/// Find an existing anchor whose markers would interleave with lines `start..=end`
+///
+/// Anchors entirely inside the range get nested under the new anchor, and a range
+/// entirely inside an anchor body nests under it; anything else straddles a marker.
+fn find_conflicting_anchor(content: &str, start: u32, end: u32) -> Option<Anchor> {
+    if end < start {
+        return None;
+    }
+
+    parse_content(content, "").into_iter().find(|anchor| {
+        let disjoint = anchor.range.end < start || anchor.range.start > end;
+        let wraps_existing = anchor.range.start >= start && anchor.range.end <= end;
+        let inside_existing = anchor.range.start < start && anchor.range.end > end;
+        !(disjoint || wraps_existing || inside_existing)
+    })
 }
 
 /// Insert anchor markers into a file
 ///
-/// Returns the new content with markers inserted
+/// Returns the new content with markers inserted. Existing anchors fully inside
+/// the range are nested; a range that straddles an existing anchor's markers is
+/// rejected with an `ANCHOR_CONFLICT` error naming that anchor.
 pub fn insert_markers(content: &str, spec: &MarkSpec) -> Result<String> {
     let lines: Vec<&str> = content.lines().collect();
     let total_lines = lines.len() as u32;
 
+    if spec.id.is_empty() {
+        bail!("anchor id must not be empty (use --auto-id to generate one)");
+    }
+
     // Validate line numbers
     if spec.start_line < 1 {
         bail!("start_line must be >= 1, got {}", spec.start_line);
@@ -116,8 +349,32 @@ pub fn insert_markers(content: &str, spec: &MarkSpec) -> Result<String> {
     // Clamp end_line to file length
     let effective_end = spec.end_line.min(total_lines);
 
-    let begin_marker = generate_begin_marker(&spec.id, &spec.tags, spec.version);
-    let end_marker = generate_end_marker(&spec.id);
+    if let Some(conflict) = find_conflicting_anchor(content, spec.start_line, effective_end) {
+        bail!(
+            "{}: lines {}-{} partially overlap anchor '{}' (lines {}-{}); \
+             widen the range to cover it or mark inside its body",
+            ANCHOR_CONFLICT,
+            spec.start_line,
+            effective_end,
+            conflict.id,
+            conflict.range.start,
+            conflict.range.end
+        );
+    }
+
+    // Record the body hash, computed the same way parsing does, so edits show up in lint
+    let body = &lines[(spec.start_line - 1) as usize..effective_end as usize];
+    let content_hash = (!body.is_empty()).then(|| body_hash(body));
+
+    let style = CommentStyle::for_path(&spec.path);
+    let begin_marker = generate_begin_marker(
+        &spec.id,
+        &spec.tags,
+        spec.version,
+        content_hash.as_deref(),
+        style,
+    );
+    let end_marker = generate_end_marker(&spec.id, style);
 
     let mut result = Vec::new();
 
@@ -152,17 +409,87 @@ pub fn insert_markers(content: &str, spec: &MarkSpec) -> Result<String> {
         output.push('\n');
     }
 
-    Ok(output)
+    Ok(rehash_after_edit(content, &output, None))
+}
+
+/// Point the recorded `hash=` of selected anchors at their current body
+///
+/// Anchors marked without a hash, and those whose hash is already current, are
+/// left alone; an anchor whose body is now empty loses its `hash=`. Returns
+/// the new content and the anchors whose hash was rewritten.
+pub fn rewrite_hashes(content: &str, select: impl Fn(&Anchor) -> bool) -> (String, Vec<Anchor>) {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let mut updated = Vec::new();
+
+    for anchor in parse_content(content, "") {
+        let Some(recorded) = anchor.content_hash.as_deref() else {
+            continue;
+        };
+        if recorded == anchor.hash || !select(&anchor) {
+            continue;
+        }
+        let Some(line) = lines.get_mut(anchor.range.start as usize - 1) else {
+            continue;
+        };
+        let replacement = if anchor.hash.is_empty() {
+            String::new()
+        } else {
+            format!(" hash={}", anchor.hash)
+        };
+        *line = line.replacen(&format!(" hash={}", recorded), &replacement, 1);
+        updated.push(anchor);
+    }
+
+    if updated.is_empty() {
+        return (content.to_string(), updated);
+    }
+    let mut output = lines.join("\n");
+    if content.ends_with('\n') {
+        output.push('\n');
+    }
+    (output, updated)
+}
+
+/// Keep recorded hashes current across an edit made by mark, replace or move
+///
+/// Anchors whose hash matched their body in `before`, plus `edited` (whose body
+/// was rewritten on purpose), get their hash re-recorded in `after`. Anchors
+/// that were already stale keep theirs, so lint still reports them.
+fn rehash_after_edit(before: &str, after: &str, edited: Option<&str>) -> String {
+    let fresh: HashSet<String> = parse_content(before, "")
+        .into_iter()
+        .filter(|a| a.content_hash.as_deref() == Some(a.hash.as_str()))
+        .map(|a| a.id)
+        .collect();
+    rewrite_hashes(after, |a| {
+        fresh.contains(&a.id) || edited == Some(a.id.as_str())
+    })
+    .0
 }
 
 /// Mark a single file with anchor markers
 pub fn mark_file(root: &Path, spec: &MarkSpec, dry_run: bool) -> Result<MarkResult> {
     let file_path = root.join(&spec.path);
 
+    if is_path_denied(&file_path) {
+        return Ok(MarkResult {
+            path: spec.path.clone(),
+            id: spec.id.clone(),
+            success: false,
+            error: Some(format!("{}: path is denied by --deny policy", PATH_DENIED)),
+            lines_affected: None,
+        });
+    }
+
     // Read the file
     let content = fs::read_to_string(&file_path)
         .with_context(|| format!("Failed to read file: {}", spec.path))?;
 
+    // Synthesize a path/range id when none was given
+    let mut spec = spec.clone();
+    assign_range_ids(&content, std::slice::from_mut(&mut spec));
+    let spec = &spec;
+
     // Insert markers
     let new_content = match insert_markers(&content, spec) {
         Ok(c) => c,
@@ -212,11 +539,29 @@ pub fn mark_batch(root: &Path, specs: Vec<MarkSpec>, dry_run: bool) -> Result<Ve
 
     // Process each file
     for (path, mut file_specs) in by_file {
-        // Sort by start_line descending (process from bottom to top)
-        file_specs.sort_by(|a, b| b.start_line.cmp(&a.start_line));
+        // Sort by start_line descending (process from bottom to top); for equal
+        // starts, apply the inner (shorter) range first so the outer one wraps it
+        file_specs.sort_by(|a, b| {
+            b.start_line
+                .cmp(&a.start_line)
+                .then(a.end_line.cmp(&b.end_line))
+        });
 
         let file_path = root.join(&path);
 
+        if is_path_denied(&file_path) {
+            for spec in file_specs {
+                results.push(MarkResult {
+                    path: spec.path,
+                    id: spec.id,
+                    success: false,
+                    error: Some(format!("{}: path is denied by --deny policy", PATH_DENIED)),
+                    lines_affected: None,
+                });
+            }
+            continue;
+        }
+
         // Read file content once
         let mut content = match fs::read_to_string(&file_path) {
             Ok(c) => c,
@@ -235,12 +580,25 @@ pub fn mark_batch(root: &Path, specs: Vec<MarkSpec>, dry_run: bool) -> Result<Ve
             }
         };
 
+        // Ids derive from the original line numbers, so assign them before any insertion
+        assign_range_ids(&content, &mut file_specs);
+
         // Apply each mark from bottom to top
-        for spec in file_specs {
+        let mut applied: Vec<(u32, u32)> = Vec::new();
+        for mut spec in file_specs {
+            // Marks already applied inside this range pushed its end down two lines each
+            let nested = applied
+                .iter()
+                .filter(|(start, end)| *start >= spec.start_line && *end <= spec.end_line)
+                .count() as u32;
+            let original = (spec.start_line, spec.end_line);
+            spec.end_line += 2 * nested;
+
             match insert_markers(&content, &spec) {
                 Ok(new_content) => {
                     let lines_affected = (spec.start_line, spec.end_line + 2);
                     content = new_content;
+                    applied.push(original);
                     results.push(MarkResult {
                         path: spec.path,
                         id: spec.id,
@@ -291,7 +649,7 @@ impl MarkResult {
                     .lines_affected
                     .map(|(start, end)| crate::core::model::Range::lines(start, end)),
                 excerpt: Some(format!("Anchor '{}' marked successfully", self.id)),
-                data: None,
+                data: Some(serde_json::json!({ "id": self.id })),
                 confidence: Confidence::High,
                 source_mode: SourceMode::Anchor,
                 meta: Meta::default(),
@@ -315,11 +673,23 @@ impl MarkResult {
 }
 
 /// Run single mark command
-pub fn run_mark(root: &Path, spec: &MarkSpec, dry_run: bool, config: RenderConfig) -> Result<()> {
-    let result_set = mark_to_result_set(root, spec, dry_run)?;
+///
+/// With `auto_id`, a missing `spec.id` is generated via [`generate_auto_id`].
+pub fn run_mark(
+    root: &Path,
+    spec: &MarkSpec,
+    dry_run: bool,
+    auto_id: bool,
+    config: RenderConfig,
+) -> Result<()> {
+    let mut spec = spec.clone();
+    if auto_id {
+        assign_auto_ids(root, std::slice::from_mut(&mut spec))?;
+    }
+    let result_set = mark_to_result_set(root, &spec, dry_run)?;
 
     let renderer = Renderer::with_config(config);
-    println!("{}", renderer.render(&result_set));
+    print!("{}", renderer.render(&result_set));
 
     Ok(())
 }
@@ -338,10 +708,11 @@ pub fn run_batch_mark(
     root: &Path,
     json_input: &str,
     dry_run: bool,
+    auto_id: bool,
     config: RenderConfig,
 ) -> Result<()> {
     // Parse JSON input - support both array and object with "marks" field
-    let specs: Vec<MarkSpec> = if json_input.trim().starts_with('[') {
+    let mut specs: Vec<MarkSpec> = if json_input.trim().starts_with('[') {
         serde_json::from_str(json_input).context("Failed to parse JSON array")?
     } else {
         let batch: BatchMarkSpec =
@@ -353,6 +724,10 @@ pub fn run_batch_mark(
         bail!("No marks specified in input");
     }
 
+    if auto_id {
+        assign_auto_ids(root, &mut specs)?;
+    }
+
     let results = mark_batch(root, specs, dry_run)?;
 
     let mut result_set = ResultSet::new();
@@ -361,7 +736,7 @@ pub fn run_batch_mark(
     }
 
     let renderer = Renderer::with_config(config);
-    println!("{}", renderer.render(&result_set));
+    print!("{}", renderer.render(&result_set));
 
     Ok(())
 }
@@ -371,29 +746,37 @@ pub fn run_batch_mark_from_file(
     root: &Path,
     spec_file: &Path,
     dry_run: bool,
+    auto_id: bool,
     config: RenderConfig,
 ) -> Result<()> {
     let json_input = fs::read_to_string(spec_file)
         .with_context(|| format!("Failed to read spec file: {}", spec_file.display()))?;
 
-    run_batch_mark(root, &json_input, dry_run, config)
+    run_batch_mark(root, &json_input, dry_run, auto_id, config)
 }
 
-/// Remove anchor markers from a file (unmark)
-pub fn remove_markers(content: &str, anchor_id: &str) -> Result<String> {
+/// Build regexes matching whole begin/end marker lines for one anchor id
+fn marker_line_regexes(anchor_id: &str) -> Result<(regex::Regex, regex::Regex)> {
     use regex::Regex;
 
+    // HTML markers end with `-->`; line-comment markers must end the line
     let begin_pattern = format!(
-        r"^\s*<!--\s*Q:begin\s+id={}\s*(?:tags=[^\s]+)?\s*(?:v=\d+)?\s*-->\s*\n?",
+        r"^\s*(?:<!--|#|//)\s*Q:begin\s+id={}\s*(?:tags=[^\s]+?)?\s*(?:v=\d+)?\s*(?:hash=[0-9a-f]+)?\s*(?:-->|$)",
         regex::escape(anchor_id)
     );
     let end_pattern = format!(
-        r"^\s*<!--\s*Q:end\s+id={}\s*-->\s*\n?",
+        r"^\s*(?:<!--|#|//)\s*Q:end\s+id={}\s*(?:-->|$)",
         regex::escape(anchor_id)
     );
 
     let begin_re = Regex::new(&begin_pattern).context("Invalid begin pattern")?;
     let end_re = Regex::new(&end_pattern).context("Invalid end pattern")?;
+    Ok((begin_re, end_re))
+}
+
+/// Remove anchor markers from a file (unmark)
+pub fn remove_markers(content: &str, anchor_id: &str) -> Result<String> {
+    let (begin_re, end_re) = marker_line_regexes(anchor_id)?;
 
     let mut result = String::new();
     let mut removed_begin = false;
@@ -439,7 +822,7 @@ pub fn run_unmark(
     let result_set = unmark_to_result_set(root, path, anchor_id, dry_run)?;
 
     let renderer = Renderer::with_config(config);
-    println!("{}", renderer.render(&result_set));
+    print!("{}", renderer.render(&result_set));
 
     Ok(())
 }
@@ -474,23 +857,496 @@ pub fn unmark_to_result_set(
     Ok(result_set)
 }
 
+/// Replace the body between an anchor's begin and end markers
+///
+/// The marker lines are kept verbatim. A single trailing newline on `new_body` is
+/// ignored so the end marker stays on its own line; an empty body leaves the
+/// markers adjacent.
+pub fn replace_content(content: &str, anchor_id: &str, new_body: &str) -> Result<String> {
+    let (begin_re, end_re) = marker_line_regexes(anchor_id)?;
+    let lines: Vec<&str> = content.lines().collect();
+
+    let begin = lines
+        .iter()
+        .position(|line| begin_re.is_match(line))
+        .with_context(|| format!("Anchor '{}' not found in content", anchor_id))?;
+    let end = lines[begin + 1..]
+        .iter()
+        .position(|line| end_re.is_match(line))
+        .map(|offset| begin + 1 + offset)
+        .with_context(|| {
+            format!(
+                "Anchor '{}' has no end marker after line {}",
+                anchor_id,
+                begin + 1
+            )
+        })?;
+
+    let body = new_body.strip_suffix('\n').unwrap_or(new_body);
+    let body = body.strip_suffix('\r').unwrap_or(body);
+
+    let mut result: Vec<&str> = lines[..=begin].to_vec();
+    if !body.is_empty() {
+        result.extend(body.split('\n').map(|l| l.strip_suffix('\r').unwrap_or(l)));
+    }
+    result.extend(&lines[end..]);
+
+    // Join with newlines, preserving trailing newline if original had one
+    let mut output = result.join("\n");
+    if content.ends_with('\n') {
+        output.push('\n');
+    }
+
+    Ok(rehash_after_edit(content, &output, Some(anchor_id)))
+}
+
+/// Run replace command to swap the body of an anchor
+pub fn run_replace(
+    root: &Path,
+    path: &str,
+    anchor_id: &str,
+    new_body: &str,
+    dry_run: bool,
+    config: RenderConfig,
+) -> Result<()> {
+    let result_set = replace_to_result_set(root, path, anchor_id, new_body, dry_run)?;
+
+    let renderer = Renderer::with_config(config);
+    print!("{}", renderer.render(&result_set));
+
+    Ok(())
+}
+
+/// Replace an anchor body in a file and return ResultSet
+pub fn replace_to_result_set(
+    root: &Path,
+    path: &str,
+    anchor_id: &str,
+    new_body: &str,
+    dry_run: bool,
+) -> Result<ResultSet> {
+    let file_path = root.join(path);
+
+    if is_path_denied(&file_path) {
+        bail!(
+            "{}: path '{}' is denied by --deny policy",
+            PATH_DENIED,
+            path
+        );
+    }
+
+    let content =
+        fs::read_to_string(&file_path).with_context(|| format!("Failed to read file: {}", path))?;
+
+    let new_content = replace_content(&content, anchor_id, new_body)?;
+
+    if !dry_run {
+        fs::write(&file_path, &new_content)
+            .with_context(|| format!("Failed to write file: {}", path))?;
+    }
+
+    // Report the anchor's range in the rewritten file
+    let range = parse_content(&new_content, path)
+        .into_iter()
+        .find(|a| a.id == anchor_id)
+        .map(|a| crate::core::model::Range::Line(a.range))
+        .unwrap_or(crate::core::model::Range::lines(0, 0));
+
+    let mut result_set = ResultSet::new();
+    let mut item = crate::core::model::ResultItem::anchor(path.to_string(), range);
+    item.excerpt = Some(format!("Anchor '{}' content replaced", anchor_id));
+    item.data = Some(serde_json::json!({ "id": anchor_id, "dry_run": dry_run }));
+    result_set.push(item);
+
+    Ok(result_set)
+}
+
+/// Rewrite the begin/end markers of `old_id` to `new_id`, returning the new content
+/// and the number of markers rewritten
+///
+/// Tags, version and all other bytes (including line endings) are preserved.
+pub fn rename_markers(content: &str, old_id: &str, new_id: &str) -> (String, usize) {
+    let mut output = String::with_capacity(content.len());
+    let mut renamed = 0;
+
+    for line in content.split_inclusive('\n') {
+        let id_match = BEGIN_RE
+            .captures(line)
+            .or_else(|| END_RE.captures(line))
+            .and_then(|caps| caps.get(1))
+            .filter(|m| m.as_str() == old_id);
+
+        match id_match {
+            Some(m) => {
+                output.push_str(&line[..m.start()]);
+                output.push_str(new_id);
+                output.push_str(&line[m.end()..]);
+                renamed += 1;
+            }
+            None => output.push_str(line),
+        }
+    }
+
+    (output, renamed)
+}
+
+/// Rename an anchor id in every file under ROOT
+///
+/// Fails if `old_id` does not exist, or if `new_id` is already used (unless `force`).
+/// Returns one `MarkResult` per file touched.
+pub fn rename_anchor(
+    root: &Path,
+    old_id: &str,
+    new_id: &str,
+    force: bool,
+    dry_run: bool,
+) -> Result<Vec<MarkResult>> {
+    if new_id.is_empty() || new_id.chars().any(char::is_whitespace) || new_id.contains("-->") {
+        bail!("invalid anchor id '{}'", new_id);
+    }
+    if old_id == new_id {
+        bail!("new id is the same as the old id ('{}')", old_id);
+    }
+
+    let options = ScanOptions {
+        file_type: Some("file".to_string()),
+        ignore: true,
+        ..Default::default()
+    };
+
+    let mut targets: Vec<(String, Vec<Anchor>)> = Vec::new();
+    let mut collisions: Vec<String> = Vec::new();
+
+    for item in scan_files(root, &options)?.items {
+        let Some(path) = item.path else {
+            continue;
+        };
+        let anchors = parse_file(&root.join(&path), &path);
+        if anchors.iter().any(|a| a.id == new_id) {
+            collisions.push(path.clone());
+        }
+        let matching: Vec<Anchor> = anchors.into_iter().filter(|a| a.id == old_id).collect();
+        if !matching.is_empty() {
+            targets.push((path, matching));
+        }
+    }
+
+    if targets.is_empty() {
+        bail!("Anchor '{}' not found", old_id);
+    }
+    if !collisions.is_empty() && !force {
+        bail!(
+            "Anchor id '{}' already exists in {} (use --force to rename anyway)",
+            new_id,
+            collisions.join(", ")
+        );
+    }
+
+    let mut results = Vec::new();
+    for (path, anchors) in targets {
+        let file_path = root.join(&path);
+        let content = fs::read_to_string(&file_path)
+            .with_context(|| format!("Failed to read file: {}", path))?;
+        let (new_content, _) = rename_markers(&content, old_id, new_id);
+
+        let error = if dry_run {
+            None
+        } else {
+            fs::write(&file_path, &new_content)
+                .err()
+                .map(|e| format!("Failed to write file: {}", e))
+        };
+
+        let first = &anchors[0];
+        results.push(MarkResult {
+            path,
+            id: new_id.to_string(),
+            success: error.is_none(),
+            error,
+            lines_affected: Some((first.range.start, first.range.end)),
+        });
+    }
+
+    Ok(results)
+}
+
+/// Run anchor rename command
+pub fn run_rename(
+    root: &Path,
+    old_id: &str,
+    new_id: &str,
+    force: bool,
+    dry_run: bool,
+    config: RenderConfig,
+) -> Result<()> {
+    let results = rename_anchor(root, old_id, new_id, force, dry_run)?;
+
+    let mut result_set = ResultSet::new();
+    for result in results {
+        let mut item = result.to_result_item();
+        if result.success {
+            item.excerpt = Some(format!("Anchor '{}' renamed to '{}'", old_id, new_id));
+            item.data = Some(serde_json::json!({ "id": new_id, "previous_id": old_id }));
+        }
+        result_set.push(item);
+    }
+
+    let renderer = Renderer::with_config(config);
+    print!("{}", renderer.render(&result_set));
+
+    Ok(())
+}
+
+/// Split the block of `anchor_id` (markers and body) out of `content`
+///
+/// Returns the remaining content and the block's lines; the first begin
+/// marker and the next matching end marker delimit the block.
+pub fn take_anchor_block(content: &str, anchor_id: &str) -> Result<(String, Vec<String>)> {
+    let (begin_re, end_re) = marker_line_regexes(anchor_id)?;
+    let lines: Vec<&str> = content.lines().collect();
+
+    let begin = lines
+        .iter()
+        .position(|line| begin_re.is_match(line))
+        .with_context(|| format!("Anchor '{}' not found in content", anchor_id))?;
+    let end = lines[begin + 1..]
+        .iter()
+        .position(|line| end_re.is_match(line))
+        .map(|offset| begin + 1 + offset)
+        .with_context(|| {
+            format!(
+                "Anchor '{}' has no end marker after line {}",
+                anchor_id,
+                begin + 1
+            )
+        })?;
+
+    let block = lines[begin..=end].iter().map(|l| l.to_string()).collect();
+    let mut remaining: Vec<&str> = lines[..begin].to_vec();
+    remaining.extend(&lines[end + 1..]);
+
+    // Preserve trailing newline if original had one
+    let mut output = remaining.join("\n");
+    if content.ends_with('\n') && !output.is_empty() {
+        output.push('\n');
+    }
+
+    Ok((output, block))
+}
+
+/// Insert `block` so that it starts at line `at`, or append it when `at` is None
+pub fn insert_block(content: &str, block: &[String], at: Option<u32>) -> Result<String> {
+    let mut lines: Vec<&str> = content.lines().collect();
+    let total_lines = lines.len() as u32;
+
+    let index = match at {
+        None => total_lines,
+        Some(0) => bail!("--at must be >= 1, got 0"),
+        Some(at) if at > total_lines + 1 => {
+            bail!("--at ({}) exceeds file length ({})", at, total_lines)
+        }
+        Some(at) => at - 1,
+    } as usize;
+    lines.splice(index..index, block.iter().map(String::as_str));
+
+    // An empty destination gets a trailing newline, otherwise keep its ending
+    let mut output = lines.join("\n");
+    if content.is_empty() || content.ends_with('\n') {
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+/// Rewrite the marker lines of a block in `style`, keeping indentation and attributes
+///
+/// Used when a block moves between file types, so e.g. `// Q:begin` lines do
+/// not end up in Markdown. Body lines are returned unchanged.
+fn restyle_markers(block: &[String], style: CommentStyle) -> Vec<String> {
+    block
+        .iter()
+        .map(|line| {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            if let Some(caps) = BEGIN_RE.captures(line) {
+                let mut marker = format!("Q:begin id={}", &caps[1]);
+                for (index, key) in [(2, "tags"), (3, "v"), (4, "hash")] {
+                    if let Some(value) = caps.get(index) {
+                        marker.push_str(&format!(" {}={}", key, value.as_str()));
+                    }
+                }
+                format!("{}{}", indent, style.wrap(&marker))
+            } else if let Some(caps) = END_RE.captures(line) {
+                format!("{}{}", indent, generate_end_marker(&caps[1], style))
+            } else {
+                line.clone()
+            }
+        })
+        .collect()
+}
+
+/// Move the block of anchor `anchor_id` (markers and body) from `src` to `dest`
+///
+/// Both edits are computed before anything is written, so a missing anchor, an
+/// id already present in `dest` or a bad `at` line changes neither file. A
+/// missing `dest` is created. Marker lines are rewritten in the comment style of
+/// `dest`'s file type. Returns one result per file: the source with the
+/// block's former lines, then the destination with its new lines.
+pub fn move_anchor(
+    root: &Path,
+    src: &str,
+    anchor_id: &str,
+    dest: &str,
+    at: Option<u32>,
+    dry_run: bool,
+) -> Result<Vec<MarkResult>> {
+    let src_path = root.join(src);
+    let dest_path = root.join(dest);
+    for (path, full_path) in [(src, &src_path), (dest, &dest_path)] {
+        if is_path_denied(full_path) {
+            bail!(
+                "{}: path '{}' is denied by --deny policy",
+                PATH_DENIED,
+                path
+            );
+        }
+    }
+
+    let src_content =
+        fs::read_to_string(&src_path).with_context(|| format!("Failed to read file: {}", src))?;
+    let dest_existed = dest_path.exists();
+    if dest_existed && fs::canonicalize(&src_path)? == fs::canonicalize(&dest_path)? {
+        bail!("source and destination are the same file: {}", src);
+    }
+    let dest_content = if dest_existed {
+        fs::read_to_string(&dest_path).with_context(|| format!("Failed to read file: {}", dest))?
+    } else {
+        String::new()
+    };
+
+    let source_range = parse_content(&src_content, src)
+        .into_iter()
+        .find(|a| a.id == anchor_id)
+        .map(|a| (a.range.start, a.range.end));
+    let (new_src, block) = take_anchor_block(&src_content, anchor_id)?;
+    let block = restyle_markers(&block, CommentStyle::for_path(dest));
+
+    // Nested anchors move along with the block, so none of their ids may clash
+    let dest_ids: HashSet<String> = parse_content(&dest_content, dest)
+        .into_iter()
+        .map(|a| a.id)
+        .collect();
+    if let Some(clash) = parse_content(&block.join("\n"), src)
+        .into_iter()
+        .find(|a| dest_ids.contains(&a.id))
+    {
+        bail!("Anchor id '{}' already exists in {}", clash.id, dest);
+    }
+    let new_src = rehash_after_edit(&src_content, &new_src, None);
+    let new_dest = insert_block(&dest_content, &block, at)?;
+    let new_dest = rehash_after_edit(&dest_content, &new_dest, None);
+
+    let start = at.unwrap_or(dest_content.lines().count() as u32 + 1);
+    let dest_range = (start, start + block.len() as u32 - 1);
+
+    // Destination first: if the source write fails, the destination is restored
+    let error = if dry_run {
+        None
+    } else {
+        fs::write(&dest_path, &new_dest)
+            .with_context(|| format!("Failed to write file: {}", dest))?;
+        fs::write(&src_path, &new_src).err().map(|e| {
+            let restored = if !dest_existed {
+                fs::remove_file(&dest_path)
+            } else {
+                fs::write(&dest_path, &dest_content)
+            };
+            match restored {
+                Ok(()) => format!("Failed to write file: {} (destination restored)", e),
+                Err(_) => format!("Failed to write file: {} (destination not restored)", e),
+            }
+        })
+    };
+
+    Ok(vec![
+        MarkResult {
+            path: src.to_string(),
+            id: anchor_id.to_string(),
+            success: error.is_none(),
+            error: error.clone(),
+            lines_affected: source_range,
+        },
+        MarkResult {
+            path: dest.to_string(),
+            id: anchor_id.to_string(),
+            success: error.is_none(),
+            error,
+            lines_affected: Some(dest_range),
+        },
+    ])
+}
+
+/// Run anchor move command
+pub fn run_move(
+    root: &Path,
+    src: &str,
+    anchor_id: &str,
+    dest: &str,
+    at: Option<u32>,
+    dry_run: bool,
+    config: RenderConfig,
+) -> Result<()> {
+    let results = move_anchor(root, src, anchor_id, dest, at, dry_run)?;
+
+    let mut result_set = ResultSet::new();
+    for (result, role) in results.iter().zip(["source", "destination"]) {
+        let mut item = result.to_result_item();
+        if result.success {
+            item.excerpt = Some(format!(
+                "Anchor '{}' moved from {} to {}",
+                anchor_id, src, dest
+            ));
+            item.data = Some(serde_json::json!({
+                "id": anchor_id,
+                "role": role,
+                "dry_run": dry_run,
+            }));
+        }
+        result_set.push(item);
+    }
+
+    let renderer = Renderer::with_config(config);
+    print!("{}", renderer.render(&result_set));
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::util::{hash_bytes, HashAlgorithm};
 
     #[test]
     fn test_generate_markers() {
-        let begin = generate_begin_marker("test", &["a".to_string(), "b".to_string()], 1);
+        let begin = generate_begin_marker(
+            "test",
+            &["a".to_string(), "b".to_string()],
+            1,
+            None,
+            CommentStyle::Html,
+        );
         assert_eq!(begin, "<!--Q:begin id=test tags=a,b v=1-->");
 
-        let end = generate_end_marker("test");
+        let end = generate_end_marker("test", CommentStyle::Html);
         assert_eq!(end, "<!--Q:end id=test-->");
     }
 
     #[test]
     fn test_generate_markers_no_tags() {
-        let begin = generate_begin_marker("test", &[], 2);
+        let begin = generate_begin_marker("test", &[], 2, None, CommentStyle::Html);
         assert_eq!(begin, "<!--Q:begin id=test v=2-->");
+
+        let begin = generate_begin_marker("test", &[], 1, Some("00ff"), CommentStyle::Hash);
+        assert_eq!(begin, "# Q:begin id=test v=1 hash=00ff");
     }
 
     #[test]
@@ -509,7 +1365,11 @@ mod tests {
         let lines: Vec<&str> = result.lines().collect();
 
         assert_eq!(lines[0], "line 1");
-        assert_eq!(lines[1], "<!--Q:begin id=test tags=chapter v=1-->");
+        let hash = hash_bytes(b"line 2\nline 3\nline 4", HashAlgorithm::Xxh3);
+        assert_eq!(
+            lines[1],
+            format!("<!--Q:begin id=test tags=chapter v=1 hash={}-->", hash)
+        );
         assert_eq!(lines[2], "line 2");
         assert_eq!(lines[3], "line 3");
         assert_eq!(lines[4], "line 4");
@@ -532,7 +1392,7 @@ mod tests {
         let result = insert_markers(content, &spec).unwrap();
         let lines: Vec<&str> = result.lines().collect();
 
-        assert_eq!(lines[0], "<!--Q:begin id=test v=1-->");
+        assert!(lines[0].starts_with("<!--Q:begin id=test v=1 hash="));
         assert_eq!(lines[1], "line 1");
         assert_eq!(lines[2], "line 2");
         assert_eq!(lines[3], "<!--Q:end id=test-->");
@@ -556,7 +1416,7 @@ mod tests {
 
         assert_eq!(lines[0], "line 1");
         assert_eq!(lines[1], "line 2");
-        assert_eq!(lines[2], "<!--Q:begin id=test v=1-->");
+        assert!(lines[2].starts_with("<!--Q:begin id=test v=1 hash="));
         assert_eq!(lines[3], "line 3");
         assert_eq!(lines[4], "<!--Q:end id=test-->");
     }
@@ -744,13 +1604,15 @@ mod tests {
             "id123",
             &["tag1".to_string(), "tag2".to_string(), "tag3".to_string()],
             3,
+            None,
+            CommentStyle::Html,
         );
         assert_eq!(begin, "<!--Q:begin id=id123 tags=tag1,tag2,tag3 v=3-->");
     }
 
     #[test]
     fn test_generate_end_marker() {
-        let end = generate_end_marker("test-id");
+        let end = generate_end_marker("test-id", CommentStyle::Html);
         assert_eq!(end, "<!--Q:end id=test-id-->");
     }
 
@@ -805,7 +1667,7 @@ mod tests {
 
         // File should be modified
         let content = std::fs::read_to_string(&file_path).unwrap();
-        assert!(content.contains("<!--Q:begin id=test tags=tag1 v=1-->"));
+        assert!(content.contains("<!--Q:begin id=test tags=tag1 v=1 hash="));
         assert!(content.contains("<!--Q:end id=test-->"));
     }
 
@@ -1122,10 +1984,7 @@ mod tests {
         let content = "line 1\nline 2\nline 3\n";
         std::fs::write(temp.path().join("test.md"), content).unwrap();
 
-        let config = RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config = RenderConfig::new(crate::core::render::OutputFormat::Json);
 
         let spec = MarkSpec {
             path: "test.md".to_string(),
@@ -1137,7 +1996,7 @@ mod tests {
         };
 
         // Dry run should not modify the file
-        let result = run_mark(temp.path(), &spec, true, config);
+        let result = run_mark(temp.path(), &spec, true, false, config);
         assert!(result.is_ok());
 
         // File should be unchanged
@@ -1152,10 +2011,7 @@ mod tests {
         let content = "line 1\nline 2\nline 3\n";
         std::fs::write(temp.path().join("test.md"), content).unwrap();
 
-        let config = RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config = RenderConfig::new(crate::core::render::OutputFormat::Json);
 
         let spec = MarkSpec {
             path: "test.md".to_string(),
@@ -1166,7 +2022,7 @@ mod tests {
             version: 1,
         };
 
-        let result = run_mark(temp.path(), &spec, false, config);
+        let result = run_mark(temp.path(), &spec, false, false, config);
         assert!(result.is_ok());
 
         // File should be modified
@@ -1182,12 +2038,9 @@ mod tests {
         std::fs::write(temp.path().join("test.md"), "line 1\nline 2\nline 3\n").unwrap();
 
         let json = r#"[{"path": "test.md", "start_line": 1, "end_line": 2, "id": "test", "tags": [], "version": 1}]"#;
-        let config = RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config = RenderConfig::new(crate::core::render::OutputFormat::Json);
 
-        let result = run_batch_mark(temp.path(), json, true, config);
+        let result = run_batch_mark(temp.path(), json, true, false, config);
         assert!(result.is_ok());
     }
 
@@ -1198,12 +2051,9 @@ mod tests {
         std::fs::write(temp.path().join("test.md"), "line 1\nline 2\nline 3\n").unwrap();
 
         let json = r#"{"marks": [{"path": "test.md", "start_line": 1, "end_line": 2, "id": "test", "tags": [], "version": 1}]}"#;
-        let config = RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config = RenderConfig::new(crate::core::render::OutputFormat::Json);
 
-        let result = run_batch_mark(temp.path(), json, true, config);
+        let result = run_batch_mark(temp.path(), json, true, false, config);
         assert!(result.is_ok());
     }
 
@@ -1213,12 +2063,9 @@ mod tests {
         let temp = tempdir().unwrap();
 
         let json = "[]";
-        let config = RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config = RenderConfig::new(crate::core::render::OutputFormat::Json);
 
-        let result = run_batch_mark(temp.path(), json, true, config);
+        let result = run_batch_mark(temp.path(), json, true, false, config);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("No marks"));
     }
@@ -1229,12 +2076,9 @@ mod tests {
         let temp = tempdir().unwrap();
 
         let json = "not valid json";
-        let config = RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config = RenderConfig::new(crate::core::render::OutputFormat::Json);
 
-        let result = run_batch_mark(temp.path(), json, true, config);
+        let result = run_batch_mark(temp.path(), json, true, false, config);
         assert!(result.is_err());
     }
 
@@ -1245,10 +2089,7 @@ mod tests {
         let content = "line 1\n<!--Q:begin id=test v=1-->\nmarked\n<!--Q:end id=test-->\nline 2\n";
         std::fs::write(temp.path().join("test.md"), content).unwrap();
 
-        let config = RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config = RenderConfig::new(crate::core::render::OutputFormat::Json);
 
         let result = run_unmark(temp.path(), "test.md", "test", true, config);
         assert!(result.is_ok());
@@ -1265,10 +2106,7 @@ mod tests {
         let content = "line 1\n<!--Q:begin id=test v=1-->\nmarked\n<!--Q:end id=test-->\nline 2\n";
         std::fs::write(temp.path().join("test.md"), content).unwrap();
 
-        let config = RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config = RenderConfig::new(crate::core::render::OutputFormat::Json);
 
         let result = run_unmark(temp.path(), "test.md", "test", false, config);
         assert!(result.is_ok());
@@ -1285,10 +2123,7 @@ mod tests {
         use tempfile::tempdir;
         let temp = tempdir().unwrap();
 
-        let config = RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config = RenderConfig::new(crate::core::render::OutputFormat::Json);
 
         let result = run_unmark(temp.path(), "nonexistent.md", "test", false, config);
         assert!(result.is_err());
@@ -1301,10 +2136,7 @@ mod tests {
         let content = "line 1\nline 2\n";
         std::fs::write(temp.path().join("test.md"), content).unwrap();
 
-        let config = RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config = RenderConfig::new(crate::core::render::OutputFormat::Json);
 
         let result = run_unmark(temp.path(), "test.md", "nonexistent", false, config);
         assert!(result.is_err());
@@ -1319,13 +2151,15 @@ mod tests {
         let spec_json = r#"[{"path": "test.md", "start_line": 1, "end_line": 2, "id": "test", "tags": [], "version": 1}]"#;
         std::fs::write(temp.path().join("specs.json"), spec_json).unwrap();
 
-        let config = RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config = RenderConfig::new(crate::core::render::OutputFormat::Json);
 
-        let result =
-            run_batch_mark_from_file(temp.path(), &temp.path().join("specs.json"), true, config);
+        let result = run_batch_mark_from_file(
+            temp.path(),
+            &temp.path().join("specs.json"),
+            true,
+            false,
+            config,
+        );
         assert!(result.is_ok());
     }
 
@@ -1334,15 +2168,13 @@ mod tests {
         use tempfile::tempdir;
         let temp = tempdir().unwrap();
 
-        let config = RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config = RenderConfig::new(crate::core::render::OutputFormat::Json);
 
         let result = run_batch_mark_from_file(
             temp.path(),
             &temp.path().join("nonexistent.json"),
             true,
+            false,
             config,
         );
         assert!(result.is_err());
@@ -1368,4 +2200,421 @@ mod tests {
         let result = remove_markers(content, "test").unwrap();
         assert!(result.contains("marked"));
     }
+
+    #[test]
+    fn test_generate_auto_id() {
+        let content = "fn helper() {}\n\n// Entry point\nfn main() {}\n";
+        let taken = HashSet::new();
+        assert_eq!(
+            generate_auto_id("src/main.rs", content, 2, 4, &taken),
+            "main.entry-point"
+        );
+        assert_eq!(
+            generate_auto_id("docs/Guide.md", "\n\n", 1, 2, &taken),
+            "guide"
+        );
+
+        let taken: HashSet<String> = ["main.entry-point", "main.entry-point-2"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            generate_auto_id("src/main.rs", content, 3, 4, &taken),
+            "main.entry-point-3"
+        );
+    }
+
+    #[test]
+    fn test_run_batch_mark_auto_id_unique() {
+        use tempfile::tempdir;
+        let temp = tempdir().unwrap();
+        std::fs::write(
+            temp.path().join("notes.md"),
+            "<!--Q:begin id=notes.intro v=1-->\nold\n<!--Q:end id=notes.intro-->\n# Intro\nA\n# Intro\nB\n",
+        )
+        .unwrap();
+
+        let json = r#"[{"path": "notes.md", "start_line": 4, "end_line": 5},
+                       {"path": "notes.md", "start_line": 6, "end_line": 7}]"#;
+        let mut specs: Vec<MarkSpec> = serde_json::from_str(json).unwrap();
+        assign_auto_ids(temp.path(), &mut specs).unwrap();
+        assert_eq!(specs[0].id, "notes.intro-2");
+        assert_eq!(specs[1].id, "notes.intro-3");
+
+        let results = mark_batch(temp.path(), specs, false).unwrap();
+        assert!(results.iter().all(|r| r.success));
+        let item = results[0].to_result_item();
+        assert!(item.data.unwrap()["id"]
+            .as_str()
+            .unwrap()
+            .starts_with("notes.intro-"));
+    }
+
+    #[test]
+    fn test_insert_markers_rejects_empty_id() {
+        let spec = MarkSpec {
+            path: "test.md".to_string(),
+            start_line: 1,
+            end_line: 1,
+            id: String::new(),
+            tags: vec![],
+            version: 1,
+        };
+        assert!(insert_markers("line\n", &spec).is_err());
+    }
+
+    fn spec(start_line: u32, end_line: u32, id: &str) -> MarkSpec {
+        MarkSpec {
+            path: "test.md".to_string(),
+            start_line,
+            end_line,
+            id: id.to_string(),
+            tags: vec![],
+            version: 1,
+        }
+    }
+
+    #[test]
+    fn test_insert_markers_nests_around_existing_anchor() {
+        let content = "a\n<!--Q:begin id=inner v=1-->\nb\n<!--Q:end id=inner-->\nc\n";
+
+        // Wrapping the whole inner anchor nests it
+        let result = insert_markers(content, &spec(1, 5, "outer")).unwrap();
+        let anchors = parse_content(&result, "test.md");
+        assert_eq!(anchors.len(), 2);
+        let outer = anchors.iter().find(|a| a.id == "outer").unwrap();
+        assert_eq!((outer.range.start, outer.range.end), (1, 7));
+
+        // Marking inside the inner body nests under it
+        let result = insert_markers(content, &spec(3, 3, "leaf")).unwrap();
+        assert_eq!(parse_content(&result, "test.md").len(), 2);
+    }
+
+    #[test]
+    fn test_insert_markers_rejects_partial_overlap() {
+        let content = "a\n<!--Q:begin id=inner v=1-->\nb\n<!--Q:end id=inner-->\nc\n";
+
+        let err = insert_markers(content, &spec(1, 3, "outer")).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.starts_with(ANCHOR_CONFLICT));
+        assert!(msg.contains("'inner'"));
+
+        assert!(insert_markers(content, &spec(3, 5, "outer")).is_err());
+    }
+
+    #[test]
+    fn test_mark_batch_nested_specs() {
+        use tempfile::tempdir;
+        let temp = tempdir().unwrap();
+        let content: String = (1..=20).map(|n| format!("line {}\n", n)).collect();
+        std::fs::write(temp.path().join("test.md"), content).unwrap();
+
+        let specs = vec![
+            spec(5, 20, "outer"),
+            spec(10, 15, "inner"),
+            spec(5, 8, "head"),
+        ];
+
+        let results = mark_batch(temp.path(), specs, false).unwrap();
+        assert!(results.iter().all(|r| r.success), "{:?}", results);
+
+        let written = std::fs::read_to_string(temp.path().join("test.md")).unwrap();
+        let anchors = parse_content(&written, "test.md");
+        assert_eq!(anchors.len(), 3);
+        let outer = anchors.iter().find(|a| a.id == "outer").unwrap();
+        let body = outer.content.as_deref().unwrap();
+        assert!(body.starts_with("<!--Q:begin id=head"));
+        assert!(body.ends_with("line 20"));
+    }
+
+    #[test]
+    fn test_rename_markers_preserves_tags_and_version() {
+        let content = "<!--Q:begin id=sample.main tags=a,b v=3-->\r\nbody\r\n<!--Q:end id=sample.main-->\r\n<!--Q:begin id=sample.main.x-->\n";
+        let (renamed, count) = rename_markers(content, "sample.main", "core.entry");
+        assert_eq!(count, 2);
+        assert_eq!(
+            renamed,
+            "<!--Q:begin id=core.entry tags=a,b v=3-->\r\nbody\r\n<!--Q:end id=core.entry-->\r\n<!--Q:begin id=sample.main.x-->\n"
+        );
+    }
+
+    #[test]
+    fn test_rename_anchor_across_files_and_collision() {
+        use tempfile::tempdir;
+        let temp = tempdir().unwrap();
+        let marked =
+            |id: &str| format!("<!--Q:begin id={} v=1-->\nx\n<!--Q:end id={}-->\n", id, id);
+        fs::write(temp.path().join("a.md"), marked("old")).unwrap();
+        fs::write(temp.path().join("b.md"), marked("old")).unwrap();
+        fs::write(temp.path().join("c.md"), marked("taken")).unwrap();
+
+        let err = rename_anchor(temp.path(), "old", "taken", false, false).unwrap_err();
+        assert!(err.to_string().contains("c.md"));
+        assert!(rename_anchor(temp.path(), "missing", "new", false, false).is_err());
+
+        let results = rename_anchor(temp.path(), "old", "new", false, false).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.success && r.id == "new"));
+        assert_eq!(
+            fs::read_to_string(temp.path().join("a.md")).unwrap(),
+            marked("new")
+        );
+
+        // --force allows renaming onto an existing id
+        let results = rename_anchor(temp.path(), "new", "taken", true, true).unwrap();
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn test_take_and_insert_anchor_block() {
+        let content = "a\n<!--Q:begin id=x v=1-->\nbody\n<!--Q:end id=x-->\nb\n";
+        let (rest, block) = take_anchor_block(content, "x").unwrap();
+        assert_eq!(rest, "a\nb\n");
+        assert_eq!(
+            block,
+            ["<!--Q:begin id=x v=1-->", "body", "<!--Q:end id=x-->"]
+        );
+        assert!(take_anchor_block(content, "missing").is_err());
+
+        assert_eq!(
+            insert_block("1\n2\n", &block, Some(2)).unwrap(),
+            "1\n<!--Q:begin id=x v=1-->\nbody\n<!--Q:end id=x-->\n2\n"
+        );
+        assert_eq!(
+            insert_block("1", &block, None).unwrap(),
+            "1\n<!--Q:begin id=x v=1-->\nbody\n<!--Q:end id=x-->"
+        );
+        assert_eq!(
+            insert_block("", &block, None).unwrap(),
+            "<!--Q:begin id=x v=1-->\nbody\n<!--Q:end id=x-->\n"
+        );
+        assert!(insert_block("1\n", &block, Some(3)).is_err());
+        assert!(insert_block("1\n", &block, Some(0)).is_err());
+    }
+
+    #[test]
+    fn test_move_anchor() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let src = "intro\n<!--Q:begin id=sec v=1-->\n<!--Q:begin id=inner v=1-->\nx\n\
+<!--Q:end id=inner-->\n<!--Q:end id=sec-->\noutro\n";
+        std::fs::write(root.join("old.md"), src).unwrap();
+        std::fs::write(root.join("new.md"), "one\ntwo\n").unwrap();
+
+        // Dry run and failed moves leave both files untouched
+        let results = move_anchor(root, "old.md", "sec", "new.md", Some(2), true).unwrap();
+        assert!(results.iter().all(|r| r.success));
+        assert!(move_anchor(root, "old.md", "sec", "new.md", Some(9), false).is_err());
+        assert!(move_anchor(root, "old.md", "nope", "new.md", None, false).is_err());
+        assert!(move_anchor(root, "old.md", "sec", "old.md", None, false).is_err());
+        assert_eq!(std::fs::read_to_string(root.join("old.md")).unwrap(), src);
+        assert_eq!(
+            std::fs::read_to_string(root.join("new.md")).unwrap(),
+            "one\ntwo\n"
+        );
+
+        let results = move_anchor(root, "old.md", "sec", "new.md", Some(2), false).unwrap();
+        assert_eq!(results[0].path, "old.md");
+        assert_eq!(results[0].lines_affected, Some((2, 6)));
+        assert_eq!(results[1].path, "new.md");
+        assert_eq!(results[1].lines_affected, Some((2, 6)));
+        assert_eq!(
+            std::fs::read_to_string(root.join("old.md")).unwrap(),
+            "intro\noutro\n"
+        );
+        let moved = parse_file(&root.join("new.md"), "new.md");
+        let ids: Vec<_> = moved.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, ["inner", "sec"]);
+        assert_eq!(moved[1].range.start, 2);
+
+        // The id now exists in the destination, so moving it back in is refused
+        std::fs::write(root.join("old.md"), src).unwrap();
+        assert!(move_anchor(root, "old.md", "sec", "new.md", None, false).is_err());
+
+        // So is a block whose nested anchor clashes with the destination
+        std::fs::write(
+            root.join("other.md"),
+            "<!--Q:begin id=inner v=1-->\ny\n<!--Q:end id=inner-->\n",
+        )
+        .unwrap();
+        let err = move_anchor(root, "old.md", "sec", "other.md", None, false).unwrap_err();
+        assert!(err.to_string().contains("'inner' already exists"));
+        assert_eq!(std::fs::read_to_string(root.join("old.md")).unwrap(), src);
+
+        // A missing destination is created
+        move_anchor(root, "old.md", "sec", "fresh.md", None, false).unwrap();
+        assert!(std::fs::read_to_string(root.join("fresh.md"))
+            .unwrap()
+            .starts_with("<!--Q:begin id=sec v=1-->\n"));
+    }
+
+    #[test]
+    fn test_move_anchor_across_comment_styles() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::write(
+            root.join("m.rs"),
+            "fn a() {}\n// Q:begin id=api tags=x,y v=2 hash=00ff\nfn b() {}\n    // Q:begin id=inner\n    fn c() {}\n    // Q:end id=inner\n// Q:end id=api\n",
+        )
+        .unwrap();
+
+        move_anchor(root, "m.rs", "api", "d.md", None, false).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join("d.md")).unwrap(),
+            "<!--Q:begin id=api tags=x,y v=2 hash=00ff-->\nfn b() {}\n    <!--Q:begin id=inner-->\n    fn c() {}\n    <!--Q:end id=inner-->\n<!--Q:end id=api-->\n"
+        );
+
+        // And back into code: HTML comments would not compile in a .py file
+        move_anchor(root, "d.md", "api", "p.py", None, false).unwrap();
+        let moved = std::fs::read_to_string(root.join("p.py")).unwrap();
+        assert!(moved.starts_with("# Q:begin id=api tags=x,y v=2 hash=00ff\n"));
+        assert!(moved.ends_with("    # Q:end id=inner\n# Q:end id=api\n"));
+        let anchors = parse_content(&moved, "p.py");
+        assert_eq!(anchors.len(), 2);
+        assert_eq!(anchors[1].tags, vec!["x", "y"]);
+        assert_eq!(anchors[1].version, 2);
+    }
+
+    #[test]
+    fn test_comment_style_for_path() {
+        assert_eq!(CommentStyle::for_path("README.md"), CommentStyle::Html);
+        assert_eq!(CommentStyle::for_path("app/main.py"), CommentStyle::Hash);
+        assert_eq!(CommentStyle::for_path("src/lib.RS"), CommentStyle::Slash);
+        assert_eq!(CommentStyle::for_path("Makefile"), CommentStyle::Html);
+        assert_eq!(
+            generate_begin_marker("x", &["a".to_string()], 1, None, CommentStyle::Hash),
+            "# Q:begin id=x tags=a v=1"
+        );
+        assert_eq!(
+            generate_end_marker("x", CommentStyle::Slash),
+            "// Q:end id=x"
+        );
+    }
+
+    #[test]
+    fn test_mark_and_unmark_code_files_roundtrip() {
+        let content = "def main():\n    pass\n";
+        let mut spec = spec(1, 2, "py.main");
+        spec.path = "app.py".to_string();
+
+        let marked = insert_markers(content, &spec).unwrap();
+        let hash = hash_bytes(b"def main():\n    pass", HashAlgorithm::Xxh3);
+        assert_eq!(
+            marked,
+            format!(
+                "# Q:begin id=py.main v=1 hash={}\ndef main():\n    pass\n# Q:end id=py.main\n",
+                hash
+            )
+        );
+        let anchors = parse_content(&marked, "app.py");
+        assert_eq!(anchors.len(), 1);
+        assert_eq!(anchors[0].content.as_deref(), Some("def main():\n    pass"));
+        // The recorded hash matches the parsed body hash
+        assert_eq!(
+            anchors[0].content_hash.as_deref(),
+            Some(anchors[0].hash.as_str())
+        );
+
+        assert_eq!(remove_markers(&marked, "py.main").unwrap(), content);
+        let (renamed, count) = rename_markers(&marked, "py.main", "py.entry");
+        assert_eq!(count, 2);
+        assert_eq!(parse_content(&renamed, "app.py")[0].id, "py.entry");
+    }
+
+    #[test]
+    fn test_replace_content() {
+        let content =
+            "intro\n<!--Q:begin id=sec tags=a v=2-->\nold 1\nold 2\n<!--Q:end id=sec-->\noutro";
+        let replaced = replace_content(content, "sec", "new body\n").unwrap();
+        assert_eq!(
+            replaced,
+            "intro\n<!--Q:begin id=sec tags=a v=2-->\nnew body\n<!--Q:end id=sec-->\noutro"
+        );
+
+        let emptied = replace_content("# Q:begin id=x v=1\na\n# Q:end id=x\n", "x", "").unwrap();
+        assert_eq!(emptied, "# Q:begin id=x v=1\n# Q:end id=x\n");
+    }
+
+    #[test]
+    fn test_edits_keep_content_hashes_current() {
+        let is_fresh = |content: &str, id: &str| {
+            let anchor = parse_content(content, "doc.md")
+                .into_iter()
+                .find(|a| a.id == id)
+                .unwrap();
+            anchor.content_hash.as_deref() == Some(anchor.hash.as_str())
+        };
+        // Marking inside a hashed anchor leaves it unchanged
+        let content = insert_markers("a\nb\nc\n", &spec(1, 3, "outer")).unwrap();
+        let content = insert_markers(&content, &spec(3, 3, "inner")).unwrap();
+        assert!(is_fresh(&content, "outer"));
+        assert!(is_fresh(&content, "inner"));
+
+        // Replacing a body re-records it and the anchors around it
+        let replaced = replace_content(&content, "inner", "new\n").unwrap();
+        assert!(is_fresh(&replaced, "inner"));
+        assert!(is_fresh(&replaced, "outer"));
+
+        // An anchor that was already stale stays reported
+        let stale = content.replacen("\na\n", "\nedited\n", 1);
+        assert!(!is_fresh(&stale, "outer"));
+        let replaced = replace_content(&stale, "inner", "new\n").unwrap();
+        assert!(!is_fresh(&replaced, "outer"));
+
+        let (rebased, updated) = rewrite_hashes(&replaced, |_| true);
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].id, "outer");
+        assert!(is_fresh(&rebased, "outer"));
+    }
+
+    #[test]
+    fn test_replace_content_errors() {
+        let err = replace_content("no anchors\n", "sec", "x").unwrap_err();
+        assert!(err.to_string().contains("not found"));
+
+        let err = replace_content("<!--Q:begin id=sec-->\nbody\n", "sec", "x").unwrap_err();
+        assert!(err.to_string().contains("no end marker"));
+    }
+
+    #[test]
+    fn test_range_id() {
+        assert_eq!(range_id("src/main.rs", 10, 25), "src_main_rs.L10-25");
+        assert_eq!(range_id("./docs/a-b.md", 1, 1), "docs_a_b_md.L1-1");
+    }
+
+    #[test]
+    fn test_mark_file_without_id_uses_range_id() {
+        use tempfile::tempdir;
+        let temp = tempdir().unwrap();
+        std::fs::write(
+            temp.path().join("test.md"),
+            "<!--Q:begin id=test_md.L1-2 v=1-->\na\n<!--Q:end id=test_md.L1-2-->\nb\nc\n",
+        )
+        .unwrap();
+
+        let result = mark_file(temp.path(), &spec(4, 5, ""), true).unwrap();
+        assert!(result.success);
+        assert_eq!(result.id, "test_md.L4-5");
+
+        // Collides with the existing anchor, so a suffix is appended
+        let result = mark_file(temp.path(), &spec(1, 2, ""), true).unwrap();
+        assert_eq!(result.id, "test_md.L1-2-2");
+    }
+
+    #[test]
+    fn test_mark_batch_without_ids_never_clash() {
+        use tempfile::tempdir;
+        let temp = tempdir().unwrap();
+        let content: String = (1..=10).map(|n| format!("line {}\n", n)).collect();
+        std::fs::write(temp.path().join("test.md"), content).unwrap();
+
+        let specs = vec![spec(1, 3, ""), spec(1, 3, ""), spec(5, 6, "")];
+        let results = mark_batch(temp.path(), specs, false).unwrap();
+        let mut ids: Vec<&str> = results.iter().map(|r| r.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["test_md.L1-3", "test_md.L1-3-2", "test_md.L5-6"]);
+
+        let written = std::fs::read_to_string(temp.path().join("test.md")).unwrap();
+        assert_eq!(parse_content(&written, "test.md").len(), 3);
+    }
 }
@@ -2,6 +2,7 @@
 //!
 //! Anchors are markers in documents that allow referencing specific sections.
 //! Format: <!--Q:begin id=xxx tags=a,b v=1--> ... <!--Q:end id=xxx-->
+//! (or `# Q:begin ...` / `// Q:begin ...` line comments in code files)
 
 pub mod api;
 pub mod lint;
@@ -1,9 +1,15 @@
 //! Anchor parsing module
 //!
 //! Parses anchor markers from files:
-//! <!--Q:begin id=xxx tags=a,b v=1-->
+//! <!--Q:begin id=xxx tags=a,b v=1 hash=0123456789abcdef-->
 //! ...content...
 //! <!--Q:end id=xxx-->
+//!
+//! Line-comment forms are recognised too, so markers read naturally in code:
+//! `# Q:begin id=xxx` / `# Q:end id=xxx` and `// Q:begin id=xxx` / `// Q:end id=xxx`.
+//! Every marker must start its line (leading whitespace is allowed), so marker
+//! text inside a string literal or a trailing comment is ignored; the
+//! line-comment forms must also end the line.
 
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -15,16 +21,20 @@ use crate::core::model::{Meta, Range, RangeLine};
 use crate::core::util::{hash_bytes, HashAlgorithm};
 
 /// Static regex for parsing anchor begin markers
-/// Format: <!--Q:begin id=xxx tags=a,b v=1-->
+/// Format: <!--Q:begin id=xxx tags=a,b v=1 hash=...-->, # Q:begin ..., // Q:begin ...
 pub static BEGIN_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r#"<!--\s*Q:begin\s+id=([^\s]+)(?:\s+tags=([^\s]+))?(?:\s+v=(\d+))?\s*-->"#)
-        .expect("Invalid BEGIN_RE regex")
+    Regex::new(
+        r#"^\s*(?:<!--|#|//)\s*Q:begin\s+id=([^\s]+?)(?:\s+tags=([^\s]+?))?(?:\s+v=(\d+))?(?:\s+hash=([0-9a-f]+))?\s*(?:-->|$)"#,
+    )
+    .expect("Invalid BEGIN_RE regex")
 });
 
 /// Static regex for parsing anchor end markers
-/// Format: <!--Q:end id=xxx-->
-pub static END_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r#"<!--\s*Q:end\s+id=([^\s]+)\s*-->"#).expect("Invalid END_RE regex"));
+/// Format: <!--Q:end id=xxx-->, # Q:end id=xxx, // Q:end id=xxx
+pub static END_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^\s*(?:<!--|#|//)\s*Q:end\s+id=([^\s]+?)\s*(?:-->|$)"#)
+        .expect("Invalid END_RE regex")
+});
 
 /// Anchor definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +57,10 @@ pub struct Anchor {
     /// Content hash
     pub hash: String,
 
+    /// Content hash recorded in the begin marker when the anchor was marked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+
     /// The content between begin and end markers
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
@@ -58,6 +72,7 @@ struct BeginMarker {
     id: String,
     tags: Vec<String>,
     version: u32,
+    content_hash: Option<String>,
     line: u32,
 }
 
@@ -120,11 +135,13 @@ pub fn parse_content(content: &str, path: &str) -> Vec<Anchor> {
                 .get(3)
                 .and_then(|m| m.as_str().parse().ok())
                 .unwrap_or(1);
+            let content_hash = caps.get(4).map(|m| m.as_str().to_string());
 
             open_markers.push(BeginMarker {
                 id,
                 tags,
                 version,
+                content_hash,
                 line: line_num,
             });
         }
@@ -149,7 +166,7 @@ pub fn parse_content(content: &str, path: &str) -> Vec<Anchor> {
 
                 let hash = anchor_content
                     .as_ref()
-                    .map(|c| hash_bytes(c.as_bytes(), HashAlgorithm::Xxh3))
+                    .map(|_| body_hash(&lines[content_start..content_end]))
                     .unwrap_or_default();
 
                 anchors.push(Anchor {
@@ -160,8 +177,11 @@ pub fn parse_content(content: &str, path: &str) -> Vec<Anchor> {
                     range: RangeLine {
                         start: begin.line,
                         end: line_num,
+                        start_col: None,
+                        end_col: None,
                     },
                     hash,
+                    content_hash: begin.content_hash,
                     content: anchor_content,
                 });
             }
@@ -171,6 +191,19 @@ pub fn parse_content(content: &str, path: &str) -> Vec<Anchor> {
     anchors
 }
 
+/// Hash of an anchor body, skipping nested marker lines
+///
+/// Nested anchors can then be marked, renamed or removed without changing the
+/// hash of the anchors around them.
+pub fn body_hash(lines: &[&str]) -> String {
+    let body: Vec<&str> = lines
+        .iter()
+        .copied()
+        .filter(|line| !BEGIN_RE.is_match(line) && !END_RE.is_match(line))
+        .collect();
+    hash_bytes(body.join("\n").as_bytes(), HashAlgorithm::Xxh3)
+}
+
 /// Convert anchor to ResultItem
 impl Anchor {
     pub fn to_result_item(&self) -> crate::core::model::ResultItem {
@@ -263,4 +296,57 @@ Content without tags
         assert!(anchors[0].tags.is_empty());
         assert_eq!(anchors[0].version, 1);
     }
+
+    #[test]
+    fn test_parse_line_comment_styles() {
+        let content = "# Q:begin id=py.main tags=entry v=2\nprint('hi')\n# Q:end id=py.main\n\
+// Q:begin id=rs.main\nfn main() {}\n    // Q:end id=rs.main\r\n\
+let s = \"# Q:begin id=not.an.anchor\";\n\
+let t = \"<!--Q:begin id=in.string-->\";\n\
+call(); // Q:begin id=trailing.comment\n";
+        let anchors = parse_content(content, "mixed.txt");
+        assert_eq!(anchors.len(), 2);
+        assert_eq!(anchors[0].id, "py.main");
+        assert_eq!(anchors[0].tags, vec!["entry"]);
+        assert_eq!(anchors[0].version, 2);
+        assert_eq!(anchors[0].content.as_deref(), Some("print('hi')"));
+        assert_eq!(anchors[1].id, "rs.main");
+    }
+
+    #[test]
+    fn test_parse_html_marker_ids_stop_at_terminator() {
+        let content = "<!--Q:begin id=a-b tags=x,y-->\nbody\n<!--Q:end id=a-b-->\n";
+        let anchors = parse_content(content, "test.md");
+        assert_eq!(anchors.len(), 1);
+        assert_eq!(anchors[0].id, "a-b");
+        assert_eq!(anchors[0].tags, vec!["x", "y"]);
+    }
+
+    #[test]
+    fn test_body_hash_skips_nested_markers() {
+        let content = "<!--Q:begin id=outer-->\na\n<!--Q:begin id=inner-->\nb\n\
+<!--Q:end id=inner-->\n<!--Q:end id=outer-->\n";
+        let anchors = parse_content(content, "doc.md");
+        let outer = anchors.iter().find(|a| a.id == "outer").unwrap();
+        assert_eq!(outer.hash, hash_bytes(b"a\nb", HashAlgorithm::Xxh3));
+
+        // Marker text that does not start the line is body, not a marker
+        let lines = ["x = \"<!--Q:end id=outer-->\"", "y // Q:begin id=z"];
+        assert_eq!(
+            body_hash(&lines),
+            hash_bytes(lines.join("\n").as_bytes(), HashAlgorithm::Xxh3)
+        );
+    }
+
+    #[test]
+    fn test_parse_content_hash_attribute() {
+        let content = "<!--Q:begin id=h tags=a v=2 hash=00ff-->\nbody\n<!--Q:end id=h-->\n\
+# Q:begin id=plain v=1\nbody\n# Q:end id=plain\n";
+        let anchors = parse_content(content, "test.md");
+        assert_eq!(anchors.len(), 2);
+        assert_eq!(anchors[0].id, "h");
+        assert_eq!(anchors[0].version, 2);
+        assert_eq!(anchors[0].content_hash.as_deref(), Some("00ff"));
+        assert_eq!(anchors[1].content_hash, None);
+    }
 }
@@ -2,22 +2,33 @@
 //!
 //! Calls sg/ast-grep and parses the output to ResultItems
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use serde::Deserialize;
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use crate::core::model::{MiseError, Range, ResultItem, ResultSet, SourceMode};
+use crate::backends::scan::GlobFilter;
+use crate::core::model::{Confidence, MiseError, Range, ResultItem, ResultSet, SourceMode};
 use crate::core::paths::make_relative;
+use crate::core::policy::is_denied;
 use crate::core::render::{RenderConfig, Renderer};
 use crate::core::util::command_exists;
 
 /// Options for the ast command
 #[derive(Debug, Default)]
 pub struct AstOptions {
-    pub include: Vec<String>,
-    pub exclude: Vec<String>,
+    /// Include/exclude globs over root-relative paths
+    pub filter: GlobFilter,
     pub lang: Option<String>,
+    /// ast-grep worker threads (0 = ast-grep's default)
+    pub threads: usize,
+    /// Rewrite template passed to `sg run --rewrite`
+    pub rewrite: Option<String>,
+    /// With `rewrite`, write the replacements to disk instead of previewing them
+    pub apply: bool,
+    /// ast-grep YAML rule file; runs `sg scan --rule` instead of `sg run --pattern`
+    pub rule: Option<PathBuf>,
 }
 
 /// Check which ast-grep command is available
@@ -39,12 +50,39 @@ struct SgMatch {
     text: String,
     #[serde(default)]
     lines: String,
+    /// Rewritten text (present with `--rewrite`)
+    #[serde(default)]
+    replacement: Option<String>,
+    /// Rule id (present with `sg scan`)
+    #[serde(default, rename = "ruleId")]
+    rule_id: Option<String>,
+    #[serde(default)]
+    severity: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// Map an ast-grep rule severity to result confidence
+fn severity_confidence(severity: Option<&str>) -> Confidence {
+    match severity {
+        Some("error") => Confidence::High,
+        Some("warning") => Confidence::Medium,
+        _ => Confidence::Low,
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct SgRange {
     start: SgPosition,
     end: SgPosition,
+    #[serde(default, rename = "byteOffset")]
+    byte_offset: Option<SgByteOffset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SgByteOffset {
+    start: usize,
+    end: usize,
 }
 
 #[derive(Debug, Deserialize)]
@@ -72,11 +110,27 @@ pub fn run_ast_grep(
     };
 
     let mut cmd = Command::new(cmd_name);
-    cmd.arg("run").arg("--pattern").arg(pattern).arg("--json");
+    if let Some(rule) = &options.rule {
+        if !rule.is_file() {
+            bail!("Rule file not found: {}", rule.display());
+        }
+        // The rule file carries its own pattern and language
+        cmd.arg("scan").arg("--rule").arg(rule).arg("--json");
+    } else {
+        cmd.arg("run").arg("--pattern").arg(pattern).arg("--json");
+
+        // Add language option
+        if let Some(lang) = &options.lang {
+            cmd.arg("--lang").arg(lang);
+        }
+    }
 
-    // Add language option
-    if let Some(lang) = &options.lang {
-        cmd.arg("--lang").arg(lang);
+    if options.threads > 0 {
+        cmd.arg("--threads").arg(options.threads.to_string());
+    }
+
+    if let Some(rewrite) = &options.rewrite {
+        cmd.arg("--rewrite").arg(rewrite);
     }
 
     // Add scope paths
@@ -92,6 +146,8 @@ pub fn run_ast_grep(
     let stdout = String::from_utf8_lossy(&output.stdout);
 
     let mut result_set = ResultSet::new();
+    // path -> (start, end, replacement) edits, applied after all matches are filtered
+    let mut edits: BTreeMap<String, Vec<(usize, usize, String)>> = BTreeMap::new();
 
     // Try to parse as JSON array
     if let Ok(matches) = serde_json::from_str::<Vec<SgMatch>>(&stdout) {
@@ -99,57 +155,129 @@ pub fn run_ast_grep(
             let relative_path =
                 make_relative(Path::new(&m.file), root).unwrap_or_else(|| m.file.clone());
 
-            // Apply include/exclude filters
-            if !options.include.is_empty() {
-                let matched = options
-                    .include
-                    .iter()
-                    .any(|glob| glob_match(glob, &relative_path));
-                if !matched {
-                    continue;
-                }
+            if is_denied(&relative_path) {
+                continue;
             }
-            if options
-                .exclude
-                .iter()
-                .any(|glob| glob_match(glob, &relative_path))
-            {
+
+            // Apply include/exclude filters
+            if !options.filter.matches(&relative_path) {
                 continue;
             }
 
             let range = Range::lines(m.range.start.line + 1, m.range.end.line + 1);
+
+            if let (Some(replacement), Some(offset)) = (&m.replacement, &m.range.byte_offset) {
+                let content = std::fs::read_to_string(&m.file)
+                    .with_context(|| format!("Failed to read {}", relative_path))?;
+                let Some(diff) = rewrite_diff(&content, offset.start, offset.end, replacement)
+                else {
+                    // The file changed since ast-grep read it
+                    let mut item = ResultItem::error(MiseError::new(
+                        "AST_REWRITE_RANGE_INVALID",
+                        format!(
+                            "Rewrite range {}..{} does not fit the current file contents",
+                            offset.start, offset.end
+                        ),
+                    ));
+                    item.path = Some(relative_path);
+                    result_set.push(item);
+                    continue;
+                };
+
+                let mut item = ResultItem::match_result(&relative_path, range, diff);
+                item.source_mode = SourceMode::AstGrep;
+                item.data = Some(serde_json::json!({
+                    "text": m.text,
+                    "replacement": replacement,
+                    "applied": options.apply,
+                }));
+                result_set.push(item);
+
+                edits.entry(relative_path).or_default().push((
+                    offset.start,
+                    offset.end,
+                    replacement.clone(),
+                ));
+                continue;
+            }
+
             let excerpt = if m.lines.is_empty() { m.text } else { m.lines };
 
             let mut item = ResultItem::match_result(relative_path, range, excerpt);
             item.source_mode = SourceMode::AstGrep;
+            if let Some(rule_id) = m.rule_id {
+                item.confidence = severity_confidence(m.severity.as_deref());
+                item.data = Some(serde_json::json!({
+                    "rule_id": rule_id,
+                    "severity": m.severity,
+                    "message": m.message,
+                }));
+            }
 
             result_set.push(item);
         }
     }
 
+    if options.apply {
+        for (path, file_edits) in edits {
+            let file = root.join(&path);
+            let content = std::fs::read_to_string(&file)
+                .with_context(|| format!("Failed to read {}", path))?;
+            std::fs::write(&file, apply_rewrites(&content, file_edits))
+                .with_context(|| format!("Failed to write {}", path))?;
+        }
+    }
+
     result_set.sort();
     Ok(result_set)
 }
 
-/// Simple glob matching (supports * and **)
-fn glob_match(pattern: &str, path: &str) -> bool {
-    // Convert glob pattern to regex-like matching
-    if pattern.starts_with("*.") {
-        // Extension match: *.rs -> ends with .rs
-        let ext = &pattern[1..];
-        path.ends_with(ext)
-    } else if pattern.ends_with("/*") {
-        // Directory match: vendor/* -> starts with vendor/
-        let prefix = &pattern[..pattern.len() - 1];
-        path.starts_with(prefix)
-    } else if pattern.contains('*') {
-        // Generic wildcard - simple contains check for the non-wildcard part
-        let parts: Vec<&str> = pattern.split('*').filter(|s| !s.is_empty()).collect();
-        parts.iter().all(|part| path.contains(part))
-    } else {
-        // Exact match
-        path == pattern || path.contains(pattern)
+/// Diff-style excerpt of a rewrite: the affected lines prefixed `- `, then the rewritten lines `+ `
+///
+/// Returns `None` when `start..end` is not a valid range of `content`.
+fn rewrite_diff(content: &str, start: usize, end: usize, replacement: &str) -> Option<String> {
+    let before = content.get(..start)?;
+    let after = content.get(end..)?;
+    content.get(start..end)?;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line_end = after.find('\n').map_or(content.len(), |i| end + i);
+
+    let old = &content[line_start..line_end];
+    let new = format!(
+        "{}{}{}",
+        &content[line_start..start],
+        replacement,
+        &content[end..line_end]
+    );
+
+    let mut diff = String::new();
+    for line in old.lines() {
+        diff.push_str(&format!("- {}\n", line));
+    }
+    for line in new.lines() {
+        diff.push_str(&format!("+ {}\n", line));
     }
+    diff.pop();
+    Some(diff)
+}
+
+/// Splice byte-range replacements into `content`
+///
+/// Edits are applied back to front so earlier offsets stay valid; an edit
+/// overlapping one already applied, or outside `content`, is skipped.
+fn apply_rewrites(content: &str, mut edits: Vec<(usize, usize, String)>) -> String {
+    edits.sort_by_key(|e| std::cmp::Reverse(e.0));
+
+    let mut result = content.to_string();
+    let mut applied_start = usize::MAX;
+    for (start, end, replacement) in edits {
+        if end > applied_start || result.get(start..end).is_none() {
+            continue;
+        }
+        result.replace_range(start..end, &replacement);
+        applied_start = start;
+    }
+    result
 }
 
 /// Run the ast command
@@ -163,7 +291,7 @@ pub fn run_ast(
     let result_set = run_ast_grep(root, pattern, scopes, &options)?;
 
     let renderer = Renderer::with_config(config);
-    println!("{}", renderer.render(&result_set));
+    print!("{}", renderer.render(&result_set));
 
     Ok(())
 }
@@ -171,6 +299,7 @@ pub fn run_ast(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backends::scan::glob_match;
 
     fn default_options() -> AstOptions {
         AstOptions::default()
@@ -280,10 +409,8 @@ mod tests {
         let temp = tempfile::tempdir().unwrap();
         std::fs::write(temp.path().join("test.rs"), "fn main() {}").unwrap();
 
-        let config = crate::core::render::RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config =
+            crate::core::render::RenderConfig::new(crate::core::render::OutputFormat::Json);
 
         let result = run_ast(
             temp.path(),
@@ -387,8 +514,9 @@ mod tests {
         assert!(glob_match("vendor/*", "vendor/lib.rs"));
         assert!(!glob_match("vendor/*", "src/main.rs"));
 
-        // Contains match
-        assert!(glob_match("test", "src/test.rs"));
+        // Name match, not substring
+        assert!(!glob_match("test", "src/test.rs"));
+        assert!(glob_match("test.rs", "src/test.rs"));
         assert!(glob_match("*test*", "src/test_util.rs"));
     }
 
@@ -402,15 +530,15 @@ mod tests {
     #[test]
     fn test_glob_match_directory_pattern() {
         assert!(glob_match("tests/*", "tests/unit.rs"));
-        assert!(glob_match("tests/*", "tests/integration/foo.rs"));
+        assert!(!glob_match("tests/*", "tests/integration/foo.rs"));
         assert!(!glob_match("tests/*", "src/tests.rs"));
     }
 
     #[test]
     fn test_ast_options_default() {
         let options = AstOptions::default();
-        assert!(options.include.is_empty());
-        assert!(options.exclude.is_empty());
+        assert!(options.filter.include.is_empty());
+        assert!(options.filter.exclude.is_empty());
         assert!(options.lang.is_none());
     }
 
@@ -422,7 +550,7 @@ mod tests {
             std::fs::write(temp.path().join("test.py"), "def main(): pass").unwrap();
 
             let options = AstOptions {
-                include: vec!["*.rs".to_string()],
+                filter: GlobFilter::new(vec!["*.rs".to_string()], vec![]).unwrap(),
                 ..Default::default()
             };
             let result =
@@ -446,7 +574,7 @@ mod tests {
             std::fs::write(temp.path().join("main_test.rs"), "fn test_main() {}").unwrap();
 
             let options = AstOptions {
-                exclude: vec!["*_test.rs".to_string()],
+                filter: GlobFilter::new(vec![], vec!["*_test.rs".to_string()]).unwrap(),
                 ..Default::default()
             };
             let result =
@@ -487,12 +615,93 @@ mod tests {
             std::fs::write(temp.path().join("lib.rs"), "fn lib() {}").unwrap();
 
             let options = AstOptions {
-                include: vec!["*.rs".to_string()],
-                exclude: vec!["*_test.rs".to_string()],
+                filter: GlobFilter::new(vec!["*.rs".to_string()], vec!["*_test.rs".to_string()])
+                    .unwrap(),
                 lang: Some("rust".to_string()),
+                threads: 2,
+                ..Default::default()
             };
             let result = run_ast_grep(temp.path(), "fn $NAME()", &[] as &[&Path], &options);
             assert!(result.is_ok());
         }
     }
+
+    #[test]
+    fn test_sg_match_with_replacement() {
+        let json = r#"{"file": "a.js", "range": {"start": {"line": 0}, "end": {"line": 0}, "byteOffset": {"start": 4, "end": 18}}, "text": "console.log(x)", "replacement": "logger.info(x)"}"#;
+        let m: SgMatch = serde_json::from_str(json).unwrap();
+        assert_eq!(m.replacement.as_deref(), Some("logger.info(x)"));
+        let offset = m.range.byte_offset.unwrap();
+        assert_eq!((offset.start, offset.end), (4, 18));
+    }
+
+    #[test]
+    fn test_rewrite_diff() {
+        let content = "let a = 1;\nif (a) console.log(a);\nreturn a;\n";
+        let start = content.find("console").unwrap();
+        let end = start + "console.log(a)".len();
+
+        assert_eq!(
+            rewrite_diff(content, start, end, "logger.info(a)").as_deref(),
+            Some("- if (a) console.log(a);\n+ if (a) logger.info(a);")
+        );
+        assert_eq!(
+            rewrite_diff(content, start, end, "{\n  log(a)\n}").as_deref(),
+            Some("- if (a) console.log(a);\n+ if (a) {\n+   log(a)\n+ };")
+        );
+
+        // Offsets past the end or inside a UTF-8 sequence are rejected
+        assert_eq!(rewrite_diff(content, start, content.len() + 1, "x"), None);
+        assert_eq!(rewrite_diff(content, end, start, "x"), None);
+        assert_eq!(rewrite_diff("日本", 1, 3, "x"), None);
+    }
+
+    #[test]
+    fn test_apply_rewrites() {
+        let content = "f(1); f(2); f(3);";
+        let edits = vec![
+            (0, 4, "g(1)".to_string()),
+            (12, 16, "g(3)".to_string()),
+            (6, 10, "g(22)".to_string()),
+        ];
+        assert_eq!(apply_rewrites(content, edits), "g(1); g(22); g(3);");
+
+        // Overlapping edits keep the later one
+        let edits = vec![(0, 10, "x".to_string()), (6, 10, "y".to_string())];
+        assert_eq!(apply_rewrites(content, edits), "f(1); y; f(3);");
+
+        // Edits outside the content are dropped rather than panicking
+        let edits = vec![(0, 4, "g(1)".to_string()), (16, 40, "z".to_string())];
+        assert_eq!(apply_rewrites(content, edits), "g(1); f(2); f(3);");
+    }
+
+    #[test]
+    fn test_sg_scan_match_deserialization() {
+        let json = r#"{"file": "a.ts", "range": {"start": {"line": 2}, "end": {"line": 2}}, "text": "console.log(x)", "ruleId": "no-console", "severity": "warning", "message": "Remove console.log"}"#;
+        let m: SgMatch = serde_json::from_str(json).unwrap();
+        assert_eq!(m.rule_id.as_deref(), Some("no-console"));
+        assert_eq!(m.severity.as_deref(), Some("warning"));
+        assert_eq!(m.message.as_deref(), Some("Remove console.log"));
+    }
+
+    #[test]
+    fn test_severity_confidence() {
+        assert_eq!(severity_confidence(Some("error")), Confidence::High);
+        assert_eq!(severity_confidence(Some("warning")), Confidence::Medium);
+        assert_eq!(severity_confidence(Some("hint")), Confidence::Low);
+        assert_eq!(severity_confidence(None), Confidence::Low);
+    }
+
+    #[test]
+    fn test_run_ast_grep_missing_rule_file() {
+        if get_ast_grep_command().is_some() {
+            let temp = tempfile::tempdir().unwrap();
+            let options = AstOptions {
+                rule: Some(temp.path().join("missing.yml")),
+                ..Default::default()
+            };
+            let err = run_ast_grep(temp.path(), "", &[] as &[&Path], &options).unwrap_err();
+            assert!(err.to_string().contains("Rule file not found"));
+        }
+    }
 }
@@ -4,28 +4,33 @@
 //! "what does this file depend on" and "what depends on this file".
 
 use anyhow::Result;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
 
 use crate::backends::ast_grep::get_ast_grep_command;
-use crate::backends::scan::{scan_files, ScanOptions};
+use crate::backends::scan::{scan_files, GlobFilter, ScanOptions};
 use crate::core::model::{Confidence, Kind, MiseError, ResultItem, ResultSet, SourceMode};
 use crate::core::paths::{make_relative, normalize_path};
-use crate::core::render::{RenderConfig, Renderer};
-use crate::core::util::command_exists;
+use crate::core::render::{BoxStyle, RenderConfig, Renderer};
+use crate::core::util::{command_exists, escape_xml};
 
 /// Supported languages for dependency analysis
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Language {
     Rust,
     TypeScript,
     JavaScript,
     Python,
+    Go,
     Unknown,
 }
 
@@ -37,6 +42,7 @@ impl Language {
             Some("ts") | Some("tsx") => Language::TypeScript,
             Some("js") | Some("jsx") | Some("mjs") | Some("cjs") => Language::JavaScript,
             Some("py") => Language::Python,
+            Some("go") => Language::Go,
             _ => Language::Unknown,
         }
     }
@@ -49,6 +55,7 @@ impl Language {
             Language::TypeScript => Some("typescript"),
             Language::JavaScript => Some("javascript"),
             Language::Python => Some("python"),
+            Language::Go => Some("go"),
             Language::Unknown => None,
         }
     }
@@ -61,6 +68,7 @@ impl Language {
             Language::TypeScript => &["ts", "tsx"],
             Language::JavaScript => &["js", "jsx", "mjs", "cjs"],
             Language::Python => &["py"],
+            Language::Go => &["go"],
             Language::Unknown => &[],
         }
     }
@@ -120,6 +128,10 @@ impl ImportPatterns {
                 ],
                 lang: "python",
             }),
+            Language::Go => Some(ImportPatterns {
+                patterns: vec!["import \"$PATH\"", "import $_ \"$PATH\"", "import ($$$_)"],
+                lang: "go",
+            }),
             Language::Unknown => None,
         }
     }
@@ -152,6 +164,97 @@ pub struct FileDeps {
     pub depended_by: Vec<String>,
 }
 
+impl FileDeps {
+    /// Attach resolution counts and the unique unresolved module names to `data`
+    ///
+    /// Counts are per import statement, so a file whose resolution fails
+    /// wholesale shows up as `resolved_count: 0` rather than as a leaf.
+    fn insert_resolution_stats(&self, data: &mut serde_json::Value) {
+        let resolved = self
+            .depends_on
+            .iter()
+            .filter(|d| d.resolved_path.is_some())
+            .count();
+        let mut unresolved: Vec<&str> = self
+            .depends_on
+            .iter()
+            .filter(|d| d.resolved_path.is_none())
+            .map(|d| d.module.as_str())
+            .collect();
+        unresolved.sort_unstable();
+        unresolved.dedup();
+
+        data["resolved_count"] = resolved.into();
+        data["unresolved_count"] = (self.depends_on.len() - resolved).into();
+        data["unresolved"] = serde_json::json!(unresolved);
+    }
+}
+
+/// Shape of the `data` payload on `deps` result items (published by `schema`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum DepsData {
+    File(DepsFileData),
+    Cycle(DepsCycleData),
+    Dir(DepsDirData),
+}
+
+/// Per-file dependency payload
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DepsFileData {
+    /// Resolved files this file imports (full graph and forward mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depends_on: Option<Vec<String>>,
+    /// Files importing this file (full graph and `--reverse` mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depended_by: Option<Vec<String>>,
+    pub language: Language,
+    /// Importing statements (`--reverse --show-imports`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub imports: Option<Vec<DepsImportData>>,
+    pub resolved_count: usize,
+    pub unresolved_count: usize,
+    /// Unique module names that could not be resolved
+    pub unresolved: Vec<String>,
+}
+
+/// One import statement that pulls in the queried file
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DepsImportData {
+    pub path: String,
+    pub line: u32,
+    pub import_text: String,
+}
+
+/// Payload of a `CIRCULAR_DEPENDENCY` item
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DepsCycleData {
+    pub cycle: Vec<String>,
+    pub cycle_length: usize,
+}
+
+/// Per-directory payload (`--group-by-dir`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DepsDirData {
+    /// Number of analyzed files in the directory
+    pub files: usize,
+    pub depends_on: Vec<DepsDirEdge>,
+    pub depended_by: Vec<DepsDirEdge>,
+}
+
+/// Weighted edge to another directory
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DepsDirEdge {
+    pub dir: String,
+    /// Number of file-level edges between the two directories
+    pub weight: usize,
+}
+
 /// The complete dependency graph
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DepGraph {
@@ -182,9 +285,10 @@ impl DepGraph {
             }
         }
 
-        // Update files with reverse deps
-        for (path, deps) in reverse_map {
+        // Update files with reverse deps (sorted, independent of map order)
+        for (path, mut deps) in reverse_map {
             if let Some(file_deps) = self.files.get_mut(&path) {
+                deps.sort();
                 file_deps.depended_by = deps;
             }
         }
@@ -198,6 +302,27 @@ impl DepGraph {
             .unwrap_or_default()
     }
 
+    /// Get the import statements in each dependent that resolve to the given file
+    pub fn get_reverse_imports(&self, path: &str) -> Vec<(String, Vec<&Dependency>)> {
+        let mut dependents = self.get_reverse_deps(path);
+        dependents.sort();
+        dependents.dedup();
+
+        dependents
+            .into_iter()
+            .filter_map(|source| {
+                let imports: Vec<&Dependency> = self
+                    .files
+                    .get(&source)?
+                    .depends_on
+                    .iter()
+                    .filter(|d| d.resolved_path.as_deref() == Some(path))
+                    .collect();
+                Some((source, imports))
+            })
+            .collect()
+    }
+
     /// Get files that the given file depends on
     pub fn get_forward_deps(&self, path: &str) -> Vec<String> {
         self.files
@@ -231,6 +356,14 @@ impl DepGraph {
         cycles
     }
 
+    /// Detect circular dependencies, reporting each cycle once
+    ///
+    /// Every cycle is rotated to start at its smallest member, so the same loop
+    /// reached from different entry points collapses into one entry.
+    pub fn unique_cycles(&self) -> Vec<Vec<String>> {
+        dedup_cycles(self.find_cycles())
+    }
+
     fn dfs_cycle(
         &self,
         node: &str,
@@ -261,6 +394,24 @@ impl DepGraph {
     }
 }
 
+/// Rotate each cycle to start at its smallest member and drop repeats
+fn dedup_cycles(cycles: Vec<Vec<String>>) -> Vec<Vec<String>> {
+    let unique: BTreeSet<Vec<String>> = cycles
+        .into_iter()
+        .map(|mut cycle| {
+            let start = cycle
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, p)| p.as_str())
+                .map(|(idx, _)| idx)
+                .unwrap_or(0);
+            cycle.rotate_left(start);
+            cycle
+        })
+        .collect();
+    unique.into_iter().collect()
+}
+
 /// Output format for deps command
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum DepsFormat {
@@ -271,6 +422,7 @@ pub enum DepsFormat {
     Tree,
     Table,
     Mermaid,
+    GraphML,
 }
 
 impl std::str::FromStr for DepsFormat {
@@ -284,6 +436,7 @@ impl std::str::FromStr for DepsFormat {
             "tree" => Ok(DepsFormat::Tree),
             "table" => Ok(DepsFormat::Table),
             "mermaid" | "mmd" => Ok(DepsFormat::Mermaid),
+            "graphml" => Ok(DepsFormat::GraphML),
             _ => Err(format!("Unknown deps format: {}", s)),
         }
     }
@@ -402,61 +555,173 @@ pub fn render_mermaid_to_image(
     Ok(())
 }
 
-/// Parse import statements from a file using ast-grep
-fn parse_imports_with_sg(root: &Path, file_path: &Path, lang: Language) -> Result<Vec<Dependency>> {
-    let patterns = match ImportPatterns::for_language(lang) {
-        Some(p) => p,
-        None => return Ok(Vec::new()),
-    };
+/// Files per ast-grep invocation, keeping command lines well below OS limits
+const SG_BATCH_SIZE: usize = 256;
+
+/// Run every import pattern over `files`, one ast-grep process per pattern and batch
+///
+/// Files are passed explicitly rather than as their directory, so ast-grep sees
+/// exactly the scanned set (ignore rules, `.miseignore` and `--deny` still
+/// apply). Matches are bucketed by their `file` field under the path given in
+/// `files`, in pattern order, as if each file had been run on its own.
+///
+/// Stops between batches once the time budget is spent, returning what was
+/// matched so far. A batch whose output cannot be parsed is reported on
+/// stderr; its files fall back to regex parsing.
+fn sg_matches_by_file(
+    sg_cmd: &str,
+    patterns: &ImportPatterns,
+    files: &[PathBuf],
+) -> Result<HashMap<PathBuf, Vec<SgMatch>>> {
+    let owners = sg_match_owners(files);
+
+    let mut by_file: HashMap<PathBuf, Vec<SgMatch>> = HashMap::new();
+    for pattern in &patterns.patterns {
+        for batch in files.chunks(SG_BATCH_SIZE) {
+            if crate::core::budget::should_stop() {
+                return Ok(by_file);
+            }
+            let output = Command::new(sg_cmd)
+                .arg("run")
+                .arg("--pattern")
+                .arg(pattern)
+                .arg("--lang")
+                .arg(patterns.lang)
+                .arg("--json")
+                .args(batch)
+                .output()?;
+            let stdout = String::from_utf8_lossy(&output.stdout);
+
+            // Parse ast-grep JSON output
+            let Ok(matches) = serde_json::from_str::<Vec<SgMatch>>(&stdout) else {
+                eprintln!(
+                    "warning: unreadable ast-grep output for {} {} file(s); using regex imports",
+                    batch.len(),
+                    patterns.lang
+                );
+                continue;
+            };
+            for m in matches {
+                let owner = match batch {
+                    [only] => Some(only),
+                    _ => sg_match_owner(&m.file, &owners),
+                };
+                if let Some(owner) = owner {
+                    by_file.entry(owner.clone()).or_default().push(m);
+                }
+            }
+        }
+    }
 
-    let sg_cmd = match get_ast_grep_command() {
-        Some(cmd) => cmd,
-        None => return Ok(Vec::new()),
-    };
+    Ok(by_file)
+}
 
-    // Ensure we use absolute path for ast-grep
-    let abs_file_path = if file_path.is_absolute() {
-        file_path.to_path_buf()
-    } else {
-        root.join(file_path)
-    };
+/// Map each input file to itself, under both its given and its canonical path
+///
+/// ast-grep may report paths differently than given; canonical paths map back.
+fn sg_match_owners(files: &[PathBuf]) -> HashMap<PathBuf, &PathBuf> {
+    let mut owners = HashMap::with_capacity(files.len() * 2);
+    for file in files {
+        if let Ok(canonical) = fs::canonicalize(file) {
+            owners.insert(canonical, file);
+        }
+        owners.insert(file.clone(), file);
+    }
+    owners
+}
 
-    let mut all_deps = Vec::new();
+/// The input file an ast-grep match belongs to
+fn sg_match_owner<'a>(
+    reported: &str,
+    owners: &HashMap<PathBuf, &'a PathBuf>,
+) -> Option<&'a PathBuf> {
+    let reported = Path::new(reported);
+    owners
+        .get(reported)
+        .or_else(|| owners.get(&fs::canonicalize(reported).ok()?))
+        .copied()
+}
 
-    for pattern in patterns.patterns {
-        let mut cmd = Command::new(sg_cmd);
-        cmd.arg("run")
-            .arg("--pattern")
-            .arg(pattern)
-            .arg("--lang")
-            .arg(patterns.lang)
-            .arg("--json")
-            .arg(&abs_file_path);
-
-        let output = cmd.output()?;
-        let stdout = String::from_utf8_lossy(&output.stdout);
-
-        // Parse ast-grep JSON output
-        if let Ok(matches) = serde_json::from_str::<Vec<SgMatch>>(&stdout) {
-            for m in matches {
-                let module = extract_module_from_match(&m.text, lang);
-                let resolved = resolve_module(root, file_path, &module, lang);
+/// Turn the ast-grep matches of one file into its dependencies
+fn deps_from_sg_matches(
+    root: &Path,
+    file_path: &Path,
+    lang: Language,
+    matches: &[SgMatch],
+) -> Vec<Dependency> {
+    let mut all_deps = Vec::new();
 
-                all_deps.push(Dependency {
-                    import_text: m.text.trim().to_string(),
-                    module,
-                    resolved_path: resolved,
-                    line: m.range.start.line + 1,
-                });
+    for m in matches {
+        // A Go `import ( ... )` block yields one dependency per spec line
+        if lang == Language::Go {
+            for (offset, line) in m.text.lines().enumerate() {
+                if let Some(module) = extract_go_import_path(line) {
+                    let resolved = resolve_module(root, file_path, &module, lang);
+                    all_deps.push(Dependency {
+                        import_text: line.trim().to_string(),
+                        module,
+                        resolved_path: resolved,
+                        line: m.range.start.line + 1 + offset as u32,
+                    });
+                }
             }
+            continue;
         }
+
+        let module = extract_module_from_match(&m.text, lang);
+        let resolved = resolve_module(root, file_path, &module, lang);
+
+        all_deps.push(Dependency {
+            import_text: m.text.trim().to_string(),
+            module,
+            resolved_path: resolved,
+            line: m.range.start.line + 1,
+        });
     }
 
     // Deduplicate by line number
     all_deps.sort_by_key(|d| d.line);
     all_deps.dedup_by_key(|d| d.line);
 
-    Ok(all_deps)
+    all_deps
+}
+
+/// Run ast-grep once per pattern over all `files`, grouped by language
+///
+/// Returns each file's dependencies as a run on that file alone would; files with
+/// no matches are absent. Empty when ast-grep is not installed.
+fn batch_imports_with_sg(root: &Path, files: &[PathBuf]) -> HashMap<PathBuf, Vec<Dependency>> {
+    let mut deps = HashMap::new();
+    let Some(sg_cmd) = get_ast_grep_command() else {
+        return deps;
+    };
+
+    let mut by_lang: BTreeMap<&'static str, (Language, Vec<PathBuf>)> = BTreeMap::new();
+    for file in files {
+        let lang = Language::from_path(file);
+        if let Some(name) = lang.sg_lang() {
+            by_lang
+                .entry(name)
+                .or_insert((lang, Vec::new()))
+                .1
+                .push(file.clone());
+        }
+    }
+
+    for (lang, files) in by_lang.into_values() {
+        let Some(patterns) = ImportPatterns::for_language(lang) else {
+            continue;
+        };
+        let Ok(by_file) = sg_matches_by_file(sg_cmd, &patterns, &files) else {
+            continue;
+        };
+        for (file, matches) in by_file {
+            let file_deps = deps_from_sg_matches(root, &file, lang, &matches);
+            deps.insert(file, file_deps);
+        }
+    }
+
+    deps
 }
 
 /// Parse imports using regex as fallback
@@ -467,6 +732,8 @@ fn parse_imports_with_regex(
 ) -> Result<Vec<Dependency>> {
     let content = std::fs::read_to_string(file_path)?;
     let mut deps = Vec::new();
+    // Inside a Go `import ( ... )` block
+    let mut in_go_block = false;
 
     for (line_num, line) in content.lines().enumerate() {
         let line = line.trim();
@@ -520,6 +787,31 @@ fn parse_imports_with_regex(
                     None
                 }
             }
+            Language::Go => {
+                if in_go_block {
+                    if line.starts_with(')') {
+                        in_go_block = false;
+                        None
+                    } else {
+                        extract_go_import_path(line)
+                    }
+                } else if let Some(rest) = line
+                    .strip_prefix("import")
+                    .filter(|r| r.starts_with([' ', '\t', '(', '"']))
+                {
+                    let rest = rest.trim_start();
+                    match rest.strip_prefix('(') {
+                        Some(block) => {
+                            // `import (` opens a block unless it closes on the same line
+                            in_go_block = !block.contains(')');
+                            extract_go_import_path(block)
+                        }
+                        None => extract_go_import_path(rest),
+                    }
+                } else {
+                    None
+                }
+            }
             Language::Unknown => None,
         };
 
@@ -560,6 +852,20 @@ fn extract_js_import_path(line: &str) -> Option<String> {
     None
 }
 
+/// Extract the quoted import path from a Go import spec (`"fmt"`, `db "acme/db"`)
+fn extract_go_import_path(line: &str) -> Option<String> {
+    let line = line.trim();
+    if line.starts_with("//") {
+        return None;
+    }
+
+    let quote = line.find(['"', '`'])?;
+    let delim = line[quote..].chars().next()?;
+    let rest = &line[quote + 1..];
+    let end = rest.find(delim)?;
+    Some(rest[..end].to_string()).filter(|p| !p.is_empty())
+}
+
 /// Resolve a module name to a file path
 fn resolve_module(root: &Path, source_file: &Path, module: &str, lang: Language) -> Option<String> {
     let source_dir = source_file.parent()?;
@@ -570,6 +876,7 @@ fn resolve_module(root: &Path, source_file: &Path, module: &str, lang: Language)
             resolve_js_module(root, source_dir, module, lang)
         }
         Language::Python => resolve_python_module(root, source_dir, module),
+        Language::Go => resolve_go_module(root, source_file, module),
         Language::Unknown => None,
     }
 }
@@ -670,6 +977,63 @@ fn resolve_js_module(
     None
 }
 
+/// Find the nearest `go.mod` at or above the source file (within ROOT)
+///
+/// Returns the module directory and the declared module path.
+fn find_go_mod(root: &Path, source_file: &Path) -> Option<(PathBuf, String)> {
+    let mut dir = source_file.parent();
+
+    while let Some(d) = dir {
+        if let Ok(content) = std::fs::read_to_string(d.join("go.mod")) {
+            let module = content.lines().find_map(|line| {
+                line.trim()
+                    .strip_prefix("module")
+                    .filter(|r| r.starts_with([' ', '\t']))
+                    .map(|r| r.trim().trim_matches('"').to_string())
+            })?;
+            return Some((d.to_path_buf(), module));
+        }
+        if d == root {
+            break;
+        }
+        dir = d.parent();
+    }
+
+    None
+}
+
+/// Resolve Go import path
+///
+/// Go imports name a package directory. Paths under the local module (per the
+/// nearest `go.mod`) resolve to the package's first non-test `.go` file, so the
+/// edge lands on a graph node; standard library and third-party imports are `None`.
+fn resolve_go_module(root: &Path, source_file: &Path, module: &str) -> Option<String> {
+    let package_dir = if module.starts_with('.') {
+        source_file.parent()?.join(module)
+    } else {
+        let (module_dir, module_path) = find_go_mod(root, source_file)?;
+        let relative = if module == module_path {
+            ""
+        } else {
+            module.strip_prefix(&format!("{}/", module_path))?
+        };
+        module_dir.join(relative)
+    };
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(&package_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.extension().is_some_and(|e| e == "go")
+                && !p.to_string_lossy().ends_with("_test.go")
+        })
+        .collect();
+    files.sort();
+
+    files.first().and_then(|f| make_relative(f, root))
+}
+
 /// Resolve Python module
 fn resolve_python_module(root: &Path, source_dir: &Path, module: &str) -> Option<String> {
     // Handle relative imports
@@ -707,7 +1071,6 @@ fn resolve_python_module(root: &Path, source_dir: &Path, module: &str) -> Option
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct SgMatch {
-    #[allow(dead_code)]
     file: String,
     range: SgRange,
     text: String,
@@ -789,22 +1152,16 @@ fn extract_module_from_match(text: &str, lang: Language) -> String {
                 .unwrap_or(text)
                 .to_string()
         }
+        Language::Go => extract_go_import_path(text).unwrap_or_else(|| text.to_string()),
         Language::Unknown => text.to_string(),
     }
 }
 
-/// Analyze dependencies for a single file
-pub fn analyze_file(root: &Path, file_path: &Path) -> Result<FileDeps> {
+/// Build `FileDeps` from ast-grep results, parsing with regex when there are none
+fn file_deps_with_fallback(root: &Path, file_path: &Path, deps: Vec<Dependency>) -> FileDeps {
     let lang = Language::from_path(file_path);
     let relative_path = make_relative(file_path, root).unwrap_or_else(|| normalize_path(file_path));
 
-    // Try ast-grep first, fall back to regex
-    let deps = if get_ast_grep_command().is_some() {
-        parse_imports_with_sg(root, file_path, lang).unwrap_or_default()
-    } else {
-        Vec::new()
-    };
-
     // If ast-grep found nothing, try regex
     let deps = if deps.is_empty() {
         parse_imports_with_regex(root, file_path, lang).unwrap_or_default()
@@ -812,16 +1169,114 @@ pub fn analyze_file(root: &Path, file_path: &Path) -> Result<FileDeps> {
         deps
     };
 
-    Ok(FileDeps {
+    FileDeps {
         path: relative_path,
         language: lang,
         depends_on: deps,
         depended_by: Vec::new(),
-    })
+    }
+}
+
+/// Whether a path is test code by name: `tests/` or `__tests__/` directories,
+/// `*_test.*`, `test_*.py`, and `*.test.*`/`*.spec.*` (JS/TS) files
+pub fn is_test_path(path: &str) -> bool {
+    let mut components = path.split('/');
+    let name = components.next_back().unwrap_or(path);
+    if components.any(|c| c == "tests" || c == "__tests__") {
+        return true;
+    }
+
+    let (stem, ext) = name.rsplit_once('.').unwrap_or((name, ""));
+    stem.ends_with("_test")
+        || (ext == "py" && stem.starts_with("test_"))
+        || (matches!(ext, "ts" | "tsx" | "js" | "jsx")
+            && (stem.ends_with(".test") || stem.ends_with(".spec")))
+}
+
+/// Line ranges (1-based, inclusive) of Rust items gated by `#[cfg(test)]`
+///
+/// The attribute applies to the next item: a `mod tests;` declaration covers
+/// its own line, a block item extends to its matching closing brace. Braces
+/// are counted naively, without skipping strings or comments.
+fn rust_test_ranges(content: &str) -> Vec<(u32, u32)> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut ranges = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let Some(rest) = lines[i].trim().strip_prefix("#[cfg(test)]") else {
+            i += 1;
+            continue;
+        };
+
+        let start = i;
+        let mut depth = 0i32;
+        let mut opened = false;
+        let mut end = i;
+        for (j, line) in lines.iter().enumerate().skip(i) {
+            // The item may follow the attribute on the same line
+            let line = if j == start { rest } else { line };
+            end = j;
+            if line.trim().is_empty() {
+                continue;
+            }
+            for c in line.chars() {
+                match c {
+                    '{' => {
+                        depth += 1;
+                        opened = true;
+                    }
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+            }
+            let declaration = !opened && line.trim_end().ends_with(';');
+            if declaration || (opened && depth <= 0) {
+                break;
+            }
+        }
+
+        ranges.push((start as u32 + 1, end as u32 + 1));
+        i = end + 1;
+    }
+
+    ranges
+}
+
+/// Drop the imports of a Rust file that sit inside `#[cfg(test)]` items
+fn strip_test_imports(file_deps: &mut FileDeps, file_path: &Path) {
+    if file_deps.language != Language::Rust {
+        return;
+    }
+    let Ok(content) = fs::read_to_string(file_path) else {
+        return;
+    };
+    let ranges = rust_test_ranges(&content);
+    file_deps.depends_on.retain(|d| {
+        !ranges
+            .iter()
+            .any(|&(start, end)| d.line >= start && d.line <= end)
+    });
 }
 
 /// Analyze dependencies for all files in scope
-pub fn analyze_deps(root: &Path, scope: Option<&Path>) -> Result<DepGraph> {
+///
+/// ast-grep runs once per import pattern over batches of files, then the
+/// per-file work is done on a pool of worker threads (`jobs`, or one per CPU
+/// core when 0) that
+/// pull paths from a shared cursor; the resulting graph does not depend on the
+/// order in which workers finish.
+///
+/// With `exclude_tests`, test files (see [`is_test_path`]) are left out and
+/// imports inside Rust `#[cfg(test)]` items are dropped, so the graph holds
+/// production dependencies only.
+pub fn analyze_deps(
+    root: &Path,
+    scope: Option<&Path>,
+    filter: &GlobFilter,
+    exclude_tests: bool,
+    jobs: usize,
+) -> Result<DepGraph> {
     let scan_root = scope.unwrap_or(root);
     let options = ScanOptions {
         scope: if scope.is_some() {
@@ -831,86 +1286,219 @@ pub fn analyze_deps(root: &Path, scope: Option<&Path>) -> Result<DepGraph> {
         },
         file_type: Some("file".to_string()),
         ignore: true,
+        filter: filter.clone(),
         ..Default::default()
     };
     let file_results = scan_files(root, &options)?;
 
-    let mut graph = DepGraph::new();
+    let paths: Vec<PathBuf> = file_results
+        .items
+        .iter()
+        .filter_map(|item| item.path.as_deref())
+        .filter(|path| !(exclude_tests && is_test_path(path)))
+        .map(|path| root.join(path))
+        // Skip non-supported languages and files that don't exist
+        .filter(|path| Language::from_path(path) != Language::Unknown && path.exists())
+        .collect();
+
+    let sg_deps = batch_imports_with_sg(root, &paths);
+    let graph = Mutex::new(DepGraph::new());
+    let next = AtomicUsize::new(0);
+    let workers = if jobs > 0 {
+        jobs
+    } else {
+        thread::available_parallelism().map_or(4, |n| n.get())
+    }
+    .min(paths.len())
+    .max(1);
+
+    thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| loop {
+                if crate::core::budget::should_stop() {
+                    break;
+                }
+                let Some(file_path) = paths.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                    break;
+                };
+                let deps = sg_deps.get(file_path).cloned().unwrap_or_default();
+                let mut file_deps = file_deps_with_fallback(root, file_path, deps);
+                if exclude_tests {
+                    strip_test_imports(&mut file_deps, file_path);
+                }
+                let mut graph = graph.lock().unwrap_or_else(|e| e.into_inner());
+                graph.files.insert(file_deps.path.clone(), file_deps);
+            });
+        }
+    });
+
+    let mut graph = graph.into_inner().unwrap_or_else(|e| e.into_inner());
 
-    for file_result in file_results.items {
-        if let Some(path_str) = &file_result.path {
-            let file_path = root.join(path_str);
-            let lang = Language::from_path(&file_path);
+    // Build reverse dependency map
+    graph.build_reverse_deps();
 
-            // Skip non-supported languages
-            if lang == Language::Unknown {
-                continue;
-            }
+    Ok(graph)
+}
 
-            // Skip files that don't exist
-            if !file_path.exists() {
-                continue;
+/// Files drawn for a graph: all of them, or `file` with its direct neighbours
+fn files_to_show(graph: &DepGraph, file: Option<&str>) -> BTreeSet<String> {
+    let Some(f) = file else {
+        return graph.files.keys().cloned().collect();
+    };
+
+    // Show only deps related to this file
+    let mut set = BTreeSet::new();
+    set.insert(f.to_string());
+
+    if let Some(file_deps) = graph.files.get(f) {
+        for dep in &file_deps.depends_on {
+            if let Some(resolved) = &dep.resolved_path {
+                set.insert(resolved.clone());
             }
+        }
+        for dep_by in &file_deps.depended_by {
+            set.insert(dep_by.clone());
+        }
+    }
+    set
+}
 
-            if let Ok(file_deps) = analyze_file(root, &file_path) {
-                graph.files.insert(file_deps.path.clone(), file_deps);
+/// Build a GraphML document of a directed graph
+///
+/// Nodes are `(path, label)` pairs, identified by their path and carrying
+/// `label` and `path` data; edges are `(from, to, weight)`.
+fn graphml_document<'a>(
+    nodes: impl IntoIterator<Item = (&'a str, String)>,
+    edges: impl IntoIterator<Item = (&'a str, &'a str, Option<usize>)>,
+) -> String {
+    let mut output = String::new();
+    output.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    output.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    output
+        .push_str("  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n");
+    output.push_str("  <key id=\"path\" for=\"node\" attr.name=\"path\" attr.type=\"string\"/>\n");
+    output.push_str("  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"int\"/>\n");
+    output.push_str("  <graph id=\"deps\" edgedefault=\"directed\">\n");
+
+    for (path, label) in nodes {
+        let path = escape_xml(path);
+        output.push_str(&format!("    <node id=\"{}\">\n", path));
+        output.push_str(&format!(
+            "      <data key=\"label\">{}</data>\n",
+            escape_xml(&label)
+        ));
+        output.push_str(&format!("      <data key=\"path\">{}</data>\n", path));
+        output.push_str("    </node>\n");
+    }
+
+    for (idx, (from, to, weight)) in edges.into_iter().enumerate() {
+        let edge = format!(
+            "    <edge id=\"e{}\" source=\"{}\" target=\"{}\"",
+            idx,
+            escape_xml(from),
+            escape_xml(to)
+        );
+        match weight {
+            Some(weight) => output.push_str(&format!(
+                "{}>\n      <data key=\"weight\">{}</data>\n    </edge>\n",
+                edge, weight
+            )),
+            None => output.push_str(&format!("{}/>\n", edge)),
+        }
+    }
+
+    output.push_str("  </graph>\n");
+    output.push_str("</graphml>\n");
+    output
+}
+
+/// Edges between the shown files, weighted by how many import statements
+/// of the source resolve to the target
+fn weighted_edges<'a>(
+    graph: &'a DepGraph,
+    files_to_show: &BTreeSet<String>,
+) -> BTreeMap<(&'a str, &'a str), usize> {
+    let mut edges = BTreeMap::new();
+    for (path, file_deps) in &graph.files {
+        if !files_to_show.contains(path) {
+            continue;
+        }
+        for dep in &file_deps.depends_on {
+            if let Some(resolved) = &dep.resolved_path {
+                if files_to_show.contains(resolved) {
+                    *edges.entry((path.as_str(), resolved.as_str())).or_insert(0) += 1;
+                }
             }
         }
     }
+    edges
+}
 
-    // Build reverse dependency map
-    graph.build_reverse_deps();
+/// Format dependency graph as GraphML (Gephi, yEd)
+///
+/// Nodes are labelled with the file name and keep the full path as data;
+/// edges point from the importing file to the file it depends on, with the
+/// number of import statements as their weight.
+fn format_graphml(graph: &DepGraph, file: Option<&str>) -> String {
+    let files_to_show = files_to_show(graph, file);
+    let edges = weighted_edges(graph, &files_to_show);
 
-    Ok(graph)
+    let nodes = files_to_show.iter().map(|path| {
+        let label = path.rsplit('/').next().unwrap_or(path);
+        (path.as_str(), label.to_string())
+    });
+    graphml_document(
+        nodes,
+        edges
+            .into_iter()
+            .map(|((from, to), count)| (from, to, Some(count))),
+    )
+}
+
+/// Quote a string as a DOT identifier or label, escaping `"` and `\`
+fn dot_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
 }
 
+/// Maximum DOT `penwidth` for heavily imported edges
+const MAX_PENWIDTH: usize = 5;
+
 /// Format dependency graph as DOT (Graphviz)
+///
+/// Repeated imports between two files collapse into one edge labelled with
+/// the import count, drawn thicker as the count grows.
 fn format_dot(graph: &DepGraph, file: Option<&str>) -> String {
     let mut output = String::new();
     output.push_str("digraph deps {\n");
     output.push_str("    rankdir=LR;\n");
     output.push_str("    node [shape=box, style=rounded];\n\n");
 
-    let files_to_show: HashSet<String> = if let Some(f) = file {
-        // Show only deps related to this file
-        let mut set = HashSet::new();
-        set.insert(f.to_string());
-
-        if let Some(file_deps) = graph.files.get(f) {
-            for dep in &file_deps.depends_on {
-                if let Some(resolved) = &dep.resolved_path {
-                    set.insert(resolved.clone());
-                }
-            }
-            for dep_by in &file_deps.depended_by {
-                set.insert(dep_by.clone());
-            }
-        }
-        set
-    } else {
-        graph.files.keys().cloned().collect()
-    };
+    let files_to_show = files_to_show(graph, file);
 
     // Add nodes
     for path in &files_to_show {
         let label = path.rsplit('/').next().unwrap_or(path);
-        output.push_str(&format!("    \"{}\" [label=\"{}\"];\n", path, label));
+        output.push_str(&format!(
+            "    {} [label={}];\n",
+            dot_quote(path),
+            dot_quote(label)
+        ));
     }
 
     output.push('\n');
 
     // Add edges
-    for (path, file_deps) in &graph.files {
-        if !files_to_show.contains(path) {
-            continue;
-        }
-
-        for dep in &file_deps.depends_on {
-            if let Some(resolved) = &dep.resolved_path {
-                if files_to_show.contains(resolved) {
-                    output.push_str(&format!("    \"{}\" -> \"{}\";\n", path, resolved));
-                }
-            }
+    for ((from, to), count) in weighted_edges(graph, &files_to_show) {
+        if count > 1 {
+            output.push_str(&format!(
+                "    {} -> {} [label=\"{}\", penwidth={}];\n",
+                dot_quote(from),
+                dot_quote(to),
+                count,
+                count.min(MAX_PENWIDTH)
+            ));
+        } else {
+            output.push_str(&format!("    {} -> {};\n", dot_quote(from), dot_quote(to)));
         }
     }
 
@@ -919,55 +1507,31 @@ fn format_dot(graph: &DepGraph, file: Option<&str>) -> String {
 }
 
 /// Format dependency graph as Mermaid
+///
+/// Repeated imports between two files collapse into one edge labelled with
+/// the import count.
 fn format_mermaid(graph: &DepGraph, file: Option<&str>) -> String {
     let mut output = String::new();
     output.push_str("graph LR\n");
 
-    let files_to_show: HashSet<String> = if let Some(f) = file {
-        let mut set = HashSet::new();
-        set.insert(f.to_string());
-
-        if let Some(file_deps) = graph.files.get(f) {
-            for dep in &file_deps.depends_on {
-                if let Some(resolved) = &dep.resolved_path {
-                    set.insert(resolved.clone());
-                }
-            }
-            for dep_by in &file_deps.depended_by {
-                set.insert(dep_by.clone());
-            }
-        }
-        set
-    } else {
-        graph.files.keys().cloned().collect()
-    };
+    let files_to_show = files_to_show(graph, file);
 
     // Mermaid node IDs can't have special chars, create mapping
-    let mut node_ids: HashMap<String, String> = HashMap::new();
+    let mut node_ids: HashMap<&str, String> = HashMap::new();
     for (idx, path) in files_to_show.iter().enumerate() {
         let id = format!("N{}", idx);
         let label = path.rsplit('/').next().unwrap_or(path);
         output.push_str(&format!("    {}[{}]\n", id, label));
-        node_ids.insert(path.clone(), id);
+        node_ids.insert(path.as_str(), id);
     }
 
     // Add edges
-    for (path, file_deps) in &graph.files {
-        if !files_to_show.contains(path) {
-            continue;
-        }
-
-        let from_id = match node_ids.get(path) {
-            Some(id) => id,
-            None => continue,
-        };
-
-        for dep in &file_deps.depends_on {
-            if let Some(resolved) = &dep.resolved_path {
-                if let Some(to_id) = node_ids.get(resolved) {
-                    output.push_str(&format!("    {} --> {}\n", from_id, to_id));
-                }
-            }
+    for ((from, to), count) in weighted_edges(graph, &files_to_show) {
+        let (from_id, to_id) = (&node_ids[from], &node_ids[to]);
+        if count > 1 {
+            output.push_str(&format!("    {} -->|{}| {}\n", from_id, count, to_id));
+        } else {
+            output.push_str(&format!("    {} --> {}\n", from_id, to_id));
         }
     }
 
@@ -975,33 +1539,98 @@ fn format_mermaid(graph: &DepGraph, file: Option<&str>) -> String {
 }
 
 /// Format as tree
-fn format_tree(graph: &DepGraph, file: &str, reverse: bool) -> String {
-    let mut output = String::new();
-    output.push_str(&format!("{}\n", file));
-
-    let deps = if reverse {
-        graph.get_reverse_deps(file)
-    } else {
-        graph.get_forward_deps(file)
+fn format_tree(
+    graph: &DepGraph,
+    file: &str,
+    reverse: bool,
+    show_imports: bool,
+    depth: usize,
+    style: BoxStyle,
+) -> String {
+    let mut tree = TreeWalk {
+        graph,
+        reverse,
+        show_imports,
+        style,
+        ancestors: vec![file.to_string()],
+        output: format!("{}\n", file),
     };
+    tree.push_children(file, depth.max(1), "");
+    tree.output
+}
 
-    for (idx, dep) in deps.iter().enumerate() {
-        let is_last = idx == deps.len() - 1;
-        let prefix = if is_last { "└── " } else { "├── " };
-        output.push_str(&format!("{}{}\n", prefix, dep));
-    }
-
-    output
+/// State for drawing a nested dependency tree
+struct TreeWalk<'a> {
+    graph: &'a DepGraph,
+    reverse: bool,
+    show_imports: bool,
+    style: BoxStyle,
+    /// Files on the path from the root to the current node
+    ancestors: Vec<String>,
+    output: String,
 }
 
-/// Format as table
-fn format_table(graph: &DepGraph) -> String {
-    let mut output = String::new();
+impl TreeWalk<'_> {
+    /// Append the children of `node`, recursing `depth` levels
+    ///
+    /// A child already on the path from the root is marked `(cycle)` and not expanded.
+    fn push_children(&mut self, node: &str, depth: usize, indent: &str) {
+        let graph = self.graph;
+        let children: Vec<(String, Vec<&Dependency>)> = if self.reverse && self.show_imports {
+            graph.get_reverse_imports(node)
+        } else {
+            let mut deps = if self.reverse {
+                graph.get_reverse_deps(node)
+            } else {
+                graph.get_forward_deps(node)
+            };
+            // depended_by follows hash order; sort so nested trees are stable
+            deps.sort();
+            deps.dedup();
+            deps.into_iter().map(|d| (d, Vec::new())).collect()
+        };
 
-    // Calculate column widths
-    let max_path_len = graph
-        .files
-        .keys()
+        for (idx, (child, imports)) in children.iter().enumerate() {
+            let is_last = idx == children.len() - 1;
+            let prefix = if is_last {
+                self.style.elbow()
+            } else {
+                self.style.tee()
+            };
+            let child_indent = format!(
+                "{}{}",
+                indent,
+                if is_last { "    " } else { self.style.pipe() }
+            );
+            let is_cycle = self.ancestors.contains(child);
+
+            let marker = if is_cycle { " (cycle)" } else { "" };
+            self.output
+                .push_str(&format!("{}{}{}{}\n", indent, prefix, child, marker));
+            for dep in imports {
+                self.output.push_str(&format!(
+                    "{}  {}: {}\n",
+                    child_indent, dep.line, dep.import_text
+                ));
+            }
+
+            if !is_cycle && depth > 1 {
+                self.ancestors.push(child.clone());
+                self.push_children(child, depth - 1, &child_indent);
+                self.ancestors.pop();
+            }
+        }
+    }
+}
+
+/// Format as table
+fn format_table(graph: &DepGraph, style: BoxStyle) -> String {
+    let mut output = String::new();
+
+    // Calculate column widths
+    let max_path_len = graph
+        .files
+        .keys()
         .map(|p| p.len())
         .max()
         .unwrap_or(4)
@@ -1072,36 +1701,212 @@ fn format_table(graph: &DepGraph) -> String {
         deps_width = max_deps_len
     ));
 
+    style.table(output)
+}
+
+/// Build the CIRCULAR_DEPENDENCY warning item for one cycle
+fn cycle_item(cycle: &[String]) -> ResultItem {
+    let cycle_str = cycle.join(" -> ");
+    let mut warning_item = ResultItem::error(MiseError::new(
+        "CIRCULAR_DEPENDENCY",
+        format!("Circular dependency detected: {}", cycle_str),
+    ));
+    warning_item.confidence = Confidence::High;
+    warning_item.source_mode = SourceMode::AstGrep;
+    // Set path to first file in cycle for reference
+    if let Some(first) = cycle.first() {
+        warning_item.path = Some(first.clone());
+    }
+    warning_item.data = Some(serde_json::json!({
+        "cycle": cycle,
+        "cycle_length": cycle.len(),
+    }));
+    warning_item
+}
+
+/// Format a cycle as a closed arrow chain (`a -> b -> a`)
+fn cycle_chain(cycle: &[String]) -> String {
+    let mut members: Vec<&str> = cycle.iter().map(String::as_str).collect();
+    if let Some(first) = cycle.first() {
+        members.push(first);
+    }
+    members.join(" -> ")
+}
+
+/// Format cycles as a numbered list of arrow chains
+fn format_cycles_tree(cycles: &[Vec<String>]) -> String {
+    if cycles.is_empty() {
+        return "No circular dependencies found\n".to_string();
+    }
+
+    let mut output = format!("{} circular dependencies\n", cycles.len());
+    for (idx, cycle) in cycles.iter().enumerate() {
+        output.push_str(&format!("{:>3}. {}\n", idx + 1, cycle_chain(cycle)));
+    }
     output
 }
 
+/// Format cycles as a table of arrow chains with their length
+fn format_cycles_table(cycles: &[Vec<String>], style: BoxStyle) -> String {
+    let mut output = String::new();
+
+    let chains: Vec<String> = cycles.iter().map(|c| cycle_chain(c)).collect();
+    let width = chains
+        .iter()
+        .map(|c| c.chars().count())
+        .max()
+        .unwrap_or(5)
+        .max(5);
+
+    output.push_str(&format!(
+        "┌─────┬─{:─<width$}─┬────────┐\n",
+        "",
+        width = width
+    ));
+    output.push_str(&format!(
+        "│   # │ {:width$} │ Length │\n",
+        "Cycle",
+        width = width
+    ));
+    output.push_str(&format!(
+        "├─────┼─{:─<width$}─┼────────┤\n",
+        "",
+        width = width
+    ));
+
+    for (idx, (chain, cycle)) in chains.iter().zip(cycles).enumerate() {
+        output.push_str(&format!(
+            "│ {:>3} │ {:width$} │ {:>6} │\n",
+            idx + 1,
+            chain,
+            cycle.len(),
+            width = width
+        ));
+    }
+
+    output.push_str(&format!(
+        "└─────┴─{:─<width$}─┴────────┘\n",
+        "",
+        width = width
+    ));
+
+    style.table(output)
+}
+
+/// Format the edges that make up each cycle as a DOT graph
+fn format_cycles_dot(cycles: &[Vec<String>]) -> String {
+    let mut output = String::new();
+    output.push_str("digraph cycles {\n");
+    output.push_str("    rankdir=LR;\n");
+    output.push_str("    node [shape=box];\n\n");
+
+    for edge in cycle_edges(cycles) {
+        output.push_str(&format!("    \"{}\" -> \"{}\";\n", edge.0, edge.1));
+    }
+
+    output.push_str("}\n");
+    output
+}
+
+/// Format the edges that make up each cycle as GraphML
+fn format_cycles_graphml(cycles: &[Vec<String>]) -> String {
+    let edges = cycle_edges(cycles);
+    let nodes: BTreeSet<&str> = edges.iter().flat_map(|&(from, to)| [from, to]).collect();
+    graphml_document(
+        nodes.into_iter().map(|path| {
+            let label = path.rsplit('/').next().unwrap_or(path);
+            (path, label.to_string())
+        }),
+        edges.into_iter().map(|(from, to)| (from, to, None)),
+    )
+}
+
+/// Format the edges that make up each cycle as Mermaid
+fn format_cycles_mermaid(cycles: &[Vec<String>]) -> String {
+    let mut output = String::new();
+    output.push_str("graph LR\n");
+
+    let mut node_ids: BTreeMap<&str, String> = BTreeMap::new();
+    for cycle in cycles {
+        for member in cycle {
+            let next_id = format!("C{}", node_ids.len());
+            if let std::collections::btree_map::Entry::Vacant(entry) =
+                node_ids.entry(member.as_str())
+            {
+                output.push_str(&format!("    {}[\"{}\"]\n", next_id, member));
+                entry.insert(next_id);
+            }
+        }
+    }
+
+    for (from, to) in cycle_edges(cycles) {
+        output.push_str(&format!("    {} --> {}\n", node_ids[from], node_ids[to]));
+    }
+
+    output
+}
+
+/// Distinct `from -> to` edges across all cycles, in a stable order
+fn cycle_edges(cycles: &[Vec<String>]) -> BTreeSet<(&str, &str)> {
+    let mut edges = BTreeSet::new();
+    for cycle in cycles {
+        for (idx, from) in cycle.iter().enumerate() {
+            let to = &cycle[(idx + 1) % cycle.len()];
+            edges.insert((from.as_str(), to.as_str()));
+        }
+    }
+    edges
+}
+
+/// Print a formatted graph; rendered result sets already end with a newline
+fn print_output(format: DepsFormat, text: &str) {
+    if matches!(format, DepsFormat::Jsonl | DepsFormat::Json) {
+        print!("{}", text);
+    } else {
+        println!("{}", text);
+    }
+}
+
+/// Render only the detected cycles; exits with status 1 when any exist
+fn run_cycles_only(graph: &DepGraph, format: DepsFormat, config: RenderConfig) -> Result<()> {
+    let cycles = graph.unique_cycles();
+
+    let output_text = match format {
+        DepsFormat::Dot => format_cycles_dot(&cycles),
+        DepsFormat::Mermaid => format_cycles_mermaid(&cycles),
+        DepsFormat::GraphML => format_cycles_graphml(&cycles),
+        DepsFormat::Tree => format_cycles_tree(&cycles),
+        DepsFormat::Table => format_cycles_table(&cycles, config.box_style()),
+        DepsFormat::Jsonl | DepsFormat::Json => {
+            let mut result_set = ResultSet::new();
+            for cycle in &cycles {
+                result_set.push(cycle_item(cycle));
+            }
+            Renderer::with_config(config).render(&result_set)
+        }
+    };
+
+    print_output(format, &output_text);
+
+    if !cycles.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
 /// Convert dependency analysis to ResultSet
 fn deps_to_result_set(
     graph: &DepGraph,
     file: Option<&str>,
     reverse: bool,
+    show_imports: bool,
     cycles: &[Vec<String>],
 ) -> ResultSet {
     let mut result_set = ResultSet::new();
 
     // Add circular dependency warnings to result set
     for cycle in cycles {
-        let cycle_str = cycle.join(" -> ");
-        let mut warning_item = ResultItem::error(MiseError::new(
-            "CIRCULAR_DEPENDENCY",
-            format!("Circular dependency detected: {}", cycle_str),
-        ));
-        warning_item.confidence = Confidence::High;
-        warning_item.source_mode = SourceMode::AstGrep;
-        // Set path to first file in cycle for reference
-        if let Some(first) = cycle.first() {
-            warning_item.path = Some(first.clone());
-        }
-        warning_item.data = Some(serde_json::json!({
-            "cycle": cycle,
-            "cycle_length": cycle.len(),
-        }));
-        result_set.push(warning_item);
+        result_set.push(cycle_item(cycle));
     }
 
     if let Some(file_path) = file {
@@ -1127,10 +1932,28 @@ fn deps_to_result_set(
             let mut item = ResultItem::file(file_path);
             item.kind = Kind::Flow; // Use Flow kind for deps
             item.source_mode = SourceMode::AstGrep;
-            item.data = Some(serde_json::json!({
+            let mut data = serde_json::json!({
                 kind_str: deps,
                 "language": file_deps.language,
-            }));
+            });
+            if reverse && show_imports {
+                let imports: Vec<serde_json::Value> = graph
+                    .get_reverse_imports(file_path)
+                    .into_iter()
+                    .flat_map(|(source, deps)| {
+                        deps.into_iter().map(move |d| {
+                            serde_json::json!({
+                                "path": source,
+                                "line": d.line,
+                                "import_text": d.import_text,
+                            })
+                        })
+                    })
+                    .collect();
+                data["imports"] = serde_json::Value::Array(imports);
+            }
+            file_deps.insert_resolution_stats(&mut data);
+            item.data = Some(data);
 
             result_set.push(item);
         }
@@ -1148,11 +1971,13 @@ fn deps_to_result_set(
                 .filter_map(|d| d.resolved_path.clone())
                 .collect();
 
-            item.data = Some(serde_json::json!({
+            let mut data = serde_json::json!({
                 "depends_on": forward_deps,
                 "depended_by": file_deps.depended_by,
                 "language": file_deps.language,
-            }));
+            });
+            file_deps.insert_resolution_stats(&mut data);
+            item.data = Some(data);
 
             result_set.push(item);
         }
@@ -1162,15 +1987,294 @@ fn deps_to_result_set(
     result_set
 }
 
+/// Directory-level dependency graph aggregated from the file graph
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirGraph {
+    /// Directory -> number of analyzed files in it
+    pub nodes: BTreeMap<String, usize>,
+    /// (from, to) directory -> number of file-level edges between them
+    pub edges: BTreeMap<(String, String), usize>,
+}
+
+/// Directory key for a file: its first `depth` parent components (`.` at ROOT)
+fn dir_key(path: &str, depth: usize) -> String {
+    let parents: Vec<&str> = path.split('/').collect();
+    let parents = &parents[..parents.len().saturating_sub(1)];
+    if parents.is_empty() || depth == 0 {
+        return ".".to_string();
+    }
+    parents[..depth.min(parents.len())].join("/")
+}
+
+impl DirGraph {
+    /// Collapse files into directory nodes, merging edges into weights
+    ///
+    /// Edges between files in the same directory are not reported. Directories
+    /// only reached through an edge (their files were excluded from analysis)
+    /// are still nodes, with 0 files, so every edge has both endpoints.
+    pub fn from_file_graph(graph: &DepGraph, depth: usize) -> Self {
+        let mut dir_graph = DirGraph::default();
+
+        for (path, file_deps) in &graph.files {
+            let from = dir_key(path, depth);
+            *dir_graph.nodes.entry(from.clone()).or_default() += 1;
+
+            for dep in &file_deps.depends_on {
+                if let Some(resolved) = &dep.resolved_path {
+                    let to = dir_key(resolved, depth);
+                    if to != from {
+                        dir_graph.nodes.entry(to.clone()).or_default();
+                        *dir_graph.edges.entry((from.clone(), to)).or_default() += 1;
+                    }
+                }
+            }
+        }
+
+        dir_graph
+    }
+}
+
+/// Format directory graph as DOT with weighted edges
+fn format_dir_dot(graph: &DirGraph) -> String {
+    let mut output = String::new();
+    output.push_str("digraph deps {\n");
+    output.push_str("    rankdir=LR;\n");
+    output.push_str("    node [shape=folder];\n\n");
+
+    for (dir, files) in &graph.nodes {
+        output.push_str(&format!(
+            "    {} [label={}];\n",
+            dot_quote(dir),
+            dot_quote(&format!("{} ({})", dir, files))
+        ));
+    }
+
+    output.push('\n');
+
+    for ((from, to), weight) in &graph.edges {
+        output.push_str(&format!(
+            "    {} -> {} [label=\"{}\", weight={}];\n",
+            dot_quote(from),
+            dot_quote(to),
+            weight,
+            weight
+        ));
+    }
+
+    output.push_str("}\n");
+    output
+}
+
+/// Format directory graph as GraphML with weighted edges
+fn format_dir_graphml(graph: &DirGraph) -> String {
+    graphml_document(
+        graph
+            .nodes
+            .iter()
+            .map(|(dir, files)| (dir.as_str(), format!("{} ({})", dir, files))),
+        graph
+            .edges
+            .iter()
+            .map(|((from, to), weight)| (from.as_str(), to.as_str(), Some(*weight))),
+    )
+}
+
+/// Format directory graph as Mermaid with weighted edge labels
+fn format_dir_mermaid(graph: &DirGraph) -> String {
+    let mut output = String::new();
+    output.push_str("graph LR\n");
+
+    let mut node_ids: HashMap<&str, String> = HashMap::new();
+    for (idx, (dir, files)) in graph.nodes.iter().enumerate() {
+        let id = format!("D{}", idx);
+        output.push_str(&format!("    {}[\"{} ({})\"]\n", id, dir, files));
+        node_ids.insert(dir, id);
+    }
+
+    // Every edge endpoint is a node (see `DirGraph::from_file_graph`), so
+    // Mermaid draws the same edges as DOT
+    for ((from, to), weight) in &graph.edges {
+        let (from_id, to_id) = (&node_ids[from.as_str()], &node_ids[to.as_str()]);
+        output.push_str(&format!("    {} -->|{}| {}\n", from_id, weight, to_id));
+    }
+
+    output
+}
+
+/// Format directory graph as a table of weighted edges
+fn format_dir_table(graph: &DirGraph, style: BoxStyle) -> String {
+    let mut output = String::new();
+
+    let width = graph
+        .nodes
+        .keys()
+        .map(|d| d.len())
+        .max()
+        .unwrap_or(4)
+        .max(4);
+
+    output.push_str(&format!(
+        "┌─{:─<width$}─┬─{:─<width$}─┬────────┐\n",
+        "",
+        "",
+        width = width
+    ));
+    output.push_str(&format!(
+        "│ {:width$} │ {:width$} │ Weight │\n",
+        "From",
+        "To",
+        width = width
+    ));
+    output.push_str(&format!(
+        "├─{:─<width$}─┼─{:─<width$}─┼────────┤\n",
+        "",
+        "",
+        width = width
+    ));
+
+    for ((from, to), weight) in &graph.edges {
+        output.push_str(&format!(
+            "│ {:width$} │ {:width$} │ {:>6} │\n",
+            from,
+            to,
+            weight,
+            width = width
+        ));
+    }
+
+    output.push_str(&format!(
+        "└─{:─<width$}─┴─{:─<width$}─┴────────┘\n",
+        "",
+        "",
+        width = width
+    ));
+
+    style.table(output)
+}
+
+/// Convert directory graph to ResultSet (one item per directory)
+fn dir_graph_to_result_set(graph: &DirGraph) -> ResultSet {
+    let mut result_set = ResultSet::new();
+
+    for (dir, files) in &graph.nodes {
+        let depends_on: Vec<_> = graph
+            .edges
+            .iter()
+            .filter(|((from, _), _)| from == dir)
+            .map(|((_, to), weight)| serde_json::json!({"dir": to, "weight": weight}))
+            .collect();
+        let depended_by: Vec<_> = graph
+            .edges
+            .iter()
+            .filter(|((_, to), _)| to == dir)
+            .map(|((from, _), weight)| serde_json::json!({"dir": from, "weight": weight}))
+            .collect();
+
+        let mut item = ResultItem::file(dir.clone());
+        item.kind = Kind::Flow;
+        item.source_mode = SourceMode::Mixed;
+        item.data = Some(serde_json::json!({
+            "files": files,
+            "depends_on": depends_on,
+            "depended_by": depended_by,
+        }));
+        result_set.push(item);
+    }
+
+    result_set
+}
+
+/// Options for the deps command
+#[derive(Debug, Clone, Default)]
+pub struct DepsOptions {
+    /// Show what depends on the file instead of what it depends on
+    pub reverse: bool,
+    /// With `reverse`, include the resolving import statements
+    pub show_imports: bool,
+    /// Aggregate files into directories of this depth
+    pub group_by_dir: Option<usize>,
+    /// Levels of transitive dependencies shown by the tree format (0 or 1: direct only)
+    pub depth: usize,
+    /// Emit only the detected cycles and exit with status 1 if any exist
+    pub cycles_only: bool,
+    /// Include/exclude globs limiting which files are analyzed
+    pub filter: GlobFilter,
+    /// Leave out test files and imports inside Rust `#[cfg(test)]` items
+    pub no_tests: bool,
+    /// Worker threads for per-file analysis (0 = one per CPU core)
+    pub jobs: usize,
+}
+
+/// Render a directory-level graph in the requested format
+fn run_dir_deps(
+    graph: &DepGraph,
+    depth: usize,
+    format: DepsFormat,
+    output: Option<&Path>,
+    config: RenderConfig,
+) -> Result<()> {
+    let dir_graph = DirGraph::from_file_graph(graph, depth);
+    let renderer = Renderer::with_config(config);
+
+    if let Some(output_path) = output {
+        let Some(img_format) = ImageFormat::from_path(output_path) else {
+            let mut result_set = ResultSet::new();
+            result_set.push(ResultItem::error(MiseError::new(
+                "UNSUPPORTED_IMAGE_FORMAT",
+                "Output file must have .png, .svg, or .pdf extension",
+            )));
+            print!("{}", renderer.render(&result_set));
+            return Ok(());
+        };
+
+        let use_mermaid = format == DepsFormat::Mermaid
+            || (format != DepsFormat::Dot && !is_graphviz_available());
+        let result = if use_mermaid {
+            render_mermaid_to_image(&format_dir_mermaid(&dir_graph), output_path, img_format)
+        } else {
+            render_dot_to_image(&format_dir_dot(&dir_graph), output_path, img_format)
+        };
+
+        match result {
+            Ok(()) => eprintln!("✓ Graph rendered to: {}", output_path.display()),
+            Err(e) => {
+                let mut result_set = ResultSet::new();
+                result_set.push(ResultItem::error(MiseError::new(
+                    "RENDER_FAILED",
+                    format!("Failed to render graph: {}", e),
+                )));
+                print!("{}", renderer.render(&result_set));
+            }
+        }
+        return Ok(());
+    }
+
+    let output_text = match format {
+        DepsFormat::Dot => format_dir_dot(&dir_graph),
+        DepsFormat::Mermaid => format_dir_mermaid(&dir_graph),
+        DepsFormat::GraphML => format_dir_graphml(&dir_graph),
+        DepsFormat::Table | DepsFormat::Tree => format_dir_table(&dir_graph, config.box_style()),
+        DepsFormat::Jsonl | DepsFormat::Json => {
+            renderer.render(&dir_graph_to_result_set(&dir_graph))
+        }
+    };
+
+    print_output(format, &output_text);
+    Ok(())
+}
+
 /// Run the deps command
 pub fn run_deps(
     root: &Path,
     file: Option<&Path>,
-    reverse: bool,
+    options: &DepsOptions,
     format: DepsFormat,
     output: Option<&Path>,
     config: RenderConfig,
 ) -> Result<()> {
+    let reverse = options.reverse;
+    let show_imports = options.show_imports;
+
     // Check if ast-grep is available
     if get_ast_grep_command().is_none() && !command_exists("rg") {
         let mut result_set = ResultSet::new();
@@ -1179,12 +2283,20 @@ pub fn run_deps(
             "Neither ast-grep (sg) nor ripgrep (rg) is installed. Please install at least one.",
         )));
         let renderer = Renderer::with_config(config);
-        println!("{}", renderer.render(&result_set));
+        print!("{}", renderer.render(&result_set));
         return Ok(());
     }
 
     // Analyze dependencies
-    let graph = analyze_deps(root, None)?;
+    let graph = analyze_deps(root, None, &options.filter, options.no_tests, options.jobs)?;
+
+    if let Some(depth) = options.group_by_dir {
+        return run_dir_deps(&graph, depth, format, output, config);
+    }
+
+    if options.cycles_only {
+        return run_cycles_only(&graph, format, config);
+    }
 
     // Convert file path to relative string
     let file_str = file.map(|f| {
@@ -1198,7 +2310,7 @@ pub fn run_deps(
     });
 
     // Check for circular dependencies
-    let cycles = graph.find_cycles();
+    let cycles = graph.unique_cycles();
 
     // Handle image output
     if let Some(output_path) = output {
@@ -1211,7 +2323,7 @@ pub fn run_deps(
                     "Output file must have .png, .svg, or .pdf extension",
                 )));
                 let renderer = Renderer::with_config(config);
-                println!("{}", renderer.render(&result_set));
+                print!("{}", renderer.render(&result_set));
                 return Ok(());
             }
         };
@@ -1226,7 +2338,7 @@ pub fn run_deps(
                         "graphviz (dot) is not installed. Install: brew install graphviz",
                     )));
                     let renderer = Renderer::with_config(config);
-                    println!("{}", renderer.render(&result_set));
+                    print!("{}", renderer.render(&result_set));
                     return Ok(());
                 }
                 DepsFormat::Dot
@@ -1239,7 +2351,7 @@ pub fn run_deps(
                         "mermaid-cli (mmdc) is not installed. Install: npm install -g @mermaid-js/mermaid-cli",
                     )));
                     let renderer = Renderer::with_config(config);
-                    println!("{}", renderer.render(&result_set));
+                    print!("{}", renderer.render(&result_set));
                     return Ok(());
                 }
                 DepsFormat::Mermaid
@@ -1259,7 +2371,7 @@ Install graphviz: brew install graphviz\n\
 Install mermaid-cli: npm install -g @mermaid-js/mermaid-cli",
                     )));
                     let renderer = Renderer::with_config(config);
-                    println!("{}", renderer.render(&result_set));
+                    print!("{}", renderer.render(&result_set));
                     return Ok(());
                 }
             }
@@ -1290,7 +2402,7 @@ Install mermaid-cli: npm install -g @mermaid-js/mermaid-cli",
                     format!("Failed to render graph: {}", e),
                 )));
                 let renderer = Renderer::with_config(config);
-                println!("{}", renderer.render(&result_set));
+                print!("{}", renderer.render(&result_set));
                 return Ok(());
             }
         }
@@ -1300,9 +2412,17 @@ Install mermaid-cli: npm install -g @mermaid-js/mermaid-cli",
     let output_text = match format {
         DepsFormat::Dot => format_dot(&graph, file_str.as_deref()),
         DepsFormat::Mermaid => format_mermaid(&graph, file_str.as_deref()),
+        DepsFormat::GraphML => format_graphml(&graph, file_str.as_deref()),
         DepsFormat::Tree => {
             if let Some(f) = &file_str {
-                format_tree(&graph, f, reverse)
+                format_tree(
+                    &graph,
+                    f,
+                    reverse,
+                    show_imports,
+                    options.depth,
+                    config.box_style(),
+                )
             } else {
                 // Tree format requires a file - return as structured error
                 let mut result_set = ResultSet::new();
@@ -1311,19 +2431,27 @@ Install mermaid-cli: npm install -g @mermaid-js/mermaid-cli",
                     "Tree format requires a specific file. Use: mise deps <file> --format tree",
                 )));
                 let renderer = Renderer::with_config(config);
-                println!("{}", renderer.render(&result_set));
+                print!("{}", renderer.render(&result_set));
                 return Ok(());
             }
         }
-        DepsFormat::Table => format_table(&graph),
+        DepsFormat::Table => format_table(&graph, config.box_style()),
         DepsFormat::Jsonl | DepsFormat::Json => {
-            let result_set = deps_to_result_set(&graph, file_str.as_deref(), reverse, &cycles);
+            let result_set =
+                deps_to_result_set(&graph, file_str.as_deref(), reverse, show_imports, &cycles);
             let renderer = Renderer::with_config(config);
             renderer.render(&result_set)
         }
     };
 
-    println!("{}", output_text);
+    print_output(format, &output_text);
+
+    // Text formats have no room for a notice item
+    if !matches!(format, DepsFormat::Jsonl | DepsFormat::Json)
+        && crate::core::budget::was_interrupted()
+    {
+        eprintln!("warning: time budget exceeded; dependency graph is partial");
+    }
     Ok(())
 }
 
@@ -1347,6 +2475,7 @@ mod tests {
             Language::JavaScript
         );
         assert_eq!(Language::from_path(Path::new("foo.py")), Language::Python);
+        assert_eq!(Language::from_path(Path::new("foo.go")), Language::Go);
         assert_eq!(Language::from_path(Path::new("foo.txt")), Language::Unknown);
     }
 
@@ -1376,6 +2505,10 @@ mod tests {
         );
         assert_eq!("tree".parse::<DepsFormat>().unwrap(), DepsFormat::Tree);
         assert_eq!("table".parse::<DepsFormat>().unwrap(), DepsFormat::Table);
+        assert_eq!(
+            "GraphML".parse::<DepsFormat>().unwrap(),
+            DepsFormat::GraphML
+        );
     }
 
     #[test]
@@ -1400,6 +2533,7 @@ mod tests {
         assert_eq!(Language::TypeScript.sg_lang(), Some("typescript"));
         assert_eq!(Language::JavaScript.sg_lang(), Some("javascript"));
         assert_eq!(Language::Python.sg_lang(), Some("python"));
+        assert_eq!(Language::Go.sg_lang(), Some("go"));
         assert_eq!(Language::Unknown.sg_lang(), None);
     }
 
@@ -1412,9 +2546,224 @@ mod tests {
             &["js", "jsx", "mjs", "cjs"]
         );
         assert_eq!(Language::Python.extensions(), &["py"]);
+        assert_eq!(Language::Go.extensions(), &["go"]);
         assert_eq!(Language::Unknown.extensions(), &[] as &[&str]);
     }
 
+    fn file_with_deps(path: &str, deps: &[&str]) -> FileDeps {
+        FileDeps {
+            path: path.to_string(),
+            language: Language::Rust,
+            depends_on: deps
+                .iter()
+                .map(|d| Dependency {
+                    import_text: format!("use {}", d),
+                    module: d.to_string(),
+                    resolved_path: Some(d.to_string()),
+                    line: 1,
+                })
+                .collect(),
+            depended_by: vec![],
+        }
+    }
+
+    #[test]
+    fn test_format_graphml() {
+        let mut graph = DepGraph::new();
+        for (path, deps) in [
+            ("src/main.rs", vec!["src/a&b.rs", "src/util.rs"]),
+            ("src/a&b.rs", vec!["src/util.rs"]),
+            ("src/util.rs", vec![]),
+            ("src/other.rs", vec![]),
+        ] {
+            graph
+                .files
+                .insert(path.to_string(), file_with_deps(path, &deps));
+        }
+        graph.build_reverse_deps();
+
+        let xml = format_graphml(&graph, None);
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<graphml "));
+        assert!(xml.contains("<graph id=\"deps\" edgedefault=\"directed\">"));
+        assert!(xml.contains(
+            "    <node id=\"src/a&amp;b.rs\">\n      <data key=\"label\">a&amp;b.rs</data>\n      \
+<data key=\"path\">src/a&amp;b.rs</data>\n    </node>"
+        ));
+        assert!(xml.contains(
+            "source=\"src/main.rs\" target=\"src/util.rs\">\n      <data key=\"weight\">1</data>"
+        ));
+        assert_eq!(xml.matches("<node ").count(), 4);
+        assert_eq!(xml.matches("<edge ").count(), 3);
+        assert!(xml.ends_with("  </graph>\n</graphml>\n"));
+
+        // Focused on one file: it and its direct neighbours
+        let xml = format_graphml(&graph, Some("src/util.rs"));
+        assert_eq!(xml.matches("<node ").count(), 3);
+        assert!(!xml.contains("other.rs"));
+    }
+
+    #[test]
+    fn test_format_weighted_edges() {
+        let mut graph = DepGraph::new();
+        for (path, deps) in [
+            ("a.rs", vec!["b.rs", "b.rs", "b.rs", "c.rs"]),
+            ("b.rs", vec!["c.rs"; 9]),
+            ("c.rs", vec![]),
+        ] {
+            graph
+                .files
+                .insert(path.to_string(), file_with_deps(path, &deps));
+        }
+
+        let dot = format_dot(&graph, None);
+        assert!(dot.contains("    \"a.rs\" -> \"b.rs\" [label=\"3\", penwidth=3];\n"));
+        assert!(dot.contains("    \"a.rs\" -> \"c.rs\";\n"));
+        assert!(dot.contains("    \"b.rs\" -> \"c.rs\" [label=\"9\", penwidth=5];\n"));
+        assert_eq!(dot.matches(" -> ").count(), 3);
+
+        let mermaid = format_mermaid(&graph, None);
+        assert!(mermaid.contains("    N0 -->|3| N1\n"));
+        assert!(mermaid.contains("    N0 --> N2\n"));
+        assert!(mermaid.contains("    N1 -->|9| N2\n"));
+        assert_eq!(mermaid.matches("-->").count(), 3);
+
+        assert_eq!(dot_quote(r#"say "hi"\now"#), r#""say \"hi\"\\now""#);
+    }
+
+    #[test]
+    fn test_dir_key() {
+        assert_eq!(dir_key("src/backends/deps.rs", 2), "src/backends");
+        assert_eq!(dir_key("src/backends/deps.rs", 1), "src");
+        assert_eq!(dir_key("src/main.rs", 2), "src");
+        assert_eq!(dir_key("build.rs", 2), ".");
+    }
+
+    #[test]
+    fn test_dir_graph_from_file_graph() {
+        let mut graph = DepGraph::new();
+        for (path, deps) in [
+            (
+                "src/cli.rs",
+                vec!["src/core/model.rs", "src/backends/rg.rs"],
+            ),
+            (
+                "src/backends/rg.rs",
+                vec!["src/core/model.rs", "src/core/paths.rs"],
+            ),
+            (
+                "src/backends/scan.rs",
+                vec!["src/core/model.rs", "src/backends/rg.rs"],
+            ),
+            ("src/core/model.rs", vec![]),
+            ("src/core/paths.rs", vec!["src/core/model.rs"]),
+        ] {
+            graph
+                .files
+                .insert(path.to_string(), file_with_deps(path, &deps));
+        }
+
+        let dirs = DirGraph::from_file_graph(&graph, 2);
+        assert_eq!(dirs.nodes["src/backends"], 2);
+        assert_eq!(dirs.nodes["src/core"], 2);
+        assert_eq!(
+            dirs.edges[&("src/backends".to_string(), "src/core".to_string())],
+            3
+        );
+        // Intra-directory edges are dropped
+        assert!(!dirs
+            .edges
+            .contains_key(&("src/core".to_string(), "src/core".to_string())));
+
+        assert!(format_dir_dot(&dirs).contains("\"src/backends\" -> \"src/core\" [label=\"3\""));
+        assert!(format_dir_mermaid(&dirs).contains("-->|3|"));
+        assert!(format_dir_graphml(&dirs).contains(
+            "source=\"src/backends\" target=\"src/core\">\n      <data key=\"weight\">3</data>"
+        ));
+        assert!(format_dir_table(&dirs, BoxStyle::Ascii)
+            .contains("| src/backends | src/core     |      3 |"));
+
+        // Edges into directories that were not analyzed still get a node
+        graph.files.insert(
+            "src/main.rs".to_string(),
+            file_with_deps("src/main.rs", &["vendor/lib/mod.rs"]),
+        );
+        let with_vendor = DirGraph::from_file_graph(&graph, 2);
+        assert_eq!(with_vendor.nodes["vendor/lib"], 0);
+        let graphml = format_dir_graphml(&with_vendor);
+        for (from, to) in with_vendor.edges.keys() {
+            assert!(graphml.contains(&format!("<node id=\"{}\">", from)));
+            assert!(graphml.contains(&format!("<node id=\"{}\">", to)));
+        }
+        assert!(graphml.contains("source=\"src\" target=\"vendor/lib\""));
+        // DOT and Mermaid draw the same edges
+        let dot = format_dir_dot(&with_vendor);
+        let mermaid = format_dir_mermaid(&with_vendor);
+        assert_eq!(dot.matches(" -> ").count(), with_vendor.edges.len());
+        assert_eq!(mermaid.matches(" -->").count(), with_vendor.edges.len());
+        assert!(dot.contains("\"src\" -> \"vendor/lib\" [label=\"1\""));
+
+        let rs = dir_graph_to_result_set(&dirs);
+        let backends = rs
+            .items
+            .iter()
+            .find(|i| i.path.as_deref() == Some("src/backends"))
+            .unwrap();
+        let data = backends.data.as_ref().unwrap();
+        assert_eq!(data["depends_on"][0]["weight"], 3);
+        assert_eq!(data["depended_by"][0]["dir"], "src");
+        assert!(matches!(
+            serde_json::from_value(data.clone()),
+            Ok(DepsData::Dir(_))
+        ));
+    }
+
+    #[test]
+    fn test_unique_cycles() {
+        let owned = |c: &[&str]| c.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let cycles = dedup_cycles(vec![
+            owned(&["b.rs", "c.rs", "a.rs"]),
+            owned(&["c.rs", "a.rs", "b.rs"]),
+            owned(&["a.rs", "b.rs", "c.rs"]),
+            owned(&["e.rs", "d.rs"]),
+        ]);
+        assert_eq!(
+            cycles,
+            vec![owned(&["a.rs", "b.rs", "c.rs"]), owned(&["d.rs", "e.rs"])]
+        );
+
+        let mut graph = DepGraph::new();
+        for (path, deps) in [
+            ("a.rs", vec!["b.rs"]),
+            ("b.rs", vec!["c.rs"]),
+            ("c.rs", vec!["a.rs"]),
+            ("d.rs", vec!["e.rs"]),
+            ("e.rs", vec!["d.rs"]),
+            ("f.rs", vec!["a.rs"]),
+        ] {
+            graph
+                .files
+                .insert(path.to_string(), file_with_deps(path, &deps));
+        }
+        assert_eq!(graph.unique_cycles(), cycles);
+
+        let tree = format_cycles_tree(&cycles);
+        assert!(tree.contains("  1. a.rs -> b.rs -> c.rs -> a.rs"));
+        assert!(tree.contains("  2. d.rs -> e.rs -> d.rs"));
+        assert_eq!(format_cycles_tree(&[]), "No circular dependencies found\n");
+
+        let table = format_cycles_table(&cycles, BoxStyle::Ascii);
+        assert!(table.contains("|   1 | a.rs -> b.rs -> c.rs -> a.rs |      3 |"));
+        assert!(table.contains("|   2 | d.rs -> e.rs -> d.rs         |      2 |"));
+
+        let dot = format_cycles_dot(&cycles);
+        assert!(dot.contains("\"c.rs\" -> \"a.rs\";"));
+        assert!(!dot.contains("f.rs"));
+        assert!(format_cycles_mermaid(&cycles).contains("C2 --> C0"));
+        let graphml = format_cycles_graphml(&cycles);
+        assert!(graphml.contains("source=\"c.rs\" target=\"a.rs\"/>"));
+        assert!(!graphml.contains("f.rs"));
+    }
+
     #[test]
     fn test_dep_graph_new() {
         let graph = DepGraph::new();
@@ -1484,6 +2833,141 @@ mod tests {
         assert_eq!(reverse, vec!["main.rs".to_string()]);
     }
 
+    #[test]
+    fn test_format_tree_depth() {
+        let mut graph = DepGraph::new();
+        let dep = |module: &str| Dependency {
+            import_text: format!("mod {};", module),
+            module: module.to_string(),
+            resolved_path: Some(format!("{}.rs", module)),
+            line: 1,
+        };
+        for (path, deps) in [
+            ("main.rs", vec![dep("a"), dep("b")]),
+            ("a.rs", vec![dep("c")]),
+            ("b.rs", vec![]),
+            ("c.rs", vec![dep("a")]),
+        ] {
+            graph.files.insert(
+                path.to_string(),
+                FileDeps {
+                    path: path.to_string(),
+                    language: Language::Rust,
+                    depends_on: deps,
+                    depended_by: vec![],
+                },
+            );
+        }
+        graph.build_reverse_deps();
+
+        assert_eq!(
+            format_tree(&graph, "main.rs", false, false, 1, BoxStyle::Ascii),
+            "main.rs\n|-- a.rs\n`-- b.rs\n"
+        );
+        assert_eq!(
+            format_tree(&graph, "main.rs", false, false, 5, BoxStyle::Ascii),
+            "main.rs\n|-- a.rs\n|   `-- c.rs\n|       `-- a.rs (cycle)\n`-- b.rs\n"
+        );
+        assert_eq!(
+            format_tree(&graph, "c.rs", true, false, 3, BoxStyle::Unicode),
+            "c.rs\n└── a.rs\n    ├── c.rs (cycle)\n    └── main.rs\n"
+        );
+    }
+
+    #[test]
+    fn test_dep_graph_reverse_imports() {
+        let mut graph = DepGraph::new();
+
+        graph.files.insert(
+            "main.rs".to_string(),
+            FileDeps {
+                path: "main.rs".to_string(),
+                language: Language::Rust,
+                depends_on: vec![
+                    Dependency {
+                        import_text: "mod lib;".to_string(),
+                        module: "lib".to_string(),
+                        resolved_path: Some("lib.rs".to_string()),
+                        line: 1,
+                    },
+                    Dependency {
+                        import_text: "use std::fs;".to_string(),
+                        module: "std".to_string(),
+                        resolved_path: None,
+                        line: 2,
+                    },
+                    Dependency {
+                        import_text: "use crate::lib::run;".to_string(),
+                        module: "lib".to_string(),
+                        resolved_path: Some("lib.rs".to_string()),
+                        line: 3,
+                    },
+                ],
+                depended_by: vec![],
+            },
+        );
+        graph.files.insert(
+            "lib.rs".to_string(),
+            FileDeps {
+                path: "lib.rs".to_string(),
+                language: Language::Rust,
+                depends_on: vec![],
+                depended_by: vec![],
+            },
+        );
+        graph.build_reverse_deps();
+
+        let imports = graph.get_reverse_imports("lib.rs");
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].0, "main.rs");
+        let lines: Vec<u32> = imports[0].1.iter().map(|d| d.line).collect();
+        assert_eq!(lines, vec![1, 3]);
+
+        let cycles = vec![vec!["lib.rs".to_string(), "main.rs".to_string()]];
+        let rs = deps_to_result_set(&graph, Some("lib.rs"), true, true, &cycles);
+        // The published payload types cover every shape emitted here
+        for item in &rs.items {
+            let data = item.data.clone().unwrap();
+            let parsed: DepsData = serde_json::from_value(data).unwrap();
+            match item.kind {
+                Kind::Error => assert!(matches!(parsed, DepsData::Cycle(_))),
+                _ => assert!(matches!(parsed, DepsData::File(_))),
+            }
+        }
+        let data = rs.items[1].data.as_ref().unwrap();
+        assert_eq!(data["imports"][1]["import_text"], "use crate::lib::run;");
+        assert_eq!(data["imports"][1]["path"], "main.rs");
+        assert_eq!(data["resolved_count"], 0);
+        assert_eq!(data["unresolved_count"], 0);
+
+        let rs = deps_to_result_set(&graph, None, false, false, &[]);
+        let main = rs
+            .items
+            .iter()
+            .find(|i| i.path.as_deref() == Some("main.rs"))
+            .unwrap();
+        let data = main.data.as_ref().unwrap();
+        assert!(matches!(
+            serde_json::from_value(data.clone()),
+            Ok(DepsData::File(_))
+        ));
+        assert_eq!(data["resolved_count"], 2);
+        assert_eq!(data["unresolved_count"], 1);
+        assert_eq!(data["unresolved"], serde_json::json!(["std"]));
+
+        let tree = format_tree(&graph, "lib.rs", true, true, 1, BoxStyle::Unicode);
+        assert!(tree.contains("└── main.rs"));
+        assert!(tree.contains("3: use crate::lib::run;"));
+
+        let is_box_drawing = |c: char| ('\u{2500}'..='\u{257F}').contains(&c);
+        let tree = format_tree(&graph, "lib.rs", true, true, 1, BoxStyle::Ascii);
+        assert!(tree.contains("`-- main.rs"));
+        assert!(!tree.chars().any(is_box_drawing));
+        let table = format_table(&graph, BoxStyle::Ascii);
+        assert!(table.starts_with("+-"));
+        assert!(!table.chars().any(is_box_drawing));
+    }
+
     #[test]
     fn test_dep_graph_find_cycles_empty() {
         let graph = DepGraph::new();
@@ -1709,4 +3193,250 @@ mod tests {
             let _ = result;
         }
     }
+
+    #[test]
+    fn test_extract_go_import_path() {
+        assert_eq!(extract_go_import_path("\"fmt\""), Some("fmt".to_string()));
+        assert_eq!(
+            extract_go_import_path("db \"example.com/app/internal/db\" // storage"),
+            Some("example.com/app/internal/db".to_string())
+        );
+        assert_eq!(
+            extract_go_import_path("_ `embed`"),
+            Some("embed".to_string())
+        );
+        assert_eq!(extract_go_import_path("// \"commented\""), None);
+        assert_eq!(extract_go_import_path(")"), None);
+    }
+
+    #[test]
+    fn test_analyze_deps_matches_sequential() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::write(root.join("go.mod"), "module example.com/app\n").unwrap();
+        for name in ["a", "b", "c", "d"] {
+            std::fs::create_dir_all(root.join(name)).unwrap();
+            std::fs::write(
+                root.join(format!("{name}/{name}.go")),
+                format!("package {name}\n\nimport \"example.com/app/util\"\n"),
+            )
+            .unwrap();
+        }
+        std::fs::create_dir_all(root.join("util")).unwrap();
+        std::fs::write(root.join("util/util.go"), "package util\n").unwrap();
+
+        let graph = analyze_deps(root, None, &GlobFilter::default(), false, 0).unwrap();
+
+        // One ast-grep run per file, as before batching
+        let mut sequential = DepGraph::new();
+        for path in ["a/a.go", "b/b.go", "c/c.go", "d/d.go", "util/util.go"] {
+            let path = root.join(path);
+            let matches = match (
+                get_ast_grep_command(),
+                ImportPatterns::for_language(Language::Go),
+            ) {
+                (Some(sg), Some(patterns)) => {
+                    sg_matches_by_file(sg, &patterns, std::slice::from_ref(&path))
+                        .unwrap()
+                        .remove(&path)
+                        .unwrap_or_default()
+                }
+                _ => Vec::new(),
+            };
+            let deps = deps_from_sg_matches(root, &path, Language::Go, &matches);
+            let file_deps = file_deps_with_fallback(root, &path, deps);
+            sequential.files.insert(file_deps.path.clone(), file_deps);
+        }
+        sequential.build_reverse_deps();
+
+        let as_json = |g: &DepGraph| {
+            let files: BTreeMap<_, _> = g.files.iter().collect();
+            serde_json::to_value(files).unwrap()
+        };
+        assert_eq!(as_json(&graph), as_json(&sequential));
+
+        // An explicit --jobs count yields the same graph
+        let single = analyze_deps(root, None, &GlobFilter::default(), false, 1).unwrap();
+        assert_eq!(as_json(&single), as_json(&graph));
+        assert_eq!(
+            graph.get_reverse_deps("util/util.go"),
+            ["a/a.go", "b/b.go", "c/c.go", "d/d.go"]
+        );
+    }
+
+    #[test]
+    fn test_analyze_deps_glob_filter() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::write(root.join("go.mod"), "module example.com/app\n").unwrap();
+        for dir in ["app", "app/generated", "util"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join("app/main.go"), "package app\n").unwrap();
+        std::fs::write(root.join("app/generated/api.go"), "package generated\n").unwrap();
+        std::fs::write(root.join("util/util.go"), "package util\n").unwrap();
+
+        let filter = GlobFilter::new(
+            vec!["app/*".to_string()],
+            vec!["**/generated/**".to_string()],
+        )
+        .unwrap();
+        let graph = analyze_deps(root, None, &filter, false, 0).unwrap();
+        let files: Vec<_> = graph.files.keys().collect();
+        assert_eq!(files, ["app/main.go"]);
+    }
+
+    #[test]
+    fn test_is_test_path() {
+        for path in [
+            "tests/cli.rs",
+            "web/__tests__/app.ts",
+            "internal/db/db_test.go",
+            "pkg/test_models.py",
+            "src/app.test.ts",
+            "src/button.spec.jsx",
+        ] {
+            assert!(is_test_path(path), "{path}");
+        }
+        for path in [
+            "src/main.rs",
+            "src/testing.rs",
+            "test_data.rs",
+            "contest/a.py",
+        ] {
+            assert!(!is_test_path(path), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_rust_test_ranges() {
+        let content = "use crate::a;\n\
+#[cfg(test)]\n\
+mod tests {\n\
+    use super::*;\n\
+    fn f() { if true {} }\n\
+}\n\
+#[cfg(test)]\n\
+mod fixtures;\n\
+#[cfg(test)] use crate::b;\n\
+use crate::c;\n";
+        assert_eq!(rust_test_ranges(content), [(2, 6), (7, 8), (9, 9)]);
+    }
+
+    #[test]
+    fn test_analyze_deps_exclude_tests() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("tests")).unwrap();
+        std::fs::write(
+            root.join("src/lib.rs"),
+            "mod util;\n\n#[cfg(test)]\nmod tests {\n    use crate::helper;\n}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/util.rs"), "").unwrap();
+        std::fs::write(root.join("src/helper.rs"), "").unwrap();
+        std::fs::write(root.join("tests/it.rs"), "use crate::util;\n").unwrap();
+
+        let modules = |graph: &DepGraph, path: &str| -> Vec<String> {
+            graph.files[path]
+                .depends_on
+                .iter()
+                .map(|d| d.module.clone())
+                .collect()
+        };
+
+        let graph = analyze_deps(root, None, &GlobFilter::default(), false, 0).unwrap();
+        assert!(graph.files.contains_key("tests/it.rs"));
+        assert!(modules(&graph, "src/lib.rs").contains(&"helper".to_string()));
+
+        let graph = analyze_deps(root, None, &GlobFilter::default(), true, 0).unwrap();
+        assert!(!graph.files.contains_key("tests/it.rs"));
+        assert_eq!(modules(&graph, "src/lib.rs"), ["util"]);
+    }
+
+    #[test]
+    fn test_deps_from_sg_matches() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::write(root.join("go.mod"), "module example.com/app\n").unwrap();
+        std::fs::create_dir_all(root.join("util")).unwrap();
+        std::fs::write(root.join("util/str.go"), "package util\n").unwrap();
+        let main = root.join("main.go");
+
+        // The same block matched by two patterns, as batched runs return it
+        let json = r#"[
+            {"file":"main.go","range":{"start":{"line":2,"column":0},"end":{"line":5,"column":1}},
+             "text":"import (\n\t\"fmt\"\n\t\"example.com/app/util\"\n)"},
+            {"file":"main.go","range":{"start":{"line":2,"column":0},"end":{"line":5,"column":1}},
+             "text":"import (\n\t\"fmt\"\n\t\"example.com/app/util\"\n)"}
+        ]"#;
+        let matches: Vec<SgMatch> = serde_json::from_str(json).unwrap();
+        let deps = deps_from_sg_matches(root, &main, Language::Go, &matches);
+        let resolved: Vec<(&str, Option<&str>, u32)> = deps
+            .iter()
+            .map(|d| (d.module.as_str(), d.resolved_path.as_deref(), d.line))
+            .collect();
+        assert_eq!(
+            resolved,
+            [
+                ("fmt", None, 4),
+                ("example.com/app/util", Some("util/str.go"), 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sg_match_owner() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(temp.path().join("src")).unwrap();
+        let a = temp.path().join("src/a.rs");
+        let b = temp.path().join("src/b.rs");
+        std::fs::write(&a, "").unwrap();
+        std::fs::write(&b, "").unwrap();
+        let files = [a.clone(), b.clone()];
+        let owners = sg_match_owners(&files);
+
+        assert_eq!(sg_match_owner(b.to_str().unwrap(), &owners), Some(&b));
+        let indirect = temp.path().join("src/../src/a.rs");
+        assert_eq!(
+            sg_match_owner(indirect.to_str().unwrap(), &owners),
+            Some(&a)
+        );
+        assert_eq!(sg_match_owner("elsewhere/c.rs", &owners), None);
+    }
+
+    #[test]
+    fn test_parse_go_imports_with_regex() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::write(root.join("go.mod"), "module example.com/app\n\ngo 1.22\n").unwrap();
+        std::fs::create_dir_all(root.join("internal/db")).unwrap();
+        std::fs::write(root.join("internal/db/db_test.go"), "package db\n").unwrap();
+        std::fs::write(root.join("internal/db/db.go"), "package db\n").unwrap();
+        std::fs::create_dir_all(root.join("util")).unwrap();
+        std::fs::write(root.join("util/str.go"), "package util\n").unwrap();
+        let main = root.join("main.go");
+        std::fs::write(
+            &main,
+            "package main\n\nimport \"example.com/app/util\"\n\nimport (\n\t\"fmt\"\n\tstore \"example.com/app/internal/db\"\n\t\"github.com/pkg/errors\"\n)\n",
+        )
+        .unwrap();
+
+        let deps = parse_imports_with_regex(root, &main, Language::Go).unwrap();
+        let resolved: Vec<(&str, Option<&str>)> = deps
+            .iter()
+            .map(|d| (d.module.as_str(), d.resolved_path.as_deref()))
+            .collect();
+        assert_eq!(
+            resolved,
+            vec![
+                ("example.com/app/util", Some("util/str.go")),
+                ("fmt", None),
+                ("example.com/app/internal/db", Some("internal/db/db.go")),
+                ("github.com/pkg/errors", None),
+            ]
+        );
+        assert_eq!(deps[2].line, 7);
+    }
 }
@@ -1,13 +1,16 @@
 //! Doctor - Dependency checking
 
 use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::process::{Command, Stdio};
 
 use crate::backends::ast_grep::get_ast_grep_command;
 use crate::backends::rg::is_rg_available;
 use crate::core::model::{Confidence, Kind, MiseError, ResultItem, ResultSet, SourceMode};
 use crate::core::render::{RenderConfig, Renderer};
 use crate::core::tokenizer::check_all_tiktoken_models;
-use crate::core::util::command_exists;
+use crate::core::util::{command_exists, command_path};
 
 /// Dependency status
 #[derive(Debug, Clone)]
@@ -17,9 +20,33 @@ pub struct DependencyStatus {
     pub command: Option<String>,
     pub required: bool,
     pub notes: Option<String>,
+    /// Resolved location of `command` on PATH
+    pub path: Option<String>,
+    /// Version reported by `command --version`
+    pub version: Option<String>,
 }
 
 impl DependencyStatus {
+    /// A checked tool; it is available when `command` was found
+    ///
+    /// `path` and `version` start unset and are filled in for installed tools.
+    pub fn new(
+        name: impl Into<String>,
+        command: Option<String>,
+        required: bool,
+        notes: Option<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            available: command.is_some(),
+            command,
+            required,
+            notes,
+            path: None,
+            version: None,
+        }
+    }
+
     pub fn to_result_item(&self) -> ResultItem {
         let status = if self.available { "✓" } else { "✗" };
         let required = if self.required {
@@ -58,7 +85,13 @@ impl DependencyStatus {
             path: None,
             range: None,
             excerpt: Some(message),
-            data: None,
+            data: Some(serde_json::json!({
+                "name": self.name,
+                "found": self.available,
+                "path": self.path,
+                "version": self.version,
+                "required": self.required,
+            })),
             confidence: if self.available || self.required {
                 Confidence::High
             } else {
@@ -82,130 +115,213 @@ impl DependencyStatus {
 
 /// Check all dependencies
 pub fn check_dependencies() -> Vec<DependencyStatus> {
-    let mut deps = Vec::new();
-
-    // ripgrep (required for match command)
-    deps.push(DependencyStatus {
-        name: "ripgrep".to_string(),
-        available: is_rg_available(),
-        command: if is_rg_available() {
-            Some("rg".to_string())
-        } else {
-            None
-        },
-        required: true,
-        notes: Some("Install: brew install ripgrep / cargo install ripgrep".to_string()),
-    });
-
-    // ast-grep (required for ast command)
-    let ast_grep_cmd = get_ast_grep_command();
-    deps.push(DependencyStatus {
-        name: "ast-grep".to_string(),
-        available: ast_grep_cmd.is_some(),
-        command: ast_grep_cmd.map(|s| s.to_string()),
-        required: true,
-        notes: Some("Install: cargo install ast-grep / npm install -g @ast-grep/cli".to_string()),
-    });
-
-    // watchexec (optional, for watch command)
-    let watchexec_available = command_exists("watchexec");
-    deps.push(DependencyStatus {
-        name: "watchexec".to_string(),
-        available: watchexec_available,
-        command: if watchexec_available {
-            Some("watchexec".to_string())
-        } else {
-            None
-        },
-        required: false,
-        notes: Some("Install: brew install watchexec / cargo install watchexec-cli".to_string()),
-    });
-
-    // graphviz (optional, for deps graph rendering)
-    let graphviz_available = command_exists("dot");
-    deps.push(DependencyStatus {
-        name: "graphviz".to_string(),
-        available: graphviz_available,
-        command: if graphviz_available {
-            Some("dot".to_string())
-        } else {
-            None
-        },
-        required: false,
-        notes: Some("Install: brew install graphviz (for deps -o output.png)".to_string()),
-    });
-
-    // mermaid-cli (optional, for deps graph rendering)
-    let mermaid_cli_available = command_exists("mmdc");
-    deps.push(DependencyStatus {
-        name: "mermaid-cli".to_string(),
-        available: mermaid_cli_available,
-        command: if mermaid_cli_available {
-            Some("mmdc".to_string())
-        } else {
-            None
-        },
-        required: false,
-        notes: Some(
-            "Install: npm install -g @mermaid-js/mermaid-cli (for deps -o output.svg)".to_string(),
+    let found = |command: &str| command_exists(command).then(|| command.to_string());
+    let mut deps = vec![
+        // ripgrep (required for match command)
+        DependencyStatus::new(
+            "ripgrep",
+            is_rg_available().then(|| "rg".to_string()),
+            true,
+            Some("Install: brew install ripgrep / cargo install ripgrep".to_string()),
+        ),
+        // ast-grep (required for ast command)
+        DependencyStatus::new(
+            "ast-grep",
+            get_ast_grep_command().map(|s| s.to_string()),
+            true,
+            Some("Install: cargo install ast-grep / npm install -g @ast-grep/cli".to_string()),
+        ),
+        // watchexec (optional, for watch command)
+        DependencyStatus::new(
+            "watchexec",
+            found("watchexec"),
+            false,
+            Some("Install: brew install watchexec / cargo install watchexec-cli".to_string()),
         ),
-    });
+        // graphviz (optional, for deps graph rendering)
+        DependencyStatus::new(
+            "graphviz",
+            found("dot"),
+            false,
+            Some("Install: brew install graphviz (for deps -o output.png)".to_string()),
+        ),
+        // mermaid-cli (optional, for deps graph rendering)
+        DependencyStatus::new(
+            "mermaid-cli",
+            found("mmdc"),
+            false,
+            Some(
+                "Install: npm install -g @mermaid-js/mermaid-cli (for deps -o output.svg)"
+                    .to_string(),
+            ),
+        ),
+    ];
+
+    // Where each installed tool lives and which version it reports
+    for dep in &mut deps {
+        if let Some(command) = dep.command.as_deref() {
+            dep.path = command_path(command);
+            dep.version = detect_version(command);
+        }
+    }
 
     // tiktoken models (optional, for accurate token counting)
     for (model_name, available, error) in check_all_tiktoken_models() {
-        deps.push(DependencyStatus {
-            name: format!("tiktoken:{}", model_name),
-            available,
-            command: if available {
-                Some("cached".to_string())
-            } else {
-                None
-            },
-            required: false,
-            notes: if available {
-                None
-            } else {
-                Some(format!(
-                    "{}. Will download on first use, or use --model heuristic",
-                    error.unwrap_or_else(|| "Not loaded".to_string())
-                ))
-            },
+        let notes = (!available).then(|| {
+            format!(
+                "{}. Will download on first use, or use --model heuristic",
+                error.unwrap_or_else(|| "Not loaded".to_string())
+            )
         });
+        deps.push(DependencyStatus::new(
+            format!("tiktoken:{}", model_name),
+            available.then(|| "cached".to_string()),
+            false,
+            notes,
+        ));
     }
 
     deps
 }
 
+/// Minimum versions of external tools (dependency name, required version)
+const MIN_TOOL_VERSIONS: &[(&str, &str)] = &[("ripgrep", "13.0.0"), ("ast-grep", "0.20.0")];
+
+static VERSION_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\d+)\.(\d+)(?:\.(\d+))?").unwrap());
+
+/// Parse the first `major.minor[.patch]` version in a string
+fn parse_version(text: &str) -> Option<(u64, u64, u64)> {
+    let caps = VERSION_RE.captures(text)?;
+    let part = |i: usize| caps.get(i).map_or(Some(0), |m| m.as_str().parse().ok());
+    Some((part(1)?, part(2)?, part(3)?))
+}
+
+/// Run `<command> --version` and return the reported version string
+fn detect_version(command: &str) -> Option<String> {
+    let output = Command::new(command)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let caps = VERSION_RE.captures(&stdout)?;
+    Some(caps[0].to_string())
+}
+
+/// Build a `VERSION_TOO_OLD` warning when `detected` is older than `required`
+///
+/// An outdated tool still works for most commands, so this is a medium
+/// confidence flow item tagged `"severity": "warning"` rather than an error.
+fn version_warning(name: &str, detected: &str, required: &str) -> Option<ResultItem> {
+    if parse_version(detected)? >= parse_version(required)? {
+        return None;
+    }
+
+    Some(ResultItem {
+        kind: Kind::Flow,
+        path: None,
+        range: None,
+        excerpt: Some(format!(
+            "⚠ {} {} - version {} or newer required",
+            name, detected, required
+        )),
+        data: Some(serde_json::json!({
+            "code": "VERSION_TOO_OLD",
+            "severity": "warning",
+            "message": format!("{} {} is older than the required {}", name, detected, required),
+            "tool": name,
+            "detected": detected,
+            "required": required,
+        })),
+        confidence: Confidence::Medium,
+        source_mode: SourceMode::Scan,
+        meta: Default::default(),
+        errors: Vec::new(),
+    })
+}
+
+/// Check installed tools against `MIN_TOOL_VERSIONS`
+///
+/// Tools whose version cannot be determined are not reported.
+pub fn check_tool_versions(deps: &[DependencyStatus]) -> Vec<ResultItem> {
+    MIN_TOOL_VERSIONS
+        .iter()
+        .filter_map(|(name, required)| {
+            let dep = deps.iter().find(|d| d.name == *name && d.available)?;
+            version_warning(name, dep.version.as_deref()?, required)
+        })
+        .collect()
+}
+
+/// Overall readiness: every required tool is installed
+///
+/// `data` lists the missing tools so scripts can branch without parsing text:
+/// `{"ready":false,"missing_required":["ast-grep"],"missing_optional":[...]}`.
+pub fn readiness_item(deps: &[DependencyStatus]) -> ResultItem {
+    let missing = |required: bool| -> Vec<&str> {
+        deps.iter()
+            .filter(|d| d.required == required && !d.available)
+            .map(|d| d.name.as_str())
+            .collect()
+    };
+    let missing_required = missing(true);
+    let missing_optional = missing(false);
+    let ready = missing_required.is_empty();
+
+    let excerpt = if ready {
+        "✓ Ready - all required tools found".to_string()
+    } else {
+        format!("✗ Not ready - missing {}", missing_required.join(", "))
+    };
+
+    ResultItem {
+        kind: Kind::Flow,
+        path: None,
+        range: None,
+        excerpt: Some(excerpt),
+        data: Some(serde_json::json!({
+            "ready": ready,
+            "missing_required": missing_required,
+            "missing_optional": missing_optional,
+        })),
+        confidence: Confidence::High,
+        source_mode: SourceMode::Scan,
+        meta: Default::default(),
+        errors: Vec::new(),
+    }
+}
+
 /// Run the doctor command
 pub fn run_doctor(config: RenderConfig) -> Result<()> {
-    let result_set = doctor_to_result_set()?;
+    let deps = check_dependencies();
+    let result_set = dependencies_to_result_set(&deps);
 
     let renderer = Renderer::with_config(config);
-    println!("{}", renderer.render(&result_set));
+    print!("{}", renderer.render(&result_set));
 
     // Return error if any required dependency is missing
-    let missing_required: Vec<_> = check_dependencies()
-        .into_iter()
-        .filter(|d| d.required && !d.available)
-        .collect();
-
-    if !missing_required.is_empty() {
+    if deps.iter().any(|d| d.required && !d.available) {
         eprintln!("\n⚠️  Some required dependencies are missing!");
     }
 
     Ok(())
 }
 
-/// Public API for MCP: check dependencies and return ResultSet
-pub fn doctor_to_result_set() -> Result<ResultSet> {
-    let deps = check_dependencies();
-
+/// Readiness summary first, then one item per tool and any version warnings
+fn dependencies_to_result_set(deps: &[DependencyStatus]) -> ResultSet {
     let mut result_set = ResultSet::new();
+    result_set.push(readiness_item(deps));
     for dep in deps {
         result_set.push(dep.to_result_item());
     }
+    for warning in check_tool_versions(deps) {
+        result_set.push(warning);
+    }
 
-    Ok(result_set)
+    result_set
 }
 
 #[cfg(test)]
@@ -229,13 +345,7 @@ mod tests {
 
     #[test]
     fn test_dependency_status_to_result_item_available() {
-        let status = DependencyStatus {
-            name: "test-tool".to_string(),
-            available: true,
-            command: Some("test".to_string()),
-            required: true,
-            notes: None,
-        };
+        let status = DependencyStatus::new("test-tool", Some("test".to_string()), true, None);
         let item = status.to_result_item();
         assert!(matches!(item.kind, Kind::File));
         assert!(item.excerpt.is_some());
@@ -245,13 +355,12 @@ mod tests {
 
     #[test]
     fn test_dependency_status_to_result_item_unavailable_required() {
-        let status = DependencyStatus {
-            name: "missing-tool".to_string(),
-            available: false,
-            command: None,
-            required: true,
-            notes: Some("Install with: cargo install missing-tool".to_string()),
-        };
+        let status = DependencyStatus::new(
+            "missing-tool",
+            None,
+            true,
+            Some("Install with: cargo install missing-tool".to_string()),
+        );
         let item = status.to_result_item();
         assert!(matches!(item.kind, Kind::Error));
         assert!(!item.errors.is_empty());
@@ -262,13 +371,12 @@ mod tests {
 
     #[test]
     fn test_dependency_status_to_result_item_unavailable_optional() {
-        let status = DependencyStatus {
-            name: "optional-tool".to_string(),
-            available: false,
-            command: None,
-            required: false,
-            notes: Some("Optional install".to_string()),
-        };
+        let status = DependencyStatus::new(
+            "optional-tool",
+            None,
+            false,
+            Some("Optional install".to_string()),
+        );
         let item = status.to_result_item();
         // Optional missing deps don't add errors
         assert!(item.errors.is_empty());
@@ -277,37 +385,19 @@ mod tests {
 
     #[test]
     fn test_dependency_status_confidence() {
-        let available_required = DependencyStatus {
-            name: "tool1".to_string(),
-            available: true,
-            command: Some("t1".to_string()),
-            required: true,
-            notes: None,
-        };
+        let available_required = DependencyStatus::new("tool1", Some("t1".to_string()), true, None);
         assert!(matches!(
             available_required.to_result_item().confidence,
             Confidence::High
         ));
 
-        let unavailable_required = DependencyStatus {
-            name: "tool2".to_string(),
-            available: false,
-            command: None,
-            required: true,
-            notes: None,
-        };
+        let unavailable_required = DependencyStatus::new("tool2", None, true, None);
         assert!(matches!(
             unavailable_required.to_result_item().confidence,
             Confidence::High
         ));
 
-        let unavailable_optional = DependencyStatus {
-            name: "tool3".to_string(),
-            available: false,
-            command: None,
-            required: false,
-            notes: None,
-        };
+        let unavailable_optional = DependencyStatus::new("tool3", None, false, None);
         assert!(matches!(
             unavailable_optional.to_result_item().confidence,
             Confidence::Low
@@ -330,37 +420,63 @@ mod tests {
 
     #[test]
     fn test_dependency_status_notes_in_output() {
-        let status = DependencyStatus {
-            name: "tool".to_string(),
-            available: false,
-            command: None,
-            required: true,
-            notes: Some("brew install tool".to_string()),
-        };
+        let status =
+            DependencyStatus::new("tool", None, true, Some("brew install tool".to_string()));
         let item = status.to_result_item();
         assert!(item.excerpt.as_ref().unwrap().contains("brew install"));
     }
 
     #[test]
-    fn test_run_doctor_command() {
-        let config = crate::core::render::RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
+    fn test_dependency_status_data() {
+        let status = DependencyStatus {
+            path: Some("/usr/local/bin/sg".to_string()),
+            version: Some("0.38.1".to_string()),
+            ..DependencyStatus::new("ast-grep", Some("sg".to_string()), true, None)
+        };
+        let data = status.to_result_item().data.unwrap();
+        assert_eq!(
+            data,
+            serde_json::json!({
+                "name": "ast-grep",
+                "found": true,
+                "path": "/usr/local/bin/sg",
+                "version": "0.38.1",
+                "required": true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_readiness_item() {
+        let tool = |name: &str, available: bool, required: bool| {
+            DependencyStatus::new(name, available.then(|| name.to_string()), required, None)
         };
 
+        let item = readiness_item(&[tool("ripgrep", true, true), tool("mmdc", false, false)]);
+        let data = item.data.unwrap();
+        assert_eq!(data["ready"], true);
+        assert_eq!(data["missing_optional"], serde_json::json!(["mmdc"]));
+        assert!(item.excerpt.unwrap().starts_with("✓ Ready"));
+
+        let item = readiness_item(&[tool("ripgrep", true, true), tool("ast-grep", false, true)]);
+        let data = item.data.unwrap();
+        assert_eq!(data["ready"], false);
+        assert_eq!(data["missing_required"], serde_json::json!(["ast-grep"]));
+        assert!(item.excerpt.unwrap().contains("missing ast-grep"));
+    }
+
+    #[test]
+    fn test_run_doctor_command() {
+        let config =
+            crate::core::render::RenderConfig::new(crate::core::render::OutputFormat::Json);
+
         let result = run_doctor(config);
         assert!(result.is_ok());
     }
 
     #[test]
     fn test_dependency_status_available_without_notes() {
-        let status = DependencyStatus {
-            name: "tool".to_string(),
-            available: true,
-            command: Some("tool".to_string()),
-            required: true,
-            notes: None,
-        };
+        let status = DependencyStatus::new("tool", Some("tool".to_string()), true, None);
         let item = status.to_result_item();
         assert!(item.excerpt.is_some());
         // Should still produce valid output without notes
@@ -369,13 +485,7 @@ mod tests {
 
     #[test]
     fn test_dependency_status_command_in_output() {
-        let status = DependencyStatus {
-            name: "ripgrep".to_string(),
-            available: true,
-            command: Some("rg".to_string()),
-            required: true,
-            notes: None,
-        };
+        let status = DependencyStatus::new("ripgrep", Some("rg".to_string()), true, None);
         let item = status.to_result_item();
         // Command should be mentioned in excerpt
         assert!(item.excerpt.as_ref().unwrap().contains("rg"));
@@ -408,15 +518,36 @@ mod tests {
         assert!(ast.required);
     }
 
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            parse_version("ripgrep 14.1.1 (rev f08e57bec0)"),
+            Some((14, 1, 1))
+        );
+        assert_eq!(parse_version("ast-grep 0.25.3"), Some((0, 25, 3)));
+        assert_eq!(parse_version("tool 2.5"), Some((2, 5, 0)));
+        assert_eq!(parse_version("no version here"), None);
+    }
+
+    #[test]
+    fn test_version_warning() {
+        assert!(version_warning("ast-grep", "0.25.3", "0.20.0").is_none());
+        assert!(version_warning("ripgrep", "13.0.0", "13.0.0").is_none());
+
+        let item = version_warning("ast-grep", "0.9.12", "0.20.0").unwrap();
+        assert!(matches!(item.kind, Kind::Flow));
+        assert!(item.errors.is_empty());
+        assert_eq!(item.confidence, Confidence::Medium);
+        let data = item.data.unwrap();
+        assert_eq!(data["code"], "VERSION_TOO_OLD");
+        assert_eq!(data["severity"], "warning");
+        assert_eq!(data["detected"], "0.9.12");
+        assert_eq!(data["required"], "0.20.0");
+    }
+
     #[test]
     fn test_dependency_status_source_mode() {
-        let status = DependencyStatus {
-            name: "test".to_string(),
-            available: true,
-            command: Some("test".to_string()),
-            required: true,
-            notes: None,
-        };
+        let status = DependencyStatus::new("test", Some("test".to_string()), true, None);
         let item = status.to_result_item();
         // Doctor results should have appropriate source mode
         assert!(matches!(
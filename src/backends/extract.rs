@@ -1,14 +1,48 @@
 //! Extract backend - Ranged file reading
 
 use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde_json::{json, Value};
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, Read, Seek, SeekFrom};
 use std::path::Path;
 
-use crate::core::model::{Meta, Range, ResultItem, ResultSet};
+use crate::anchors::parse::parse_file;
+use crate::core::file_reader::{
+    binary_skipped_item, decode_text, detect_file_encoding, is_probably_binary, open_decoded,
+};
+use crate::core::model::{Meta, MiseError, Range, ResultItem, ResultSet};
 use crate::core::paths::make_relative;
+use crate::core::policy::{denied_item, is_path_denied};
+use crate::core::render::truncate_excerpt;
 use crate::core::render::{RenderConfig, Renderer};
-use crate::core::util::truncate_string;
+
+/// Options for the extract command
+#[derive(Debug, Clone)]
+pub struct ExtractOptions {
+    /// Maximum bytes to emit in the excerpt
+    pub max_bytes: usize,
+    /// Annotate the result with the anchors covering the extracted lines
+    pub merge_with_anchors: bool,
+    /// Read files that look binary instead of emitting a `BINARY_SKIPPED` note
+    pub include_binary: bool,
+    /// Lines of context reported before each extracted range
+    pub context_before: u32,
+    /// Lines of context reported after each extracted range
+    pub context_after: u32,
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        Self {
+            max_bytes: 65536,
+            merge_with_anchors: false,
+            include_binary: false,
+            context_before: 0,
+            context_after: 0,
+        }
+    }
+}
 
 /// Parse line range string (format: "start:end")
 fn parse_line_range(s: &str) -> Result<(u32, u32)> {
@@ -38,6 +72,36 @@ fn parse_line_range(s: &str) -> Result<(u32, u32)> {
     Ok((start, end))
 }
 
+/// Parse byte range string (format: "start:end", 0-indexed, end exclusive)
+fn parse_byte_range(s: &str) -> Result<(u64, u64)> {
+    let (start, end) = s.split_once(':').with_context(|| {
+        format!(
+            "Invalid byte range format. Expected 'start:end', got '{}'",
+            s
+        )
+    })?;
+
+    let start: u64 = start
+        .parse()
+        .with_context(|| format!("Invalid start offset: {}", start))?;
+    let end: u64 = end
+        .parse()
+        .with_context(|| format!("Invalid end offset: {}", end))?;
+
+    if start >= end {
+        bail!("Start offset ({}) must be < end offset ({})", start, end);
+    }
+
+    Ok((start, end))
+}
+
+/// Parse one or more comma-separated line ranges (format: "1:20,50:70")
+fn parse_line_ranges(s: &str) -> Result<Vec<(u32, u32)>> {
+    s.split(',')
+        .map(|part| parse_line_range(part.trim()))
+        .collect()
+}
+
 /// Extract lines from a file
 pub fn extract_lines(
     root: &Path,
@@ -55,15 +119,36 @@ pub fn extract_lines(
     let relative_path =
         make_relative(&full_path, root).unwrap_or_else(|| path.display().to_string());
 
-    let file =
-        File::open(&full_path).with_context(|| format!("Failed to open file: {:?}", full_path))?;
-    let reader = BufReader::new(file);
+    let (reader, encoding) = open_decoded(&full_path)
+        .with_context(|| format!("Failed to open file: {:?}", full_path))?;
+    let encoding = (encoding != encoding_rs::UTF_8).then(|| encoding.name().to_string());
 
+    lines_to_item(
+        relative_path,
+        reader.lines(),
+        start_line,
+        end_line,
+        max_bytes,
+        encoding,
+    )
+}
+
+/// Build the extract item for lines `start_line..=end_line` of already decoded `lines`
+///
+/// `encoding` is the legacy codec name reported in `meta.encoding`, if any.
+fn lines_to_item<S: AsRef<str>>(
+    relative_path: String,
+    lines: impl Iterator<Item = std::io::Result<S>>,
+    start_line: u32,
+    end_line: u32,
+    max_bytes: usize,
+    encoding: Option<String>,
+) -> Result<ResultItem> {
     let mut content = String::new();
     let mut current_line = 0u32;
     let mut actual_end = start_line;
 
-    for line in reader.lines() {
+    for line in lines {
         current_line += 1;
 
         if current_line < start_line {
@@ -75,19 +160,23 @@ pub fn extract_lines(
         }
 
         let line = line?;
+        let line = line.as_ref();
 
         // Check if adding this line would exceed max_bytes
-        if content.len() + line.len() + 1 > max_bytes {
-            let (truncated, _) = truncate_string(&line, max_bytes - content.len());
-            content.push_str(&truncated);
+        let separator = if content.is_empty() { "" } else { "\n" };
+        if content.len() + separator.len() + line.len() > max_bytes {
+            content.push_str(separator);
+            content.push_str(line);
+            let (excerpt, _) = truncate_excerpt(&content, max_bytes);
 
             return Ok(ResultItem::extract(
                 relative_path,
                 Range::lines(start_line, current_line),
-                content,
+                excerpt,
             )
             .with_meta(Meta {
                 truncated: true,
+                encoding,
                 ..Default::default()
             }));
         }
@@ -95,7 +184,7 @@ pub fn extract_lines(
         if !content.is_empty() {
             content.push('\n');
         }
-        content.push_str(&line);
+        content.push_str(line);
         actual_end = current_line;
     }
 
@@ -107,11 +196,295 @@ pub fn extract_lines(
         );
     }
 
-    Ok(ResultItem::extract(
+    let item = ResultItem::extract(relative_path, Range::lines(start_line, actual_end), content);
+    Ok(match encoding {
+        Some(_) => item.with_meta(Meta {
+            encoding,
+            ..Default::default()
+        }),
+        None => item,
+    })
+}
+
+/// Whether a byte is a UTF-8 continuation byte (not the start of a character)
+fn is_continuation(byte: u8) -> bool {
+    byte & 0xC0 == 0x80
+}
+
+/// Extract a byte range `[start, end)` from a file
+///
+/// The range is widened outward to UTF-8 character boundaries so no codepoint is
+/// split; `meta.adjusted` is set when that happens. Files in a legacy encoding are
+/// decoded as-is (no widening) and report it in `meta.encoding`. Offsets past the
+/// end of the file are an error.
+pub fn extract_bytes(
+    root: &Path,
+    path: &Path,
+    start: u64,
+    end: u64,
+    max_bytes: usize,
+) -> Result<ResultItem> {
+    let full_path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        root.join(path)
+    };
+
+    let relative_path =
+        make_relative(&full_path, root).unwrap_or_else(|| path.display().to_string());
+
+    let size = std::fs::metadata(&full_path)
+        .with_context(|| format!("Failed to read file: {:?}", full_path))?
+        .len();
+    if end > size {
+        bail!(
+            "Byte range {}:{} exceeds file size ({} bytes)",
+            start,
+            end,
+            size
+        );
+    }
+
+    let encoding = detect_file_encoding(&full_path)
+        .with_context(|| format!("Failed to read file: {:?}", full_path))?;
+
+    // Read the range plus up to 3 bytes on each side: enough to widen it to
+    // UTF-8 character boundaries without loading the rest of the file
+    let window_start = start.saturating_sub(3);
+    let window_end = (end + 3).min(size);
+    let mut file =
+        File::open(&full_path).with_context(|| format!("Failed to read file: {:?}", full_path))?;
+    file.seek(SeekFrom::Start(window_start))?;
+    let mut bytes = Vec::with_capacity((window_end - window_start) as usize);
+    file.take(window_end - window_start)
+        .read_to_end(&mut bytes)?;
+
+    let (mut from, mut to) = (
+        (start - window_start) as usize,
+        (end - window_start) as usize,
+    );
+    if encoding == encoding_rs::UTF_8 {
+        while from > 0 && is_continuation(bytes[from]) {
+            from -= 1;
+        }
+        while to < bytes.len() && is_continuation(bytes[to]) {
+            to += 1;
+        }
+    }
+    let (text, _) = encoding.decode_without_bom_handling(&bytes[from..to]);
+    let (from, to) = (window_start + from as u64, window_start + to as u64);
+    let adjusted = (from, to) != (start, end);
+
+    let (excerpt, truncated) = truncate_excerpt(&text, max_bytes);
+
+    let mut data = json!({ "unit": "bytes", "start": from, "end": to });
+    if adjusted {
+        data["requested"] = json!({ "start": start, "end": end });
+    }
+
+    Ok(
+        ResultItem::extract(relative_path, Range::bytes(from, to), excerpt)
+            .with_data(data)
+            .with_meta(Meta {
+                size: Some(size),
+                truncated,
+                adjusted,
+                encoding: (encoding != encoding_rs::UTF_8).then(|| encoding.name().to_string()),
+                ..Default::default()
+            }),
+    )
+}
+
+/// Describe which anchors in the file overlap the given line range
+///
+/// Anchor ranges include their marker lines. Each entry reports whether the
+/// anchor covers the whole range (`full`) or only part of it (`partial`).
+fn anchor_membership(full_path: &Path, relative_path: &str, start: u32, end: u32) -> Vec<Value> {
+    let mut anchors = parse_file(full_path, relative_path);
+    anchors.sort_by_key(|a| (a.range.start, a.range.end));
+
+    anchors
+        .iter()
+        .filter(|a| a.range.start <= end && a.range.end >= start)
+        .map(|a| {
+            let coverage = if a.range.start <= start && a.range.end >= end {
+                "full"
+            } else {
+                "partial"
+            };
+            json!({
+                "id": a.id,
+                "tags": a.tags,
+                "range": {"start": a.range.start, "end": a.range.end},
+                "coverage": coverage,
+                "lines": {"start": start.max(a.range.start), "end": end.min(a.range.end)},
+            })
+        })
+        .collect()
+}
+
+/// Add the lines around an extracted range to its `data`
+///
+/// The excerpt and range stay on the requested lines; `data.before` and
+/// `data.after` hold up to `context_before`/`context_after` neighbouring lines
+/// (clamped to the file), with their counts in `context_before`/`context_after`.
+fn attach_context(item: &mut ResultItem, full_path: &Path, options: &ExtractOptions) -> Result<()> {
+    let (before, after) = (options.context_before, options.context_after);
+    let Some(Range::Line(range)) = &item.range else {
+        return Ok(());
+    };
+    if before == 0 && after == 0 {
+        return Ok(());
+    }
+
+    let bytes = std::fs::read(full_path)
+        .with_context(|| format!("Failed to open file: {:?}", full_path))?;
+    let text = decode_text(&bytes).text;
+    let lines: Vec<&str> = text.lines().collect();
+
+    // 0-based line indices: the range covers [start - 1, end)
+    let start = (range.start as usize).saturating_sub(1).min(lines.len());
+    let end = (range.end as usize).clamp(start, lines.len());
+    let before_lines = &lines[start.saturating_sub(before as usize)..start];
+    let after_lines = &lines[end..(end + after as usize).min(lines.len())];
+
+    let data = item.data.get_or_insert_with(|| json!({}));
+    data["context_before"] = json!(before_lines.len());
+    data["context_after"] = json!(after_lines.len());
+    data["before"] = json!(before_lines.join("\n"));
+    data["after"] = json!(after_lines.join("\n"));
+    Ok(())
+}
+
+/// Note item for a file that is denied or looks binary, if extract should skip it
+fn skipped_item(
+    root: &Path,
+    path: &Path,
+    full_path: &Path,
+    options: &ExtractOptions,
+) -> Option<ResultItem> {
+    let relative = || make_relative(full_path, root).unwrap_or_else(|| path.display().to_string());
+    if is_path_denied(full_path) {
+        Some(denied_item(relative()))
+    } else if !options.include_binary && is_probably_binary(full_path) {
+        Some(binary_skipped_item(relative()))
+    } else {
+        None
+    }
+}
+
+/// Which pattern of an `--from`/`--to` pair matched no line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PatternBound {
+    From,
+    To,
+}
+
+/// Find the span from the first line matching `from` to the next line after it matching `to`
+///
+/// Returns 1-based inclusive line numbers, or which pattern failed to match.
+fn find_pattern_range(text: &str, from: &Regex, to: &Regex) -> Result<(u32, u32), PatternBound> {
+    let mut lines = text.lines().zip(1u32..);
+    let start = lines
+        .find(|(line, _)| from.is_match(line))
+        .map(|(_, n)| n)
+        .ok_or(PatternBound::From)?;
+    let end = lines
+        .find(|(line, _)| to.is_match(line))
+        .map(|(_, n)| n)
+        .ok_or(PatternBound::To)?;
+    Ok((start, end))
+}
+
+/// Extract the span between two regex-matched lines to ResultSet
+///
+/// The span starts at the first line matching `from` and ends at the next
+/// line after it matching `to`, both inclusive; `data` records the patterns
+/// and the lines they matched. A pattern without a match yields a
+/// `RANGE_PATTERN_NOT_FOUND` error item.
+pub fn extract_pattern_to_result_set(
+    root: &Path,
+    path: &Path,
+    from: &str,
+    to: &str,
+    options: &ExtractOptions,
+) -> Result<ResultSet> {
+    let from_re = Regex::new(from).with_context(|| format!("Invalid --from pattern: {}", from))?;
+    let to_re = Regex::new(to).with_context(|| format!("Invalid --to pattern: {}", to))?;
+
+    let mut result_set = ResultSet::new();
+    let full_path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        root.join(path)
+    };
+    if let Some(item) = skipped_item(root, path, &full_path, options) {
+        result_set.push(item);
+        return Ok(result_set);
+    }
+
+    let bytes = std::fs::read(&full_path)
+        .with_context(|| format!("Failed to open file: {:?}", full_path))?;
+    let decoded = decode_text(&bytes);
+    let relative_path =
+        make_relative(&full_path, root).unwrap_or_else(|| path.display().to_string());
+    let (start, end) = match find_pattern_range(&decoded.text, &from_re, &to_re) {
+        Ok(range) => range,
+        Err(bound) => {
+            let (flag, pattern) = match bound {
+                PatternBound::From => ("--from", from),
+                PatternBound::To => ("--to", to),
+            };
+            let mut item = ResultItem::error(MiseError::new(
+                "RANGE_PATTERN_NOT_FOUND",
+                format!("No line matches {} pattern: {}", flag, pattern),
+            ));
+            item.path = Some(relative_path);
+            result_set.push(item);
+            return Ok(result_set);
+        }
+    };
+
+    // Reuse the decoded text rather than reading and decoding the file again
+    let lines = decoded.text.lines().map(Ok::<_, std::io::Error>);
+    let mut item = lines_to_item(
         relative_path,
-        Range::lines(start_line, actual_end),
-        content,
-    ))
+        lines,
+        start,
+        end,
+        options.max_bytes,
+        decoded.legacy_encoding(),
+    )?;
+    let mut data = json!({
+        "from": { "pattern": from, "line": start },
+        "to": { "pattern": to, "line": end },
+    });
+    if options.merge_with_anchors {
+        if let Some(relative) = item.path.clone() {
+            data["anchors"] = json!(anchor_membership(&full_path, &relative, start, end));
+        }
+    }
+    item.data = Some(data);
+    attach_context(&mut item, &full_path, options)?;
+    result_set.push(item);
+    Ok(result_set)
+}
+
+/// Run the extract command in regex-range mode
+pub fn run_extract_pattern(
+    root: &Path,
+    path: &Path,
+    from: &str,
+    to: &str,
+    options: &ExtractOptions,
+    config: RenderConfig,
+) -> Result<()> {
+    let result_set = extract_pattern_to_result_set(root, path, from, to, options)?;
+
+    let renderer = Renderer::with_config(config);
+    print!("{}", renderer.render(&result_set));
+
+    Ok(())
 }
 
 /// Run the extract command
@@ -119,29 +492,102 @@ pub fn run_extract(
     root: &Path,
     path: &Path,
     lines: &str,
-    max_bytes: usize,
+    options: &ExtractOptions,
+    config: RenderConfig,
+) -> Result<()> {
+    let result_set = extract_to_result_set(root, path, lines, options)?;
+
+    let renderer = Renderer::with_config(config);
+    print!("{}", renderer.render(&result_set));
+
+    Ok(())
+}
+
+/// Run the extract command in byte-range mode
+pub fn run_extract_bytes(
+    root: &Path,
+    path: &Path,
+    bytes: &str,
+    options: &ExtractOptions,
     config: RenderConfig,
 ) -> Result<()> {
-    let result_set = extract_to_result_set(root, path, lines, max_bytes)?;
+    let result_set = extract_bytes_to_result_set(root, path, bytes, options)?;
 
     let renderer = Renderer::with_config(config);
-    println!("{}", renderer.render(&result_set));
+    print!("{}", renderer.render(&result_set));
 
     Ok(())
 }
 
+/// Extract a byte range to ResultSet (for MCP and programmatic use)
+pub fn extract_bytes_to_result_set(
+    root: &Path,
+    path: &Path,
+    bytes: &str,
+    options: &ExtractOptions,
+) -> Result<ResultSet> {
+    let (start, end) = parse_byte_range(bytes)?;
+
+    let mut result_set = ResultSet::new();
+    let full_path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        root.join(path)
+    };
+    if let Some(item) = skipped_item(root, path, &full_path, options) {
+        result_set.push(item);
+        return Ok(result_set);
+    }
+
+    result_set.push(extract_bytes(root, path, start, end, options.max_bytes)?);
+    Ok(result_set)
+}
+
 /// Extract to ResultSet (for MCP and programmatic use)
 pub fn extract_to_result_set(
     root: &Path,
     path: &Path,
     lines: &str,
-    max_bytes: usize,
+    options: &ExtractOptions,
 ) -> Result<ResultSet> {
-    let (start, end) = parse_line_range(lines)?;
-    let item = extract_lines(root, path, start, end, max_bytes)?;
+    let ranges = parse_line_ranges(lines)?;
 
     let mut result_set = ResultSet::new();
-    result_set.push(item);
+    let full_path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        root.join(path)
+    };
+    if let Some(item) = skipped_item(root, path, &full_path, options) {
+        result_set.push(item);
+        return Ok(result_set);
+    }
+
+    // One item per range; max_bytes is a budget shared across all of them
+    let mut remaining = options.max_bytes;
+    let last = ranges.len() - 1;
+    for (i, (start, end)) in ranges.into_iter().enumerate() {
+        let mut item = extract_lines(root, path, start, end, remaining)?;
+        if options.merge_with_anchors {
+            if let (Some(relative), Some(Range::Line(range))) = (item.path.clone(), &item.range) {
+                let anchors = anchor_membership(&full_path, &relative, range.start, range.end);
+                item.data = Some(json!({ "anchors": anchors }));
+            }
+        }
+        attach_context(&mut item, &full_path, options)?;
+
+        remaining = remaining.saturating_sub(item.excerpt.as_ref().map(|e| e.len()).unwrap_or(0));
+        // Out of budget with ranges left over also counts as truncation
+        let exhausted = item.meta.truncated || (remaining == 0 && i < last);
+        result_set.push(item);
+
+        if exhausted {
+            if let Some(last) = result_set.items.last_mut() {
+                last.meta.truncated = true;
+            }
+            break;
+        }
+    }
 
     Ok(result_set)
 }
@@ -149,6 +595,7 @@ pub fn extract_to_result_set(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs::File;
     use std::io::Write;
     use tempfile::tempdir;
 
@@ -177,6 +624,196 @@ mod tests {
         assert_eq!(result.excerpt, Some("line 2\nline 3\nline 4".to_string()));
     }
 
+    #[test]
+    fn test_extract_merge_with_anchors() {
+        let temp = tempdir().unwrap();
+        std::fs::write(
+            temp.path().join("doc.md"),
+            "intro\n<!--Q:begin id=outer tags=a-->\none\n<!--Q:begin id=inner-->\ntwo\n<!--Q:end id=inner-->\n<!--Q:end id=outer-->\ntail\n",
+        )
+        .unwrap();
+
+        let options = ExtractOptions {
+            merge_with_anchors: true,
+            ..Default::default()
+        };
+        let result =
+            extract_to_result_set(temp.path(), Path::new("doc.md"), "3:8", &options).unwrap();
+        let anchors = &result.items[0].data.as_ref().unwrap()["anchors"];
+        assert_eq!(anchors.as_array().unwrap().len(), 2);
+        assert_eq!(anchors[0]["id"], "outer");
+        assert_eq!(anchors[0]["coverage"], "partial");
+        assert_eq!(anchors[0]["lines"]["end"], 7);
+        assert_eq!(anchors[1]["id"], "inner");
+        assert_eq!(anchors[1]["coverage"], "partial");
+
+        let result =
+            extract_to_result_set(temp.path(), Path::new("doc.md"), "5:5", &options).unwrap();
+        let anchors = &result.items[0].data.as_ref().unwrap()["anchors"];
+        assert_eq!(anchors[0]["coverage"], "full");
+        assert_eq!(anchors[1]["coverage"], "full");
+
+        let result = extract_to_result_set(
+            temp.path(),
+            Path::new("doc.md"),
+            "5:5",
+            &ExtractOptions::default(),
+        )
+        .unwrap();
+        assert!(result.items[0].data.is_none());
+    }
+
+    #[test]
+    fn test_extract_binary_skipped() {
+        let temp = tempdir().unwrap();
+        std::fs::write(temp.path().join("blob.bin"), b"\x00\x01\x02\nabc\n").unwrap();
+
+        let result = extract_to_result_set(
+            temp.path(),
+            Path::new("blob.bin"),
+            "1:2",
+            &ExtractOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(result.items.len(), 1);
+        assert_eq!(result.items[0].errors[0].code, "BINARY_SKIPPED");
+        assert!(result.items[0].excerpt.is_none());
+
+        let options = ExtractOptions {
+            include_binary: true,
+            ..Default::default()
+        };
+        let result =
+            extract_bytes_to_result_set(temp.path(), Path::new("blob.bin"), "4:7", &options)
+                .unwrap();
+        assert!(result.items[0].errors.is_empty());
+        assert_eq!(result.items[0].excerpt.as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn test_extract_legacy_encoding() {
+        let temp = tempdir().unwrap();
+        let (gbk, _, _) = encoding_rs::GBK.encode("第一行\n第二行中文\n第三行\n");
+        std::fs::write(temp.path().join("legacy.txt"), &gbk).unwrap();
+
+        let item = extract_lines(temp.path(), Path::new("legacy.txt"), 2, 2, 65536).unwrap();
+        assert_eq!(item.excerpt.as_deref(), Some("第二行中文"));
+        assert_eq!(item.meta.encoding.as_deref(), Some("GBK"));
+
+        // GBK characters are two bytes: "第一行\n" spans bytes 0..7
+        let item = extract_bytes(temp.path(), Path::new("legacy.txt"), 7, 13, 65536).unwrap();
+        assert_eq!(item.excerpt.as_deref(), Some("第二行"));
+        assert_eq!(item.meta.encoding.as_deref(), Some("GBK"));
+
+        // Pattern ranges match and extract from the same decoded text
+        let result = extract_pattern_to_result_set(
+            temp.path(),
+            Path::new("legacy.txt"),
+            "第二",
+            "第三",
+            &ExtractOptions::default(),
+        )
+        .unwrap();
+        let item = &result.items[0];
+        assert_eq!(item.excerpt.as_deref(), Some("第二行中文\n第三行"));
+        assert_eq!(item.meta.encoding.as_deref(), Some("GBK"));
+
+        std::fs::write(temp.path().join("utf8.txt"), "one\ntwo\n").unwrap();
+        let item = extract_lines(temp.path(), Path::new("utf8.txt"), 1, 1, 65536).unwrap();
+        assert_eq!(item.meta.encoding, None);
+    }
+
+    #[test]
+    fn test_extract_pattern_range() {
+        let temp = tempdir().unwrap();
+        std::fs::write(
+            temp.path().join("main.rs"),
+            "use std::io;\n\nfn main() {\n    run();\n}\n\nfn run() {\n}\n",
+        )
+        .unwrap();
+        let options = ExtractOptions::default();
+
+        let result = extract_pattern_to_result_set(
+            temp.path(),
+            Path::new("main.rs"),
+            r"fn main",
+            r"^\}",
+            &options,
+        )
+        .unwrap();
+        let item = &result.items[0];
+        assert_eq!(item.range, Some(Range::lines(3, 5)));
+        assert_eq!(item.excerpt.as_deref(), Some("fn main() {\n    run();\n}"));
+        assert_eq!(item.data.as_ref().unwrap()["to"]["line"], 5);
+
+        // `to` is only searched after the `from` line
+        let result = extract_pattern_to_result_set(
+            temp.path(),
+            Path::new("main.rs"),
+            r"fn run",
+            r"use std",
+            &options,
+        )
+        .unwrap();
+        let error = &result.items[0].errors[0];
+        assert_eq!(error.code, "RANGE_PATTERN_NOT_FOUND");
+        assert!(error.message.contains("--to"));
+
+        let result = extract_pattern_to_result_set(
+            temp.path(),
+            Path::new("main.rs"),
+            r"fn missing",
+            r"\}",
+            &options,
+        )
+        .unwrap();
+        assert!(result.items[0].errors[0].message.contains("--from"));
+        assert_eq!(result.items[0].path.as_deref(), Some("main.rs"));
+
+        assert!(extract_pattern_to_result_set(
+            temp.path(),
+            Path::new("main.rs"),
+            "(",
+            "x",
+            &options
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_extract_with_context() {
+        let temp = tempdir().unwrap();
+        std::fs::write(temp.path().join("a.txt"), "1\n2\n3\n4\n5\n6\n").unwrap();
+        let options = ExtractOptions {
+            context_before: 3,
+            context_after: 1,
+            ..Default::default()
+        };
+
+        let result =
+            extract_to_result_set(temp.path(), Path::new("a.txt"), "3:4,6:6", &options).unwrap();
+        let item = &result.items[0];
+        assert_eq!(item.excerpt.as_deref(), Some("3\n4"));
+        assert_eq!(item.range, Some(Range::lines(3, 4)));
+        let data = item.data.as_ref().unwrap();
+        // Clamped at the start of the file
+        assert_eq!(data["context_before"], 2);
+        assert_eq!(data["before"], "1\n2");
+        assert_eq!(data["context_after"], 1);
+        assert_eq!(data["after"], "5");
+
+        // Clamped at the end of the file
+        let data = result.items[1].data.as_ref().unwrap();
+        assert_eq!(data["context_after"], 0);
+        assert_eq!(data["after"], "");
+
+        // No context requested: no data
+        let result =
+            extract_to_result_set(temp.path(), Path::new("a.txt"), "3:4", &Default::default())
+                .unwrap();
+        assert!(result.items[0].data.is_none());
+    }
+
     #[test]
     fn test_extract_with_truncation() {
         let temp = tempdir().unwrap();
@@ -296,4 +933,73 @@ mod tests {
         let content = result.excerpt.unwrap();
         assert!(content.contains("content"));
     }
+
+    #[test]
+    fn test_parse_line_ranges() {
+        assert_eq!(parse_line_ranges("5:12").unwrap(), vec![(5, 12)]);
+        assert_eq!(
+            parse_line_ranges("1:20, 50:70,120:125").unwrap(),
+            vec![(1, 20), (50, 70), (120, 125)]
+        );
+        assert!(parse_line_ranges("1:2,").is_err());
+        assert!(parse_line_ranges("1:2,5:3").is_err());
+    }
+
+    #[test]
+    fn test_extract_multiple_ranges_shared_budget() {
+        let temp = tempdir().unwrap();
+        let content: String = (1..=10).map(|n| format!("line {:02}\n", n)).collect();
+        std::fs::write(temp.path().join("f.txt"), content).unwrap();
+
+        let options = ExtractOptions::default();
+        let result =
+            extract_to_result_set(temp.path(), Path::new("f.txt"), "1:2,5:5,9:10", &options)
+                .unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result.items[1].excerpt.as_deref(), Some("line 05"));
+        assert_eq!(result.items[2].range, Some(Range::lines(9, 10)));
+        assert!(result.items.iter().all(|i| !i.meta.truncated));
+
+        // 7 bytes per line: the first range uses 15, leaving 5 for the second
+        let options = ExtractOptions {
+            max_bytes: 20,
+            ..Default::default()
+        };
+        let result =
+            extract_to_result_set(temp.path(), Path::new("f.txt"), "1:2,5:5,9:10", &options)
+                .unwrap();
+        assert_eq!(result.len(), 2);
+        assert!(!result.items[0].meta.truncated);
+        assert!(result.items[1].meta.truncated);
+    }
+
+    #[test]
+    fn test_parse_byte_range() {
+        assert_eq!(parse_byte_range("0:10").unwrap(), (0, 10));
+        assert!(parse_byte_range("10:10").is_err());
+        assert!(parse_byte_range("10").is_err());
+        assert!(parse_byte_range("a:5").is_err());
+    }
+
+    #[test]
+    fn test_extract_bytes() {
+        let temp = tempdir().unwrap();
+        // "é" is two bytes (offsets 3-4)
+        std::fs::write(temp.path().join("f.txt"), "abcédef").unwrap();
+
+        let item = extract_bytes(temp.path(), Path::new("f.txt"), 1, 3, 65536).unwrap();
+        assert_eq!(item.excerpt.as_deref(), Some("bc"));
+        assert_eq!(item.range, Some(Range::bytes(1, 3)));
+        assert!(!item.meta.adjusted);
+
+        // Ending inside "é" snaps forward to include the whole character
+        let item = extract_bytes(temp.path(), Path::new("f.txt"), 4, 6, 65536).unwrap();
+        assert_eq!(item.excerpt.as_deref(), Some("éd"));
+        assert_eq!(item.range, Some(Range::bytes(3, 6)));
+        assert!(item.meta.adjusted);
+        assert_eq!(item.data.as_ref().unwrap()["requested"]["start"], 4);
+
+        let err = extract_bytes(temp.path(), Path::new("f.txt"), 0, 100, 65536).unwrap_err();
+        assert!(err.to_string().contains("exceeds file size"));
+    }
 }
@@ -4,6 +4,7 @@
 //! the dependency graph to understand "what will be affected by this change".
 
 use anyhow::Result;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::path::Path;
@@ -11,10 +12,10 @@ use std::process::Command;
 
 use crate::anchors::parse::parse_file;
 use crate::backends::deps::{analyze_deps, DepGraph};
-use crate::backends::scan::{scan_files, ScanOptions};
+use crate::backends::scan::{scan_files, GlobFilter, ScanOptions};
 use crate::core::model::{Confidence, Kind, MiseError, ResultItem, ResultSet, SourceMode};
 use crate::core::render::{RenderConfig, Renderer};
-use crate::core::util::command_exists;
+use crate::core::util::{command_exists, escape_xml};
 
 /// Source of diff information
 #[derive(Debug, Clone, Default)]
@@ -28,6 +29,8 @@ pub enum DiffSource {
     Commit(String),
     /// Branch comparison (git diff base..head)
     Diff(String, String),
+    /// Changes on head since it forked from base (git diff base...head)
+    MergeBase(String, String),
 }
 
 impl DiffSource {
@@ -38,8 +41,10 @@ impl DiffSource {
         } else if let Some(c) = commit {
             DiffSource::Commit(c.to_string())
         } else if let Some(d) = diff {
-            // Parse "base..head" format
-            if let Some((base, head)) = d.split_once("..") {
+            // Parse "base...head" (merge-base) and "base..head" formats
+            if let Some((base, head)) = d.split_once("...") {
+                DiffSource::MergeBase(base.to_string(), head.to_string())
+            } else if let Some((base, head)) = d.split_once("..") {
                 DiffSource::Diff(base.to_string(), head.to_string())
             } else {
                 // Treat as commit
@@ -70,6 +75,24 @@ impl DiffSource {
                 "--name-only".to_string(),
                 format!("{}..{}", base, head),
             ],
+            DiffSource::MergeBase(base, head) => vec![
+                "diff".to_string(),
+                "--name-only".to_string(),
+                format!("{}...{}", base, head),
+            ],
+        }
+    }
+
+    /// Revisions named by this source (empty range ends mean HEAD)
+    fn refs(&self) -> Vec<&str> {
+        match self {
+            DiffSource::Unstaged | DiffSource::Staged => Vec::new(),
+            DiffSource::Commit(c) => vec![c.as_str()],
+            DiffSource::Diff(base, head) | DiffSource::MergeBase(base, head) => [base, head]
+                .into_iter()
+                .map(String::as_str)
+                .filter(|r| !r.is_empty())
+                .collect(),
         }
     }
 
@@ -80,12 +103,100 @@ impl DiffSource {
             DiffSource::Staged => "staged changes".to_string(),
             DiffSource::Commit(c) => format!("commit {}", c),
             DiffSource::Diff(base, head) => format!("{}..{}", base, head),
+            DiffSource::MergeBase(base, head) => format!("{}...{}", base, head),
+        }
+    }
+}
+
+/// Severity of a changed file, from its reverse-dependency fan-out
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
         }
     }
 }
 
+/// Minimum fan-out for each severity above low
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeverityThresholds {
+    pub medium: usize,
+    pub high: usize,
+    pub critical: usize,
+}
+
+impl Default for SeverityThresholds {
+    fn default() -> Self {
+        Self {
+            medium: 1,
+            high: 5,
+            critical: 20,
+        }
+    }
+}
+
+impl SeverityThresholds {
+    /// Classify a fan-out (number of transitive dependents)
+    pub fn classify(&self, fan_out: usize) -> Severity {
+        if fan_out >= self.critical {
+            Severity::Critical
+        } else if fan_out >= self.high {
+            Severity::High
+        } else if fan_out >= self.medium {
+            Severity::Medium
+        } else {
+            Severity::Low
+        }
+    }
+}
+
+impl std::str::FromStr for SeverityThresholds {
+    type Err = String;
+
+    /// Parse `MEDIUM,HIGH,CRITICAL`, e.g. `1,5,20`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid severity thresholds: {} (expected e.g. 1,5,20)", s);
+        let values = s
+            .split(',')
+            .map(|v| v.trim().parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid())?;
+        match values[..] {
+            [medium, high, critical] if medium <= high && high <= critical => Ok(Self {
+                medium,
+                high,
+                critical,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Fan-out and severity of one changed file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FileSeverity {
+    pub file: String,
+    /// Number of files that transitively depend on this file
+    pub fan_out: usize,
+    pub severity: Severity,
+}
+
 /// Impact analysis result
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ImpactAnalysis {
     /// Files that were changed
     pub changed_files: Vec<String>,
@@ -95,6 +206,12 @@ pub struct ImpactAnalysis {
     pub transitive_impacts: Vec<String>,
     /// Anchors that are affected by the changes
     pub anchors_affected: Vec<String>,
+    /// Test files covering the changed and impacted files (heuristic)
+    #[serde(default)]
+    pub affected_tests: Vec<String>,
+    /// Per changed file severity, most severe first
+    #[serde(default)]
+    pub severities: Vec<FileSeverity>,
     /// Description of the diff source
     pub source: String,
 }
@@ -106,6 +223,8 @@ impl ImpactAnalysis {
             direct_impacts: Vec::new(),
             transitive_impacts: Vec::new(),
             anchors_affected: Vec::new(),
+            affected_tests: Vec::new(),
+            severities: Vec::new(),
             source: source.to_string(),
         }
     }
@@ -114,10 +233,65 @@ impl ImpactAnalysis {
     pub fn total_affected(&self) -> usize {
         self.changed_files.len() + self.direct_impacts.len() + self.transitive_impacts.len()
     }
+
+    /// Risk score: number of downstream files reached by the change
+    pub fn risk_score(&self) -> usize {
+        self.direct_impacts.len() + self.transitive_impacts.len()
+    }
+
+    /// Severity of a changed file, if it was scored
+    pub fn severity_of(&self, file: &str) -> Option<&FileSeverity> {
+        self.severities.iter().find(|s| s.file == file)
+    }
+}
+
+/// First revision of the source that git cannot resolve to a commit
+fn find_invalid_ref(root: &Path, source: &DiffSource) -> Option<String> {
+    source.refs().into_iter().find_map(|rev| {
+        let output = Command::new("git")
+            .current_dir(root)
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("{}^{{commit}}", rev))
+            .output()
+            .ok()?;
+        // Outside a repository every ref fails; leave that to get_changed_files
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        (!output.status.success() && !stderr.contains("not a git repository"))
+            .then(|| rev.to_string())
+    })
+}
+
+/// Resolve the merge base of two revisions (`git merge-base`)
+fn merge_base(root: &Path, base: &str, head: &str) -> Result<String> {
+    let or_head = |rev: &str| if rev.is_empty() { "HEAD" } else { rev }.to_string();
+    let output = Command::new("git")
+        .current_dir(root)
+        .arg("merge-base")
+        .arg(or_head(base))
+        .arg(or_head(head))
+        .output()?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "No merge base between {} and {}",
+            or_head(base),
+            or_head(head)
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
 /// Get changed files from git diff
 fn get_changed_files(root: &Path, source: &DiffSource) -> Result<Vec<String>> {
+    // Three-dot ranges diff head against the point where it forked from base
+    let resolved;
+    let source = match source {
+        DiffSource::MergeBase(base, head) => {
+            resolved = DiffSource::Diff(merge_base(root, base, head)?, head.clone());
+            &resolved
+        }
+        other => other,
+    };
     let args = source.git_args();
 
     // Build command
@@ -218,6 +392,49 @@ fn compute_transitive_impacts(
     result
 }
 
+/// Count the files that transitively depend on `file` (unbounded depth)
+fn compute_fan_out(file: &str, graph: &DepGraph) -> usize {
+    let mut seen: HashSet<String> = HashSet::from([file.to_string()]);
+    let mut queue = vec![file.to_string()];
+
+    while let Some(current) = queue.pop() {
+        for dep in graph.get_reverse_deps(&current) {
+            if seen.insert(dep.clone()) {
+                queue.push(dep);
+            }
+        }
+    }
+
+    seen.len() - 1
+}
+
+/// Score each changed file by fan-out, most severe (then widest) first
+fn compute_severities(
+    changed: &[String],
+    graph: &DepGraph,
+    thresholds: &SeverityThresholds,
+) -> Vec<FileSeverity> {
+    let mut severities: Vec<_> = changed
+        .iter()
+        .map(|file| {
+            let fan_out = compute_fan_out(file, graph);
+            FileSeverity {
+                file: file.clone(),
+                fan_out,
+                severity: thresholds.classify(fan_out),
+            }
+        })
+        .collect();
+
+    severities.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then(b.fan_out.cmp(&a.fan_out))
+            .then(a.file.cmp(&b.file))
+    });
+    severities
+}
+
 /// Find anchors that are affected by file changes
 fn find_affected_anchors(
     root: &Path,
@@ -300,19 +517,123 @@ fn is_anchor_candidate(path: &Path) -> bool {
     )
 }
 
+/// Whether a path follows a test file naming convention
+fn is_test_file(path: &str) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    path.starts_with("tests/")
+        || path.contains("/tests/")
+        || path.contains("/__tests__/")
+        || (file_name.starts_with("test_") && file_name.ends_with(".py"))
+        || [
+            "_test.rs",
+            "_test.py",
+            "_test.go",
+            ".test.ts",
+            ".test.tsx",
+            ".test.js",
+            ".test.jsx",
+            ".spec.ts",
+            ".spec.tsx",
+            ".spec.js",
+            ".spec.jsx",
+        ]
+        .iter()
+        .any(|suffix| file_name.ends_with(suffix))
+}
+
+/// Conventional locations of the tests for a source file (may not exist)
+fn test_candidates(path: &str) -> Vec<String> {
+    let (dir, file_name) = match path.rsplit_once('/') {
+        Some((dir, name)) => (format!("{}/", dir), name),
+        None => (String::new(), path),
+    };
+    let Some((stem, ext)) = file_name.rsplit_once('.') else {
+        return Vec::new();
+    };
+
+    match ext {
+        "rs" => vec![
+            format!("{}{}_test.rs", dir, stem),
+            format!("{}tests/{}.rs", dir, stem),
+            format!("tests/{}.rs", stem),
+            format!("tests/{}_test.rs", stem),
+        ],
+        "py" => vec![
+            format!("{}test_{}.py", dir, stem),
+            format!("{}{}_test.py", dir, stem),
+            format!("{}tests/test_{}.py", dir, stem),
+            format!("tests/test_{}.py", stem),
+        ],
+        "ts" | "tsx" | "js" | "jsx" => vec![
+            format!("{}{}.test.{}", dir, stem, ext),
+            format!("{}{}.spec.{}", dir, stem, ext),
+            format!("{}__tests__/{}.test.{}", dir, stem, ext),
+        ],
+        "go" => vec![format!("{}{}_test.go", dir, stem)],
+        _ => Vec::new(),
+    }
+}
+
+/// Map affected files to the test files that likely cover them
+///
+/// A file counts as its own test when it is a test file or a Rust file with
+/// an inline `#[cfg(test)]` module; otherwise sibling and `tests/` files
+/// following the language's naming convention are looked up on disk.
+fn find_affected_tests<'a>(
+    root: &Path,
+    files: impl IntoIterator<Item = &'a String>,
+) -> Vec<String> {
+    let mut tests = Vec::new();
+
+    for file in files {
+        if is_test_file(file) {
+            tests.push(file.clone());
+            continue;
+        }
+
+        if file.ends_with(".rs")
+            && std::fs::read_to_string(root.join(file))
+                .is_ok_and(|content| content.contains("#[cfg(test)]"))
+        {
+            tests.push(file.clone());
+        }
+
+        tests.extend(
+            test_candidates(file)
+                .into_iter()
+                .filter(|candidate| root.join(candidate).is_file()),
+        );
+    }
+
+    tests.sort();
+    tests.dedup();
+    tests
+}
+
 /// Analyze the impact of changes
-pub fn analyze_impact(root: &Path, source: DiffSource, max_depth: usize) -> Result<ImpactAnalysis> {
+pub fn analyze_impact(
+    root: &Path,
+    source: DiffSource,
+    max_depth: usize,
+    thresholds: &SeverityThresholds,
+    paths: &[String],
+    jobs: usize,
+) -> Result<ImpactAnalysis> {
     let mut analysis = ImpactAnalysis::new(&source.description());
 
-    // Step 1: Get changed files from git
+    // Step 1: Get changed files from git, restricted to the path globs
     analysis.changed_files = get_changed_files(root, &source)?;
+    if !paths.is_empty() {
+        let filter = GlobFilter::new(paths.to_vec(), Vec::new())?;
+        analysis.changed_files.retain(|file| filter.matches(file));
+    }
 
     if analysis.changed_files.is_empty() {
         return Ok(analysis);
     }
 
     // Step 2: Build dependency graph
-    let graph = analyze_deps(root, None)?;
+    let graph = analyze_deps(root, None, &GlobFilter::default(), false, jobs)?;
 
     // Step 3: Compute direct impacts
     analysis.direct_impacts = compute_direct_impacts(&analysis.changed_files, &graph);
@@ -325,7 +646,10 @@ pub fn analyze_impact(root: &Path, source: DiffSource, max_depth: usize) -> Resu
         max_depth,
     );
 
-    // Step 5: Find affected anchors
+    // Step 5: Score changed files by fan-out
+    analysis.severities = compute_severities(&analysis.changed_files, &graph, thresholds);
+
+    // Step 6: Find affected anchors
     analysis.anchors_affected = find_affected_anchors(
         root,
         &analysis.changed_files,
@@ -333,6 +657,16 @@ pub fn analyze_impact(root: &Path, source: DiffSource, max_depth: usize) -> Resu
         &analysis.transitive_impacts,
     );
 
+    // Step 7: Map affected files to their tests
+    analysis.affected_tests = find_affected_tests(
+        root,
+        analysis
+            .changed_files
+            .iter()
+            .chain(&analysis.direct_impacts)
+            .chain(&analysis.transitive_impacts),
+    );
+
     Ok(analysis)
 }
 
@@ -388,6 +722,7 @@ pub enum ImpactFormat {
     Json,
     Summary,
     Table,
+    Junit,
 }
 
 impl std::str::FromStr for ImpactFormat {
@@ -399,6 +734,7 @@ impl std::str::FromStr for ImpactFormat {
             "json" => Ok(ImpactFormat::Json),
             "summary" => Ok(ImpactFormat::Summary),
             "table" => Ok(ImpactFormat::Table),
+            "junit" => Ok(ImpactFormat::Junit),
             _ => Err(format!("Unknown impact format: {}", s)),
         }
     }
@@ -422,7 +758,15 @@ fn format_summary(analysis: &ImpactAnalysis) -> String {
         analysis.changed_files.len()
     ));
     for file in &analysis.changed_files {
-        output.push_str(&format!("   {}\n", file));
+        match analysis.severity_of(file) {
+            Some(s) => output.push_str(&format!(
+                "   {} [{}, {} dependents]\n",
+                file,
+                s.severity.as_str(),
+                s.fan_out
+            )),
+            None => output.push_str(&format!("   {}\n", file)),
+        }
     }
     output.push('\n');
 
@@ -462,6 +806,18 @@ fn format_summary(analysis: &ImpactAnalysis) -> String {
         output.push('\n');
     }
 
+    // Affected tests
+    if !analysis.affected_tests.is_empty() {
+        output.push_str(&format!(
+            "🧪 Affected tests ({})\n",
+            analysis.affected_tests.len()
+        ));
+        for test in &analysis.affected_tests {
+            output.push_str(&format!("   {}\n", test));
+        }
+        output.push('\n');
+    }
+
     // Summary
     output.push_str(&format!(
         "Total affected: {} files\n",
@@ -489,69 +845,80 @@ fn format_table(analysis: &ImpactAnalysis) -> String {
 
     let max_path_len = all_files.iter().map(|p| p.len()).max().unwrap_or(4).max(4);
     let type_width = 18;
+    let severity_width = 8;
 
     // Header
     output.push_str(&format!(
-        "┌─{:─<width$}─┬─{:─<type_width$}─┐\n",
+        "┌─{:─<width$}─┬─{:─<type_width$}─┬─{:─<severity_width$}─┐\n",
+        "",
         "",
         "",
         width = max_path_len,
-        type_width = type_width
+        type_width = type_width,
+        severity_width = severity_width
     ));
     output.push_str(&format!(
-        "│ {:width$} │ {:type_width$} │\n",
+        "│ {:width$} │ {:type_width$} │ {:severity_width$} │\n",
         "File",
         "Impact Type",
+        "Severity",
         width = max_path_len,
-        type_width = type_width
+        type_width = type_width,
+        severity_width = severity_width
     ));
     output.push_str(&format!(
-        "├─{:─<width$}─┼─{:─<type_width$}─┤\n",
+        "├─{:─<width$}─┼─{:─<type_width$}─┼─{:─<severity_width$}─┤\n",
+        "",
         "",
         "",
         width = max_path_len,
-        type_width = type_width
+        type_width = type_width,
+        severity_width = severity_width
     ));
 
-    // Changed files
-    for file in &analysis.changed_files {
-        output.push_str(&format!(
-            "│ {:width$} │ {:type_width$} │\n",
-            file,
-            "🔴 changed",
-            width = max_path_len,
-            type_width = type_width
-        ));
-    }
-
-    // Direct impacts
-    for file in &analysis.direct_impacts {
-        output.push_str(&format!(
-            "│ {:width$} │ {:type_width$} │\n",
-            file,
-            "🟠 direct impact",
-            width = max_path_len,
-            type_width = type_width
-        ));
-    }
-
-    // Transitive impacts
-    for file in &analysis.transitive_impacts {
+    // Changed files, most severe first
+    let mut changed: Vec<_> = analysis
+        .changed_files
+        .iter()
+        .map(|file| (file, analysis.severity_of(file)))
+        .collect();
+    changed.sort_by_key(|(_, s)| std::cmp::Reverse(s.map(|s| (s.severity, s.fan_out))));
+
+    let rows = changed
+        .into_iter()
+        .map(|(file, s)| (file, "🔴 changed", s.map_or("-", |s| s.severity.as_str())))
+        .chain(
+            analysis
+                .direct_impacts
+                .iter()
+                .map(|file| (file, "🟠 direct impact", "-")),
+        )
+        .chain(
+            analysis
+                .transitive_impacts
+                .iter()
+                .map(|file| (file, "🟡 transitive", "-")),
+        );
+    for (file, impact_type, severity) in rows {
         output.push_str(&format!(
-            "│ {:width$} │ {:type_width$} │\n",
+            "│ {:width$} │ {:type_width$} │ {:severity_width$} │\n",
             file,
-            "🟡 transitive",
+            impact_type,
+            severity,
             width = max_path_len,
-            type_width = type_width
+            type_width = type_width,
+            severity_width = severity_width
         ));
     }
 
     output.push_str(&format!(
-        "└─{:─<width$}─┴─{:─<type_width$}─┘\n",
+        "└─{:─<width$}─┴─{:─<type_width$}─┴─{:─<severity_width$}─┘\n",
+        "",
         "",
         "",
         width = max_path_len,
-        type_width = type_width
+        type_width = type_width,
+        severity_width = severity_width
     ));
 
     // Anchors section
@@ -562,19 +929,104 @@ fn format_table(analysis: &ImpactAnalysis) -> String {
         ));
     }
 
+    // Tests section
+    if !analysis.affected_tests.is_empty() {
+        output.push_str(&format!(
+            "\n🧪 Affected tests: {}\n",
+            analysis.affected_tests.join(", ")
+        ));
+    }
+
     output
 }
 
+/// Format impact analysis as JUnit XML
+///
+/// Each changed/impacted file becomes a passing `<testcase>`; exceeding
+/// `max_risk` adds a failing threshold testcase so CI dashboards flag it.
+fn format_junit(analysis: &ImpactAnalysis, max_risk: Option<usize>) -> String {
+    let mut cases = Vec::new();
+    for (class, files) in [
+        ("impact.changed", &analysis.changed_files),
+        ("impact.direct", &analysis.direct_impacts),
+        ("impact.transitive", &analysis.transitive_impacts),
+    ] {
+        for file in files {
+            cases.push(format!(
+                "    <testcase classname=\"{}\" name=\"{}\"/>\n",
+                class,
+                escape_xml(file)
+            ));
+        }
+    }
+
+    let risk = analysis.risk_score();
+    let mut failures = 0;
+    if let Some(max) = max_risk {
+        if risk > max {
+            failures += 1;
+            cases.push(format!(
+                "    <testcase classname=\"impact.threshold\" name=\"max_risk\">\n\
+                 \x20     <failure message=\"risk score {} exceeds threshold {}\"/>\n\
+                 \x20   </testcase>\n",
+                risk, max
+            ));
+        } else {
+            cases.push(
+                "    <testcase classname=\"impact.threshold\" name=\"max_risk\"/>\n".to_string(),
+            );
+        }
+    }
+
+    let mut output = String::new();
+    output.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    output.push_str("<testsuites>\n");
+    output.push_str(&format!(
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+        escape_xml(&analysis.source),
+        cases.len(),
+        failures
+    ));
+    output.push_str("    <properties>\n");
+    output.push_str(&format!(
+        "      <property name=\"risk_score\" value=\"{}\"/>\n",
+        risk
+    ));
+    output.push_str("    </properties>\n");
+    for case in cases {
+        output.push_str(&case);
+    }
+    output.push_str("  </testsuite>\n");
+    output.push_str("</testsuites>");
+
+    output
+}
+
+/// Options for the impact command
+#[derive(Debug, Clone, Default)]
+pub struct ImpactOptions {
+    /// Analyze staged changes
+    pub staged: bool,
+    /// Analyze a specific commit
+    pub commit: Option<String>,
+    /// Analyze a revision range (base..head)
+    pub diff: Option<String>,
+    /// Maximum depth for transitive impact analysis
+    pub max_depth: usize,
+    /// Output format
+    pub format: ImpactFormat,
+    /// Risk threshold reported as a failure in junit output
+    pub max_risk: Option<usize>,
+    /// Fan-out thresholds for changed file severities
+    pub severity_thresholds: SeverityThresholds,
+    /// Only changed files matching these globs are analyzed
+    pub paths: Vec<String>,
+    /// Worker threads for the dependency graph (0 = one per CPU core)
+    pub jobs: usize,
+}
+
 /// Run the impact command
-pub fn run_impact(
-    root: &Path,
-    staged: bool,
-    commit: Option<&str>,
-    diff: Option<&str>,
-    max_depth: usize,
-    format: ImpactFormat,
-    config: RenderConfig,
-) -> Result<()> {
+pub fn run_impact(root: &Path, options: ImpactOptions, config: RenderConfig) -> Result<()> {
     // Check if git is available
     if !command_exists("git") {
         let mut result_set = ResultSet::new();
@@ -583,20 +1035,44 @@ pub fn run_impact(
             "git is not installed. Please install git to use impact analysis.",
         )));
         let renderer = Renderer::with_config(config);
-        println!("{}", renderer.render(&result_set));
+        print!("{}", renderer.render(&result_set));
         return Ok(());
     }
 
     // Determine diff source
-    let source = DiffSource::from_args(staged, commit, diff);
+    let source = DiffSource::from_args(
+        options.staged,
+        options.commit.as_deref(),
+        options.diff.as_deref(),
+    );
+
+    if let Some(rev) = find_invalid_ref(root, &source) {
+        let mut result_set = ResultSet::new();
+        result_set.push(ResultItem::error(MiseError::new(
+            "INVALID_GIT_REF",
+            format!("Unknown git revision: {}", rev),
+        )));
+        let renderer = Renderer::with_config(config);
+        print!("{}", renderer.render(&result_set));
+        return Ok(());
+    }
 
     // Analyze impact
-    let analysis = analyze_impact(root, source, max_depth)?;
+    let analysis = analyze_impact(
+        root,
+        source,
+        options.max_depth,
+        &options.severity_thresholds,
+        &options.paths,
+        options.jobs,
+    )?;
 
     // Output based on format
+    let format = options.format;
     let output = match format {
         ImpactFormat::Summary => format_summary(&analysis),
-        ImpactFormat::Table => format_table(&analysis),
+        ImpactFormat::Table => config.box_style().table(format_table(&analysis)),
+        ImpactFormat::Junit => format_junit(&analysis, options.max_risk),
         ImpactFormat::Jsonl | ImpactFormat::Json => {
             // For JSON formats, output the analysis directly
             if format == ImpactFormat::Json {
@@ -650,6 +1126,10 @@ mod tests {
             "table".parse::<ImpactFormat>().unwrap(),
             ImpactFormat::Table
         );
+        assert_eq!(
+            "junit".parse::<ImpactFormat>().unwrap(),
+            ImpactFormat::Junit
+        );
     }
 
     #[test]
@@ -702,6 +1182,80 @@ mod tests {
         assert_eq!(args, vec!["diff", "--name-only", "main..feature"]);
     }
 
+    #[test]
+    fn test_diff_source_three_dot_range() {
+        let source = DiffSource::from_args(false, None, Some("main...feature"));
+        assert!(matches!(&source, DiffSource::MergeBase(b, h) if b == "main" && h == "feature"));
+        assert_eq!(source.description(), "main...feature");
+        assert_eq!(source.refs(), vec!["main", "feature"]);
+
+        // An empty side means HEAD and is not verified
+        let source = DiffSource::from_args(false, None, Some("main.."));
+        assert_eq!(source.refs(), vec!["main"]);
+        assert!(DiffSource::Staged.refs().is_empty());
+    }
+
+    #[test]
+    fn test_git_refs_merge_base_and_paths() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .current_dir(root)
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?} failed", args);
+        };
+
+        git(&["init", "-q", "-b", "main"]);
+        std::fs::write(root.join("base.txt"), "0").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "base"]);
+        git(&["checkout", "-q", "-b", "feature"]);
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("src/a.rs"), "").unwrap();
+        std::fs::write(root.join("notes.md"), "").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "feature"]);
+        git(&["checkout", "-q", "main"]);
+        std::fs::write(root.join("base.txt"), "1").unwrap();
+        git(&["commit", "-q", "-am", "main moves on"]);
+
+        // Two dots also sees main's own change; three dots only the feature's
+        let two = get_changed_files(
+            root,
+            &DiffSource::from_args(false, None, Some("main..feature")),
+        );
+        assert!(two.unwrap().contains(&"base.txt".to_string()));
+        let three = DiffSource::from_args(false, None, Some("main...feature"));
+        assert_eq!(
+            get_changed_files(root, &three).unwrap(),
+            vec!["notes.md", "src/a.rs"]
+        );
+
+        let paths = vec!["src/*".to_string()];
+        let analysis =
+            analyze_impact(root, three, 3, &SeverityThresholds::default(), &paths, 0).unwrap();
+        assert_eq!(analysis.changed_files, vec!["src/a.rs"]);
+
+        assert_eq!(
+            find_invalid_ref(
+                root,
+                &DiffSource::from_args(false, None, Some("main...feature"))
+            ),
+            None
+        );
+        assert_eq!(
+            find_invalid_ref(
+                root,
+                &DiffSource::from_args(false, None, Some("main..nope"))
+            ),
+            Some("nope".to_string())
+        );
+    }
+
     #[test]
     fn test_diff_source_description() {
         assert_eq!(DiffSource::Unstaged.description(), "unstaged changes");
@@ -822,6 +1376,148 @@ mod tests {
         assert!(output.contains("📌")); // Affected anchors
     }
 
+    #[test]
+    fn test_find_affected_tests() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        for (path, content) in [
+            ("src/inline.rs", "fn a() {}\n#[cfg(test)]\nmod tests {}\n"),
+            ("src/plain.rs", "fn b() {}\n"),
+            ("tests/plain.rs", ""),
+            ("pkg/util.py", ""),
+            ("pkg/test_util.py", ""),
+            ("web/app.ts", ""),
+            ("web/app.test.ts", ""),
+            ("cmd/main.go", ""),
+        ] {
+            let full = root.join(path);
+            std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+            std::fs::write(full, content).unwrap();
+        }
+
+        let files: Vec<String> = [
+            "src/inline.rs",
+            "src/plain.rs",
+            "pkg/util.py",
+            "web/app.ts",
+            "web/app.test.ts",
+            "cmd/main.go",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        assert_eq!(
+            find_affected_tests(root, &files),
+            vec![
+                "pkg/test_util.py",
+                "src/inline.rs",
+                "tests/plain.rs",
+                "web/app.test.ts"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_summary_and_table_list_tests() {
+        let mut analysis = ImpactAnalysis::new("test");
+        analysis.changed_files = vec!["src/lib.rs".to_string()];
+        analysis.affected_tests = vec!["tests/lib.rs".to_string()];
+
+        assert!(format_summary(&analysis).contains("🧪 Affected tests (1)\n   tests/lib.rs"));
+        assert!(format_table(&analysis).contains("🧪 Affected tests: tests/lib.rs"));
+    }
+
+    #[test]
+    fn test_severity_thresholds() {
+        let thresholds: SeverityThresholds = "2, 4,8".parse().unwrap();
+        assert_eq!(thresholds.classify(0), Severity::Low);
+        assert_eq!(thresholds.classify(2), Severity::Medium);
+        assert_eq!(thresholds.classify(7), Severity::High);
+        assert_eq!(thresholds.classify(8), Severity::Critical);
+
+        assert!("1,5".parse::<SeverityThresholds>().is_err());
+        assert!("5,1,20".parse::<SeverityThresholds>().is_err());
+        assert!("a,b,c".parse::<SeverityThresholds>().is_err());
+    }
+
+    #[test]
+    fn test_compute_severities_by_fan_out() {
+        use crate::backends::deps::{FileDeps, Language};
+
+        // core.rs <- a.rs <- b.rs <- core.rs (cycle), leaf.rs imported by nobody
+        let mut graph = DepGraph::default();
+        for (file, depended_by) in [
+            ("core.rs", vec!["a.rs"]),
+            ("a.rs", vec!["b.rs"]),
+            ("b.rs", vec!["core.rs"]),
+            ("leaf.rs", vec![]),
+        ] {
+            graph.files.insert(
+                file.to_string(),
+                FileDeps {
+                    path: file.to_string(),
+                    language: Language::Rust,
+                    depends_on: vec![],
+                    depended_by: depended_by.into_iter().map(String::from).collect(),
+                },
+            );
+        }
+
+        let changed = vec!["leaf.rs".to_string(), "core.rs".to_string()];
+        let thresholds = SeverityThresholds {
+            medium: 1,
+            high: 2,
+            critical: 3,
+        };
+        let severities = compute_severities(&changed, &graph, &thresholds);
+        assert_eq!(
+            severities,
+            vec![
+                FileSeverity {
+                    file: "core.rs".to_string(),
+                    fan_out: 2,
+                    severity: Severity::High,
+                },
+                FileSeverity {
+                    file: "leaf.rs".to_string(),
+                    fan_out: 0,
+                    severity: Severity::Low,
+                },
+            ]
+        );
+
+        let json = serde_json::to_value(&severities[0]).unwrap();
+        assert_eq!(json["severity"], "high");
+    }
+
+    #[test]
+    fn test_format_table_sorts_by_severity() {
+        let mut analysis = ImpactAnalysis::new("test");
+        analysis.changed_files = vec!["a.rs".to_string(), "z.rs".to_string()];
+        analysis.severities = vec![
+            FileSeverity {
+                file: "z.rs".to_string(),
+                fan_out: 30,
+                severity: Severity::Critical,
+            },
+            FileSeverity {
+                file: "a.rs".to_string(),
+                fan_out: 0,
+                severity: Severity::Low,
+            },
+        ];
+
+        let table = format_table(&analysis);
+        let z = table.find("z.rs").unwrap();
+        let a = table.find("a.rs").unwrap();
+        assert!(z < a);
+        assert!(table.contains("critical"));
+
+        let summary = format_summary(&analysis);
+        assert!(summary.contains("z.rs [critical, 30 dependents]"));
+    }
+
     #[test]
     fn test_impact_analysis_serialization() {
         let mut analysis = ImpactAnalysis::new("test");
@@ -834,4 +1530,34 @@ mod tests {
         let parsed: ImpactAnalysis = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.changed_files, analysis.changed_files);
     }
+
+    #[test]
+    fn test_format_junit_testcases_and_risk() {
+        let mut analysis = ImpactAnalysis::new("main..feature");
+        analysis.changed_files = vec!["a.rs".to_string()];
+        analysis.direct_impacts = vec!["b<c>.rs".to_string()];
+        analysis.transitive_impacts = vec!["d.rs".to_string()];
+
+        let output = format_junit(&analysis, None);
+        assert!(output.starts_with("<?xml"));
+        assert!(output.contains(r#"<testsuite name="main..feature" tests="3" failures="0">"#));
+        assert!(output.contains(r#"<property name="risk_score" value="2"/>"#));
+        assert!(output.contains(r#"classname="impact.direct" name="b&lt;c&gt;.rs""#));
+        assert!(!output.contains("<failure"));
+    }
+
+    #[test]
+    fn test_format_junit_threshold_violation() {
+        let mut analysis = ImpactAnalysis::new("staged changes");
+        analysis.changed_files = vec!["a.rs".to_string()];
+        analysis.direct_impacts = vec!["b.rs".to_string(), "c.rs".to_string()];
+
+        let output = format_junit(&analysis, Some(1));
+        assert!(output.contains(r#"failures="1""#));
+        assert!(output.contains(r#"<failure message="risk score 2 exceeds threshold 1"/>"#));
+
+        let output = format_junit(&analysis, Some(5));
+        assert!(output.contains(r#"failures="0""#));
+        assert!(output.contains(r#"name="max_risk"/>"#));
+    }
 }
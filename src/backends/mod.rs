@@ -5,6 +5,7 @@
 //! - extract: Ranged file reading
 //! - rg: ripgrep integration
 //! - ast_grep: ast-grep integration
+//! - search: Combined ripgrep + ast-grep search
 //! - deps: Dependency graph analysis
 //! - doctor: Dependency checking
 //! - run: Concurrent command execution
@@ -18,6 +19,7 @@ pub mod impact;
 pub mod rg;
 pub mod run;
 pub mod scan;
+pub mod search;
 
 #[cfg(feature = "watch")]
 pub mod watch;
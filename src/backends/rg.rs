@@ -2,13 +2,21 @@
 //!
 //! Calls rg with --json and parses the output to ResultItems
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use regex::{Regex, RegexBuilder};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
 
+use crate::backends::scan::emit_exists;
 use crate::core::model::{Kind, MiseError, Range, ResultItem, ResultSet, SourceMode};
 use crate::core::paths::make_relative;
-use crate::core::render::{RenderConfig, Renderer};
+use crate::core::policy::{deny_globs, is_denied};
+use crate::core::render::{finish_stream, RenderConfig, Renderer};
 use crate::core::util::command_exists;
 
 /// Options for the match command
@@ -21,6 +29,21 @@ pub struct MatchOptions {
     pub max_count: Option<usize>,
     pub ignore_case: bool,
     pub word_regexp: bool,
+    /// ripgrep worker threads (0 = ripgrep's default)
+    pub threads: usize,
+    /// Only report whether any match exists (stops at the first hit)
+    pub exists: bool,
+    /// Treat the pattern as a literal string; newline-separated lines are
+    /// searched as alternative literals
+    pub fixed_strings: bool,
+    /// Replacement text for a find/replace preview (`$1`, `${name}` expand captures)
+    pub replace: Option<String>,
+    /// With `replace`, write the replaced lines back to the files
+    pub apply: bool,
+    /// Emit one item per submatch instead of one per matched line
+    pub per_submatch: bool,
+    /// Let matches span lines (rg `--multiline`)
+    pub multiline: bool,
 }
 
 /// Check if ripgrep is available
@@ -28,35 +51,48 @@ pub fn is_rg_available() -> bool {
     command_exists("rg")
 }
 
-/// Run ripgrep and collect results
-pub fn run_rg(
+/// Build the ripgrep invocation shared by search and existence checks
+///
+/// `mode` selects the output mode (`--json` for results, `--quiet` for a
+/// status-only probe that stops at the first match).
+fn build_rg_command(
     root: &Path,
     pattern: &str,
     scopes: &[impl AsRef<Path>],
     options: &MatchOptions,
-) -> Result<ResultSet> {
-    if !is_rg_available() {
-        let mut result_set = ResultSet::new();
-        result_set.push(ResultItem::error(MiseError::new(
-            "RG_NOT_FOUND",
-            "ripgrep (rg) is not installed. Please install it: https://github.com/BurntSushi/ripgrep",
-        )));
-        return Ok(result_set);
-    }
-
+    mode: &str,
+) -> Command {
     let mut cmd = Command::new("rg");
-    cmd.arg("--json").arg(pattern);
+    cmd.arg(mode);
+    if options.fixed_strings {
+        // One -e per line: rg rejects literal newlines inside a single pattern
+        cmd.arg("--fixed-strings");
+        for literal in pattern.lines() {
+            cmd.arg("-e").arg(literal);
+        }
+    } else {
+        cmd.arg(pattern);
+    }
 
     // Add include glob patterns
     for glob in &options.include {
         cmd.arg("--glob").arg(glob);
     }
 
+    if options.threads > 0 {
+        cmd.arg("--threads").arg(options.threads.to_string());
+    }
+
     // Add exclude glob patterns (negated)
     for glob in &options.exclude {
         cmd.arg("--glob").arg(format!("!{}", glob));
     }
 
+    // Keep rg out of denied paths entirely
+    for glob in deny_globs() {
+        cmd.arg("--glob").arg(glob);
+    }
+
     // Add context lines
     if let Some(ctx) = options.context {
         cmd.arg("--context").arg(ctx.to_string());
@@ -77,6 +113,10 @@ pub fn run_rg(
         cmd.arg("--word-regexp");
     }
 
+    if options.multiline {
+        cmd.arg("--multiline");
+    }
+
     // Add scope paths
     if scopes.is_empty() {
         cmd.arg(root);
@@ -86,62 +126,315 @@ pub fn run_rg(
         }
     }
 
-    let output = cmd.output()?;
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    cmd
+}
+
+/// Run ripgrep and collect results
+pub fn run_rg(
+    root: &Path,
+    pattern: &str,
+    scopes: &[impl AsRef<Path>],
+    options: &MatchOptions,
+) -> Result<ResultSet> {
+    if !is_rg_available() {
+        let mut result_set = ResultSet::new();
+        result_set.push(ResultItem::error(MiseError::new(
+            "RG_NOT_FOUND",
+            "ripgrep (rg) is not installed. Please install it: https://github.com/BurntSushi/ripgrep",
+        )));
+        return Ok(result_set);
+    }
+
+    let mut child = build_rg_command(root, pattern, scopes, options, "--json")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to run ripgrep")?;
+    let stdout = child
+        .stdout
+        .take()
+        .context("Failed to capture ripgrep output")?;
 
     let mut result_set = ResultSet::new();
+    result_set.extend(
+        budgeted_lines(stdout)
+            .filter_map(|line| parse_rg_match(root, &line, options.per_submatch))
+            .flatten(),
+    );
 
-    for line in stdout.lines() {
-        if line.trim().is_empty() {
-            continue;
-        }
+    // Reading stops early when the time budget runs out; don't leave rg running
+    let _ = child.kill();
+    let _ = child.wait();
 
-        let v: serde_json::Value = match serde_json::from_str(line) {
-            Ok(v) => v,
-            Err(_) => continue,
-        };
+    result_set.sort();
+    Ok(result_set)
+}
 
-        if v.get("type").and_then(|t| t.as_str()) != Some("match") {
-            continue;
+/// How often a silent ripgrep is checked against the time budget
+const BUDGET_POLL: Duration = Duration::from_millis(50);
+
+/// Lines of a child's output, ending early once the time budget is spent
+///
+/// Lines are read on a helper thread so the budget is checked even while the
+/// child prints nothing (rg is silent until a file matches). The caller kills
+/// the child afterwards, which also ends the reader thread.
+fn budgeted_lines(output: impl Read + Send + 'static) -> impl Iterator<Item = String> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(output).lines().map_while(|line| line.ok()) {
+            if tx.send(line).is_err() {
+                break;
+            }
         }
+    });
 
-        let data = match v.get("data") {
-            Some(d) => d,
-            None => continue,
-        };
+    std::iter::from_fn(move || loop {
+        if crate::core::budget::should_stop() {
+            return None;
+        }
+        match rx.recv_timeout(BUDGET_POLL) {
+            Ok(line) => return Some(line),
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return None,
+        }
+    })
+}
 
-        let path_text = match data
-            .get("path")
-            .and_then(|p| p.get("text"))
-            .and_then(|t| t.as_str())
-        {
-            Some(p) => p,
-            None => continue,
-        };
+/// Convert one `rg --json` event line into match items
+///
+/// A matched line yields one item whose range carries the columns of its first
+/// submatch, or with `per_submatch` one item per submatch. Returns `None` for
+/// non-match events, malformed lines and denied paths.
+fn parse_rg_match(root: &Path, line: &str, per_submatch: bool) -> Option<Vec<ResultItem>> {
+    if line.trim().is_empty() {
+        return None;
+    }
+
+    let v: serde_json::Value = serde_json::from_str(line).ok()?;
+    if v.get("type").and_then(|t| t.as_str()) != Some("match") {
+        return None;
+    }
+
+    let data = v.get("data")?;
+    let path_text = data
+        .get("path")
+        .and_then(|p| p.get("text"))
+        .and_then(|t| t.as_str())?;
+
+    let lines_text = data
+        .get("lines")
+        .and_then(|l| l.get("text"))
+        .and_then(|t| t.as_str())
+        .unwrap_or("");
+
+    let line_num = data
+        .get("line_number")
+        .and_then(|n| n.as_u64())
+        .unwrap_or(1) as u32;
+
+    let relative_path =
+        make_relative(Path::new(path_text), root).unwrap_or_else(|| path_text.to_string());
+    if is_denied(&relative_path) {
+        return None;
+    }
+    let excerpt = lines_text.trim_end().to_string();
+
+    let mut ranges = submatch_ranges(data, lines_text, line_num);
+    if ranges.is_empty() {
+        // Without submatches the item spans every line of the event
+        let last = line_num + lines_text.trim_end().matches('\n').count() as u32;
+        ranges.push(Range::lines(line_num, last));
+    } else if !per_submatch {
+        ranges.truncate(1);
+    }
 
-        let lines_text = data
-            .get("lines")
-            .and_then(|l| l.get("text"))
-            .and_then(|t| t.as_str())
-            .unwrap_or("");
+    let items = ranges
+        .into_iter()
+        .map(|range| {
+            let mut item = ResultItem::match_result(relative_path.clone(), range, excerpt.clone());
+            item.source_mode = SourceMode::Rg;
+            item
+        })
+        .collect();
+    Some(items)
+}
 
-        let line_num = data
-            .get("line_number")
-            .and_then(|n| n.as_u64())
-            .unwrap_or(1) as u32;
+/// Line range with 1-based character columns of each submatch
+///
+/// rg reports byte offsets into `text`, the matched lines starting at
+/// `line_num`; with `--multiline` a submatch may end on a later line. A
+/// trailing line break is not counted as part of the match. Offsets that do
+/// not fall on a character boundary of `text` are skipped.
+fn submatch_ranges(data: &serde_json::Value, text: &str, line_num: u32) -> Vec<Range> {
+    // (line, column) of the character at a byte offset
+    let position = |offset: usize| -> Option<(u32, u32)> {
+        let prefix = text.get(..offset)?;
+        let line_start = prefix.rfind('\n').map_or(0, |i| i + 1);
+        Some((
+            line_num + prefix.matches('\n').count() as u32,
+            prefix[line_start..].chars().count() as u32 + 1,
+        ))
+    };
+    let range = |m: &serde_json::Value| -> Option<Range> {
+        let start = usize::try_from(m.get("start")?.as_u64()?).ok()?;
+        let end = usize::try_from(m.get("end")?.as_u64()?).ok()?;
+        let end = start + text.get(start..end)?.trim_end_matches(['\n', '\r']).len();
+        let (start_line, start_col) = position(start)?;
+        let (end_line, end_col) = position(end)?;
+        Some(Range::lines(start_line, end_line).with_columns(start_col, end_col))
+    };
+
+    data.get("submatches")
+        .and_then(|s| s.as_array())
+        .map(|submatches| submatches.iter().filter_map(range).collect())
+        .unwrap_or_default()
+}
 
-        let relative_path =
-            make_relative(Path::new(path_text), root).unwrap_or_else(|| path_text.to_string());
-        let excerpt = lines_text.trim_end().to_string();
+/// Write matches to stdout as jsonl while ripgrep is still running
+///
+/// Opt-in via `--stream`: items appear in ripgrep's arrival order, which varies
+/// between runs when several worker threads search in parallel.
+fn stream_rg(
+    root: &Path,
+    pattern: &str,
+    scopes: &[impl AsRef<Path>],
+    options: &MatchOptions,
+    renderer: &Renderer,
+) -> Result<()> {
+    let mut child = build_rg_command(root, pattern, scopes, options, "--json")
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Failed to run ripgrep")?;
+    let stdout = child
+        .stdout
+        .take()
+        .context("Failed to capture ripgrep output")?;
+
+    let items = budgeted_lines(stdout)
+        .filter_map(|line| parse_rg_match(root, &line, options.per_submatch))
+        .flatten();
+    let result = renderer.render_streaming(items, std::io::stdout().lock());
+
+    // The reader may stop early (closed pipe, time budget); don't leave rg running
+    let _ = child.kill();
+    let _ = child.wait();
+    finish_stream(result)
+}
 
-        let mut item =
-            ResultItem::match_result(relative_path, Range::lines(line_num, line_num), excerpt);
-        item.source_mode = SourceMode::Rg;
-        result_set.push(item);
+/// Check whether the pattern matches anywhere, stopping at the first hit
+///
+/// Uses ripgrep's quiet mode: exit status 0 means found, 1 means no match.
+pub fn rg_exists(
+    root: &Path,
+    pattern: &str,
+    scopes: &[impl AsRef<Path>],
+    options: &MatchOptions,
+) -> Result<bool> {
+    if !is_rg_available() {
+        bail!("ripgrep (rg) is not installed. Please install it: https://github.com/BurntSushi/ripgrep");
     }
 
-    result_set.sort();
-    Ok(result_set)
+    let output = build_rg_command(root, pattern, scopes, options, "--quiet").output()?;
+    match output.status.code() {
+        Some(0) => Ok(true),
+        Some(1) => Ok(false),
+        _ => bail!(
+            "ripgrep failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+    }
+}
+
+/// Compile the search pattern the way ripgrep interprets it under `options`
+///
+/// ripgrep's default engine shares the `regex` crate's syntax, so `$1`-style
+/// replacements expand exactly as `rg --replace` would.
+fn replace_regex(pattern: &str, options: &MatchOptions) -> Result<Regex> {
+    let mut source = if options.fixed_strings {
+        pattern
+            .lines()
+            .map(regex::escape)
+            .collect::<Vec<_>>()
+            .join("|")
+    } else {
+        pattern.to_string()
+    };
+    if options.word_regexp {
+        source = format!(r"\b(?:{})\b", source);
+    }
+
+    RegexBuilder::new(&source)
+        .case_insensitive(options.ignore_case)
+        .build()
+        .with_context(|| format!("Invalid pattern for --replace: {}", pattern))
+}
+
+/// Attach `{line, replaced}` to each match and optionally write the replacements
+///
+/// Replacement is line-based like ripgrep: only the matched lines of each file
+/// are rewritten, and their line endings are preserved.
+fn apply_replacement(
+    root: &Path,
+    result_set: &mut ResultSet,
+    pattern: &str,
+    replacement: &str,
+    options: &MatchOptions,
+) -> Result<()> {
+    let re = replace_regex(pattern, options)?;
+    let mut lines_by_file: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
+
+    for item in result_set
+        .items
+        .iter_mut()
+        .filter(|i| matches!(i.kind, Kind::Match))
+    {
+        let line = item.excerpt.clone().unwrap_or_default();
+        let replaced = re.replace_all(&line, replacement).into_owned();
+        item.data = Some(serde_json::json!({
+            "line": line,
+            "replaced": replaced,
+            "applied": options.apply,
+        }));
+
+        if let (Some(path), Some(Range::Line(range))) = (&item.path, &item.range) {
+            lines_by_file
+                .entry(path.clone())
+                .or_default()
+                .insert(range.start);
+        }
+    }
+
+    if options.apply {
+        for (path, line_nums) in lines_by_file {
+            let file = root.join(&path);
+            let content = std::fs::read_to_string(&file)
+                .with_context(|| format!("Failed to read {}", path))?;
+            std::fs::write(&file, replace_lines(&content, &line_nums, &re, replacement))
+                .with_context(|| format!("Failed to write {}", path))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Replace matches on the given 1-based lines of `content`, keeping line endings
+fn replace_lines(
+    content: &str,
+    line_nums: &BTreeSet<u32>,
+    re: &Regex,
+    replacement: &str,
+) -> String {
+    let mut output = String::with_capacity(content.len());
+    for (idx, line) in content.split_inclusive('\n').enumerate() {
+        if !line_nums.contains(&(idx as u32 + 1)) {
+            output.push_str(line);
+            continue;
+        }
+        let body = line.trim_end_matches(['\n', '\r']);
+        output.push_str(&re.replace_all(body, replacement));
+        output.push_str(&line[body.len()..]);
+    }
+    output
 }
 
 /// Run the match command
@@ -152,7 +445,20 @@ pub fn run_match(
     options: MatchOptions,
     config: RenderConfig,
 ) -> Result<()> {
-    let result_set = run_rg(root, pattern, scopes, &options)?;
+    if options.exists {
+        emit_exists(rg_exists(root, pattern, scopes, &options)?, config);
+    }
+
+    let renderer = Renderer::with_config(config);
+    if !options.count && options.replace.is_none() && is_rg_available() && renderer.can_stream() {
+        return stream_rg(root, pattern, scopes, &options, &renderer);
+    }
+
+    let mut result_set = run_rg(root, pattern, scopes, &options)?;
+
+    if let Some(replacement) = &options.replace {
+        apply_replacement(root, &mut result_set, pattern, replacement, &options)?;
+    }
 
     // If count mode is enabled, output just the count
     if options.count {
@@ -169,8 +475,7 @@ pub fn run_match(
             println!("{{\"count\":{}}}", match_count);
         }
     } else {
-        let renderer = Renderer::with_config(config);
-        println!("{}", renderer.render(&result_set));
+        print!("{}", renderer.render(&result_set));
     }
 
     Ok(())
@@ -184,6 +489,49 @@ mod tests {
         MatchOptions::default()
     }
 
+    #[test]
+    fn test_budgeted_lines_stop_when_budget_expires() {
+        // The budget is process-wide, so expire it in a child test process
+        if std::env::var_os("MISE_BUDGET_CHILD").is_none() {
+            let status = std::process::Command::new(std::env::current_exe().unwrap())
+                .args([
+                    "backends::rg::tests::test_budgeted_lines_stop_when_budget_expires",
+                    "--exact",
+                    "--quiet",
+                ])
+                .env("MISE_BUDGET_CHILD", "1")
+                .status()
+                .unwrap();
+            assert!(status.success());
+            return;
+        }
+
+        // Like rg on a large tree: one event, then a long silence
+        let mut child = Command::new("sh")
+            .args(["-c", "echo first; sleep 30"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut lines = budgeted_lines(child.stdout.take().unwrap());
+        assert_eq!(lines.next().as_deref(), Some("first"));
+
+        crate::core::budget::start_watchdog(0);
+        let started = std::time::Instant::now();
+        assert_eq!(lines.next(), None);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(crate::core::budget::was_interrupted());
+
+        let _ = child.kill();
+        let _ = child.wait();
+
+        if is_rg_available() {
+            let temp = tempfile::tempdir().unwrap();
+            std::fs::write(temp.path().join("a.txt"), "hello\n").unwrap();
+            let result = run_rg(temp.path(), "hello", &[] as &[&Path], &default_options());
+            assert!(result.unwrap().items.is_empty());
+        }
+    }
+
     #[test]
     fn test_is_rg_available() {
         // This test depends on the system having rg installed
@@ -299,10 +647,8 @@ mod tests {
             let temp = tempfile::tempdir().unwrap();
             std::fs::write(temp.path().join("test.txt"), "hello world\n").unwrap();
 
-            let config = crate::core::render::RenderConfig {
-                format: crate::core::render::OutputFormat::Json,
-                pretty: false,
-            };
+            let config =
+                crate::core::render::RenderConfig::new(crate::core::render::OutputFormat::Json);
 
             let result = run_match(
                 temp.path(),
@@ -551,6 +897,164 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_replace_regex_captures() {
+        let re = replace_regex(r"foo\((\w+)\)", &default_options()).unwrap();
+        assert_eq!(re.replace_all("x = foo(bar);", "baz($1)"), "x = baz(bar);");
+
+        let options = MatchOptions {
+            fixed_strings: true,
+            ignore_case: true,
+            ..Default::default()
+        };
+        let re = replace_regex("A.B", &options).unwrap();
+        assert_eq!(re.replace_all("a.b aXb", "c"), "c aXb");
+    }
+
+    #[test]
+    fn test_replace_lines_preserves_endings() {
+        let re = Regex::new("old").unwrap();
+        let lines: BTreeSet<u32> = [1, 3].into_iter().collect();
+        assert_eq!(
+            replace_lines("old\r\nold\nold old", &lines, &re, "new"),
+            "new\r\nold\nnew new"
+        );
+    }
+
+    #[test]
+    fn test_apply_replacement_writes_matched_lines() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("a.txt"), "call(1)\nkeep(2)\ncall(3)\n").unwrap();
+
+        let mut result_set = ResultSet::new();
+        for (line, text) in [(1, "call(1)"), (3, "call(3)")] {
+            result_set.push(ResultItem::match_result(
+                "a.txt",
+                Range::lines(line, line),
+                text.to_string(),
+            ));
+        }
+        let options = MatchOptions {
+            apply: true,
+            ..Default::default()
+        };
+        apply_replacement(
+            temp.path(),
+            &mut result_set,
+            r"call\((\d)\)",
+            "run($1)",
+            &options,
+        )
+        .unwrap();
+
+        let data = result_set.items[1].data.as_ref().unwrap();
+        assert_eq!(data["line"], "call(3)");
+        assert_eq!(data["replaced"], "run(3)");
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join("a.txt")).unwrap(),
+            "run(1)\nkeep(2)\nrun(3)\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_rg_match() {
+        let root = Path::new("/repo");
+        let line = r#"{"type":"match","data":{"path":{"text":"/repo/src/a.rs"},"lines":{"text":"fn main() {}\n"},"line_number":7}}"#;
+        let item = parse_rg_match(root, line, false).unwrap().remove(0);
+        assert_eq!(item.path.as_deref(), Some("src/a.rs"));
+        assert_eq!(item.range, Some(Range::lines(7, 7)));
+        assert_eq!(item.excerpt.as_deref(), Some("fn main() {}"));
+        assert_eq!(item.source_mode, SourceMode::Rg);
+
+        let begin = r#"{"type":"begin","data":{"path":{"text":"/repo/src/a.rs"}}}"#;
+        assert!(parse_rg_match(root, begin, false).is_none());
+        assert!(parse_rg_match(root, "not json", false).is_none());
+        assert!(parse_rg_match(root, "", false).is_none());
+    }
+
+    #[test]
+    fn test_parse_rg_match_columns() {
+        let root = Path::new("/repo");
+        // Byte offsets: "é" is two bytes, so "foo" starts at byte 3 / character 3
+        let line = r#"{"type":"match","data":{"path":{"text":"/repo/a.txt"},"lines":{"text":"é foo foo\n"},"line_number":2,"submatches":[{"match":{"text":"foo"},"start":3,"end":6},{"match":{"text":"foo"},"start":7,"end":10}]}}"#;
+
+        let items = parse_rg_match(root, line, false).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].range, Some(Range::lines(2, 2).with_columns(3, 6)));
+
+        let items = parse_rg_match(root, line, true).unwrap();
+        let ranges: Vec<_> = items.iter().map(|i| i.range.unwrap()).collect();
+        assert_eq!(
+            ranges,
+            vec![
+                Range::lines(2, 2).with_columns(3, 6),
+                Range::lines(2, 2).with_columns(7, 10),
+            ]
+        );
+        let json = serde_json::to_value(&items[1]).unwrap();
+        assert_eq!(json["range"]["start_col"], 7);
+        assert_eq!(json["range"]["end_col"], 10);
+    }
+
+    #[test]
+    fn test_parse_rg_match_multiline() {
+        let root = Path::new("/repo");
+        // rg --multiline: one event covering lines 4-6, the match ends on line 6
+        let line = r#"{"type":"match","data":{"path":{"text":"/repo/a.rs"},"lines":{"text":"fn run(\n    a: u32,\n) {}\n"},"line_number":4,"submatches":[{"match":{"text":"run(\n    a: u32,\n)"},"start":3,"end":21}]}}"#;
+
+        let item = parse_rg_match(root, line, false).unwrap().remove(0);
+        assert_eq!(item.range, Some(Range::lines(4, 6).with_columns(4, 2)));
+        assert_eq!(item.excerpt.as_deref(), Some("fn run(\n    a: u32,\n) {}"));
+        let json = serde_json::to_value(&item).unwrap();
+        assert_eq!(json["range"]["end"], 6);
+
+        // A trailing newline in the match does not push the end to the next line
+        let line = r#"{"type":"match","data":{"path":{"text":"/repo/a.rs"},"lines":{"text":"a\nb\n"},"line_number":1,"submatches":[{"match":{"text":"a\nb\n"},"start":0,"end":4}]}}"#;
+        let item = parse_rg_match(root, line, false).unwrap().remove(0);
+        assert_eq!(item.range, Some(Range::lines(1, 2).with_columns(1, 2)));
+
+        // Without submatches the range still covers every line of the event
+        let line = r#"{"type":"match","data":{"path":{"text":"/repo/a.rs"},"lines":{"text":"a\nb\n"},"line_number":1}}"#;
+        let item = parse_rg_match(root, line, false).unwrap().remove(0);
+        assert_eq!(item.range, Some(Range::lines(1, 2)));
+    }
+
+    #[test]
+    fn test_build_rg_command_fixed_strings() {
+        let args = |options: &MatchOptions| -> Vec<String> {
+            build_rg_command(
+                Path::new("."),
+                "foo(bar)\na.b",
+                &[] as &[&Path],
+                options,
+                "--json",
+            )
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect()
+        };
+
+        let regex = args(&default_options());
+        assert_eq!(regex[..2], ["--json", "foo(bar)\na.b"]);
+
+        let options = MatchOptions {
+            fixed_strings: true,
+            ..Default::default()
+        };
+        let literal = args(&options);
+        assert_eq!(
+            literal[..6],
+            ["--json", "--fixed-strings", "-e", "foo(bar)", "-e", "a.b"]
+        );
+
+        let options = MatchOptions {
+            multiline: true,
+            ..Default::default()
+        };
+        assert!(args(&options).contains(&"--multiline".to_string()));
+        assert!(!regex.contains(&"--multiline".to_string()));
+    }
+
     #[test]
     fn test_run_rg_combined_options() {
         if is_rg_available() {
@@ -568,6 +1072,7 @@ mod tests {
                 ignore_case: true,
                 word_regexp: true,
                 max_count: Some(1),
+                threads: 1,
                 ..Default::default()
             };
             let result = run_rg(temp.path(), "fn", &[] as &[&Path], &options).unwrap();
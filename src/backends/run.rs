@@ -32,11 +32,30 @@
 //!   {"id": "rebuild", "cmd": "misec rebuild"},
 //!   {"id": "lint", "cmd": "misec anchor lint", "depends_on": ["rebuild"]}
 //! ]
+//!
+//! // Matrix expansion (expands to find-src-TODO, find-src-FIXME, ...)
+//! {"id": "find", "cmd": "misec match ${matrix.pattern} ${matrix.dir}",
+//!  "matrix": {"dir": ["src", "tests"], "pattern": ["TODO", "FIXME"]}}
+//!
+//! // Hooks run once after all tasks, depending on the aggregate outcome
+//! {"tasks": [{"id": "lint", "cmd": "misec anchor lint"}],
+//!  "on_success": "echo done", "on_failure": "notify-send \"$MISE_FAILED failed\""}
+//! ```
+//!
+//! The same shapes can be written in YAML (`--file tasks.yml`):
+//!
+//! ```yaml
+//! tasks:
+//!   - id: rebuild
+//!     cmd: misec rebuild
+//!   - id: lint
+//!     cmd: misec anchor lint
+//!     depends_on: [rebuild]
 //! ```
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -46,7 +65,7 @@ use std::thread;
 use std::time::{Duration, Instant};
 
 use crate::core::model::{Confidence, Kind, Meta, MiseError, ResultItem, ResultSet, SourceMode};
-use crate::core::render::{RenderConfig, Renderer};
+use crate::core::render::{truncate_excerpt, RenderConfig, Renderer};
 
 /// Task definition for concurrent execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,6 +84,11 @@ pub struct Task {
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub env: HashMap<String, String>,
 
+    /// Dotenv-style file (relative to the working directory) loaded before
+    /// `env`; inline `env` values take precedence
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<String>,
+
     /// Timeout in seconds (default: 300)
     #[serde(default = "default_timeout")]
     pub timeout: u64,
@@ -81,12 +105,101 @@ pub struct Task {
     /// Description for documentation
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+
+    /// Matrix values; the task expands into the cartesian product of these,
+    /// substituting `${matrix.<key>}` into `cmd` and `cwd`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub matrix: BTreeMap<String, Vec<String>>,
+
+    /// Extra attempts after a failed run (default: 0)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub retries: u32,
+
+    /// Delay between attempts in milliseconds (default: 0)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub retry_delay_ms: u64,
+}
+
+impl Task {
+    /// Expand a matrix task into one task per combination of matrix values
+    ///
+    /// Keys are combined in sorted order, and each generated id is the base id
+    /// followed by the combination's values (e.g. `id-a-x`). Tasks without a
+    /// matrix are returned unchanged.
+    pub fn expand_matrix(&self) -> Vec<Task> {
+        if self.matrix.is_empty() {
+            return vec![self.clone()];
+        }
+
+        let mut combinations: Vec<Vec<(&str, &str)>> = vec![Vec::new()];
+        for (key, values) in &self.matrix {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|combo| {
+                    values.iter().map(move |value| {
+                        let mut next = combo.clone();
+                        next.push((key.as_str(), value.as_str()));
+                        next
+                    })
+                })
+                .collect();
+        }
+
+        combinations
+            .into_iter()
+            .map(|combo| {
+                let substitute = |text: &str| {
+                    combo.iter().fold(text.to_string(), |acc, (key, value)| {
+                        acc.replace(&format!("${{matrix.{}}}", key), value)
+                    })
+                };
+
+                let mut task = self.clone();
+                task.matrix = BTreeMap::new();
+                task.id = std::iter::once(self.id.as_str())
+                    .chain(combo.iter().map(|(_, value)| *value))
+                    .collect::<Vec<_>>()
+                    .join("-");
+                task.cmd = substitute(&self.cmd);
+                task.cwd = self.cwd.as_deref().map(substitute);
+                task
+            })
+            .collect()
+    }
+}
+
+/// Expand matrix tasks and rewrite dependencies on them
+///
+/// A dependency on a matrix task's base id becomes a dependency on every
+/// task generated from it.
+fn expand_matrix_tasks(tasks: Vec<Task>, expanded_ids: &HashMap<String, Vec<String>>) -> Vec<Task> {
+    tasks
+        .iter()
+        .flat_map(Task::expand_matrix)
+        .map(|mut task| {
+            task.depends_on = task
+                .depends_on
+                .iter()
+                .flat_map(|dep| {
+                    expanded_ids
+                        .get(dep)
+                        .cloned()
+                        .unwrap_or_else(|| vec![dep.clone()])
+                })
+                .collect();
+            task
+        })
+        .collect()
 }
 
 fn default_timeout() -> u64 {
     300
 }
 
+fn is_zero<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
 /// Task group for organizing related tasks
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskGroup {
@@ -123,6 +236,14 @@ pub struct TaskSet {
     /// Standalone tasks (not in any group)
     #[serde(default)]
     pub tasks: Vec<Task>,
+
+    /// Command run after all tasks when none failed or were skipped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_success: Option<String>,
+
+    /// Command run after all tasks when any failed or were skipped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<String>,
 }
 
 /// Result of a single task execution
@@ -153,6 +274,27 @@ pub struct TaskResult {
     /// Output file path (if written)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub output_file: Option<String>,
+
+    /// Number of times the command was run (0 if never started)
+    #[serde(default)]
+    pub attempts: u32,
+}
+
+impl TaskResult {
+    /// Result for a task that never ran; `reason` starts with "Skipped:"
+    fn skipped(id: &str, reason: String) -> Self {
+        Self {
+            id: id.to_string(),
+            exit_code: None,
+            stdout: String::new(),
+            stderr: String::new(),
+            duration_ms: 0,
+            success: false,
+            error: Some(reason),
+            output_file: None,
+            attempts: 0,
+        }
+    }
 }
 
 /// Run options
@@ -178,6 +320,12 @@ pub struct RunOptions {
 
     /// Dry run (show what would be executed without running)
     pub dry_run: bool,
+
+    /// Success hook command (overrides the task set's `on_success`)
+    pub on_success: Option<String>,
+
+    /// Failure hook command (overrides the task set's `on_failure`)
+    pub on_failure: Option<String>,
 }
 
 impl Default for RunOptions {
@@ -190,6 +338,8 @@ impl Default for RunOptions {
             timeout: None,
             filter_tag: None,
             dry_run: false,
+            on_success: None,
+            on_failure: None,
         }
     }
 }
@@ -217,49 +367,142 @@ pub struct ExecutionSummary {
     pub output_dir: Option<String>,
 }
 
+impl TaskSet {
+    /// Reject matrix keys with no values, which would expand to zero tasks
+    fn check_matrices(&self) -> Result<()> {
+        let tasks = self
+            .tasks
+            .iter()
+            .chain(self.groups.iter().flat_map(|g| g.tasks.iter()));
+        for task in tasks {
+            if let Some((key, _)) = task.matrix.iter().find(|(_, values)| values.is_empty()) {
+                anyhow::bail!(
+                    "Task '{}' has an empty matrix value list for '{}'; give it at least one value or remove the key",
+                    task.id,
+                    key
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Expand all matrix tasks in standalone tasks and groups
+    pub fn expand_matrices(mut self) -> Self {
+        let expanded_ids: HashMap<String, Vec<String>> = self
+            .tasks
+            .iter()
+            .chain(self.groups.iter().flat_map(|g| g.tasks.iter()))
+            .filter(|t| !t.matrix.is_empty())
+            .map(|t| {
+                let ids = t.expand_matrix().into_iter().map(|e| e.id).collect();
+                (t.id.clone(), ids)
+            })
+            .collect();
+
+        if expanded_ids.is_empty() {
+            return self;
+        }
+
+        self.tasks = expand_matrix_tasks(std::mem::take(&mut self.tasks), &expanded_ids);
+        for group in &mut self.groups {
+            group.tasks = expand_matrix_tasks(std::mem::take(&mut group.tasks), &expanded_ids);
+        }
+        self
+    }
+}
+
+/// Syntax of a task definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TaskSyntax {
+    Json,
+    Yaml,
+}
+
+impl TaskSyntax {
+    fn parse<T: serde::de::DeserializeOwned>(self, input: &str) -> Option<T> {
+        match self {
+            TaskSyntax::Json => serde_json::from_str(input).ok(),
+            TaskSyntax::Yaml => serde_norway::from_str(input).ok(),
+        }
+    }
+}
+
 /// Parse task set from JSON or YAML string
+///
+/// JSON is tried first, then YAML. Matrix tasks are expanded into their
+/// concrete combinations.
 pub fn parse_tasks(input: &str) -> Result<TaskSet> {
+    parse_tasks_as(input, &[TaskSyntax::Json, TaskSyntax::Yaml])
+}
+
+/// Parse with the given syntaxes, in order
+fn parse_tasks_as(input: &str, order: &[TaskSyntax]) -> Result<TaskSet> {
+    match order.iter().find_map(|&syntax| parse_task_set(input, syntax)) {
+        Some(task_set) => {
+            task_set.check_matrices()?;
+            Ok(task_set.expand_matrices())
+        }
+        None => anyhow::bail!("Failed to parse task definition. Expected a JSON or YAML object with 'tasks' or 'groups' field, an array of tasks, or a single task object."),
+    }
+}
+
+/// Parse the raw task set definition without matrix expansion
+fn parse_task_set(input: &str, syntax: TaskSyntax) -> Option<TaskSet> {
     // Try as single task first (most specific)
-    if let Ok(task) = serde_json::from_str::<Task>(input) {
+    if let Some(task) = syntax.parse::<Task>(input) {
         // Check if it has required fields (id and cmd)
         if !task.id.is_empty() && !task.cmd.is_empty() {
-            return Ok(TaskSet {
+            return Some(TaskSet {
                 name: "single".to_string(),
                 groups: Vec::new(),
                 tasks: vec![task],
+                on_success: None,
+                on_failure: None,
             });
         }
     }
 
     // Try as array of tasks
-    if let Ok(tasks) = serde_json::from_str::<Vec<Task>>(input) {
+    if let Some(tasks) = syntax.parse::<Vec<Task>>(input) {
         if !tasks.is_empty() {
-            return Ok(TaskSet {
+            return Some(TaskSet {
                 name: "tasks".to_string(),
                 groups: Vec::new(),
                 tasks,
+                on_success: None,
+                on_failure: None,
             });
         }
     }
 
     // Try full TaskSet
-    if let Ok(task_set) = serde_json::from_str::<TaskSet>(input) {
+    if let Some(task_set) = syntax.parse::<TaskSet>(input) {
         if !task_set.tasks.is_empty() || !task_set.groups.is_empty() {
-            return Ok(task_set);
+            return Some(task_set);
         }
     }
 
-    anyhow::bail!("Failed to parse task definition. Expected JSON object with 'tasks' or 'groups' field, an array of tasks, or a single task object.")
+    None
 }
 
 /// Parse task set from file
+///
+/// `.yml`/`.yaml` files are tried as YAML first; anything else as JSON first.
 pub fn parse_tasks_from_file(path: &Path) -> Result<TaskSet> {
     let content = fs::read_to_string(path)
         .with_context(|| format!("Failed to read task file: {}", path.display()))?;
-    parse_tasks(&content)
+    let is_yaml = matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("yml" | "yaml")
+    );
+    if is_yaml {
+        parse_tasks_as(&content, &[TaskSyntax::Yaml, TaskSyntax::Json])
+    } else {
+        parse_tasks(&content)
+    }
 }
 
-/// Execute a single task
+/// Execute a single task, retrying a failed command up to `task.retries` times
 fn execute_task(
     root: &Path,
     task: &Task,
@@ -277,6 +520,141 @@ fn execute_task(
         root.to_path_buf()
     };
 
+    let file_env = match &task.env_file {
+        Some(env_file) => match load_env_file(&work_dir.join(env_file)) {
+            Ok(vars) => vars,
+            Err(error) => {
+                return TaskResult {
+                    id: task.id.clone(),
+                    exit_code: None,
+                    stdout: String::new(),
+                    stderr: String::new(),
+                    duration_ms: start.elapsed().as_millis() as u64,
+                    success: false,
+                    error: Some(error),
+                    output_file: None,
+                    attempts: 0,
+                }
+            }
+        },
+        None => Vec::new(),
+    };
+
+    let mut attempts = 1;
+    let mut result = run_task_command(&work_dir, task, &file_env, timeout_secs, start);
+    while !result.success && attempts <= task.retries {
+        thread::sleep(Duration::from_millis(task.retry_delay_ms));
+        attempts += 1;
+        result = run_task_command(&work_dir, task, &file_env, timeout_secs, start);
+    }
+    result.attempts = attempts;
+
+    // Save output to file if requested
+    let mut final_result = result;
+    if save_output {
+        if let Some(out_dir) = output_dir {
+            let output_file = out_dir.join(format!("{}.log", sanitize_filename(&task.id)));
+            if let Ok(mut file) = fs::File::create(&output_file) {
+                // Format output - if stdout looks like JSON/JSONL, preserve it cleanly
+                let is_json_output = final_result.stdout.trim_start().starts_with('{')
+                    || final_result.stdout.trim_start().starts_with('[');
+
+                let content = if is_json_output && final_result.stderr.is_empty() {
+                    // Clean JSON output (typical misec output)
+                    format!(
+                        "# Task: {} | Exit: {:?} | Duration: {}ms\n# Command: {}\n\n{}\n",
+                        task.id,
+                        final_result.exit_code.unwrap_or(-1),
+                        final_result.duration_ms,
+                        task.cmd,
+                        final_result.stdout
+                    )
+                } else {
+                    // Full format with sections
+                    format!(
+                        "# Task: {}\n# Command: {}\n# Exit Code: {:?}\n# Duration: {}ms\n# Attempts: {}\n# Success: {}\n\n## STDOUT:\n{}\n{}",
+                        task.id,
+                        task.cmd,
+                        final_result.exit_code,
+                        final_result.duration_ms,
+                        final_result.attempts,
+                        final_result.success,
+                        final_result.stdout,
+                        if !final_result.stderr.is_empty() {
+                            format!("\n## STDERR:\n{}\n", final_result.stderr)
+                        } else {
+                            String::new()
+                        }
+                    )
+                };
+                let _ = file.write_all(content.as_bytes());
+                final_result.output_file = Some(output_file.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    final_result
+}
+
+/// Parse dotenv-style `KEY=VALUE` lines
+///
+/// Blank lines and `#` comments are ignored, an `export ` prefix is allowed
+/// and values may be wrapped in single or double quotes.
+fn parse_env_file(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let unquoted = ['"', '\'']
+                .iter()
+                .find_map(|q| value.strip_prefix(*q)?.strip_suffix(*q))
+                .unwrap_or(value);
+            Some((key.trim().to_string(), unquoted.to_string()))
+        })
+        .filter(|(key, _)| !key.is_empty())
+        .collect()
+}
+
+/// Read and parse a task's env file
+fn load_env_file(path: &Path) -> Result<Vec<(String, String)>, String> {
+    fs::read_to_string(path)
+        .map(|content| parse_env_file(&content))
+        .map_err(|e| format!("Failed to read env file {}: {}", path.display(), e))
+}
+
+/// Read a child pipe to the end on a background thread
+fn spawn_pipe_reader<R>(pipe: Option<R>) -> Option<thread::JoinHandle<String>>
+where
+    R: std::io::Read + Send + 'static,
+{
+    pipe.map(|mut pipe| {
+        thread::spawn(move || {
+            let mut s = String::new();
+            pipe.read_to_string(&mut s).ok();
+            s
+        })
+    })
+}
+
+/// Collect the output of a pipe reader (empty if there was no pipe)
+fn join_pipe_reader(reader: Option<thread::JoinHandle<String>>) -> String {
+    reader
+        .and_then(|handle| handle.join().ok())
+        .unwrap_or_default()
+}
+
+/// Run a task's command once; `duration_ms` counts from `start`
+fn run_task_command(
+    work_dir: &Path,
+    task: &Task,
+    file_env: &[(String, String)],
+    timeout_secs: u64,
+    start: Instant,
+) -> TaskResult {
     // Build command
     let shell = if cfg!(target_os = "windows") {
         ("cmd", "/C")
@@ -287,11 +665,14 @@ fn execute_task(
     let mut cmd = Command::new(shell.0);
     cmd.arg(shell.1)
         .arg(&task.cmd)
-        .current_dir(&work_dir)
+        .current_dir(work_dir)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
-    // Set environment variables
+    // Set environment variables (inline env overrides the env file)
+    for (key, value) in file_env {
+        cmd.env(key, value);
+    }
     for (key, value) in &task.env {
         cmd.env(key, value);
     }
@@ -299,6 +680,11 @@ fn execute_task(
     // Execute with timeout
     let result = match cmd.spawn() {
         Ok(mut child) => {
+            // Drain both pipes while the process runs so a full pipe buffer
+            // cannot block it
+            let stdout_reader = spawn_pipe_reader(child.stdout.take());
+            let stderr_reader = spawn_pipe_reader(child.stderr.take());
+
             // Wait with timeout
             let timeout = Duration::from_secs(timeout_secs);
             let start_wait = Instant::now();
@@ -307,25 +693,8 @@ fn execute_task(
                 match child.try_wait() {
                     Ok(Some(status)) => {
                         // Process completed
-                        let stdout = child
-                            .stdout
-                            .take()
-                            .map(|mut o| {
-                                let mut s = String::new();
-                                std::io::Read::read_to_string(&mut o, &mut s).ok();
-                                s
-                            })
-                            .unwrap_or_default();
-
-                        let stderr = child
-                            .stderr
-                            .take()
-                            .map(|mut e| {
-                                let mut s = String::new();
-                                std::io::Read::read_to_string(&mut e, &mut s).ok();
-                                s
-                            })
-                            .unwrap_or_default();
+                        let stdout = join_pipe_reader(stdout_reader);
+                        let stderr = join_pipe_reader(stderr_reader);
 
                         let exit_code = status.code();
                         let success = status.success();
@@ -343,6 +712,7 @@ fn execute_task(
                                 Some(format!("Exit code: {:?}", exit_code))
                             },
                             output_file: None,
+                            attempts: 0,
                         };
                     }
                     Ok(None) => {
@@ -358,6 +728,7 @@ fn execute_task(
                                 success: false,
                                 error: Some(format!("Timeout after {} seconds", timeout_secs)),
                                 output_file: None,
+                                attempts: 0,
                             };
                         }
                         thread::sleep(Duration::from_millis(100));
@@ -372,6 +743,7 @@ fn execute_task(
                             success: false,
                             error: Some(format!("Failed to wait for process: {}", e)),
                             output_file: None,
+                            attempts: 0,
                         };
                     }
                 }
@@ -386,53 +758,11 @@ fn execute_task(
             success: false,
             error: Some(format!("Failed to start command: {}", e)),
             output_file: None,
+            attempts: 0,
         },
     };
 
-    // Save output to file if requested
-    let mut final_result = result;
-    if save_output {
-        if let Some(out_dir) = output_dir {
-            let output_file = out_dir.join(format!("{}.log", sanitize_filename(&task.id)));
-            if let Ok(mut file) = fs::File::create(&output_file) {
-                // Format output - if stdout looks like JSON/JSONL, preserve it cleanly
-                let is_json_output = final_result.stdout.trim_start().starts_with('{')
-                    || final_result.stdout.trim_start().starts_with('[');
-
-                let content = if is_json_output && final_result.stderr.is_empty() {
-                    // Clean JSON output (typical misec output)
-                    format!(
-                        "# Task: {} | Exit: {:?} | Duration: {}ms\n# Command: {}\n\n{}\n",
-                        task.id,
-                        final_result.exit_code.unwrap_or(-1),
-                        final_result.duration_ms,
-                        task.cmd,
-                        final_result.stdout
-                    )
-                } else {
-                    // Full format with sections
-                    format!(
-                        "# Task: {}\n# Command: {}\n# Exit Code: {:?}\n# Duration: {}ms\n# Success: {}\n\n## STDOUT:\n{}\n{}",
-                        task.id,
-                        task.cmd,
-                        final_result.exit_code,
-                        final_result.duration_ms,
-                        final_result.success,
-                        final_result.stdout,
-                        if !final_result.stderr.is_empty() {
-                            format!("\n## STDERR:\n{}\n", final_result.stderr)
-                        } else {
-                            String::new()
-                        }
-                    )
-                };
-                let _ = file.write_all(content.as_bytes());
-                final_result.output_file = Some(output_file.to_string_lossy().to_string());
-            }
-        }
-    }
-
-    final_result
+    result
 }
 
 /// Sanitize filename for task output
@@ -448,7 +778,64 @@ fn sanitize_filename(name: &str) -> String {
         .collect()
 }
 
+/// Error recorded for a task whose dependency failed
+const SKIPPED_DEPENDENCY_FAILED: &str = "Skipped: dependency failed";
+
+/// Error recorded for a task not run because an earlier task failed
+const SKIPPED_RUN_STOPPED: &str = "Skipped: run stopped after a failure";
+
+/// Find a dependency cycle among the tasks, as the ids along the cycle
+///
+/// The first id is repeated at the end (e.g. `a -> b -> a`). Dependencies on
+/// ids outside `tasks` are ignored here.
+pub fn find_task_cycle(tasks: &[Task]) -> Option<Vec<String>> {
+    let by_id: HashMap<&str, &Task> = tasks.iter().map(|t| (t.id.as_str(), t)).collect();
+    // 1 = on the current DFS path, 2 = fully explored
+    let mut state: HashMap<&str, u8> = HashMap::new();
+
+    fn visit<'a>(
+        id: &'a str,
+        by_id: &HashMap<&'a str, &'a Task>,
+        state: &mut HashMap<&'a str, u8>,
+        path: &mut Vec<&'a str>,
+    ) -> Option<Vec<String>> {
+        match state.get(id) {
+            Some(2) => return None,
+            Some(_) => {
+                let start = path.iter().position(|p| *p == id).unwrap_or(0);
+                let mut cycle: Vec<String> = path[start..].iter().map(|p| p.to_string()).collect();
+                cycle.push(id.to_string());
+                return Some(cycle);
+            }
+            None => {}
+        }
+
+        state.insert(id, 1);
+        path.push(id);
+        for dep in &by_id[id].depends_on {
+            if let Some(dep) = by_id.get(dep.as_str()).map(|t| t.id.as_str()) {
+                if let Some(cycle) = visit(dep, by_id, state, path) {
+                    return Some(cycle);
+                }
+            }
+        }
+        path.pop();
+        state.insert(id, 2);
+        None
+    }
+
+    tasks
+        .iter()
+        .find_map(|t| visit(&t.id, &by_id, &mut state, &mut Vec::new()))
+}
+
 /// Execute tasks concurrently
+///
+/// Tasks run in dependency order: each wave runs, up to `max_parallel` at a
+/// time, every task whose dependencies have all succeeded. A task is skipped
+/// when one of its dependencies failed or is missing from the run, or when a
+/// failure stopped the run before it started (without `continue_on_error`).
+/// Fails up front when the dependencies form a cycle.
 pub fn execute_tasks(
     root: &Path,
     task_set: &TaskSet,
@@ -511,6 +898,7 @@ pub fn execute_tasks(
                 success: true,
                 error: None,
                 output_file: None,
+                attempts: 0,
             })
             .collect();
 
@@ -534,78 +922,95 @@ pub fn execute_tasks(
         options.max_parallel
     };
 
-    // Build dependency graph
-    let mut task_map: HashMap<String, Task> = HashMap::new();
-    for task in &all_tasks {
-        task_map.insert(task.id.clone(), task.clone());
+    if let Some(cycle) = find_task_cycle(&all_tasks) {
+        anyhow::bail!("Task dependency cycle: {}", cycle.join(" -> "));
     }
 
-    // Separate tasks by dependencies
-    let (independent_tasks, dependent_tasks): (Vec<_>, Vec<_>) =
-        all_tasks.iter().partition(|t| t.depends_on.is_empty());
-
     let results: Arc<Mutex<Vec<TaskResult>>> = Arc::new(Mutex::new(Vec::new()));
     let completed: Arc<Mutex<HashMap<String, bool>>> = Arc::new(Mutex::new(HashMap::new()));
 
-    // Execute independent tasks in parallel
-    execute_parallel(
-        root,
-        &independent_tasks.into_iter().cloned().collect::<Vec<_>>(),
-        max_parallel,
-        options.timeout,
-        output_dir.as_deref(),
-        options.save_outputs,
-        options.continue_on_error,
-        &results,
-        &completed,
-    );
+    // Run tasks in waves: each wave holds every task whose dependencies have
+    // all succeeded, so list order does not matter
+    let known: HashSet<&str> = all_tasks.iter().map(|t| t.id.as_str()).collect();
+    let mut pending: Vec<&Task> = all_tasks.iter().collect();
 
-    // Execute dependent tasks (respecting dependencies)
-    for task in dependent_tasks {
-        // Check if all dependencies succeeded
-        let deps_ok = {
-            let completed_guard = completed.lock().unwrap();
-            task.depends_on
-                .iter()
-                .all(|dep| completed_guard.get(dep).copied().unwrap_or(false))
-        };
+    while !pending.is_empty() {
+        let mut ready = Vec::new();
+        let mut skipped_any = false;
 
-        if deps_ok {
-            let result = execute_task(
-                root,
-                task,
-                options.timeout,
-                output_dir.as_deref(),
-                options.save_outputs,
-            );
+        pending.retain(|task| {
+            let mut completed_guard = completed.lock().unwrap();
+            let blocked = task.depends_on.iter().find(|dep| {
+                !known.contains(dep.as_str()) || completed_guard.get(*dep) == Some(&false)
+            });
 
+            if let Some(dep) = blocked {
+                // A dependency failed (or is not part of this run): skip the task
+                completed_guard.insert(task.id.clone(), false);
+                let reason = if known.contains(dep.as_str()) {
+                    SKIPPED_DEPENDENCY_FAILED.to_string()
+                } else {
+                    format!("Skipped: unknown dependency {}", dep)
+                };
+                results
+                    .lock()
+                    .unwrap()
+                    .push(TaskResult::skipped(&task.id, reason));
+                skipped_any = true;
+                false
+            } else if task
+                .depends_on
+                .iter()
+                .all(|dep| completed_guard.contains_key(dep))
             {
-                let mut completed_guard = completed.lock().unwrap();
-                completed_guard.insert(task.id.clone(), result.success);
-            }
-            {
-                let mut results_guard = results.lock().unwrap();
-                results_guard.push(result);
+                ready.push((*task).clone());
+                false
+            } else {
+                true
             }
-        } else {
-            // Skip task due to dependency failure
-            let result = TaskResult {
-                id: task.id.clone(),
-                exit_code: None,
-                stdout: String::new(),
-                stderr: String::new(),
-                duration_ms: 0,
-                success: false,
-                error: Some("Skipped: dependency failed".to_string()),
-                output_file: None,
-            };
-            {
-                let mut completed_guard = completed.lock().unwrap();
-                completed_guard.insert(task.id.clone(), false);
+        });
+
+        if ready.is_empty() {
+            if skipped_any {
+                continue;
             }
-            {
-                let mut results_guard = results.lock().unwrap();
-                results_guard.push(result);
+            break;
+        }
+
+        execute_parallel(
+            root,
+            &ready,
+            max_parallel,
+            options.timeout,
+            output_dir.as_deref(),
+            options.save_outputs,
+            options.continue_on_error,
+            &results,
+            &completed,
+        );
+
+        // Stop scheduling further waves after a failure unless told to continue
+        let wave_failed = {
+            let completed_guard = completed.lock().unwrap();
+            ready
+                .iter()
+                .any(|t| completed_guard.get(&t.id) != Some(&true))
+        };
+        if wave_failed && !options.continue_on_error {
+            break;
+        }
+    }
+
+    // Tasks left unrun by a stop after a failure are reported as skipped
+    {
+        let completed_guard = completed.lock().unwrap();
+        let mut results_guard = results.lock().unwrap();
+        for task in &all_tasks {
+            if !completed_guard.contains_key(&task.id) {
+                results_guard.push(TaskResult::skipped(
+                    &task.id,
+                    SKIPPED_RUN_STOPPED.to_string(),
+                ));
             }
         }
     }
@@ -616,14 +1021,16 @@ pub fn execute_tasks(
     };
 
     let succeeded = final_results.iter().filter(|r| r.success).count();
+    let is_skipped = |r: &TaskResult| {
+        r.error
+            .as_deref()
+            .is_some_and(|e| e.starts_with("Skipped:"))
+    };
     let failed = final_results
         .iter()
-        .filter(|r| !r.success && r.error.as_deref() != Some("Skipped: dependency failed"))
-        .count();
-    let skipped = final_results
-        .iter()
-        .filter(|r| r.error.as_deref() == Some("Skipped: dependency failed"))
+        .filter(|r| !r.success && !is_skipped(r))
         .count();
+    let skipped = final_results.iter().filter(|r| is_skipped(r)).count();
 
     let summary = ExecutionSummary {
         total: final_results.len(),
@@ -637,6 +1044,66 @@ pub fn execute_tasks(
     Ok((final_results, summary))
 }
 
+/// Run the `on_success` or `on_failure` hook for a finished task set
+///
+/// The hook sees the aggregate summary through `MISE_TOTAL`, `MISE_SUCCEEDED`,
+/// `MISE_FAILED` and `MISE_SKIPPED`. Returns None when no hook applies.
+pub fn execute_hook(
+    root: &Path,
+    task_set: &TaskSet,
+    summary: &ExecutionSummary,
+    options: &RunOptions,
+) -> Option<TaskResult> {
+    let succeeded = summary.failed == 0 && summary.skipped == 0;
+    let (id, cmd) = if succeeded {
+        (
+            "on_success",
+            options
+                .on_success
+                .as_ref()
+                .or(task_set.on_success.as_ref())?,
+        )
+    } else {
+        (
+            "on_failure",
+            options
+                .on_failure
+                .as_ref()
+                .or(task_set.on_failure.as_ref())?,
+        )
+    };
+
+    let env = HashMap::from([
+        ("MISE_TOTAL".to_string(), summary.total.to_string()),
+        ("MISE_SUCCEEDED".to_string(), summary.succeeded.to_string()),
+        ("MISE_FAILED".to_string(), summary.failed.to_string()),
+        ("MISE_SKIPPED".to_string(), summary.skipped.to_string()),
+    ]);
+    let hook = Task {
+        id: id.to_string(),
+        cmd: cmd.clone(),
+        cwd: None,
+        env,
+        env_file: None,
+        timeout: default_timeout(),
+        depends_on: Vec::new(),
+        tags: Vec::new(),
+        description: None,
+        matrix: BTreeMap::new(),
+        retries: 0,
+        retry_delay_ms: 0,
+    };
+
+    let output_dir = summary.output_dir.as_ref().map(PathBuf::from);
+    Some(execute_task(
+        root,
+        &hook,
+        options.timeout,
+        output_dir.as_deref(),
+        options.save_outputs,
+    ))
+}
+
 /// Execute tasks in parallel using thread pool
 fn execute_parallel(
     root: &Path,
@@ -729,18 +1196,14 @@ fn results_to_result_set(results: &[TaskResult], summary: &ExecutionSummary) ->
                         String::new()
                     }
                 );
-                let truncated = if combined.len() > 4096 {
-                    format!("{}...[truncated]", &combined[..4096])
-                } else {
-                    combined
-                };
-                Some(truncated)
+                Some(truncate_excerpt(&combined, 4096).0)
             },
             data: Some(serde_json::json!({
                 "task_id": task_result.id,
                 "exit_code": task_result.exit_code,
                 "duration_ms": task_result.duration_ms,
                 "success": task_result.success,
+                "attempts": task_result.attempts,
                 "error": task_result.error,
             })),
             confidence: if task_result.success {
@@ -809,6 +1272,30 @@ pub fn run_run(
     let total_tasks =
         task_set.tasks.len() + task_set.groups.iter().map(|g| g.tasks.len()).sum::<usize>();
 
+    // A dependency cycle can never be scheduled; report it instead of running
+    let all_tasks: Vec<Task> = task_set
+        .groups
+        .iter()
+        .flat_map(|g| g.tasks.iter())
+        .chain(&task_set.tasks)
+        .cloned()
+        .collect();
+    if let Some(cycle) = find_task_cycle(&all_tasks) {
+        let mut result_set = ResultSet::new();
+        result_set.push(
+            ResultItem::error(MiseError::new(
+                "TASK_CYCLE",
+                format!("Task dependency cycle: {}", cycle.join(" -> ")),
+            ))
+            .with_data(serde_json::json!({ "cycle": cycle })),
+        );
+        print!(
+            "{}",
+            Renderer::with_config(render_config).render(&result_set)
+        );
+        std::process::exit(1);
+    }
+
     // Dry run output
     if options.dry_run {
         eprintln!("╭─── DRY RUN ───────────────────────────────────────────╮");
@@ -845,18 +1332,43 @@ pub fn run_run(
             .as_ref()
             .map(|p| p.to_string_lossy().to_string())
             .unwrap_or_else(|| format!("{}/rundata", root.display()));
+        let hooks = [
+            (
+                "on success",
+                options.on_success.as_ref().or(task_set.on_success.as_ref()),
+            ),
+            (
+                "on failure",
+                options.on_failure.as_ref().or(task_set.on_failure.as_ref()),
+            ),
+        ];
+        for (label, cmd) in hooks {
+            if let Some(cmd) = cmd {
+                eprintln!("│ Hook ({}): {}", label, cmd);
+            }
+        }
+
         eprintln!("├────────────────────────────────────────────────────────┤");
         eprintln!("│ Output: {}", output_dir);
         eprintln!("╰────────────────────────────────────────────────────────╯");
     }
 
     // Execute tasks
-    let (results, summary) = execute_tasks(root, &task_set, &options)?;
+    let (mut results, summary) = execute_tasks(root, &task_set, &options)?;
+
+    // Run the matching hook; it is reported but not counted in the summary
+    let hook_result = if options.dry_run {
+        None
+    } else {
+        execute_hook(root, &task_set, &summary, &options)
+    };
+    let hook_failed = hook_result.as_ref().is_some_and(|h| !h.success);
+    results.extend(hook_result);
 
     // Convert to ResultSet and render
     let result_set = results_to_result_set(&results, &summary);
     let renderer = Renderer::with_config(render_config);
-    println!("{}", renderer.render(&result_set));
+    print!("{}", renderer.render(&result_set));
 
     // Print summary to stderr
     if !options.dry_run {
@@ -873,8 +1385,8 @@ pub fn run_run(
         eprintln!("╰────────────────────────────────────────────────────────╯");
     }
 
-    // Exit with error if any task failed and not continuing
-    if summary.failed > 0 && !options.continue_on_error {
+    // Exit with error if any task failed and not continuing, or the hook failed
+    if (summary.failed > 0 && !options.continue_on_error) || hook_failed {
         std::process::exit(1);
     }
 
@@ -930,6 +1442,295 @@ mod tests {
         assert_eq!(task_set.groups[0].tasks.len(), 2);
     }
 
+    #[test]
+    fn test_parse_tasks_yaml_shapes() {
+        let single = parse_tasks("id: build\ncmd: cargo build\nretries: 2\n").unwrap();
+        assert_eq!(single.name, "single");
+        assert_eq!(single.tasks[0].retries, 2);
+
+        let array = parse_tasks("- id: a\n  cmd: echo a\n- id: b\n  cmd: echo b\n").unwrap();
+        assert_eq!(array.tasks.len(), 2);
+
+        let yaml = r#"
+name: ci
+tasks:
+  - id: build
+    cmd: cargo build
+  - id: test
+    cmd: cargo test
+    depends_on: [build]
+    env:
+      RUST_LOG: debug
+groups:
+  - name: lint
+    parallel: true
+    tasks:
+      - id: clippy
+        cmd: cargo clippy
+"#;
+        let task_set = parse_tasks(yaml).unwrap();
+        assert_eq!(task_set.name, "ci");
+        assert_eq!(task_set.tasks[1].depends_on, ["build"]);
+        assert_eq!(task_set.groups[0].tasks[0].id, "clippy");
+
+        assert!(parse_tasks("just some text").is_err());
+    }
+
+    #[test]
+    fn test_parse_tasks_from_yaml_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("tasks.yml");
+        fs::write(&path, "tasks:\n  - id: hello\n    cmd: echo hello\n").unwrap();
+        let task_set = parse_tasks_from_file(&path).unwrap();
+        assert_eq!(task_set.tasks[0].cmd, "echo hello");
+
+        // JSON content still parses from a .yaml file
+        let path = temp.path().join("tasks.yaml");
+        fs::write(&path, r#"[{"id": "a", "cmd": "echo a"}]"#).unwrap();
+        assert_eq!(parse_tasks_from_file(&path).unwrap().tasks.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_matrix_task_expansion() {
+        let json = r#"{
+            "id": "find",
+            "cmd": "misec match ${matrix.pattern} ${matrix.dir}",
+            "cwd": "${matrix.dir}",
+            "matrix": {"dir": ["a", "b"], "pattern": ["x", "y"]}
+        }"#;
+        let task_set = parse_tasks(json).unwrap();
+        let ids: Vec<_> = task_set.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["find-a-x", "find-a-y", "find-b-x", "find-b-y"]);
+        assert_eq!(task_set.tasks[1].cmd, "misec match y a");
+        assert_eq!(task_set.tasks[1].cwd.as_deref(), Some("a"));
+        assert!(task_set.tasks.iter().all(|t| t.matrix.is_empty()));
+    }
+
+    #[test]
+    fn test_matrix_dependencies_are_rewritten() {
+        let json = r#"[
+            {"id": "scan", "cmd": "misec scan --scope ${matrix.dir}", "matrix": {"dir": ["src", "docs"]}},
+            {"id": "report", "cmd": "echo done", "depends_on": ["scan"]}
+        ]"#;
+        let task_set = parse_tasks(json).unwrap();
+        assert_eq!(task_set.tasks.len(), 3);
+        let report = task_set.tasks.iter().find(|t| t.id == "report").unwrap();
+        assert_eq!(report.depends_on, vec!["scan-src", "scan-docs"]);
+    }
+
+    #[test]
+    fn test_empty_matrix_values_are_rejected() {
+        let json = r#"{"groups": [{"name": "g", "tasks": [
+            {"id": "scan", "cmd": "misec scan --scope ${matrix.dir}", "matrix": {"dir": []}}
+        ]}]}"#;
+        let err = parse_tasks(json).unwrap_err().to_string();
+        assert!(err.contains("'scan'"), "{}", err);
+        assert!(err.contains("empty matrix value list for 'dir'"), "{}", err);
+    }
+
+    #[test]
+    fn test_execute_hook_selects_by_outcome() {
+        let temp = tempfile::tempdir().unwrap();
+        let task_set = parse_tasks(
+            r#"{"tasks": [{"id": "ok", "cmd": "true"}],
+                "on_success": "echo ok $MISE_TOTAL", "on_failure": "echo failed $MISE_FAILED"}"#,
+        )
+        .unwrap();
+        let options = RunOptions {
+            save_outputs: false,
+            ..Default::default()
+        };
+        let mut summary = ExecutionSummary {
+            total: 3,
+            succeeded: 3,
+            failed: 0,
+            skipped: 0,
+            total_duration_ms: 0,
+            output_dir: None,
+        };
+
+        let hook = execute_hook(temp.path(), &task_set, &summary, &options).unwrap();
+        assert_eq!(hook.id, "on_success");
+        assert_eq!(hook.stdout.trim(), "ok 3");
+
+        summary.failed = 2;
+        let hook = execute_hook(temp.path(), &task_set, &summary, &options).unwrap();
+        assert_eq!(hook.id, "on_failure");
+        assert_eq!(hook.stdout.trim(), "failed 2");
+
+        // CLI override wins; no hook configured means nothing runs
+        let options = RunOptions {
+            on_failure: Some("exit 3".to_string()),
+            ..options
+        };
+        let hook = execute_hook(temp.path(), &task_set, &summary, &options).unwrap();
+        assert!(!hook.success);
+        let bare = parse_tasks(r#"{"id": "ok", "cmd": "true"}"#).unwrap();
+        assert!(execute_hook(temp.path(), &bare, &summary, &RunOptions::default()).is_none());
+    }
+
+    #[test]
+    fn test_execute_tasks_runs_dependencies_first() {
+        let temp = tempfile::tempdir().unwrap();
+        // "report" is listed before the task it depends on
+        let task_set = parse_tasks(
+            r#"[{"id": "report", "cmd": "cat built.txt", "depends_on": ["build"]},
+                {"id": "build", "cmd": "echo ok > built.txt", "depends_on": ["fetch"]},
+                {"id": "fetch", "cmd": "true"},
+                {"id": "broken", "cmd": "exit 1"},
+                {"id": "after-broken", "cmd": "true", "depends_on": ["broken"]},
+                {"id": "after-skip", "cmd": "true", "depends_on": ["after-broken"]}]"#,
+        )
+        .unwrap();
+        let options = RunOptions {
+            save_outputs: false,
+            continue_on_error: true,
+            max_parallel: 2,
+            ..Default::default()
+        };
+
+        let (results, summary) = execute_tasks(temp.path(), &task_set, &options).unwrap();
+        let by_id: HashMap<_, _> = results.iter().map(|r| (r.id.as_str(), r)).collect();
+        assert!(by_id["report"].success);
+        assert_eq!(by_id["report"].stdout.trim(), "ok");
+        assert_eq!(
+            by_id["after-skip"].error.as_deref(),
+            Some(SKIPPED_DEPENDENCY_FAILED)
+        );
+        assert_eq!(
+            (summary.succeeded, summary.failed, summary.skipped),
+            (3, 1, 2)
+        );
+    }
+
+    #[test]
+    fn test_execute_tasks_stop_reports_remaining_as_skipped() {
+        let temp = tempfile::tempdir().unwrap();
+        let task_set = parse_tasks(
+            r#"[{"id": "broken", "cmd": "exit 1"},
+                {"id": "never", "cmd": "true"},
+                {"id": "later", "cmd": "true", "depends_on": ["never"]}]"#,
+        )
+        .unwrap();
+        let options = RunOptions {
+            save_outputs: false,
+            max_parallel: 1,
+            ..Default::default()
+        };
+
+        let (results, summary) = execute_tasks(temp.path(), &task_set, &options).unwrap();
+        let by_id: HashMap<_, _> = results.iter().map(|r| (r.id.as_str(), r)).collect();
+        assert_eq!(results.len(), 3);
+        assert!(!by_id["broken"].success);
+        for id in ["never", "later"] {
+            assert_eq!(by_id[id].error.as_deref(), Some(SKIPPED_RUN_STOPPED));
+        }
+        assert_eq!((summary.total, summary.failed, summary.skipped), (3, 1, 2));
+    }
+
+    #[test]
+    fn test_execute_task_retries_until_success() {
+        let temp = tempfile::tempdir().unwrap();
+        let task_set = parse_tasks(
+            r#"[{"id": "flaky", "cmd": "echo x >> tries; [ $(wc -l < tries) -ge 3 ]",
+                 "retries": 3, "retry_delay_ms": 10},
+                {"id": "down", "cmd": "exit 1", "retries": 1}]"#,
+        )
+        .unwrap();
+        assert_eq!(task_set.tasks[0].retries, 3);
+        assert_eq!(task_set.tasks[0].retry_delay_ms, 10);
+
+        let flaky = execute_task(temp.path(), &task_set.tasks[0], None, None, false);
+        assert!(flaky.success);
+        assert_eq!(flaky.attempts, 3);
+
+        let down = execute_task(temp.path(), &task_set.tasks[1], None, None, false);
+        assert!(!down.success);
+        assert_eq!(down.attempts, 2);
+    }
+
+    #[test]
+    fn test_env_file_merges_under_inline_env() {
+        assert_eq!(
+            parse_env_file("# comment\n\nexport A=1\nB = \"two words\"\nC='x=y'\nnoequals\n"),
+            vec![
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "two words".to_string()),
+                ("C".to_string(), "x=y".to_string()),
+            ]
+        );
+
+        let temp = tempfile::tempdir().unwrap();
+        fs::create_dir(temp.path().join("sub")).unwrap();
+        fs::write(temp.path().join("sub/.env"), "A=file\nB=file\n").unwrap();
+        let task_set = parse_tasks(
+            r#"[{"id": "env", "cmd": "echo $A $B", "cwd": "sub", "env_file": ".env",
+                 "env": {"B": "inline"}},
+                {"id": "missing", "cmd": "true", "env_file": "nope.env"}]"#,
+        )
+        .unwrap();
+
+        let result = execute_task(temp.path(), &task_set.tasks[0], None, None, false);
+        assert_eq!(result.stdout.trim(), "file inline");
+
+        let result = execute_task(temp.path(), &task_set.tasks[1], None, None, false);
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Failed to read env file"));
+    }
+
+    #[test]
+    fn test_execute_task_large_output_does_not_block() {
+        let temp = tempfile::tempdir().unwrap();
+        // Well past a 64 KiB pipe buffer on both streams
+        let task_set = parse_tasks(
+            r#"{"id": "big", "cmd": "head -c 300000 /dev/zero | tr '\\0' a; head -c 200000 /dev/zero | tr '\\0' b >&2",
+                "timeout": 20}"#,
+        )
+        .unwrap();
+
+        let result = execute_task(temp.path(), &task_set.tasks[0], None, None, false);
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.stdout.len(), 300_000);
+        assert_eq!(result.stderr.len(), 200_000);
+    }
+
+    #[test]
+    fn test_find_task_cycle() {
+        let task_set = parse_tasks(
+            r#"[{"id": "a", "cmd": "true"},
+                {"id": "b", "cmd": "true", "depends_on": ["a", "d"]},
+                {"id": "c", "cmd": "true", "depends_on": ["b"]},
+                {"id": "d", "cmd": "true", "depends_on": ["c"]}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            find_task_cycle(&task_set.tasks),
+            Some(vec![
+                "b".to_string(),
+                "d".to_string(),
+                "c".to_string(),
+                "b".to_string()
+            ])
+        );
+        let err = execute_tasks(
+            Path::new("."),
+            &task_set,
+            &RunOptions {
+                save_outputs: false,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("cycle"));
+
+        let acyclic = parse_tasks(
+            r#"[{"id": "a", "cmd": "true", "depends_on": ["missing"]},
+                {"id": "b", "cmd": "true", "depends_on": ["a"]}]"#,
+        )
+        .unwrap();
+        assert_eq!(find_task_cycle(&acyclic.tasks), None);
+    }
+
     #[test]
     fn test_sanitize_filename() {
         assert_eq!(sanitize_filename("my-task_1"), "my-task_1");
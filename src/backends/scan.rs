@@ -2,14 +2,25 @@
 //!
 //! Uses walkdir and ignore crate for efficient file traversal
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use globset::{Glob, GlobBuilder, GlobMatcher, GlobSet, GlobSetBuilder};
 use ignore::WalkBuilder;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::ops::ControlFlow;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use crate::core::model::{Meta, ResultItem, ResultSet};
+use crate::core::model::{Confidence, Kind, Meta, MiseError, ResultItem, ResultSet};
 use crate::core::paths::make_relative;
-use crate::core::render::{RenderConfig, Renderer};
-use crate::core::util::{get_file_size, get_mtime_ms};
+use crate::core::render::{finish_stream, JsonlStream, RenderConfig, Renderer};
+use crate::core::util::{
+    format_rfc3339_ms, get_file_size, get_mtime_ms, hash_file, now_ms, HashAlgorithm,
+};
+
+/// mise-specific ignore file, layered on top of .gitignore (disabled by `--no-ignore`)
+pub const MISE_IGNORE_FILE: &str = ".miseignore";
 
 /// Options for the scan command
 #[derive(Debug, Default)]
@@ -19,32 +30,167 @@ pub struct ScanOptions {
     pub hidden: bool,
     pub ignore: bool,
     pub file_type: Option<String>,
+    /// Include/exclude globs over root-relative paths
+    pub filter: GlobFilter,
+    /// Emit compact `{"p":..,"t":..}` lines instead of full ResultItems
+    pub minimal: bool,
+    /// Skip files smaller than this many bytes
+    pub min_size: Option<u64>,
+    /// Skip files larger than this many bytes
+    pub max_size: Option<u64>,
+    /// Report groups of files with identical content instead of listing entries
+    pub find_duplicates: bool,
+    /// Report file counts and bytes per extension and top-level directory
+    pub summary: bool,
+    /// Only files modified within this long before now
+    pub modified_within: Option<Duration>,
+    /// Only files last modified longer than this before now
+    pub modified_before: Option<Duration>,
+    /// Descend into symlinked directories (loops become `SYMLINK_LOOP` items)
+    pub follow_links: bool,
+    /// Add each regular file's content hash to `data.hash`
+    pub hash: Option<HashAlgorithm>,
+}
+
+impl ScanOptions {
+    /// Whether a size or mtime filter is active (file items then carry `data`)
+    fn has_file_filters(&self) -> bool {
+        self.min_size.is_some()
+            || self.max_size.is_some()
+            || self.modified_within.is_some()
+            || self.modified_before.is_some()
+    }
+}
+
+/// Include/exclude globs shared by every scan-consuming command
+///
+/// Globs follow ripgrep's `-g` rules: a pattern without `/` matches the name
+/// at any depth, one with `/` the whole root-relative path; `*` stays within a
+/// component and `**` crosses directories. An excluded directory excludes
+/// everything below it. Applied to root-relative paths that already passed the
+/// ignore rules and the deny list; an empty include list keeps every path.
+#[derive(Debug, Clone, Default)]
+pub struct GlobFilter {
     pub include: Vec<String>,
     pub exclude: Vec<String>,
+    include_set: GlobSet,
+    exclude_set: GlobSet,
 }
 
-/// Simple glob matching (supports * and **)
-fn glob_match(pattern: &str, path: &str) -> bool {
-    if pattern.starts_with("*.") {
-        // Extension match: *.rs -> ends with .rs
-        let ext = &pattern[1..];
-        path.ends_with(ext)
-    } else if pattern.ends_with("/*") {
-        // Directory match: vendor/* -> starts with vendor/
-        let prefix = &pattern[..pattern.len() - 1];
-        path.starts_with(prefix)
-    } else if pattern.contains('*') {
-        // Generic wildcard - simple contains check for the non-wildcard part
-        let parts: Vec<&str> = pattern.split('*').filter(|s| !s.is_empty()).collect();
-        parts.iter().all(|part| path.contains(part))
-    } else {
-        // Exact match
-        path == pattern || path.contains(pattern)
+impl GlobFilter {
+    pub fn new(include: Vec<String>, exclude: Vec<String>) -> Result<Self> {
+        Ok(Self {
+            include_set: glob_set(&include)?,
+            exclude_set: glob_set(&exclude)?,
+            include,
+            exclude,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Whether a root-relative path passes the filter
+    pub fn matches(&self, path: &str) -> bool {
+        (self.include.is_empty() || self.include_set.is_match(path))
+            && !path_prefixes(path).any(|prefix| self.exclude_set.is_match(prefix))
+    }
+
+    /// Drop items whose path does not pass the filter (path-less items are kept)
+    pub fn retain(&self, result_set: &mut ResultSet) {
+        if self.is_empty() {
+            return;
+        }
+        result_set
+            .items
+            .retain(|item| item.path.as_deref().is_none_or(|p| self.matches(p)));
     }
 }
 
-/// Scan files in a directory
-pub fn scan_files(root: &Path, options: &ScanOptions) -> Result<ResultSet> {
+/// `a`, `a/b`, `a/b/c` for `a/b/c`
+fn path_prefixes(path: &str) -> impl Iterator<Item = &str> {
+    path.match_indices('/')
+        .map(move |(i, _)| &path[..i])
+        .chain(std::iter::once(path))
+}
+
+/// Compile one include/exclude glob with ripgrep-style anchoring
+fn compile_glob(pattern: &str) -> Result<Glob> {
+    let trimmed = pattern.trim_start_matches("./").trim_end_matches('/');
+    let anchored = match trimmed.strip_prefix('/') {
+        Some(rooted) => rooted.to_string(),
+        None if trimmed.contains('/') => trimmed.to_string(),
+        None => format!("**/{}", trimmed),
+    };
+    GlobBuilder::new(&anchored)
+        .literal_separator(true)
+        .build()
+        .with_context(|| format!("Invalid glob pattern: {}", pattern))
+}
+
+fn glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(compile_glob(pattern)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Whether a root-relative path matches a single glob (invalid globs match nothing)
+#[cfg(test)]
+pub(crate) fn glob_match(pattern: &str, path: &str) -> bool {
+    compile_glob(pattern).is_ok_and(|glob| glob.compile_matcher().is_match(path))
+}
+
+/// Minimal scan entry: path and type only, with single-letter keys
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MinimalEntry {
+    /// Path relative to root
+    #[serde(rename = "p")]
+    pub path: String,
+    /// Entry type: "f" for file, "d" for directory
+    #[serde(rename = "t")]
+    pub entry_type: &'static str,
+}
+
+/// Find the symlink loop behind a walk error, as (link, ancestor) paths
+fn symlink_loop(err: &ignore::Error) -> Option<(&Path, &Path)> {
+    match err {
+        ignore::Error::Loop { ancestor, child } => Some((child, ancestor)),
+        ignore::Error::WithPath { err, .. }
+        | ignore::Error::WithDepth { err, .. }
+        | ignore::Error::WithLineNumber { err, .. } => symlink_loop(err),
+        ignore::Error::Partial(errs) => errs.iter().find_map(symlink_loop),
+        _ => None,
+    }
+}
+
+/// Build the `SYMLINK_LOOP` error item for a link that points to an ancestor
+fn symlink_loop_item(root: &Path, link: &Path, ancestor: &Path) -> ResultItem {
+    let display = |p: &Path| make_relative(p, root).unwrap_or_else(|| p.display().to_string());
+    let (link, ancestor) = (display(link), display(ancestor));
+
+    let mut item = ResultItem::error(MiseError::new(
+        "SYMLINK_LOOP",
+        format!("Symlink loop: {} points back to {}", link, ancestor),
+    ));
+    item.data = Some(serde_json::json!({ "ancestor": ancestor }));
+    item.path = Some(link);
+    item
+}
+
+/// Walk entries under the scan path, applying type and glob filters
+///
+/// Calls `visit` with the absolute path, the root-relative path and whether
+/// the entry is a directory. The walk stops early when `visit` breaks.
+/// Symlink cycles are not descended into; each one is returned as a
+/// `SYMLINK_LOOP` error item for the caller to report.
+fn walk_entries(
+    root: &Path,
+    options: &ScanOptions,
+    mut visit: impl FnMut(&Path, String, bool) -> ControlFlow<()>,
+) -> Result<Vec<ResultItem>> {
     let scan_path = options.scope.as_deref().unwrap_or(root);
 
     let mut builder = WalkBuilder::new(scan_path);
@@ -52,18 +198,44 @@ pub fn scan_files(root: &Path, options: &ScanOptions) -> Result<ResultSet> {
         .hidden(!options.hidden)
         .git_ignore(options.ignore)
         .git_global(options.ignore)
-        .git_exclude(options.ignore);
+        .git_exclude(options.ignore)
+        .follow_links(options.follow_links)
+        // Name order within each directory keeps streamed output deterministic
+        .sort_by_file_name(|a, b| a.cmp(b));
+
+    if options.ignore {
+        builder.add_custom_ignore_filename(MISE_IGNORE_FILE);
+    }
 
     if let Some(depth) = options.max_depth {
         builder.max_depth(Some(depth));
     }
 
-    let mut result_set = ResultSet::new();
+    // Prune denied paths so they are never descended into or emitted
+    builder.filter_entry(|entry| !crate::core::policy::is_path_denied(entry.path()));
+
+    // mtime cutoffs in ms since the epoch, fixed at the start of the walk;
+    // durations too large for i64 ms clamp to the start of time
+    let now = now_ms();
+    let cutoff = |d: Duration| now.saturating_sub(i64::try_from(d.as_millis()).unwrap_or(i64::MAX));
+    let modified_after = options.modified_within.map(cutoff);
+    let modified_until = options.modified_before.map(cutoff);
 
+    let mut loops = Vec::new();
     for entry in builder.build() {
+        // Stop walking once the time budget is spent; keep what we have
+        if crate::core::budget::should_stop() {
+            break;
+        }
+
         let entry = match entry {
             Ok(e) => e,
-            Err(_) => continue,
+            Err(err) => {
+                if let Some((link, ancestor)) = symlink_loop(&err) {
+                    loops.push(symlink_loop_item(root, link, ancestor));
+                }
+                continue;
+            }
         };
 
         let path = entry.path();
@@ -88,72 +260,535 @@ pub fn scan_files(root: &Path, options: &ScanOptions) -> Result<ResultSet> {
         };
 
         // Apply include/exclude filters
-        if !options.include.is_empty() {
-            let matched = options
-                .include
-                .iter()
-                .any(|glob| glob_match(glob, &relative));
-            if !matched {
+        if !options.filter.matches(&relative) {
+            continue;
+        }
+
+        // Apply size filters (files only)
+        if !is_dir && (options.min_size.is_some() || options.max_size.is_some()) {
+            let size = match get_file_size(path) {
+                Ok(size) => size,
+                Err(_) => continue,
+            };
+            if options.min_size.is_some_and(|min| size < min)
+                || options.max_size.is_some_and(|max| size > max)
+            {
                 continue;
             }
         }
-        if options
-            .exclude
-            .iter()
-            .any(|glob| glob_match(glob, &relative))
-        {
-            continue;
+
+        // Apply mtime filters (files only)
+        if !is_dir && (modified_after.is_some() || modified_until.is_some()) {
+            let mtime = match get_mtime_ms(path) {
+                Ok(mtime) => mtime,
+                Err(_) => continue,
+            };
+            if modified_after.is_some_and(|after| mtime < after)
+                || modified_until.is_some_and(|until| mtime >= until)
+            {
+                continue;
+            }
+        }
+
+        if visit(path, relative, is_dir).is_break() {
+            break;
+        }
+    }
+
+    Ok(loops)
+}
+
+/// Build the scan result item for a walked entry (files get size and mtime)
+///
+/// With a size or mtime filter active, files also carry `data.size` and
+/// `data.mtime` (RFC 3339) for sorting; with `hash`, regular files carry
+/// `data.hash`.
+fn entry_item(path: &Path, relative: String, is_dir: bool, options: &ScanOptions) -> ResultItem {
+    let mut item = ResultItem::file(relative);
+    if is_dir {
+        return item;
+    }
+
+    let mut meta = Meta::default();
+    if let Ok(size) = get_file_size(path) {
+        meta.size = Some(size);
+    }
+    if let Ok(mtime) = get_mtime_ms(path) {
+        meta.mtime_ms = Some(mtime);
+    }
+    if options.has_file_filters() {
+        item.data = Some(serde_json::json!({
+            "size": meta.size,
+            "mtime": meta.mtime_ms.and_then(format_rfc3339_ms),
+        }));
+    }
+    // Only regular files: reading a FIFO or device would block or never end
+    if let Some(algorithm) = options.hash.filter(|_| path.is_file()) {
+        if let Ok(hash) = hash_file(path, algorithm) {
+            let data = item.data.get_or_insert_with(|| serde_json::json!({}));
+            data["hash"] = serde_json::json!(hash);
         }
+    }
+    item.with_meta(meta)
+}
+
+/// Scan files in a directory
+pub fn scan_files(root: &Path, options: &ScanOptions) -> Result<ResultSet> {
+    let mut result_set = ResultSet::new();
 
-        // Build result item
-        let mut item = ResultItem::file(relative);
+    let loops = walk_entries(root, options, |path, relative, is_dir| {
+        result_set.push(entry_item(path, relative, is_dir, options));
+        ControlFlow::Continue(())
+    })?;
+    result_set.items.extend(loops);
 
-        // Add metadata for files
+    result_set.sort();
+    Ok(result_set)
+}
+
+/// Write scan items to stdout as jsonl while the walk runs
+///
+/// Opt-in via `--stream`: output follows walk order (names sorted per
+/// directory, so `a/x` precedes `a.txt`) instead of the global path sort of
+/// `scan_files`, in exchange for the first line appearing immediately.
+fn stream_scan(root: &Path, options: &ScanOptions) -> Result<()> {
+    let mut stream = JsonlStream::new(std::io::stdout().lock());
+    let mut write_error = None;
+
+    let loops = walk_entries(root, options, |path, relative, is_dir| {
+        match stream.write_item(&entry_item(path, relative, is_dir, options)) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(e) => {
+                write_error = Some(e);
+                ControlFlow::Break(())
+            }
+        }
+    })?;
+
+    // Loops are only known once the walk is done, so they trail the entries
+    let written = match write_error {
+        Some(e) => Err(e),
+        None => loops.iter().try_for_each(|item| stream.write_item(item)),
+    };
+    finish_stream(written.and_then(|()| stream.finish()))
+}
+
+/// Find groups of files with identical content
+///
+/// Files are first bucketed by size so only same-size candidates get hashed
+/// (xxh3). Each group with more than one member becomes one item whose path is
+/// the first duplicate and whose `data.paths` lists every member.
+pub fn find_duplicates(root: &Path, options: &ScanOptions) -> Result<ResultSet> {
+    let mut by_size: BTreeMap<u64, Vec<(PathBuf, String)>> = BTreeMap::new();
+
+    walk_entries(root, options, |path, relative, is_dir| {
         if !is_dir {
-            let mut meta = Meta::default();
             if let Ok(size) = get_file_size(path) {
-                meta.size = Some(size);
+                by_size
+                    .entry(size)
+                    .or_default()
+                    .push((path.to_path_buf(), relative));
             }
-            if let Ok(mtime) = get_mtime_ms(path) {
-                meta.mtime_ms = Some(mtime);
+        }
+        ControlFlow::Continue(())
+    })?;
+
+    let mut result_set = ResultSet::new();
+
+    for (size, candidates) in by_size {
+        if candidates.len() < 2 {
+            continue;
+        }
+
+        let mut by_hash: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (path, relative) in candidates {
+            if crate::core::budget::should_stop() {
+                break;
+            }
+            if let Ok(hash) = hash_file(&path, HashAlgorithm::Xxh3) {
+                by_hash.entry(hash).or_default().push(relative);
             }
-            item = item.with_meta(meta);
         }
 
-        result_set.push(item);
+        for (hash, mut paths) in by_hash {
+            if paths.len() < 2 {
+                continue;
+            }
+            paths.sort();
+
+            let meta = Meta {
+                size: Some(size),
+                hash: Some(hash.clone()),
+                ..Default::default()
+            };
+            let item = ResultItem::file(paths[0].clone())
+                .with_meta(meta)
+                .with_data(serde_json::json!({
+                    "hash": hash,
+                    "size": size,
+                    "count": paths.len(),
+                    "paths": paths,
+                }));
+            result_set.push(item);
+        }
     }
 
     result_set.sort();
     Ok(result_set)
 }
 
+/// File count and total size of one scan summary group
+#[derive(Debug, Default, Clone, Copy)]
+struct Tally {
+    files: usize,
+    bytes: u64,
+}
+
+impl Tally {
+    fn add(&mut self, bytes: u64) {
+        self.files += 1;
+        self.bytes += bytes;
+    }
+}
+
+/// Aggregate files per extension and per top-level directory
+///
+/// Uses the same walk as a plain scan, so ignore rules, scope and filters apply.
+/// Emits a `total` item, then the `extension` and `directory` groups, each
+/// sorted by file count and then bytes (descending). Extension items use their
+/// `*.ext` glob as path so it can be passed straight to `--include`; files
+/// directly in the scan path are grouped under `.`.
+pub fn scan_summary(root: &Path, options: &ScanOptions) -> Result<ResultSet> {
+    let scan_path = options.scope.as_deref().unwrap_or(root);
+    let mut total = Tally::default();
+    let mut by_ext: BTreeMap<String, Tally> = BTreeMap::new();
+    let mut by_dir: BTreeMap<String, Tally> = BTreeMap::new();
+
+    walk_entries(root, options, |path, relative, is_dir| {
+        if is_dir {
+            return ControlFlow::Continue(());
+        }
+        let size = get_file_size(path).unwrap_or(0);
+        total.add(size);
+
+        let ext = Path::new(&relative)
+            .extension()
+            .map(|e| e.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        by_ext.entry(ext).or_default().add(size);
+
+        let inner = path.strip_prefix(scan_path).unwrap_or(path);
+        let dir = match inner.components().count() {
+            0 | 1 => ".".to_string(),
+            _ => inner
+                .components()
+                .next()
+                .and_then(|c| make_relative(&scan_path.join(c), root))
+                .map_or_else(|| ".".to_string(), |d| format!("{}/", d)),
+        };
+        by_dir.entry(dir).or_default().add(size);
+        ControlFlow::Continue(())
+    })?;
+
+    let mut result_set = ResultSet::new();
+    result_set.push(summary_item("total", ".", ".", total));
+
+    let mut exts: Vec<_> = by_ext.into_iter().collect();
+    exts.sort_by(|(_, a), (_, b)| b.files.cmp(&a.files).then(b.bytes.cmp(&a.bytes)));
+    for (ext, tally) in exts {
+        let label = if ext.is_empty() {
+            "(no extension)".to_string()
+        } else {
+            format!("*.{}", ext)
+        };
+        result_set.push(summary_item("extension", &label, &ext, tally));
+    }
+
+    let mut dirs: Vec<_> = by_dir.into_iter().collect();
+    dirs.sort_by(|(_, a), (_, b)| b.files.cmp(&a.files).then(b.bytes.cmp(&a.bytes)));
+    for (dir, tally) in dirs {
+        result_set.push(summary_item("directory", &dir, &dir, tally));
+    }
+
+    Ok(result_set)
+}
+
+/// One row of the scan summary: `data` carries the group, key and totals
+fn summary_item(group: &str, label: &str, key: &str, tally: Tally) -> ResultItem {
+    let mut item = ResultItem::file(label);
+    item.kind = Kind::Flow;
+    item.excerpt = Some(format!("{} files, {} bytes", tally.files, tally.bytes));
+    item.with_data(serde_json::json!({
+        "group": group,
+        "key": key,
+        "files": tally.files,
+        "bytes": tally.bytes,
+    }))
+}
+
+/// Scan entries as minimal path+type records (no metadata, no envelope)
+pub fn scan_minimal(root: &Path, options: &ScanOptions) -> Result<Vec<MinimalEntry>> {
+    let mut entries = Vec::new();
+
+    walk_entries(root, options, |_, relative, is_dir| {
+        entries.push(MinimalEntry {
+            path: relative,
+            entry_type: if is_dir { "d" } else { "f" },
+        });
+        ControlFlow::Continue(())
+    })?;
+
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
 /// Run the scan command
 pub fn run_scan(root: &Path, options: ScanOptions, config: RenderConfig) -> Result<()> {
-    let result_set = scan_files(root, &options)?;
+    // Minimal mode bypasses the ResultItem envelope and the renderer entirely
+    if options.minimal {
+        let entries = scan_minimal(root, &options)?;
+        let stdout = std::io::stdout();
+        let mut out = std::io::BufWriter::new(stdout.lock());
+        for entry in &entries {
+            serde_json::to_writer(&mut out, entry)?;
+            out.write_all(b"\n")?;
+        }
+        out.flush()?;
+        return Ok(());
+    }
 
     let renderer = Renderer::with_config(config);
-    println!("{}", renderer.render(&result_set));
+    if !options.find_duplicates && !options.summary && renderer.can_stream() {
+        return stream_scan(root, &options);
+    }
+
+    let result_set = if options.find_duplicates {
+        find_duplicates(root, &options)?
+    } else if options.summary {
+        scan_summary(root, &options)?
+    } else {
+        scan_files(root, &options)?
+    };
+
+    print!("{}", renderer.render(&result_set));
 
     Ok(())
 }
 
+/// Match semantics selected by the `find` flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FindMode {
+    /// Case-insensitive substring
+    #[default]
+    Substring,
+    /// Glob over the whole relative path (`--glob`)
+    Glob,
+    /// Scored subsequence match (`--fuzzy`)
+    Fuzzy,
+}
+
+/// How `find` matches its pattern against relative paths
+#[derive(Debug, Clone, Default)]
+pub enum PathMatcher {
+    /// No pattern: every path matches
+    #[default]
+    Any,
+    /// Case-insensitive substring (lowercased pattern)
+    Substring(String),
+    /// Case-insensitive glob over the whole relative path
+    Glob(GlobMatcher),
+    /// Case-insensitive subsequence (lowercased pattern characters)
+    Fuzzy(Vec<char>),
+}
+
+impl PathMatcher {
+    /// Build a matcher for `pattern` with the given semantics
+    ///
+    /// In globs `*` stays within one path component and `**` crosses directories.
+    pub fn new(pattern: Option<&str>, mode: FindMode) -> Result<Self, globset::Error> {
+        let Some(p) = pattern else {
+            return Ok(PathMatcher::Any);
+        };
+        Ok(match mode {
+            FindMode::Substring => PathMatcher::Substring(p.to_lowercase()),
+            FindMode::Glob => PathMatcher::Glob(
+                GlobBuilder::new(p)
+                    .case_insensitive(true)
+                    .literal_separator(true)
+                    .build()?
+                    .compile_matcher(),
+            ),
+            FindMode::Fuzzy => PathMatcher::Fuzzy(
+                p.to_lowercase()
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .collect(),
+            ),
+        })
+    }
+
+    pub fn is_match(&self, path: &str) -> bool {
+        self.score(path).is_some()
+    }
+
+    /// Relevance of a matching path (higher is better); `None` if it does not match
+    ///
+    /// Only fuzzy matching ranks paths; every other match scores 0.
+    pub fn score(&self, path: &str) -> Option<i64> {
+        match self {
+            PathMatcher::Any => Some(0),
+            PathMatcher::Substring(p) => path.to_lowercase().contains(p).then_some(0),
+            PathMatcher::Glob(glob) => glob.is_match(path).then_some(0),
+            PathMatcher::Fuzzy(p) => fuzzy_score(p, path),
+        }
+    }
+}
+
+/// Points for each matched character
+const FUZZY_MATCH: i64 = 16;
+/// Bonus when a character directly follows the previous match
+const FUZZY_CONSECUTIVE: i64 = 15;
+/// Bonus for matching at the start of a word (after a separator or a camelCase hump)
+const FUZZY_BOUNDARY: i64 = 10;
+/// Bonus for matching inside the file name rather than a directory
+const FUZZY_BASENAME: i64 = 8;
+/// Penalty per skipped character between two matches
+const FUZZY_GAP: i64 = 1;
+
+/// Score `path` as a fuzzy match for the lowercased `pattern`
+///
+/// Pattern characters must appear in order (case-insensitively); the best
+/// placement is chosen by dynamic programming over the match positions.
+fn fuzzy_score(pattern: &[char], path: &str) -> Option<i64> {
+    if pattern.is_empty() {
+        return Some(0);
+    }
+
+    let chars: Vec<char> = path.chars().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let basename_start = chars.iter().rposition(|&c| c == '/').map_or(0, |i| i + 1);
+
+    let bonus = |j: usize| -> i64 {
+        let boundary = j == 0
+            || matches!(chars[j - 1], '/' | '_' | '-' | '.' | ' ')
+            || (chars[j - 1].is_lowercase() && chars[j].is_uppercase());
+        let mut bonus = FUZZY_MATCH;
+        if boundary {
+            bonus += FUZZY_BOUNDARY;
+        }
+        if j >= basename_start {
+            bonus += FUZZY_BASENAME;
+        }
+        bonus
+    };
+
+    // best[j]: best score with the current pattern character matched at j
+    let mut best: Vec<Option<i64>> = lower
+        .iter()
+        .enumerate()
+        .map(|(j, &c)| (c == pattern[0]).then(|| bonus(j)))
+        .collect();
+
+    for &pc in &pattern[1..] {
+        let mut next = vec![None; chars.len()];
+        // Max of best[k] + FUZZY_GAP * k over k < j - 1, for a linear gap penalty
+        let mut gapped: Option<i64> = None;
+        for j in 1..chars.len() {
+            if j >= 2 {
+                if let Some(score) = best[j - 2] {
+                    let candidate = score + FUZZY_GAP * (j as i64 - 2);
+                    gapped = Some(gapped.map_or(candidate, |g| g.max(candidate)));
+                }
+            }
+            if lower[j] != pc {
+                continue;
+            }
+            let after_gap = gapped.map(|g| g - FUZZY_GAP * (j as i64 - 1));
+            let consecutive = best[j - 1].map(|s| s + FUZZY_CONSECUTIVE);
+            next[j] = after_gap.max(consecutive).map(|s| s + bonus(j));
+        }
+        best = next;
+    }
+
+    best.into_iter().flatten().max()
+}
+
 /// Run the find command (scan with pattern filtering)
 pub fn run_find(
     root: &Path,
     pattern: Option<&str>,
     scope: Option<&Path>,
+    mode: FindMode,
+    exists: bool,
     config: RenderConfig,
 ) -> Result<()> {
-    let result_set = find_files(root, pattern, scope)?;
-
     let renderer = Renderer::with_config(config);
-    println!("{}", renderer.render(&result_set));
+    let matcher = match PathMatcher::new(pattern, mode) {
+        Ok(matcher) => matcher,
+        Err(e) => {
+            let mut result_set = ResultSet::new();
+            result_set.push(ResultItem::error(MiseError::new(
+                "INVALID_GLOB",
+                format!("Invalid glob pattern: {}", e),
+            )));
+            print!("{}", renderer.render(&result_set));
+            return Ok(());
+        }
+    };
+
+    if exists {
+        emit_exists(find_exists(root, &matcher, scope)?, config);
+    }
+
+    let result_set = find_files(root, &matcher, scope)?;
+
+    print!("{}", renderer.render(&result_set));
 
     Ok(())
 }
 
+/// Print a single `{exists: bool}` item and exit 0 (found) or 1 (not found)
+pub fn emit_exists(exists: bool, config: RenderConfig) -> ! {
+    let mut item = ResultItem::file("");
+    item.path = None;
+    item.kind = Kind::Flow;
+    item.confidence = Confidence::High;
+    item.data = Some(serde_json::json!({ "exists": exists }));
+
+    let mut result_set = ResultSet::new();
+    result_set.push(item);
+    print!("{}", Renderer::with_config(config).render(&result_set));
+
+    std::process::exit(if exists { 0 } else { 1 });
+}
+
+/// Check whether any file path matches the pattern, stopping at the first hit
+pub fn find_exists(root: &Path, matcher: &PathMatcher, scope: Option<&Path>) -> Result<bool> {
+    let options = ScanOptions {
+        scope: scope.map(|p| p.to_path_buf()),
+        file_type: Some("file".to_string()),
+        ignore: true,
+        ..Default::default()
+    };
+
+    let mut found = false;
+    walk_entries(root, &options, |_, relative, _| {
+        found = matcher.is_match(&relative);
+        if found {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    })?;
+
+    Ok(found)
+}
+
 /// Find files by pattern (for MCP and programmatic use)
-pub fn find_files(root: &Path, pattern: Option<&str>, scope: Option<&Path>) -> Result<ResultSet> {
+pub fn find_files(root: &Path, matcher: &PathMatcher, scope: Option<&Path>) -> Result<ResultSet> {
     let options = ScanOptions {
         scope: scope.map(|p| p.to_path_buf()),
         file_type: Some("file".to_string()),
@@ -163,14 +798,31 @@ pub fn find_files(root: &Path, pattern: Option<&str>, scope: Option<&Path>) -> R
     let mut result_set = scan_files(root, &options)?;
 
     // Filter by pattern if provided
-    if let Some(pattern) = pattern {
-        let pattern_lower = pattern.to_lowercase();
-        result_set.items.retain(|item| {
-            item.path
-                .as_ref()
-                .map(|p| p.to_lowercase().contains(&pattern_lower))
-                .unwrap_or(false)
+    result_set
+        .items
+        .retain(|item| item.path.as_deref().is_some_and(|p| matcher.is_match(p)));
+
+    // Fuzzy results are ranked best first, ties broken by shorter path
+    if let PathMatcher::Fuzzy(_) = matcher {
+        let mut scored: Vec<(i64, ResultItem)> = result_set
+            .items
+            .drain(..)
+            .map(|item| {
+                let score = item
+                    .path
+                    .as_deref()
+                    .and_then(|p| matcher.score(p))
+                    .unwrap_or(0);
+                (score, item.with_data(serde_json::json!({ "score": score })))
+            })
+            .collect();
+        scored.sort_by(|(sa, a), (sb, b)| {
+            let len = |i: &ResultItem| i.path.as_deref().map_or(0, str::len);
+            sb.cmp(sa)
+                .then(len(a).cmp(&len(b)))
+                .then(a.path.cmp(&b.path))
         });
+        result_set.items = scored.into_iter().map(|(_, item)| item).collect();
     }
 
     Ok(result_set)
@@ -180,6 +832,7 @@ pub fn find_files(root: &Path, pattern: Option<&str>, scope: Option<&Path>) -> R
 mod tests {
     use super::*;
     use std::fs::{self, File};
+    use std::time::SystemTime;
     use tempfile::tempdir;
 
     fn default_options() -> ScanOptions {
@@ -343,15 +996,44 @@ mod tests {
         assert_eq!(paths, sorted_paths);
     }
 
+    #[test]
+    fn test_default_jsonl_order_matches_json() {
+        use crate::core::render::{OutputFormat, RenderConfig};
+
+        let temp = tempdir().unwrap();
+        fs::create_dir(temp.path().join("a")).unwrap();
+        File::create(temp.path().join("a/x")).unwrap();
+        File::create(temp.path().join("a.txt")).unwrap();
+
+        // Without --stream, jsonl goes through the sorted ResultSet too
+        let config = RenderConfig::new(OutputFormat::Jsonl);
+        assert!(!Renderer::with_config(config).can_stream());
+
+        let result_set = scan_files(temp.path(), &file_options()).unwrap();
+        let paths = |items: Vec<ResultItem>| -> Vec<String> {
+            items.into_iter().filter_map(|i| i.path).collect()
+        };
+        let jsonl = Renderer::with_config(config).render(&result_set);
+        let from_jsonl = paths(
+            jsonl
+                .lines()
+                .map(|l| serde_json::from_str(l).unwrap())
+                .collect(),
+        );
+        let json = Renderer::new(OutputFormat::Json).render(&result_set);
+        let from_json = paths(serde_json::from_str(&json).unwrap());
+
+        assert_eq!(from_jsonl, ["a.txt", "a/x"]);
+        assert_eq!(from_jsonl, from_json);
+    }
+
     #[test]
     fn test_run_scan_command() {
         let temp = tempdir().unwrap();
         File::create(temp.path().join("test.txt")).unwrap();
 
-        let config = crate::core::render::RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config =
+            crate::core::render::RenderConfig::new(crate::core::render::OutputFormat::Json);
 
         let result = run_scan(temp.path(), file_options(), config);
         assert!(result.is_ok());
@@ -363,13 +1045,11 @@ mod tests {
         File::create(temp.path().join("test.txt")).unwrap();
         File::create(temp.path().join("other.rs")).unwrap();
 
-        let config = crate::core::render::RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config =
+            crate::core::render::RenderConfig::new(crate::core::render::OutputFormat::Json);
 
         // No pattern should return all files
-        let result = run_find(temp.path(), None, None, config);
+        let result = run_find(temp.path(), None, None, FindMode::Substring, false, config);
         assert!(result.is_ok());
     }
 
@@ -379,12 +1059,17 @@ mod tests {
         File::create(temp.path().join("test.txt")).unwrap();
         File::create(temp.path().join("other.rs")).unwrap();
 
-        let config = crate::core::render::RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
-
-        let result = run_find(temp.path(), Some(".txt"), None, config);
+        let config =
+            crate::core::render::RenderConfig::new(crate::core::render::OutputFormat::Json);
+
+        let result = run_find(
+            temp.path(),
+            Some(".txt"),
+            None,
+            FindMode::Substring,
+            false,
+            config,
+        );
         assert!(result.is_ok());
     }
 
@@ -394,16 +1079,110 @@ mod tests {
         File::create(temp.path().join("TEST.TXT")).unwrap();
         File::create(temp.path().join("other.rs")).unwrap();
 
-        let config = crate::core::render::RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config =
+            crate::core::render::RenderConfig::new(crate::core::render::OutputFormat::Json);
 
         // Pattern matching should be case-insensitive
-        let result = run_find(temp.path(), Some("test"), None, config);
+        let result = run_find(
+            temp.path(),
+            Some("test"),
+            None,
+            FindMode::Substring,
+            false,
+            config,
+        );
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_find_exists() {
+        let temp = tempdir().unwrap();
+        fs::create_dir(temp.path().join("src")).unwrap();
+        File::create(temp.path().join("src/Main.rs")).unwrap();
+
+        let matcher = |p: Option<&str>| PathMatcher::new(p, FindMode::Substring).unwrap();
+        assert!(find_exists(temp.path(), &matcher(Some("main")), None).unwrap());
+        assert!(find_exists(temp.path(), &matcher(None), None).unwrap());
+        assert!(!find_exists(temp.path(), &matcher(Some("missing")), None).unwrap());
+    }
+
+    #[test]
+    fn test_find_files_glob() {
+        let temp = tempdir().unwrap();
+        fs::create_dir_all(temp.path().join("src/backends")).unwrap();
+        File::create(temp.path().join("src/Main.rs")).unwrap();
+        File::create(temp.path().join("src/backends/scan.rs")).unwrap();
+        File::create(temp.path().join("src/backends/notes.md")).unwrap();
+        File::create(temp.path().join("build.rs")).unwrap();
+
+        let paths = |pattern: &str| -> Vec<String> {
+            let matcher = PathMatcher::new(Some(pattern), FindMode::Glob).unwrap();
+            find_files(temp.path(), &matcher, None)
+                .unwrap()
+                .items
+                .into_iter()
+                .filter_map(|i| i.path)
+                .collect()
+        };
+
+        assert_eq!(
+            paths("src/**/*.rs"),
+            ["src/Main.rs", "src/backends/scan.rs"]
+        );
+        // `*` does not cross directories; matching is case-insensitive
+        assert_eq!(paths("src/*.rs"), ["src/Main.rs"]);
+        assert_eq!(paths("SRC/MAIN.RS"), ["src/Main.rs"]);
+        // The glob must cover the whole path, unlike a substring
+        assert!(paths("*.rs").contains(&"build.rs".to_string()));
+        assert!(paths("backends").is_empty());
+
+        assert!(PathMatcher::new(Some("src/["), FindMode::Glob).is_err());
+        assert!(PathMatcher::new(Some("src/["), FindMode::Substring).is_ok());
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        let score = |pattern: &str, path: &str| {
+            let chars: Vec<char> = pattern.chars().collect();
+            fuzzy_score(&chars, path)
+        };
+
+        assert!(score("cli", "src/cli.rs") > score("cli", "src/core/client/list.rs"));
+        assert!(score("scrs", "src/scan.rs").is_some());
+        assert_eq!(score("xyz", "src/cli.rs"), None);
+        assert_eq!(score("sc", "cs"), None);
+        // Consecutive beats scattered; word starts beat mid-word hits
+        assert!(score("abc", "x/abc") > score("abc", "x/a_b_c"));
+        assert!(score("fb", "x/fooBar") > score("fb", "x/fxxbxx"));
+        // File name hits beat directory hits
+        assert!(score("deps", "deps/main.rs") < score("deps", "src/deps.rs"));
+    }
+
+    #[test]
+    fn test_find_files_fuzzy() {
+        let temp = tempdir().unwrap();
+        fs::create_dir_all(temp.path().join("src/core/client")).unwrap();
+        File::create(temp.path().join("src/core/client/list.rs")).unwrap();
+        File::create(temp.path().join("src/cli.rs")).unwrap();
+        File::create(temp.path().join("src/main.rs")).unwrap();
+
+        let matcher = PathMatcher::new(Some("cli"), FindMode::Fuzzy).unwrap();
+        let result = find_files(temp.path(), &matcher, None).unwrap();
+        let paths: Vec<_> = result
+            .items
+            .iter()
+            .filter_map(|i| i.path.as_deref())
+            .collect();
+        assert_eq!(paths, ["src/cli.rs", "src/core/client/list.rs"]);
+
+        let scores: Vec<i64> = result
+            .items
+            .iter()
+            .map(|i| i.data.as_ref().unwrap()["score"].as_i64().unwrap())
+            .collect();
+        assert!(scores[0] > scores[1]);
+    }
+
     #[test]
     fn test_scan_gitignore_respected() {
         let temp = tempdir().unwrap();
@@ -461,6 +1240,42 @@ mod tests {
         assert!(paths.iter().any(|p| p.contains("ignored.txt")));
     }
 
+    #[test]
+    fn test_scan_miseignore() {
+        let temp = tempdir().unwrap();
+        std::fs::create_dir_all(temp.path().join("docs/generated")).unwrap();
+        std::fs::write(temp.path().join(MISE_IGNORE_FILE), "generated/\n*.tmp\n").unwrap();
+        File::create(temp.path().join("docs/guide.md")).unwrap();
+        File::create(temp.path().join("docs/generated/api.md")).unwrap();
+        File::create(temp.path().join("notes.tmp")).unwrap();
+
+        let paths = |options: &ScanOptions| -> Vec<String> {
+            scan_files(temp.path(), options)
+                .unwrap()
+                .items
+                .into_iter()
+                .filter_map(|i| i.path)
+                .collect()
+        };
+
+        // Applies without a git repo, and from the root when scoped below it
+        assert_eq!(paths(&file_options()), ["docs/guide.md"]);
+        let scoped = ScanOptions {
+            scope: Some(temp.path().join("docs")),
+            ..file_options()
+        };
+        assert_eq!(paths(&scoped), ["docs/guide.md"]);
+
+        let no_ignore = ScanOptions {
+            ignore: false,
+            ..file_options()
+        };
+        assert_eq!(
+            paths(&no_ignore),
+            ["docs/generated/api.md", "docs/guide.md", "notes.tmp"]
+        );
+    }
+
     #[test]
     fn test_scan_deep_nesting() {
         let temp = tempdir().unwrap();
@@ -490,6 +1305,37 @@ mod tests {
             .unwrap_or(false)));
     }
 
+    #[test]
+    fn test_scan_minimal_entries() {
+        let temp = tempdir().unwrap();
+        File::create(temp.path().join("b.txt")).unwrap();
+        fs::create_dir(temp.path().join("a")).unwrap();
+
+        let options = ScanOptions {
+            ignore: true,
+            minimal: true,
+            ..Default::default()
+        };
+        let entries = scan_minimal(temp.path(), &options).unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                MinimalEntry {
+                    path: "a".to_string(),
+                    entry_type: "d"
+                },
+                MinimalEntry {
+                    path: "b.txt".to_string(),
+                    entry_type: "f"
+                },
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&entries[1]).unwrap(),
+            r#"{"p":"b.txt","t":"f"}"#
+        );
+    }
+
     // ==================== glob_match tests ====================
 
     #[test]
@@ -505,7 +1351,7 @@ mod tests {
     fn test_glob_match_directory_wildcard() {
         // Test dir/* pattern
         assert!(glob_match("vendor/*", "vendor/package"));
-        assert!(glob_match("vendor/*", "vendor/a/b/c"));
+        assert!(!glob_match("vendor/*", "vendor/a/b/c"));
         assert!(!glob_match("vendor/*", "src/vendor"));
     }
 
@@ -521,15 +1367,90 @@ mod tests {
     fn test_glob_match_exact() {
         // Test exact match
         assert!(glob_match("README.md", "README.md"));
-        assert!(glob_match("README.md", "docs/README.md")); // contains
+        assert!(glob_match("README.md", "docs/README.md")); // any depth
         assert!(!glob_match("README.md", "readme.md")); // case sensitive
     }
 
     #[test]
-    fn test_glob_match_contains() {
-        // Test simple contains pattern
-        assert!(glob_match("test", "src/test/main.rs"));
-        assert!(glob_match("test", "test.rs"));
+    fn test_glob_match_double_star() {
+        assert!(glob_match("src/**", "src/a/b.rs"));
+        assert!(glob_match("**/generated/**", "src/generated/x.rs"));
+        assert!(glob_match("**/generated/**", "generated/x.rs"));
+        assert!(!glob_match("**/generated/**", "src/degenerated/x.rs"));
+    }
+
+    #[test]
+    fn test_scan_with_hash() {
+        let temp = tempdir().unwrap();
+        fs::create_dir(temp.path().join("sub")).unwrap();
+        fs::write(temp.path().join("sub/a.txt"), "hello").unwrap();
+
+        let options = ScanOptions {
+            hash: Some(HashAlgorithm::Sha1),
+            ..Default::default()
+        };
+        let result = scan_files(temp.path(), &options).unwrap();
+        let dir = result
+            .items
+            .iter()
+            .find(|i| i.path.as_deref() == Some("sub"));
+        assert!(dir.unwrap().data.is_none());
+        let file = result
+            .items
+            .iter()
+            .find(|i| i.path.as_deref() == Some("sub/a.txt"))
+            .unwrap();
+        assert_eq!(
+            file.data.as_ref().unwrap()["hash"],
+            "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d"
+        );
+
+        // Without the flag nothing is hashed
+        let result = scan_files(temp.path(), &ScanOptions::default()).unwrap();
+        assert!(result.items.iter().all(|i| i.data.is_none()));
+    }
+
+    #[test]
+    fn test_glob_filter() {
+        let filter = GlobFilter::new(
+            vec!["src/**".to_string()],
+            vec!["**/generated/**".to_string()],
+        )
+        .unwrap();
+        assert!(filter.matches("src/main.rs"));
+        assert!(!filter.matches("src/generated/api.rs"));
+        assert!(!filter.matches("docs/intro.md"));
+        assert!(GlobFilter::default().matches("anything"));
+
+        // ripgrep semantics: no substring matches across components
+        let include = |glob: &str| GlobFilter::new(vec![glob.to_string()], vec![]).unwrap();
+        assert!(!include("src/**").matches("lib/src/x.rs"));
+        assert!(!include("src/*.rs").matches("a/src/b/c.rs.bak"));
+        assert!(!include("src/*.rs").matches("src/b/c.rs"));
+        assert!(!include("vendor/*").matches("vendor/a/b.rs"));
+        assert!(include("vendor/*").matches("vendor/a"));
+        assert!(include("*.rs").matches("src/deep/main.rs"));
+
+        // An excluded directory takes its contents with it
+        let exclude = GlobFilter::new(vec![], vec!["node_modules".to_string()]).unwrap();
+        assert!(!exclude.matches("web/node_modules/pkg/index.js"));
+        assert!(exclude.matches("web/node_modules_old.txt"));
+
+        assert!(GlobFilter::new(vec!["src/[".to_string()], vec![]).is_err());
+
+        let mut result_set = ResultSet::new();
+        result_set.push(ResultItem::file("src/main.rs"));
+        result_set.push(ResultItem::file("docs/intro.md"));
+        filter.retain(&mut result_set);
+        assert_eq!(result_set.len(), 1);
+    }
+
+    #[test]
+    fn test_glob_match_name() {
+        // A pattern without '/' names a file or directory at any depth
+        assert!(glob_match("test", "src/test"));
+        assert!(glob_match("test", "test"));
+        assert!(!glob_match("test", "test.rs"));
         assert!(!glob_match("test", "spec.rs"));
     }
 
@@ -545,7 +1466,7 @@ mod tests {
         let options = ScanOptions {
             file_type: Some("file".to_string()),
             ignore: true,
-            include: vec!["*.rs".to_string()],
+            filter: GlobFilter::new(vec!["*.rs".to_string()], vec![]).unwrap(),
             ..Default::default()
         };
         let result = scan_files(temp.path(), &options).unwrap();
@@ -569,7 +1490,7 @@ mod tests {
         let options = ScanOptions {
             file_type: Some("file".to_string()),
             ignore: true,
-            exclude: vec!["*_test.rs".to_string()],
+            filter: GlobFilter::new(vec![], vec!["*_test.rs".to_string()]).unwrap(),
             ..Default::default()
         };
         let result = scan_files(temp.path(), &options).unwrap();
@@ -593,8 +1514,8 @@ mod tests {
         let options = ScanOptions {
             file_type: Some("file".to_string()),
             ignore: true,
-            include: vec!["*.rs".to_string()],
-            exclude: vec!["*_test.rs".to_string()],
+            filter: GlobFilter::new(vec!["*.rs".to_string()], vec!["*_test.rs".to_string()])
+                .unwrap(),
             ..Default::default()
         };
         let result = scan_files(temp.path(), &options).unwrap();
@@ -615,7 +1536,7 @@ mod tests {
         let options = ScanOptions {
             file_type: Some("file".to_string()),
             ignore: true,
-            include: vec!["*.rs".to_string(), "*.py".to_string()],
+            filter: GlobFilter::new(vec!["*.rs".to_string(), "*.py".to_string()], vec![]).unwrap(),
             ..Default::default()
         };
         let result = scan_files(temp.path(), &options).unwrap();
@@ -623,4 +1544,205 @@ mod tests {
         // Should include both .rs and .py files
         assert_eq!(result.len(), 2);
     }
+
+    #[test]
+    fn test_scan_with_size_filters() {
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join("small.txt"), "a").unwrap();
+        fs::write(temp.path().join("large.txt"), "a".repeat(100)).unwrap();
+
+        let options = ScanOptions {
+            min_size: Some(10),
+            ..file_options()
+        };
+        let result = scan_files(temp.path(), &options).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result.items[0].path.as_deref(), Some("large.txt"));
+
+        let options = ScanOptions {
+            max_size: Some(10),
+            ..file_options()
+        };
+        let result = scan_files(temp.path(), &options).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result.items[0].path.as_deref(), Some("small.txt"));
+        assert_eq!(result.items[0].data.as_ref().unwrap()["size"], 1);
+
+        // Directories pass through the size filter untouched
+        fs::create_dir(temp.path().join("sub")).unwrap();
+        let options = ScanOptions {
+            min_size: Some(10),
+            ..Default::default()
+        };
+        let result = scan_files(temp.path(), &options).unwrap();
+        let paths: Vec<_> = result
+            .items
+            .iter()
+            .filter_map(|i| i.path.as_deref())
+            .collect();
+        assert_eq!(paths, vec!["large.txt", "sub"]);
+        assert!(result.items[1].data.is_none());
+    }
+
+    #[test]
+    fn test_scan_with_mtime_filters() {
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join("new.txt"), "a").unwrap();
+        let old = File::create(temp.path().join("old.txt")).unwrap();
+        old.set_modified(SystemTime::now() - Duration::from_secs(10 * 86400))
+            .unwrap();
+        drop(old);
+
+        let options = ScanOptions {
+            modified_within: Some(Duration::from_secs(7 * 86400)),
+            ..file_options()
+        };
+        let result = scan_files(temp.path(), &options).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result.items[0].path.as_deref(), Some("new.txt"));
+        let mtime = result.items[0].data.as_ref().unwrap()["mtime"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(mtime.ends_with('Z'), "{}", mtime);
+
+        let options = ScanOptions {
+            modified_before: Some(Duration::from_secs(7 * 86400)),
+            ..file_options()
+        };
+        let result = scan_files(temp.path(), &options).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result.items[0].path.as_deref(), Some("old.txt"));
+        // Durations past the i64 ms range clamp instead of overflowing
+        let options = ScanOptions {
+            modified_within: Some(Duration::MAX),
+            ..file_options()
+        };
+        assert_eq!(scan_files(temp.path(), &options).unwrap().len(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_follow_symlinks_reports_loops() {
+        let temp = tempdir().unwrap();
+        fs::create_dir_all(temp.path().join("a/b")).unwrap();
+        fs::write(temp.path().join("a/b/file.txt"), "x").unwrap();
+        std::os::unix::fs::symlink(temp.path().join("a"), temp.path().join("a/b/up")).unwrap();
+        fs::create_dir(temp.path().join("real")).unwrap();
+        fs::write(temp.path().join("real/inner.txt"), "x").unwrap();
+        std::os::unix::fs::symlink(temp.path().join("real"), temp.path().join("link")).unwrap();
+
+        // Not following: links are listed but never descended into
+        let result = scan_files(temp.path(), &file_options()).unwrap();
+        let paths: Vec<_> = result
+            .items
+            .iter()
+            .filter_map(|i| i.path.as_deref())
+            .collect();
+        assert!(!paths.contains(&"link/inner.txt"));
+        assert!(result.items.iter().all(|i| i.kind != Kind::Error));
+
+        let options = ScanOptions {
+            follow_links: true,
+            ..file_options()
+        };
+        let result = scan_files(temp.path(), &options).unwrap();
+        let paths: Vec<_> = result
+            .items
+            .iter()
+            .filter_map(|i| i.path.as_deref())
+            .collect();
+        assert!(paths.contains(&"link/inner.txt"));
+
+        let loops: Vec<_> = result
+            .items
+            .iter()
+            .filter(|i| i.kind == Kind::Error)
+            .collect();
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].errors[0].code, "SYMLINK_LOOP");
+        assert_eq!(loops[0].path.as_deref(), Some("a/b/up"));
+        assert_eq!(loops[0].data.as_ref().unwrap()["ancestor"], "a");
+    }
+
+    #[test]
+    fn test_scan_summary() {
+        let temp = tempdir().unwrap();
+        fs::create_dir_all(temp.path().join("src/nested")).unwrap();
+        fs::create_dir(temp.path().join("docs")).unwrap();
+        fs::write(temp.path().join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(temp.path().join("src/nested/lib.rs"), "pub fn f() {}").unwrap();
+        fs::write(temp.path().join("docs/guide.md"), "# Guide").unwrap();
+        fs::write(temp.path().join("Makefile"), "all:").unwrap();
+
+        let result = scan_summary(temp.path(), &file_options()).unwrap();
+        let rows: Vec<(&str, &str, u64)> = result
+            .items
+            .iter()
+            .map(|i| {
+                let data = i.data.as_ref().unwrap();
+                (
+                    data["group"].as_str().unwrap(),
+                    i.path.as_deref().unwrap(),
+                    data["files"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("total", ".", 4),
+                ("extension", "*.rs", 2),
+                ("extension", "*.md", 1),
+                ("extension", "(no extension)", 1),
+                ("directory", "src/", 2),
+                ("directory", "docs/", 1),
+                ("directory", ".", 1),
+            ]
+        );
+        assert_eq!(
+            result.items[0].data.as_ref().unwrap()["bytes"],
+            12 + 13 + 7 + 4
+        );
+
+        // Directories are top-level within the scope
+        let options = ScanOptions {
+            scope: Some(temp.path().join("src")),
+            ..file_options()
+        };
+        let result = scan_summary(temp.path(), &options).unwrap();
+        let dirs: Vec<_> = result
+            .items
+            .iter()
+            .filter(|i| i.data.as_ref().unwrap()["group"] == "directory")
+            .filter_map(|i| i.path.as_deref())
+            .collect();
+        assert_eq!(dirs, ["src/nested/", "."]);
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let temp = tempdir().unwrap();
+        fs::create_dir(temp.path().join("copy")).unwrap();
+        fs::write(temp.path().join("a.txt"), "same content").unwrap();
+        fs::write(temp.path().join("copy/a.txt"), "same content").unwrap();
+        fs::write(temp.path().join("b.txt"), "diff content").unwrap();
+        fs::write(temp.path().join("c.rs"), "same content").unwrap();
+
+        let result = find_duplicates(temp.path(), &file_options()).unwrap();
+        assert_eq!(result.len(), 1);
+        let data = result.items[0].data.as_ref().unwrap();
+        assert_eq!(data["count"], 3);
+        assert_eq!(
+            data["paths"],
+            serde_json::json!(["a.txt", "c.rs", "copy/a.txt"])
+        );
+
+        // Filters narrow the candidate set
+        let options = ScanOptions {
+            filter: GlobFilter::new(vec!["*.rs".to_string()], vec![]).unwrap(),
+            ..file_options()
+        };
+        assert!(find_duplicates(temp.path(), &options).unwrap().is_empty());
+    }
 }
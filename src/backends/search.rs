@@ -0,0 +1,215 @@
+//! Combined search
+//!
+//! Runs ripgrep (textual) and ast-grep (structural) concurrently and merges
+//! their matches into a single ResultSet.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+use std::thread;
+
+use crate::backends::ast_grep::{run_ast_grep, AstOptions};
+use crate::backends::rg::{run_rg, MatchOptions};
+use crate::backends::scan::GlobFilter;
+use crate::core::model::{Range, ResultItem, ResultSet, SourceMode};
+use crate::core::render::{RenderConfig, Renderer};
+
+/// Options for the search command
+#[derive(Debug, Default)]
+pub struct SearchOptions {
+    /// ast-grep pattern (defaults to the textual pattern)
+    pub ast_pattern: Option<String>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub lang: Option<String>,
+    pub ignore_case: bool,
+    /// Thread hint forwarded to both backends (0 = their defaults)
+    pub threads: usize,
+}
+
+/// Run ripgrep and ast-grep in parallel and merge their results
+pub fn run_combined_search(
+    root: &Path,
+    pattern: &str,
+    scopes: &[impl AsRef<Path> + Sync],
+    options: &SearchOptions,
+) -> Result<ResultSet> {
+    let match_options = MatchOptions {
+        include: options.include.clone(),
+        exclude: options.exclude.clone(),
+        ignore_case: options.ignore_case,
+        threads: options.threads,
+        ..Default::default()
+    };
+    let ast_options = AstOptions {
+        filter: GlobFilter::new(options.include.clone(), options.exclude.clone())?,
+        lang: options.lang.clone(),
+        threads: options.threads,
+        ..Default::default()
+    };
+    let ast_pattern = options.ast_pattern.as_deref().unwrap_or(pattern);
+
+    let (rg_results, ast_results) = thread::scope(|s| {
+        let rg = s.spawn(|| run_rg(root, pattern, scopes, &match_options));
+        let ast = run_ast_grep(root, ast_pattern, scopes, &ast_options);
+        let rg = rg
+            .join()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("ripgrep search thread panicked")));
+        (rg, ast)
+    });
+
+    Ok(merge_results(rg_results?, ast_results?))
+}
+
+/// Merge two result sets, deduplicating matches on the same path and start line
+///
+/// Hits found by both backends keep the textual excerpt and are tagged `mixed`.
+fn merge_results(rg_results: ResultSet, ast_results: ResultSet) -> ResultSet {
+    let mut merged = ResultSet::new();
+    let mut index: HashMap<(String, u32), usize> = HashMap::new();
+
+    for item in rg_results.items.into_iter().chain(ast_results.items) {
+        let key = match (&item.path, &item.range) {
+            (Some(path), Some(Range::Line(r))) => Some((path.clone(), r.start)),
+            _ => None,
+        };
+
+        match key.as_ref().and_then(|k| index.get(k)) {
+            Some(&existing) => {
+                let kept: &mut ResultItem = &mut merged.items[existing];
+                if kept.source_mode != item.source_mode {
+                    kept.source_mode = SourceMode::Mixed;
+                }
+            }
+            None => {
+                if let Some(key) = key {
+                    index.insert(key, merged.items.len());
+                }
+                merged.push(item);
+            }
+        }
+    }
+
+    merged.sort();
+    merged
+}
+
+/// Run the search command
+pub fn run_search(
+    root: &Path,
+    pattern: &str,
+    scopes: &[impl AsRef<Path> + Sync],
+    options: SearchOptions,
+    config: RenderConfig,
+) -> Result<()> {
+    let result_set = run_combined_search(root, pattern, scopes, &options)?;
+
+    let renderer = Renderer::with_config(config);
+    print!("{}", renderer.render(&result_set));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::rg::is_rg_available;
+
+    fn hit(path: &str, line: u32, mode: SourceMode) -> ResultItem {
+        let mut item = ResultItem::match_result(path, Range::lines(line, line), "hit");
+        item.source_mode = mode;
+        item
+    }
+
+    #[test]
+    fn test_merge_results_dedupes_by_path_and_line() {
+        let mut rg = ResultSet::new();
+        rg.push(hit("src/a.rs", 3, SourceMode::Rg));
+        rg.push(hit("src/b.rs", 1, SourceMode::Rg));
+
+        let mut ast = ResultSet::new();
+        ast.push(hit("src/a.rs", 3, SourceMode::AstGrep));
+        ast.push(hit("src/a.rs", 10, SourceMode::AstGrep));
+
+        let merged = merge_results(rg, ast);
+        assert_eq!(merged.len(), 3);
+
+        let modes: Vec<_> = merged.items.iter().map(|i| i.source_mode).collect();
+        assert_eq!(
+            modes,
+            vec![SourceMode::Mixed, SourceMode::AstGrep, SourceMode::Rg]
+        );
+    }
+
+    #[test]
+    fn test_merge_results_keeps_errors() {
+        let mut rg = ResultSet::new();
+        rg.push(ResultItem::error(crate::core::model::MiseError::new(
+            "RG_NOT_FOUND",
+            "missing",
+        )));
+        let mut ast = ResultSet::new();
+        ast.push(ResultItem::error(crate::core::model::MiseError::new(
+            "AST_GREP_NOT_FOUND",
+            "missing",
+        )));
+
+        let merged = merge_results(rg, ast);
+        assert_eq!(merged.len(), 2);
+    }
+
+    #[test]
+    fn test_run_combined_search() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp.path().join("test.rs"),
+            "fn main() {}\nfn helper() { main(); }\n",
+        )
+        .unwrap();
+
+        let scopes: Vec<&Path> = vec![];
+        let options = SearchOptions::default();
+        let result = run_combined_search(temp.path(), "main", &scopes, &options).unwrap();
+
+        // Reference: each backend on its own
+        let rg = run_rg(temp.path(), "main", &scopes, &MatchOptions::default()).unwrap();
+        let ast = run_ast_grep(temp.path(), "main", &scopes, &AstOptions::default()).unwrap();
+        let key = |item: &ResultItem| match (&item.path, &item.range) {
+            (Some(path), Some(Range::Line(r))) => Some((path.clone(), r.start)),
+            _ => None,
+        };
+        let modes_of = |set: &ResultSet| -> HashMap<(String, u32), SourceMode> {
+            set.items
+                .iter()
+                .filter_map(|i| key(i).map(|k| (k, i.source_mode)))
+                .collect()
+        };
+        let (rg_modes, ast_modes) = (modes_of(&rg), modes_of(&ast));
+
+        // One item per path and line, tagged by the backends that found it
+        let merged = modes_of(&result);
+        let keyed = result.items.iter().filter(|i| key(i).is_some()).count();
+        assert_eq!(keyed, merged.len(), "duplicate hits: {:?}", result.items);
+        for (k, mode) in &merged {
+            let expected = match (rg_modes.get(k), ast_modes.get(k)) {
+                (Some(_), Some(_)) => SourceMode::Mixed,
+                (Some(&m), None) | (None, Some(&m)) => m,
+                (None, None) => panic!("hit {:?} from neither backend", k),
+            };
+            assert_eq!(*mode, expected, "{:?}", k);
+        }
+        let mut expected_keys: Vec<_> = rg_modes.keys().chain(ast_modes.keys()).collect();
+        expected_keys.sort();
+        expected_keys.dedup();
+        assert_eq!(merged.len(), expected_keys.len());
+
+        // Backend errors (e.g. a missing tool) pass through unmerged
+        let errors = |set: &ResultSet| set.items.iter().filter(|i| key(i).is_none()).count();
+        assert_eq!(result.len() - keyed, errors(&rg) + errors(&ast));
+
+        if is_rg_available() {
+            let lines: Vec<u32> = result.items.iter().filter_map(key).map(|k| k.1).collect();
+            assert_eq!(lines, vec![1, 2]);
+        }
+    }
+}
@@ -64,7 +64,7 @@ pub fn run_watch(root: &Path, opts: WatchOptions, config: RenderConfig) -> Resul
             "watchexec is not installed. Install: cargo install watchexec-cli / brew install watchexec",
         )));
         let renderer = Renderer::with_config(config);
-        println!("{}", renderer.render(&result_set));
+        print!("{}", renderer.render(&result_set));
         bail!("watchexec is not installed");
     }
 
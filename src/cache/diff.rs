@@ -0,0 +1,189 @@
+//! Compare two files.jsonl snapshots
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::cache::store::read_cache_jsonl;
+use crate::core::model::{Kind, ResultItem, ResultSet};
+use crate::core::render::{RenderConfig, Renderer};
+
+/// Load a files.jsonl snapshot keyed by path (directory entries are skipped)
+pub fn read_snapshot(path: &Path) -> Result<BTreeMap<String, ResultItem>> {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let name = path.file_name().unwrap_or(path.as_os_str());
+    let items = read_cache_jsonl(dir, &name.to_string_lossy())?;
+
+    Ok(items
+        .into_iter()
+        .filter(|item| item.kind == Kind::File && item.meta.size.is_some())
+        .filter_map(|item| Some((item.path.clone()?, item)))
+        .collect())
+}
+
+/// Content hash of a record, from `meta.hash` or the `data.hash` of `scan --hash`
+fn record_hash(item: &ResultItem) -> Option<&str> {
+    item.meta.hash.as_deref().or_else(|| {
+        item.data
+            .as_ref()
+            .and_then(|d| d.get("hash"))
+            .and_then(|h| h.as_str())
+    })
+}
+
+/// What distinguishes two records of the same path, if anything
+///
+/// Hashes win when both records have one; otherwise mtime, then size.
+fn modified_by(old: &ResultItem, new: &ResultItem) -> Option<&'static str> {
+    if let (Some(a), Some(b)) = (record_hash(old), record_hash(new)) {
+        return (a != b).then_some("hash");
+    }
+    if old.meta.mtime_ms != new.meta.mtime_ms {
+        Some("mtime")
+    } else if old.meta.size != new.meta.size {
+        Some("size")
+    } else {
+        None
+    }
+}
+
+fn change_item(
+    path: &str,
+    change: &str,
+    old: Option<&ResultItem>,
+    new: Option<&ResultItem>,
+) -> ResultItem {
+    let stamp = |item: Option<&ResultItem>| {
+        item.map(|i| serde_json::json!({ "size": i.meta.size, "mtime_ms": i.meta.mtime_ms }))
+    };
+    let mut data = serde_json::json!({ "change": change });
+    if let Some(old) = stamp(old) {
+        data["old"] = old;
+    }
+    if let Some(new) = stamp(new) {
+        data["new"] = new;
+    }
+    ResultItem::file(path).with_data(data)
+}
+
+/// Added, removed and modified files between two snapshots, sorted by path
+///
+/// Each item carries `data.change` (`added`, `removed` or `modified`), the
+/// `old`/`new` size and mtime, and for modified files `data.by` (`hash`,
+/// `mtime` or `size`). Unchanged files are omitted.
+pub fn diff_snapshots(
+    old: &BTreeMap<String, ResultItem>,
+    new: &BTreeMap<String, ResultItem>,
+) -> ResultSet {
+    let mut result_set = ResultSet::new();
+
+    for (path, old_item) in old {
+        match new.get(path) {
+            None => result_set.push(change_item(path, "removed", Some(old_item), None)),
+            Some(new_item) => {
+                if let Some(by) = modified_by(old_item, new_item) {
+                    let mut item = change_item(path, "modified", Some(old_item), Some(new_item));
+                    if let Some(data) = item.data.as_mut() {
+                        data["by"] = serde_json::json!(by);
+                    }
+                    result_set.push(item);
+                }
+            }
+        }
+    }
+    for (path, new_item) in new {
+        if !old.contains_key(path) {
+            result_set.push(change_item(path, "added", None, Some(new_item)));
+        }
+    }
+
+    result_set.sort();
+    result_set
+}
+
+/// Run the cache diff command
+pub fn run_diff(old: &Path, new: &Path, config: RenderConfig) -> Result<()> {
+    let result_set = diff_snapshots(&read_snapshot(old)?, &read_snapshot(new)?);
+
+    let renderer = Renderer::with_config(config);
+    print!("{}", renderer.render(&result_set));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::model::Meta;
+
+    fn record(path: &str, mtime_ms: i64, size: u64) -> ResultItem {
+        ResultItem::file(path).with_meta(Meta {
+            mtime_ms: Some(mtime_ms),
+            size: Some(size),
+            ..Default::default()
+        })
+    }
+
+    fn snapshot(items: Vec<ResultItem>) -> BTreeMap<String, ResultItem> {
+        items
+            .into_iter()
+            .map(|i| (i.path.clone().unwrap(), i))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_snapshots() {
+        let old = snapshot(vec![
+            record("gone.rs", 1, 10),
+            record("same.rs", 1, 10),
+            record("touched.rs", 1, 10),
+            record("hashed.rs", 1, 10).with_data(serde_json::json!({ "hash": "aa" })),
+        ]);
+        let new = snapshot(vec![
+            record("added.rs", 2, 5),
+            record("same.rs", 1, 10),
+            record("touched.rs", 2, 10),
+            // Same hash despite a new mtime: not modified
+            record("hashed.rs", 3, 10).with_data(serde_json::json!({ "hash": "aa" })),
+        ]);
+
+        let result = diff_snapshots(&old, &new);
+        let changes: Vec<_> = result
+            .items
+            .iter()
+            .map(|i| {
+                let data = i.data.as_ref().unwrap();
+                (i.path.as_deref().unwrap(), data["change"].as_str().unwrap())
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("added.rs", "added"),
+                ("gone.rs", "removed"),
+                ("touched.rs", "modified")
+            ]
+        );
+        let touched = result.items[2].data.as_ref().unwrap();
+        assert_eq!(touched["by"], "mtime");
+        assert_eq!(touched["old"]["mtime_ms"], 1);
+        assert_eq!(touched["new"]["mtime_ms"], 2);
+    }
+
+    #[test]
+    fn test_read_snapshot() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("old.jsonl");
+        std::fs::write(
+            &path,
+            r#"{"kind":"file","path":"src","confidence":"high","source_mode":"scan","meta":{"mtime_ms":1,"truncated":false}}
+{"kind":"file","path":"src/a.rs","confidence":"high","source_mode":"scan","meta":{"mtime_ms":1,"size":3,"truncated":false}}
+"#,
+        )
+        .unwrap();
+
+        let snapshot = read_snapshot(&path).unwrap();
+        assert_eq!(snapshot.keys().collect::<Vec<_>>(), ["src/a.rs"]);
+        assert!(read_snapshot(&temp.path().join("missing.jsonl")).is_err());
+    }
+}
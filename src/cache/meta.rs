@@ -1,6 +1,14 @@
 //! Cache metadata management
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Modification time and size of a file when anchors.jsonl was built
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub mtime_ms: i64,
+    pub size: u64,
+}
 
 /// Cache metadata stored in .mise/meta.json
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +24,18 @@ pub struct CacheMeta {
 
     /// Timestamp when cache was generated (ms since epoch)
     pub generated_at: i64,
+
+    /// Timestamp when files.jsonl was last rebuilt (ms since epoch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files_generated_at: Option<i64>,
+
+    /// Timestamp when anchors.jsonl was last rebuilt (ms since epoch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchors_generated_at: Option<i64>,
+
+    /// Per-file stamps anchors.jsonl was built from, diffed by incremental rebuilds
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub file_stamps: BTreeMap<String, FileStamp>,
 }
 
 impl CacheMeta {
@@ -25,6 +45,9 @@ impl CacheMeta {
             root: root.to_string(),
             policy_hash: policy_hash.to_string(),
             generated_at: crate::core::util::now_ms(),
+            files_generated_at: None,
+            anchors_generated_at: None,
+            file_stamps: BTreeMap::new(),
         }
     }
 }
@@ -5,7 +5,9 @@
 //! - Cache metadata management
 //! - Rebuild functionality
 //! - Smart cache reader with fallback
+//! - Snapshot comparison (cache diff)
 
+pub mod diff;
 pub mod meta;
 pub mod reader;
 pub mod store;
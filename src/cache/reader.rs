@@ -5,29 +5,134 @@
 
 use anyhow::Result;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use crate::anchors::api::list_anchors;
 use crate::anchors::parse::{parse_file, Anchor};
 use crate::backends::scan::{scan_files, ScanOptions};
-use crate::cache::store::{is_cache_valid, read_cache_jsonl, ANCHORS_CACHE, FILES_CACHE};
-use crate::core::model::ResultSet;
+use crate::cache::store::{
+    check_cache, read_cache_jsonl, CacheInvalid, ANCHORS_CACHE, FILES_CACHE,
+};
+use crate::core::model::{ResultItem, ResultSet};
 use crate::core::paths::cache_dir;
+use crate::core::policy::is_denied;
+use crate::core::util::get_mtime_ms;
+
+/// Whether cache decisions are logged to stderr (set once from `--verbose`)
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable logging of cache decisions
+pub fn set_verbose(enabled: bool) {
+    VERBOSE.store(enabled, Ordering::Relaxed);
+}
+
+/// Where a cached lookup got its data from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CacheSource {
+    /// Read from a cache that no listed file has changed since
+    Fresh,
+    /// Read from the cache, although some files changed after it was built
+    CacheHit,
+    /// A cache exists but is unusable, so the data was rebuilt by a live scan
+    CacheMissRebuilt,
+    /// No cache exists; the data came from a live scan
+    LiveScan,
+}
+
+impl CacheSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            CacheSource::Fresh => "fresh",
+            CacheSource::CacheHit => "cache-hit",
+            CacheSource::CacheMissRebuilt => "cache-miss-rebuilt",
+            CacheSource::LiveScan => "live-scan",
+        }
+    }
+}
+
+/// Log which source a lookup used and why, under `--verbose`
+fn log_source(artifact: &str, source: CacheSource, reason: &str) {
+    if VERBOSE.load(Ordering::Relaxed) {
+        eprintln!("cache: {} via {} ({})", artifact, source.as_str(), reason);
+    }
+}
+
+/// Read a cache artifact, or explain why it cannot be used
+///
+/// Under `--verbose` the hit is logged as `fresh` or `cache-hit`, depending on
+/// whether any listed file was modified after the artifact was generated; that
+/// check stats every file, so it is skipped otherwise.
+fn read_artifact(
+    root: &Path,
+    filename: &str,
+    artifact: &str,
+) -> std::result::Result<Vec<ResultItem>, (CacheSource, String)> {
+    let meta = check_cache(root).map_err(|invalid| match invalid {
+        CacheInvalid::Missing => (CacheSource::LiveScan, "no .mise directory".to_string()),
+        CacheInvalid::Unusable(reason) => (CacheSource::CacheMissRebuilt, reason),
+    })?;
+
+    let items = read_cache_jsonl(&cache_dir(root), filename)
+        .map_err(|e| (CacheSource::CacheMissRebuilt, format!("{:#}", e)))?;
+
+    if VERBOSE.load(Ordering::Relaxed) {
+        let generated_at = if filename == FILES_CACHE {
+            meta.files_generated_at
+        } else {
+            meta.anchors_generated_at
+        }
+        .unwrap_or(meta.generated_at);
+        let (source, reason) = staleness(root, filename, generated_at, &items);
+        log_source(artifact, source, &reason);
+    }
+    Ok(items)
+}
+
+/// Whether any listed file was modified after the artifact was generated
+fn staleness(
+    root: &Path,
+    filename: &str,
+    generated_at: i64,
+    items: &[ResultItem],
+) -> (CacheSource, String) {
+    let newer = items
+        .iter()
+        .filter_map(|i| i.path.as_deref())
+        .find(|path| get_mtime_ms(&root.join(path)).is_ok_and(|mtime| mtime > generated_at));
+
+    match newer {
+        Some(path) => (
+            CacheSource::CacheHit,
+            format!("{} older than {}", filename, path),
+        ),
+        None => (
+            CacheSource::Fresh,
+            format!("{} newer than every listed file", filename),
+        ),
+    }
+}
 
 /// Get files list, preferring cache if valid
 pub fn get_files_cached(root: &Path) -> Result<ResultSet> {
     // Try cache first
-    if is_cache_valid(root) {
-        let cache = cache_dir(root);
-        if let Ok(items) = read_cache_jsonl(&cache, FILES_CACHE) {
+    let fallback = match read_artifact(root, FILES_CACHE, "file list") {
+        Ok(items) => {
             let mut result_set = ResultSet::new();
+            // The cache may predate the current --deny policy
             for item in items {
+                if item.path.as_deref().is_some_and(is_denied) {
+                    continue;
+                }
                 result_set.push(item);
             }
             return Ok(result_set);
         }
-    }
+        Err(fallback) => fallback,
+    };
 
     // Fall back to live scan
+    let (source, reason) = fallback;
+    log_source("file list", source, &reason);
     let options = ScanOptions {
         file_type: Some("file".to_string()),
         ignore: true,
@@ -40,19 +145,21 @@ pub fn get_files_cached(root: &Path) -> Result<ResultSet> {
 #[allow(dead_code)]
 pub fn get_anchors_cached(root: &Path) -> Result<ResultSet> {
     // Try cache first
-    if is_cache_valid(root) {
-        let cache = cache_dir(root);
-        if let Ok(items) = read_cache_jsonl(&cache, ANCHORS_CACHE) {
+    let fallback = match read_artifact(root, ANCHORS_CACHE, "anchor list") {
+        Ok(items) => {
             let mut result_set = ResultSet::new();
             for item in items {
                 result_set.push(item);
             }
             return Ok(result_set);
         }
-    }
+        Err(fallback) => fallback,
+    };
 
     // Fall back to live list
-    list_anchors(root, None, false)
+    let (source, reason) = fallback;
+    log_source("anchor list", source, &reason);
+    list_anchors(root, None, None, false)
 }
 
 /// Get all anchors as parsed Anchor structs (more useful for flows)
@@ -143,8 +250,48 @@ mod tests {
 
         let result = get_files_cached(temp.path());
         assert!(result.is_ok());
-        let files = result.unwrap();
-        assert!(!files.items.is_empty());
+        assert!(!result.unwrap().items.is_empty());
+        let (source, _) = read_artifact(temp.path(), FILES_CACHE, "file list").unwrap_err();
+        assert_eq!(source, CacheSource::LiveScan);
+    }
+
+    #[test]
+    fn test_get_files_cached_source() {
+        use crate::cache::meta::CACHE_VERSION;
+        use crate::cache::store::{ensure_cache_dir, write_cache_jsonl, META_FILE};
+
+        let temp = tempdir().unwrap();
+        std::fs::write(temp.path().join("a.md"), "hello").unwrap();
+        let cache = ensure_cache_dir(temp.path()).unwrap();
+        write_cache_jsonl(&cache, FILES_CACHE, &[ResultItem::file("a.md")]).unwrap();
+        let write_meta = |version: &str, generated_at: i64| {
+            let meta = format!(
+                r#"{{"cache_version": "{}", "root": "", "policy_hash": "", "generated_at": {}}}"#,
+                version, generated_at
+            );
+            std::fs::write(cache.join(META_FILE), meta).unwrap();
+        };
+
+        // Unusable cache: the list comes from a live scan
+        write_meta("0.0.0", 0);
+        let (source, _) = read_artifact(temp.path(), FILES_CACHE, "file list").unwrap_err();
+        assert_eq!(source, CacheSource::CacheMissRebuilt);
+        let files = get_files_cached(temp.path()).unwrap();
+        assert!(files
+            .items
+            .iter()
+            .any(|i| i.path.as_deref() == Some("a.md")));
+
+        write_meta(CACHE_VERSION, 0);
+        let items = read_artifact(temp.path(), FILES_CACHE, "file list").unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(get_files_cached(temp.path()).unwrap().len(), 1);
+
+        // a.md was modified after a cache generated at 0, but not after i64::MAX
+        let (source, _) = staleness(temp.path(), FILES_CACHE, 0, &items);
+        assert_eq!(source, CacheSource::CacheHit);
+        let (source, _) = staleness(temp.path(), FILES_CACHE, i64::MAX, &items);
+        assert_eq!(source, CacheSource::Fresh);
     }
 
     #[test]
@@ -1,12 +1,13 @@
 //! Cache store - Read/write .mise/ cache files
 
 use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap};
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
 
-use crate::cache::meta::{CacheMeta, CACHE_VERSION};
-use crate::core::model::{ResultItem, ResultSet};
+use crate::cache::meta::{CacheMeta, FileStamp, CACHE_VERSION};
+use crate::core::model::{MiseError, ResultItem, ResultSet};
 use crate::core::paths::cache_dir;
 use crate::core::render::{RenderConfig, Renderer};
 use crate::core::util::hash_bytes;
@@ -75,66 +76,280 @@ pub fn read_meta(cache_path: &Path) -> Result<CacheMeta> {
     Ok(meta)
 }
 
-/// Check if cache is valid (exists and version matches)
-pub fn is_cache_valid(root: &Path) -> bool {
+/// Why the cache cannot be used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheInvalid {
+    /// There is no .mise directory
+    Missing,
+    /// The cache exists but its metadata is unreadable or from another version
+    Unusable(String),
+}
+
+/// Read the metadata of a valid cache, or explain why it is not valid
+pub fn check_cache(root: &Path) -> std::result::Result<CacheMeta, CacheInvalid> {
     let cache = cache_dir(root);
     if !cache.exists() {
-        return false;
+        return Err(CacheInvalid::Missing);
+    }
+
+    let meta = read_meta(&cache).map_err(|e| CacheInvalid::Unusable(format!("{:#}", e)))?;
+    if meta.cache_version != CACHE_VERSION {
+        return Err(CacheInvalid::Unusable(format!(
+            "cache version {} does not match {}",
+            meta.cache_version, CACHE_VERSION
+        )));
+    }
+    Ok(meta)
+}
+
+/// Which cache artifacts a rebuild regenerates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RebuildTarget {
+    /// Both files.jsonl and anchors.jsonl
+    #[default]
+    All,
+    /// Only files.jsonl
+    FilesOnly,
+    /// Only anchors.jsonl
+    AnchorsOnly,
+}
+
+impl RebuildTarget {
+    fn files(self) -> bool {
+        self != RebuildTarget::AnchorsOnly
     }
 
-    match read_meta(&cache) {
-        Ok(meta) => meta.cache_version == CACHE_VERSION,
-        Err(_) => false,
+    fn anchors(self) -> bool {
+        self != RebuildTarget::FilesOnly
     }
 }
 
-/// Rebuild the entire cache
-pub fn run_rebuild(root: &Path, config: RenderConfig) -> Result<()> {
-    let result_set = rebuild_to_result_set(root)?;
+/// Rebuild the cache (or the selected artifacts)
+pub fn run_rebuild(
+    root: &Path,
+    target: RebuildTarget,
+    incremental: bool,
+    config: RenderConfig,
+) -> Result<()> {
+    let result_set = rebuild_selected(root, target, incremental)?;
 
     let renderer = Renderer::with_config(config);
-    println!("{}", renderer.render(&result_set));
+    print!("{}", renderer.render(&result_set));
 
     Ok(())
 }
 
 /// Public API for MCP: rebuild cache and return ResultSet
+#[allow(dead_code)]
 pub fn rebuild_to_result_set(root: &Path) -> Result<ResultSet> {
+    rebuild_selected(root, RebuildTarget::All, false)
+}
+
+/// Rebuild the selected cache artifacts, leaving the others untouched
+///
+/// meta.json is always rewritten; per-artifact timestamps of artifacts that
+/// were not rebuilt are carried over from the previous meta.json.
+///
+/// With `incremental`, anchors of files whose mtime and size match the
+/// previous meta.json are carried forward from anchors.jsonl instead of being
+/// re-parsed. Without a usable previous cache this falls back to a full rebuild.
+pub fn rebuild_selected(
+    root: &Path,
+    target: RebuildTarget,
+    incremental: bool,
+) -> Result<ResultSet> {
     let cache_path = ensure_cache_dir(root)?;
+    let previous = read_meta(&cache_path).ok();
+    let mut result_set = ResultSet::new();
+
+    // Compute policy hash (simplified: just hash the version for now)
+    let policy_hash = hash_bytes(
+        CACHE_VERSION.as_bytes(),
+        crate::core::util::HashAlgorithm::Xxh3,
+    );
+    let root_str = root.to_string_lossy().to_string();
+    let mut meta = CacheMeta::new(&root_str, &policy_hash);
+    meta.files_generated_at = previous.as_ref().and_then(|m| m.files_generated_at);
+    meta.anchors_generated_at = previous.as_ref().and_then(|m| m.anchors_generated_at);
+    meta.file_stamps = previous
+        .as_ref()
+        .map(|m| m.file_stamps.clone())
+        .unwrap_or_default();
 
-    // Generate files.jsonl using scan
     let options = crate::backends::scan::ScanOptions {
         file_type: Some("file".to_string()),
         ignore: true,
         ..Default::default()
     };
     let files = crate::backends::scan::scan_files(root, &options)?;
-    write_cache_jsonl(&cache_path, FILES_CACHE, &files.items)?;
 
-    // Generate anchors.jsonl using anchor list
-    let anchors = crate::anchors::api::list_anchors(root, None, false)?;
-    write_cache_jsonl(&cache_path, ANCHORS_CACHE, &anchors.items)?;
+    // Generate files.jsonl using scan
+    if target.files() {
+        write_cache_jsonl(&cache_path, FILES_CACHE, &files.items)?;
+        meta.files_generated_at = Some(meta.generated_at);
 
-    // Compute policy hash (simplified: just hash the version for now)
-    let policy_hash = hash_bytes(
-        CACHE_VERSION.as_bytes(),
-        crate::core::util::HashAlgorithm::Xxh3,
-    );
+        let mut item = ResultItem::file(".mise/files.jsonl");
+        item.data = Some(serde_json::json!({ "rebuilt": true, "items": files.len() }));
+        result_set.push(item);
+    }
+
+    // Generate anchors.jsonl, re-parsing only changed files when incremental
+    if target.anchors() {
+        let stamps = file_stamps(&files);
+        let reusable = if incremental {
+            previous_anchors(&cache_path, previous.as_ref())
+        } else {
+            None
+        };
+
+        let mut anchors = ResultSet::new();
+        let mut reparsed = 0;
+        let mut reused = 0;
+        for (path, stamp) in &stamps {
+            let cached = reusable
+                .as_ref()
+                .filter(|(old_stamps, _)| old_stamps.get(path) == Some(stamp))
+                .map(|(_, by_path)| by_path.get(path).cloned().unwrap_or_default());
+            match cached {
+                Some(items) => {
+                    reused += 1;
+                    anchors.extend(items);
+                }
+                None => {
+                    reparsed += 1;
+                    anchors.extend(crate::anchors::api::file_anchor_items(root, path));
+                }
+            }
+        }
+        anchors.sort();
+
+        write_cache_jsonl(&cache_path, ANCHORS_CACHE, &anchors.items)?;
+        meta.anchors_generated_at = Some(meta.generated_at);
+        meta.file_stamps = stamps;
+
+        let mut item = ResultItem::file(".mise/anchors.jsonl");
+        let mut data = serde_json::json!({ "rebuilt": true, "items": anchors.len() });
+        if incremental {
+            data["incremental"] = reusable.is_some().into();
+            data["reparsed"] = reparsed.into();
+            data["reused"] = reused.into();
+        }
+        item.data = Some(data);
+        result_set.push(item);
+    }
 
     // Write metadata
-    let root_str = root.to_string_lossy().to_string();
-    let meta = CacheMeta::new(&root_str, &policy_hash);
     write_meta(&cache_path, &meta)?;
+    result_set.push(ResultItem::file(".mise/meta.json"));
+
+    Ok(result_set)
+}
 
-    // Output result
+/// Report cache freshness: artifact counts, age, and files modified since
+///
+/// The reference timestamp is the older of the two artifacts, so a cache
+/// refreshed with `--anchors-only` still reports files newer than files.jsonl.
+pub fn cache_status(root: &Path) -> Result<ResultSet> {
+    let cache_path = cache_dir(root);
     let mut result_set = ResultSet::new();
-    result_set.push(ResultItem::file(".mise/files.jsonl"));
-    result_set.push(ResultItem::file(".mise/anchors.jsonl"));
-    result_set.push(ResultItem::file(".mise/meta.json"));
+
+    let meta = match read_meta(&cache_path) {
+        Ok(meta) => meta,
+        Err(_) => {
+            result_set.push(ResultItem::error(MiseError::new(
+                "CACHE_NOT_FOUND",
+                "No cache metadata in .mise/meta.json. Run: mise rebuild",
+            )));
+            return Ok(result_set);
+        }
+    };
+
+    let count = |name: &str| read_cache_jsonl(&cache_path, name).ok().map(|i| i.len());
+    let cached_at = meta
+        .files_generated_at
+        .into_iter()
+        .chain(meta.anchors_generated_at)
+        .min()
+        .unwrap_or(meta.generated_at);
+
+    let options = crate::backends::scan::ScanOptions {
+        file_type: Some("file".to_string()),
+        ignore: true,
+        ..Default::default()
+    };
+    let files = crate::backends::scan::scan_files(root, &options)?;
+    let newer_files = files
+        .items
+        .iter()
+        .filter(|item| item.meta.mtime_ms.is_some_and(|mtime| mtime > cached_at))
+        .count();
+
+    let mut item = ResultItem::file(".mise/meta.json");
+    item.data = Some(serde_json::json!({
+        "cache_version": meta.cache_version,
+        "generated_at": meta.generated_at,
+        "files_generated_at": meta.files_generated_at,
+        "anchors_generated_at": meta.anchors_generated_at,
+        "age_ms": crate::core::util::now_ms() - cached_at,
+        "cached_files": count(FILES_CACHE),
+        "cached_anchors": count(ANCHORS_CACHE),
+        "workspace_files": files.len(),
+        "newer_files": newer_files,
+        "stale": newer_files > 0,
+    }));
+    result_set.push(item);
 
     Ok(result_set)
 }
 
+/// Run the cache status command
+pub fn run_status(root: &Path, config: RenderConfig) -> Result<()> {
+    let result_set = cache_status(root)?;
+
+    let renderer = Renderer::with_config(config);
+    print!("{}", renderer.render(&result_set));
+
+    Ok(())
+}
+
+/// Stamps of the scanned files, keyed by relative path
+fn file_stamps(files: &ResultSet) -> BTreeMap<String, FileStamp> {
+    files
+        .items
+        .iter()
+        .filter_map(|item| {
+            let stamp = FileStamp {
+                mtime_ms: item.meta.mtime_ms?,
+                size: item.meta.size?,
+            };
+            Some((item.path.clone()?, stamp))
+        })
+        .collect()
+}
+
+/// Previous stamps and cached anchor items by path, if the old cache is usable
+type ReusableAnchors = (
+    BTreeMap<String, FileStamp>,
+    HashMap<String, Vec<ResultItem>>,
+);
+
+fn previous_anchors(cache_path: &Path, previous: Option<&CacheMeta>) -> Option<ReusableAnchors> {
+    let previous = previous.filter(|m| {
+        m.cache_version == env!("CARGO_PKG_VERSION")
+            && m.anchors_generated_at.is_some()
+            && !m.file_stamps.is_empty()
+    })?;
+    let items = read_cache_jsonl(cache_path, ANCHORS_CACHE).ok()?;
+
+    let mut by_path: HashMap<String, Vec<ResultItem>> = HashMap::new();
+    for item in items {
+        if let Some(path) = item.path.clone() {
+            by_path.entry(path).or_default().push(item);
+        }
+    }
+    Some((previous.file_stamps.clone(), by_path))
+}
+
 /// Clear the cache
 #[allow(dead_code)]
 pub fn clear_cache(root: &Path) -> Result<()> {
@@ -223,13 +438,13 @@ mod tests {
     }
 
     #[test]
-    fn test_is_cache_valid_no_cache() {
+    fn test_check_cache_no_cache() {
         let temp = tempdir().unwrap();
-        assert!(!is_cache_valid(temp.path()));
+        assert_eq!(check_cache(temp.path()).unwrap_err(), CacheInvalid::Missing);
     }
 
     #[test]
-    fn test_is_cache_valid_with_valid_cache() {
+    fn test_check_cache_with_valid_cache() {
         let temp = tempdir().unwrap();
         let cache = ensure_cache_dir(temp.path()).unwrap();
 
@@ -242,11 +457,11 @@ mod tests {
         let file_path = cache.join(META_FILE);
         std::fs::write(&file_path, meta_content).unwrap();
 
-        assert!(is_cache_valid(temp.path()));
+        assert!(check_cache(temp.path()).is_ok());
     }
 
     #[test]
-    fn test_is_cache_valid_wrong_version() {
+    fn test_check_cache_wrong_version() {
         let temp = tempdir().unwrap();
         let cache = ensure_cache_dir(temp.path()).unwrap();
 
@@ -255,7 +470,10 @@ mod tests {
         let content = r#"{"cache_version": "0.0.0", "root": "/test", "policy_hash": "abc", "timestamp": "2024-01-01T00:00:00Z"}"#;
         std::fs::write(&file_path, content).unwrap();
 
-        assert!(!is_cache_valid(temp.path()));
+        assert!(matches!(
+            check_cache(temp.path()),
+            Err(CacheInvalid::Unusable(_))
+        ));
     }
 
     #[test]
@@ -302,12 +520,10 @@ mod tests {
         // Create some files to scan
         std::fs::write(temp.path().join("test.rs"), "fn main() {}").unwrap();
 
-        let config = crate::core::render::RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config =
+            crate::core::render::RenderConfig::new(crate::core::render::OutputFormat::Json);
 
-        let result = run_rebuild(temp.path(), config);
+        let result = run_rebuild(temp.path(), RebuildTarget::All, false, config);
         assert!(result.is_ok());
 
         // Check that cache files were created
@@ -317,6 +533,117 @@ mod tests {
         assert!(cache.join(META_FILE).exists());
     }
 
+    #[test]
+    fn test_rebuild_selected_anchors_only() {
+        let temp = tempdir().unwrap();
+        std::fs::write(temp.path().join("test.rs"), "fn main() {}").unwrap();
+
+        rebuild_selected(temp.path(), RebuildTarget::All, false).unwrap();
+        let cache = cache_dir(temp.path());
+        let files_before = std::fs::read_to_string(cache.join(FILES_CACHE)).unwrap();
+        let meta_before = read_meta(&cache).unwrap();
+
+        std::fs::write(temp.path().join("new.rs"), "fn new() {}").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let result = rebuild_selected(temp.path(), RebuildTarget::AnchorsOnly, false).unwrap();
+
+        let paths: Vec<_> = result.items.iter().filter_map(|i| i.path.clone()).collect();
+        assert_eq!(paths, vec![".mise/anchors.jsonl", ".mise/meta.json"]);
+
+        // files.jsonl untouched, its timestamp carried over
+        let files_after = std::fs::read_to_string(cache.join(FILES_CACHE)).unwrap();
+        assert_eq!(files_before, files_after);
+        let meta_after = read_meta(&cache).unwrap();
+        assert_eq!(
+            meta_after.files_generated_at,
+            meta_before.files_generated_at
+        );
+        assert!(meta_after.anchors_generated_at > meta_before.anchors_generated_at);
+        assert_eq!(
+            meta_after.anchors_generated_at,
+            Some(meta_after.generated_at)
+        );
+    }
+
+    #[test]
+    fn test_rebuild_selected_incremental() {
+        let temp = tempdir().unwrap();
+        let anchor = |id: &str| format!("<!--Q:begin id={id} v=1-->\n{id}\n<!--Q:end id={id}-->\n");
+        std::fs::write(temp.path().join("a.md"), anchor("a")).unwrap();
+        std::fs::write(temp.path().join("b.md"), anchor("b")).unwrap();
+
+        // Without a previous cache, incremental falls back to a full parse
+        let result = rebuild_selected(temp.path(), RebuildTarget::All, true).unwrap();
+        let data = result.items[1].data.as_ref().unwrap();
+        assert_eq!(data["incremental"], false);
+        assert_eq!(data["reparsed"], 2);
+
+        let cache = cache_dir(temp.path());
+        let meta = read_meta(&cache).unwrap();
+        assert_eq!(meta.file_stamps.len(), 2);
+
+        // Change b.md (size differs) and add c.md
+        std::fs::write(temp.path().join("b.md"), anchor("b2")).unwrap();
+        std::fs::write(temp.path().join("c.md"), anchor("c")).unwrap();
+        let result = rebuild_selected(temp.path(), RebuildTarget::AnchorsOnly, true).unwrap();
+        let data = result.items[0].data.as_ref().unwrap();
+        assert_eq!(data["incremental"], true);
+        assert_eq!(data["reused"], 1);
+        assert_eq!(data["reparsed"], 2);
+        assert_eq!(data["items"], 3);
+
+        let bodies: Vec<String> = read_cache_jsonl(&cache, ANCHORS_CACHE)
+            .unwrap()
+            .into_iter()
+            .filter_map(|i| i.excerpt)
+            .collect();
+        assert_eq!(bodies, vec!["a", "b2", "c"]);
+    }
+
+    #[test]
+    fn test_cache_status() {
+        let temp = tempdir().unwrap();
+        let status = cache_status(temp.path()).unwrap();
+        assert_eq!(status.items[0].errors[0].code, "CACHE_NOT_FOUND");
+
+        std::fs::write(
+            temp.path().join("a.md"),
+            "<!--Q:begin id=a v=1-->\na\n<!--Q:end id=a-->\n",
+        )
+        .unwrap();
+        rebuild_selected(temp.path(), RebuildTarget::All, false).unwrap();
+        let status = cache_status(temp.path()).unwrap();
+        let data = status.items[0].data.as_ref().unwrap();
+        assert_eq!(data["cached_files"], 1);
+        assert_eq!(data["cached_anchors"], 1);
+        assert_eq!(data["newer_files"], 0);
+        assert_eq!(data["stale"], false);
+
+        // File mtimes come from the kernel's coarse clock, which can lag now_ms by a tick
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        std::fs::write(temp.path().join("b.rs"), "fn b() {}").unwrap();
+        let status = cache_status(temp.path()).unwrap();
+        let data = status.items[0].data.as_ref().unwrap();
+        assert_eq!(data["workspace_files"], 2);
+        assert_eq!(data["newer_files"], 1);
+        assert_eq!(data["stale"], true);
+    }
+
+    #[test]
+    fn test_rebuild_selected_files_only_without_previous_cache() {
+        let temp = tempdir().unwrap();
+        std::fs::write(temp.path().join("test.rs"), "fn main() {}").unwrap();
+
+        rebuild_selected(temp.path(), RebuildTarget::FilesOnly, false).unwrap();
+        let cache = cache_dir(temp.path());
+        assert!(cache.join(FILES_CACHE).exists());
+        assert!(!cache.join(ANCHORS_CACHE).exists());
+
+        let meta = read_meta(&cache).unwrap();
+        assert!(meta.files_generated_at.is_some());
+        assert!(meta.anchors_generated_at.is_none());
+    }
+
     #[test]
     fn test_cache_dir_path() {
         let temp = tempdir().unwrap();
@@ -340,14 +667,17 @@ mod tests {
     }
 
     #[test]
-    fn test_is_cache_valid_corrupted_meta() {
+    fn test_check_cache_corrupted_meta() {
         let temp = tempdir().unwrap();
         let cache = ensure_cache_dir(temp.path()).unwrap();
 
         // Write corrupted meta file
         std::fs::write(cache.join(META_FILE), "not valid json").unwrap();
 
-        assert!(!is_cache_valid(temp.path()));
+        assert!(matches!(
+            check_cache(temp.path()),
+            Err(CacheInvalid::Unusable(_))
+        ));
     }
 
     #[test]
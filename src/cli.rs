@@ -1,7 +1,8 @@
 //! CLI module - Command-line interface definitions and handlers
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
+use std::io::IsTerminal;
 use std::path::PathBuf;
 
 use crate::core::render::{OutputFormat, RenderConfig};
@@ -23,6 +24,8 @@ Output formats:
 - json: a single JSON array
 - md: human-friendly Markdown
 - raw: excerpts only (unstable; intended for debugging)
+- csv: kind,path,start_line,end_line,confidence,excerpt rows for spreadsheets
+- yaml: a single YAML document with the same fields as json
 
 Examples:
     mise scan --type file
@@ -45,7 +48,7 @@ scopes are interpreted relative to it."
     )]
     pub root: PathBuf,
 
-    /// Output format (jsonl/json/md/raw).
+    /// Output format (jsonl/json/md/raw/rg/csv/yaml).
     #[arg(
         long,
         global = true,
@@ -53,10 +56,17 @@ scopes are interpreted relative to it."
         value_name = "FORMAT",
         long_help = "Select the output format for ResultSet.\n\n\
 Supported values:\n\
-- jsonl (default)\n\
+- jsonl (default) - one item per line, sorted by path like every other format;\n\
+  with --stream, scan and match write items as they are found instead\n\
 - json\n\
-- md (markdown)\n\
-- raw\n\n\
+- md (markdown) - sections grouped per file into collapsible <details> blocks\n\
+  with a match count in each summary; --md-flat lists items one by one instead\n\
+- raw\n\
+- rg (alias: grouped) - ripgrep-style heading per file with `line:excerpt` rows;\n\
+  only for match, ast, search and anchor grep. Colored on a terminal unless --no-color.\n\
+- csv - header row plus one RFC 4180 row per item\n\
+  (kind,path,start_line,end_line,confidence,excerpt); multi-line excerpts are quoted.\n\
+- yaml (alias: yml) - one YAML sequence with the same fields as json\n\n\
 Tip: Prefer jsonl when you want stable, line-oriented output for piping and prompts."
     )]
     pub format: String,
@@ -86,7 +96,9 @@ printed to stdout unless a command explicitly suppresses them."
         long,
         global = true,
         long_help = "Enable more detailed diagnostics. This is intended for debugging and\n\
-may increase stderr output."
+may increase stderr output.\n\n\
+Flows that read the .mise cache log which source they used and why, e.g.\n\
+`cache: file list via cache-hit (files.jsonl older than src/foo.rs)`."
     )]
     pub verbose: bool,
 
@@ -99,10 +111,203 @@ This is useful when manually inspecting results. Has no effect on md/raw formats
     )]
     pub pretty: bool,
 
+    /// Stream scan/match jsonl items as they are found (unsorted).
+    #[arg(
+        long,
+        global = true,
+        long_help = "Write scan and match results as jsonl lines while the walk or ripgrep is still\n\
+running, so the first result appears immediately on large trees.\n\n\
+Streamed output is in production order (directory walk order, or ripgrep's\n\
+arrival order across its worker threads), not the path order that the default\n\
+output follows. Ignored for other formats and with --pretty, --max-total-bytes\n\
+or --min-confidence."
+    )]
+    pub stream: bool,
+
+    /// Markdown: list items flat instead of in per-file collapsible sections.
+    #[arg(
+        long,
+        global = true,
+        long_help = "With --format md, render every item under its own heading instead of grouping\n\
+items into one collapsible <details> block per file.\n\n\
+Use it when the Markdown viewer does not support HTML or when the output is fed to\n\
+tools that expect the previous flat layout. Has no effect on other formats."
+    )]
+    pub md_flat: bool,
+
+    /// Resolve symlinks in emitted paths.
+    #[arg(
+        long,
+        global = true,
+        long_help = "Canonicalize every emitted path so symlinked files/directories are reported\n\
+by their real location.\n\n\
+By default symlinks are left as encountered during traversal. Resolved paths that\n\
+point outside ROOT are emitted as absolute paths."
+    )]
+    pub resolve_symlinks: bool,
+
+    /// Annotate each result with why it was included.
+    #[arg(
+        long,
+        global = true,
+        long_help = "Attach a human-readable `reason` to each result's `data` payload explaining\n\
+why it was included (e.g. \"primary anchor\", \"shares tag: rust\",\n\
+\"keyword match: function\").\n\n\
+Currently used by the writing and pack flows and `anchor grep`."
+    )]
+    pub explain: bool,
+
+    /// Use ASCII instead of Unicode box-drawing in trees and tables.
+    #[arg(
+        long,
+        global = true,
+        long_help = "Draw trees and table borders with ASCII (`|--`, `` `-- ``, `+`, `-`) instead of\n\
+Unicode box-drawing characters. Useful for CI logs and non-UTF-8 terminals.\n\n\
+Applies to deps tree/table, impact table and outline tree output."
+    )]
+    pub ascii: bool,
+
+    /// Hard cap on total output bytes.
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        long_help = "Cap the total rendered output (including the trailing newline) at N bytes.\n\n\
+Output is truncated at result-item boundaries and a final OUTPUT_TRUNCATED error item\n\
+reports how many items were omitted. Unlike token budgets this is an exact guarantee.\n\
+Applies to result-set output selected by --format; command-specific formats\n\
+(e.g. --deps-format dot) are not capped."
+    )]
+    pub max_total_bytes: Option<usize>,
+
+    /// Drop results below this confidence (low/medium/high).
+    #[arg(
+        long,
+        global = true,
+        value_name = "LEVEL",
+        long_help = "Drop result items whose confidence is below LEVEL (low, medium or high)\n\
+before rendering, in every --format.\n\n\
+Flows such as writing and pack mix high, medium and low confidence items; use\n\
+`--min-confidence high` to keep only the reliable ones. Error items are always kept.\n\
+Pack filters before applying --max-tokens, so every --pack-format, --template and\n\
+--stats see the same filtered set.\n\
+Command-specific formats (e.g. --deps-format dot) are not filtered."
+    )]
+    pub min_confidence: Option<crate::core::model::Confidence>,
+
+    /// Never read or emit paths matching this glob (can be repeated).
+    #[arg(
+        long,
+        global = true,
+        value_name = "GLOB",
+        long_help = "Hard guardrail: paths matching GLOB are excluded from scan, match, ast,\n\
+extract, pack and anchors, regardless of includes or ignore settings.\n\
+Explicitly requested denied files produce a PATH_DENIED error item.\n\n\
+Patterns without '/' match any path component (e.g. `.env`, `secrets/`, `*.pem`);\n\
+patterns with '/' are anchored at ROOT (e.g. `config/prod`).\n\n\
+Example: --deny secrets/ --deny .env"
+    )]
+    pub deny: Vec<String>,
+
+    /// Read non-UTF-8 text with this encoding instead of detecting it.
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        long_help = "Force the codec used to read text files (extract, stats, outline, anchors).\n\n\
+By default files that are not valid UTF-8 are detected (GBK, Big5, Shift_JIS,\n\
+EUC-KR, windows-125x, ...) and converted to UTF-8; results note the source codec\n\
+in `meta.encoding`. Use this when detection guesses wrong, e.g. for short files or\n\
+single-byte codecs such as KOI8-R, whose files otherwise look binary.\n\n\
+NAME is a WHATWG label: utf-8, gbk, gb18030, big5, shift_jis, euc-kr, latin1, ...\n\n\
+Example: --encoding gbk"
+    )]
+    pub encoding: Option<String>,
+
+    /// Bound worker threads for external search tools (0 = their defaults).
+    #[arg(
+        long,
+        global = true,
+        default_value = "0",
+        value_name = "N",
+        long_help = "Limit CPU usage of mise and the tools it drives.\n\n\
+Forwarded as --threads to ripgrep (match, search) and ast-grep (ast, search).\n\
+`match --rg-threads` takes precedence over this for ripgrep, and `run --parallel`\n\
+falls back to it when left at 0. `deps` and `impact` use it as the number of\n\
+worker threads for dependency analysis. Default (0) keeps each tool's own\n\
+default (one worker per CPU core for dependency analysis)."
+    )]
+    pub jobs: usize,
+
+    /// Wall-clock budget in milliseconds; return partial results when exceeded.
+    #[arg(
+        long,
+        global = true,
+        value_name = "MS",
+        long_help = "Stop collecting results after MS milliseconds of wall-clock time.\n\n\
+File walks (scan, find, anchors, deps, ...) check the budget cooperatively and return\n\
+what they have gathered so far. ripgrep searches (match, search) keep the matches\n\
+read before the budget ran out and kill rg. Output then ends with a\n\
+TIME_BUDGET_EXCEEDED notice item so it stays parseable."
+    )]
+    pub time_budget: Option<u64>,
+
+    /// Report command failures as a result item on stdout.
+    #[arg(
+        long,
+        global = true,
+        long_help = "Instead of printing a failure to stderr, emit it on stdout as a single\n\
+COMMAND_FAILED error item in the selected --format, so consumers that only read\n\
+stdout still get parseable output. Items already streamed before the failure\n\
+stay on stdout and the error item follows them. The exit code stays non-zero."
+    )]
+    pub json_errors: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+/// Include/exclude globs shared by the scan-based commands
+#[derive(clap::Args, Debug, Default)]
+pub struct GlobFilterArgs {
+    /// Include files matching glob pattern (can be repeated).
+    #[arg(
+        short = 'g',
+        long,
+        value_name = "GLOB",
+        num_args = 1..,
+        long_help = "Include only files matching the given glob pattern, applied to root-relative\n\
+paths after ignore rules and the deny list.\n\n\
+Globs follow ripgrep's -g rules: a pattern without '/' matches the file name at any\n\
+depth, one with '/' the whole path from ROOT; `*` stays within a directory and\n\
+`**` crosses directories.\n\n\
+Examples: --include '*.rs' --include 'src/**'"
+    )]
+    pub include: Vec<String>,
+
+    /// Exclude files matching glob pattern (can be repeated).
+    #[arg(
+        short = 'G',
+        long,
+        value_name = "GLOB",
+        num_args = 1..,
+        long_help = "Exclude files matching the given glob pattern, applied to root-relative\n\
+paths after ignore rules and the deny list.\n\n\
+Same glob rules as --include; an excluded directory also excludes everything\n\
+below it.\n\n\
+Examples: --exclude 'tests/*' --exclude node_modules --exclude '**/generated/**'"
+    )]
+    pub exclude: Vec<String>,
+}
+
+impl TryFrom<GlobFilterArgs> for crate::backends::scan::GlobFilter {
+    type Error = anyhow::Error;
+
+    fn try_from(args: GlobFilterArgs) -> Result<Self> {
+        Self::new(args.include, args.exclude)
+    }
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Scan the filesystem and output a stable list of paths.
@@ -114,7 +319,8 @@ Examples:\n\
   mise scan --type file\n\
   mise scan --type dir --max-depth 2\n\
   mise scan --scope src --hidden --no-ignore\n\
-  mise scan --include '*.rs' --exclude 'tests/*'\n"
+  mise scan --include '*.rs' --exclude 'tests/*'\n\
+  mise scan --type file --minimal\n"
     )]
     Scan {
         /// Limit scanning to a subdirectory under ROOT.
@@ -146,11 +352,22 @@ By default, hidden entries are skipped."
         /// Disable .gitignore and other ignore rules.
         #[arg(
             long,
-            long_help = "Disable respect for ignore files (.gitignore, .ignore, global ignores).\n\n\
+            long_help = "Disable respect for ignore files (.gitignore, .ignore, .miseignore, global ignores).\n\n\
 Use this for a raw scan that includes all paths, even those normally ignored."
         )]
         no_ignore: bool,
 
+        /// Descend into symlinked directories.
+        #[arg(
+            long,
+            long_help = "Follow symbolic links to directories and list their contents.\n\n\
+By default symlinks are listed but not descended into. Links that point back to one\n\
+of their own ancestors are not followed; each such cycle is reported as a\n\
+SYMLINK_LOOP error item (path = the link, data.ancestor = the target) instead of\n\
+recursing forever."
+        )]
+        follow_symlinks: bool,
+
         /// Filter results by entry type.
         #[arg(
             long,
@@ -162,27 +379,104 @@ If omitted, both files and directories may be returned."
         )]
         r#type: Option<String>,
 
-        /// Include files matching glob pattern (can be repeated).
+        #[command(flatten)]
+        filter: GlobFilterArgs,
+
+        /// Emit compact path+type records only (max throughput).
         #[arg(
-            short = 'g',
             long,
-            value_name = "GLOB",
-            num_args = 1..,
-            long_help = "Include only files matching the given glob pattern.\n\n\
-Examples: --include '*.rs' --include '*.py'"
+            long_help = "Emit one compact JSON object per line with only the path and type:\n\
+  {\"p\":\"src/main.rs\",\"t\":\"f\"}   (t is \"f\" for file, \"d\" for dir)\n\n\
+This bypasses the ResultItem envelope entirely and skips metadata collection, which\n\
+makes it the fastest way to pipe huge file lists into other programs.\n\n\
+Note: --minimal ignores --format, --pretty and any other envelope/field options;\n\
+filters (--scope, --type, --include, --exclude, ...) still apply."
         )]
-        include: Vec<String>,
+        minimal: bool,
 
-        /// Exclude files matching glob pattern (can be repeated).
+        /// Skip files smaller than SIZE (e.g. 512, 10k, 2M).
         #[arg(
-            short = 'G',
             long,
-            value_name = "GLOB",
-            num_args = 1..,
-            long_help = "Exclude files matching the given glob pattern.\n\n\
-Examples: --exclude 'tests/*' --exclude '*.bak'"
+            value_name = "SIZE",
+            value_parser = crate::core::util::parse_size,
+            long_help = "Skip files smaller than SIZE bytes. Accepts k/M/G suffixes (1k = 1024).\n\n\
+Directories are not affected. With a size filter, each file item's data carries\n\
+{\"size\": BYTES} so results can be sorted by size."
         )]
-        exclude: Vec<String>,
+        min_size: Option<u64>,
+
+        /// Skip files larger than SIZE (e.g. 512, 10k, 2M).
+        #[arg(
+            long,
+            value_name = "SIZE",
+            value_parser = crate::core::util::parse_size,
+            long_help = "Skip files larger than SIZE bytes. Accepts k/M/G suffixes (1k = 1024).\n\n\
+Directories are not affected. Example: mise scan --type file --min-size 10k --max-size 2M"
+        )]
+        max_size: Option<u64>,
+
+        /// Only files modified within DURATION (e.g. 30m, 12h, 7d).
+        #[arg(
+            long,
+            value_name = "DURATION",
+            value_parser = crate::core::util::parse_duration,
+            long_help = "Only list files modified within DURATION before now.\n\n\
+Accepts s/m/h/d/w suffixes (e.g. 30m, 12h, 7d, 2w). Directories are not affected.\n\
+With a time filter, each file item's data carries {\"mtime\": RFC3339, \"size\": BYTES}.\n\
+Example: mise scan --type file --modified-within 7d"
+        )]
+        modified_within: Option<std::time::Duration>,
+
+        /// Only files last modified more than DURATION ago (e.g. 30d).
+        #[arg(
+            long,
+            value_name = "DURATION",
+            value_parser = crate::core::util::parse_duration,
+            long_help = "Only list files last modified more than DURATION before now.\n\n\
+Accepts s/m/h/d/w suffixes. Combine with --modified-within for a window, e.g.\n\
+  mise scan --modified-within 30d --modified-before 7d"
+        )]
+        modified_before: Option<std::time::Duration>,
+
+        /// Report groups of files with identical content.
+        #[arg(
+            long,
+            conflicts_with = "minimal",
+            long_help = "Report groups of files with identical content instead of listing entries.\n\n\
+Files are bucketed by size, then same-size candidates are hashed with xxh3. Each group\n\
+with more than one member is emitted as one item: path is the first duplicate (sorted),\n\
+meta carries size and hash, and data lists every member:\n\
+  {\"hash\":\"...\",\"size\":1234,\"count\":2,\"paths\":[\"a.txt\",\"copy/a.txt\"]}\n\n\
+Combine with --include/--exclude and --min-size (e.g. --min-size 1 to skip empty files)."
+        )]
+        find_duplicates: bool,
+
+        /// Summarize file counts and bytes per extension and top-level directory.
+        #[arg(
+            long,
+            conflicts_with_all = ["minimal", "find_duplicates"],
+            long_help = "Aggregate the scanned files instead of listing every path.\n\n\
+Uses the same walk as a plain scan, so ignore rules, --scope and all filters apply.\n\
+Emits a `total` item, then one item per extension and per top-level directory of the\n\
+scan path, each sorted by file count (then bytes), with data like:\n\
+  {\"group\":\"extension\",\"key\":\"rs\",\"files\":42,\"bytes\":183201}\n\n\
+Extension items use their `*.ext` glob as path, ready to pass to --include.\n\n\
+Example: mise scan --summary --format md"
+        )]
+        summary: bool,
+
+        /// Add each file's content hash (xxhash or sha1) to its data.
+        #[arg(
+            long,
+            value_name = "ALGO",
+            conflicts_with_all = ["minimal", "find_duplicates", "summary"],
+            long_help = "Hash the content of every regular file and add it to the item's data as\n\
+{\"hash\":\"...\"}. ALGO is `xxhash` (xxh3, 16 hex digits, fast) or `sha1`\n\
+(40 hex digits). Files are read in chunks, so large files are not loaded whole.\n\
+Directories and special files are not hashed. Without this flag nothing is read.\n\n\
+Example: mise scan --type file --hash sha1"
+        )]
+        hash: Option<crate::core::util::HashAlgorithm>,
     },
 
     /// Find files by substring match (built on top of scan).
@@ -198,13 +492,46 @@ Examples:
 "#
     )]
     Find {
-        /// Substring pattern to match against paths.
+        /// Substring pattern to match against paths (a glob with --glob).
         #[arg(value_name = "PATTERN")]
         pattern: Option<String>,
 
         /// Limit search to a subdirectory under ROOT.
         #[arg(long, value_name = "PATH")]
         scope: Option<PathBuf>,
+
+        /// Match PATTERN as a glob against the whole relative path.
+        #[arg(
+            long,
+            requires = "pattern",
+            long_help = "Treat PATTERN as a glob matched against the full relative path\n\
+(with '/' separators) instead of a case-insensitive substring.\n\n\
+`*` and `?` stay within one path component, `**` crosses directories,\n\
+and `{a,b}` / `[abc]` are supported. Matching is case-insensitive.\n\n\
+Example: mise find --glob \"src/**/*.rs\""
+        )]
+        glob: bool,
+
+        /// Rank paths by a fuzzy subsequence match of PATTERN.
+        #[arg(
+            long,
+            requires = "pattern",
+            conflicts_with = "glob",
+            long_help = "Match PATTERN as a case-insensitive subsequence of the path, like a fuzzy\n\
+file finder, and sort results by descending score (data.score).\n\n\
+Consecutive characters, word starts (after / _ - . or a camelCase hump) and\n\
+hits in the file name score higher; skipped characters cost a little.\n\n\
+Example: mise find --fuzzy cli   # src/cli.rs ranks above src/core/client/list.rs"
+        )]
+        fuzzy: bool,
+
+        /// Stop at the first matching path and only report whether one exists.
+        #[arg(
+            long,
+            long_help = "Existence check: stop walking at the first matching path and emit a single\n\
+item with data {\"exists\": true|false}. Exits 0 if found, 1 otherwise."
+        )]
+        exists: bool,
     },
 
     /// Extract a line range from a file.
@@ -221,14 +548,74 @@ Examples:\n\
         #[arg(value_name = "FILE")]
         path: PathBuf,
 
-        /// Line range to extract (1-indexed, format: start:end).
+        /// Line range(s) to extract (1-indexed, format: start:end[,start:end...]).
         #[arg(
             long,
             value_name = "START:END",
+            required_unless_present_any = ["bytes", "from"],
             long_help = "Line range to extract (1-indexed). Format: start:end.\n\n\
-Example: --lines 5:12"
+Several disjoint ranges may be given comma-separated; each becomes its own result\n\
+item, in the order given.\n\n\
+Example: --lines 5:12\n\
+Example: --lines 1:20,50:70,120:125"
+        )]
+        lines: Option<String>,
+
+        /// Byte range to extract (0-indexed, end exclusive, format: start:end).
+        #[arg(
+            long,
+            value_name = "START:END",
+            conflicts_with_all = ["lines", "merge_with_anchors"],
+            long_help = "Slice the file on byte offsets instead of lines (0-indexed, end exclusive).\n\
+Useful for minified files where a single line is enormous.\n\n\
+The range is widened to UTF-8 character boundaries so no codepoint is split; when that\n\
+happens meta.adjusted is true and data.requested holds the original offsets. The item\n\
+range and data.start/data.end hold the byte offsets actually emitted. Offsets beyond\n\
+the file size are an error.\n\n\
+Example: --bytes 1024:4096"
         )]
-        lines: String,
+        bytes: Option<String>,
+
+        /// Start the range at the first line matching this regex.
+        #[arg(
+            long,
+            value_name = "REGEX",
+            requires = "to",
+            conflicts_with_all = ["lines", "bytes"],
+            long_help = "Select the range by content instead of line numbers: it starts at the first\n\
+line matching --from and ends at the next line after it matching --to (both\n\
+inclusive), so it survives line-number drift. data records each pattern and the\n\
+line it matched. If either pattern has no match, a RANGE_PATTERN_NOT_FOUND error\n\
+item is emitted.\n\n\
+Example: mise extract src/main.rs --from 'fn main' --to '^\\}'"
+        )]
+        from: Option<String>,
+
+        /// End the range at the next line matching this regex (with --from).
+        #[arg(long, value_name = "REGEX", requires = "from")]
+        to: Option<String>,
+
+        /// Report N lines of context before and after each range.
+        #[arg(
+            short = 'C',
+            long,
+            value_name = "N",
+            conflicts_with = "bytes",
+            long_help = "Report up to N lines before and after each extracted range, like grep -C\n\
+(clamped to the file). The excerpt and range stay on the requested lines; the\n\
+padding goes to data.before / data.after with counts in data.context_before /\n\
+data.context_after. --before and --after override either side.\n\n\
+Example: mise extract src/main.rs --lines 40:60 -C 3"
+        )]
+        context: Option<u32>,
+
+        /// Report N lines of context before each range.
+        #[arg(short = 'B', long, value_name = "N", conflicts_with = "bytes")]
+        before: Option<u32>,
+
+        /// Report N lines of context after each range.
+        #[arg(short = 'A', long, value_name = "N", conflicts_with = "bytes")]
+        after: Option<u32>,
 
         /// Maximum bytes to emit in the excerpt.
         #[arg(
@@ -236,10 +623,30 @@ Example: --lines 5:12"
             default_value = "65536",
             value_name = "BYTES",
             long_help = "Maximum bytes to emit in the excerpt.\n\n\
-If the selected range is larger, the excerpt is truncated and the result meta marks it\n\
-as truncated."
+If the selected range is larger, the excerpt is cut before the last partial word, ends\n\
+with `…[truncated]` (counted in the budget) and the result meta marks it as truncated.\n\
+With several ranges the budget is shared: ranges are emitted in order until it runs\n\
+out, and the last emitted item is marked truncated."
         )]
         max_bytes: usize,
+
+        /// Report which anchors cover the extracted lines.
+        #[arg(
+            long,
+            long_help = "Parse the file's anchors and add `data.anchors` listing every anchor that\n\
+overlaps the extracted range: id, tags, marker range, the overlapping lines, and\n\
+coverage (`full` if the anchor spans the whole range, otherwise `partial`)."
+        )]
+        merge_with_anchors: bool,
+
+        /// Read files that look binary instead of skipping them.
+        #[arg(
+            long,
+            long_help = "Files whose first 8 KB contain a NUL byte or are mostly invalid UTF-8 are\n\
+treated as binary: instead of an excerpt of replacement characters, extract emits\n\
+a single BINARY_SKIPPED note. Set this flag to extract them anyway (lossily)."
+        )]
+        include_binary: bool,
     },
 
     /// Manage anchors embedded in text files.
@@ -351,6 +758,84 @@ By default, ripgrep is case-sensitive. This flag makes it case-insensitive."
 This is equivalent to putting \\b before and after the pattern."
         )]
         word_regexp: bool,
+
+        /// Treat the pattern as a literal string, not a regex.
+        #[arg(
+            short = 'F',
+            long,
+            long_help = "Treat PATTERN as a literal string instead of a regular expression\n\
+(passed to ripgrep as --fixed-strings).\n\n\
+Use this for text with regex metacharacters, e.g. mise match -F 'foo(bar)'."
+        )]
+        fixed_strings: bool,
+
+        /// Preview replacing each match with TEXT (dry-run unless --apply).
+        #[arg(
+            short = 'r',
+            long,
+            value_name = "TEXT",
+            conflicts_with_all = ["count", "exists"],
+            long_help = "Preview a find/replace: each item's data holds {line, replaced, applied}\n\
+with the matched line before and after replacement. Files are not touched.\n\n\
+Capture groups expand like rg --replace: $1, ${1}, ${name}. Use $$ for a literal $.\n\n\
+Example: mise match 'foo\\((\\w+)\\)' --replace 'bar($1)' src"
+        )]
+        replace: Option<String>,
+
+        /// With --replace, write the replacements to the matched files.
+        #[arg(
+            long,
+            requires = "replace",
+            long_help = "With --replace, rewrite the matched lines in place.\n\n\
+Only lines reported as matches are changed; line endings are preserved."
+        )]
+        apply: bool,
+
+        /// Emit one item per submatch instead of one per matched line.
+        #[arg(
+            long,
+            conflicts_with = "replace",
+            long_help = "Emit one item per submatch when a line matches several times.\n\n\
+Every match item carries character columns in its range (start_col inclusive,\n\
+end_col exclusive, both 1-based). By default a line yields one item with the\n\
+columns of its first submatch; with this flag each submatch gets its own item,\n\
+so --count reports the number of matches rather than matching lines.\n\n\
+Example: mise match 'TODO' --per-submatch src"
+        )]
+        per_submatch: bool,
+
+        /// Allow matches to span multiple lines (rg --multiline).
+        #[arg(
+            short = 'U',
+            long,
+            conflicts_with = "replace",
+            long_help = "Pass --multiline to ripgrep so a pattern can match across line breaks,\n\
+e.g. a signature split over several lines. Use \\n in the pattern to match a newline.\n\n\
+A multi-line match yields one item whose range runs from the first to the last line\n\
+of the match and whose excerpt holds all of those lines.\n\n\
+Example: mise match -U 'fn run\\([^)]*\\n[^)]*\\)' src"
+        )]
+        multiline: bool,
+
+        /// Number of ripgrep threads (0 = use --jobs, or ripgrep's default).
+        #[arg(
+            long,
+            default_value = "0",
+            value_name = "N",
+            long_help = "Forward --threads N to ripgrep to bound CPU usage (e.g. on CI runners).\n\n\
+When 0 (default), the global --jobs value is used; if that is also 0,\n\
+ripgrep picks its own thread count."
+        )]
+        rg_threads: usize,
+
+        /// Stop at the first match and only report whether one exists.
+        #[arg(
+            long,
+            conflicts_with = "count",
+            long_help = "Existence check: run ripgrep in quiet mode (stops at the first match) and\n\
+emit a single item with data {\"exists\": true|false}. Exits 0 if found, 1 otherwise."
+        )]
+        exists: bool,
     },
 
     /// Structural code search using ast-grep (sg/ast-grep).
@@ -365,49 +850,111 @@ Examples:
     mise ast "unsafe { $A }"
     mise ast "fn $NAME($$$)" --lang rust
     mise ast "import $A from $B" --include "*.ts"
+    mise ast "console.log($A)" --rewrite "logger.info($A)"          # preview
+    mise ast "console.log($A)" --rewrite "logger.info($A)" --apply  # write
+    mise ast --rule rules/no-console.yml src                        # YAML rule
 "#
     )]
     Ast {
-        /// ast-grep pattern.
-        #[arg(value_name = "PATTERN")]
-        pattern: String,
+        /// ast-grep pattern (omit with --rule).
+        #[arg(value_name = "PATTERN", required_unless_present = "rule")]
+        pattern: Option<String>,
 
         /// Optional scope paths (relative to ROOT unless absolute).
         #[arg(value_name = "SCOPE", num_args = 0..)]
         scope: Vec<PathBuf>,
 
-        /// Include files matching glob pattern (can be repeated).
+        #[command(flatten)]
+        filter: GlobFilterArgs,
+
+        /// Specify the language for the search.
         #[arg(
-            short = 'g',
+            short = 'l',
             long,
-            value_name = "GLOB",
-            num_args = 1..,
-            long_help = "Include only files matching the given glob pattern.\n\n\
-Examples: --include '*.rs' --include '*.ts'"
+            value_name = "LANG",
+            long_help = "Specify the language for ast-grep.\n\n\
+Supported: rust, python, javascript, typescript, go, java, c, cpp, etc.\n\
+If not specified, ast-grep will auto-detect based on file extension."
         )]
-        include: Vec<String>,
+        lang: Option<String>,
 
-        /// Exclude files matching glob pattern (can be repeated).
+        /// Rewrite each match using an ast-grep template (dry-run unless --apply).
         #[arg(
-            short = 'G',
             long,
-            value_name = "GLOB",
-            num_args = 1..,
-            long_help = "Exclude files matching the given glob pattern.\n\n\
-Examples: --exclude '*_test.go' --exclude 'vendor/*'"
+            value_name = "TEMPLATE",
+            long_help = "Rewrite each match with an ast-grep template (passed to `sg run --rewrite`).\n\n\
+Meta variables captured by the pattern ($A, $$$ARGS) can be used in the template.\n\
+By default nothing is written: each item's excerpt is a diff of the affected lines\n\
+(`- ` old, `+ ` new) and data holds {text, replacement, applied}."
         )]
-        exclude: Vec<String>,
+        rewrite: Option<String>,
 
-        /// Specify the language for the search.
+        /// With --rewrite, write the replacements to disk.
         #[arg(
-            short = 'l',
             long,
-            value_name = "LANG",
-            long_help = "Specify the language for ast-grep.\n\n\
-Supported: rust, python, javascript, typescript, go, java, c, cpp, etc.\n\
-If not specified, ast-grep will auto-detect based on file extension."
+            requires = "rewrite",
+            long_help = "With --rewrite, write the replacements to the matched files.\n\n\
+Only matches that pass --include/--exclude and the deny list are rewritten."
         )]
+        apply: bool,
+
+        /// Run an ast-grep YAML rule file instead of a pattern.
+        #[arg(
+            long,
+            value_name = "FILE.yml",
+            conflicts_with_all = ["lang", "rewrite"],
+            long_help = "Run an ast-grep YAML rule file (`sg scan --rule FILE --json`) instead of a\n\
+single pattern. With --rule, positional arguments are all scopes.\n\n\
+Each item's data holds {rule_id, severity, message}; severity maps to confidence\n\
+(error: high, warning: medium, info/hint: low)."
+        )]
+        rule: Option<PathBuf>,
+    },
+
+    /// Textual and structural search in one pass (rg + ast-grep).
+    #[command(
+        long_about = r#"Run ripgrep and ast-grep concurrently and merge their matches into a
+single result set.
+
+Each item's source_mode is rg or ast_grep; hits found by both backends on the
+same path and line are merged into one item with source_mode "mixed".
+
+If --ast is omitted, PATTERN is used for ast-grep as well.
+
+Examples:
+    mise search "unwrap" src
+    mise search "console.log" --ast "console.log($A)" --include "*.ts"
+    mise search "new_\w+" --ast "fn $NAME($$$)" --lang rust
+"#
+    )]
+    Search {
+        /// ripgrep (regex) pattern.
+        #[arg(value_name = "PATTERN")]
+        pattern: String,
+
+        /// ast-grep pattern (defaults to PATTERN).
+        #[arg(long = "ast", value_name = "PATTERN")]
+        ast_pattern: Option<String>,
+
+        /// Optional scope paths (relative to ROOT unless absolute).
+        #[arg(value_name = "SCOPE", num_args = 0..)]
+        scope: Vec<PathBuf>,
+
+        /// Include files matching glob pattern (can be repeated).
+        #[arg(short = 'g', long, value_name = "GLOB", num_args = 1..)]
+        include: Vec<String>,
+
+        /// Exclude files matching glob pattern (can be repeated).
+        #[arg(short = 'G', long, value_name = "GLOB", num_args = 1..)]
+        exclude: Vec<String>,
+
+        /// Language for the ast-grep side of the search.
+        #[arg(short = 'l', long, value_name = "LANG")]
         lang: Option<String>,
+
+        /// Case-insensitive textual matching.
+        #[arg(short = 'i', long)]
+        ignore_case: bool,
     },
 
     /// Analyze code dependencies (imports/requires/use statements).
@@ -415,16 +962,22 @@ If not specified, ast-grep will auto-detect based on file extension."
         long_about = r#"Analyze code dependencies to understand "what does this file depend on"
 and "what depends on this file".
 
-Supports: Rust (.rs), TypeScript (.ts/.tsx), JavaScript (.js/.jsx), Python (.py)
+Supports: Rust (.rs), TypeScript (.ts/.tsx), JavaScript (.js/.jsx), Python (.py), Go (.go)
 
 Output formats:
-- jsonl (default): one JSON object per file
+- jsonl (default): one JSON object per file, with resolved_count,
+  unresolved_count and the unresolved module names
 - json: complete JSON array
 - dot: Graphviz DOT format
 - mermaid: Mermaid diagram syntax
+- graphml: GraphML XML (Gephi, yEd)
 - tree: ASCII tree view (requires a specific file)
 - table: ASCII table summary
 
+Graph formats draw one edge per pair of files; when a file has several import
+statements resolving to the same file, the edge is labelled with the count
+(DOT also thickens it, GraphML stores it as the edge weight).
+
 Graph rendering (with -o/--output):
 - Requires graphviz (dot) for DOT format
 - Requires mermaid-cli (mmdc) for Mermaid format
@@ -432,10 +985,15 @@ Graph rendering (with -o/--output):
 Examples:
     mise deps src/cli.rs                    # What does cli.rs depend on?
     mise deps src/cli.rs --reverse          # What depends on cli.rs?
+    mise deps src/cli.rs --reverse --show-imports  # ...and via which import lines
+    mise deps src/cli.rs --deps-format tree --depth 3  # Transitive subtree
     mise deps --deps-format dot -o deps.png # Render DOT to PNG
     mise deps --deps-format mermaid -o deps.svg  # Render Mermaid to SVG
+    mise deps --deps-format graphml > deps.graphml  # Import into Gephi/yEd
     mise deps -o deps.png                   # Auto-select format and render
     mise deps --deps-format dot | dot -Tpng -o deps.png  # Manual pipe
+    mise deps --include 'src/**' --exclude '**/generated/**'  # Filter the analyzed files
+    mise deps --no-tests --cycles-only      # Production dependencies only
 "#
     )]
     Deps {
@@ -443,6 +1001,9 @@ Examples:
         #[arg(value_name = "FILE")]
         file: Option<PathBuf>,
 
+        #[command(flatten)]
+        filter: GlobFilterArgs,
+
         /// Show reverse dependencies (what depends on this file).
         #[arg(
             long,
@@ -450,7 +1011,76 @@ Examples:
         )]
         reverse: bool,
 
-        /// Output format for deps (jsonl/json/dot/mermaid/tree/table).
+        /// With --reverse, show the import statements that resolve to the file.
+        #[arg(
+            long,
+            requires = "reverse",
+            long_help = "With --reverse, include the specific import statement and line number in \
+each dependent that resolved to the target file.\n\n\
+Applies to jsonl/json (an `imports` array in data) and tree output."
+        )]
+        show_imports: bool,
+
+        /// Levels of transitive dependencies to draw in tree output (default: 1).
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 1,
+            long_help = "Levels of transitive dependencies to draw in tree output (default: 1, direct only).\n\n\
+Walks what each dependency depends on (or, with --reverse, what depends on each dependent) \
+up to N levels with nested indentation. A file already on the path from the root is marked \
+`(cycle)` and not expanded.\n\n\
+Example: mise deps src/cli.rs --deps-format tree --depth 3"
+        )]
+        depth: usize,
+
+        /// Aggregate the graph into directories (default depth: 2).
+        #[arg(
+            long,
+            value_name = "DEPTH",
+            num_args = 0..=1,
+            default_missing_value = "2",
+            conflicts_with_all = ["file", "reverse"],
+            long_help = "Collapse files into their directory (first DEPTH path components, default 2)\n\
+and merge file edges into weighted directory edges. Edges within a directory are omitted.\n\n\
+Output per --deps-format:\n\
+- jsonl/json: one item per directory with files, depends_on and depended_by weights\n\
+- dot/mermaid: weighted edges (also with -o for images); graphml: weight edge data\n\
+- table/tree: From/To/Weight table\n\n\
+Example: mise deps --group-by-dir --deps-format dot -o modules.svg"
+        )]
+        group_by_dir: Option<usize>,
+
+        /// Report only circular dependencies; exit 1 if any exist.
+        #[arg(
+            long,
+            conflicts_with_all = ["file", "reverse", "group_by_dir", "output"],
+            long_help = "Run the project-wide analysis and emit only the detected circular-dependency \
+chains. Each cycle is reported once, starting at its lexicographically smallest file, no matter \
+which member the search entered it from.\n\n\
+Output per --deps-format:\n\
+- jsonl/json: one CIRCULAR_DEPENDENCY item per cycle (data: cycle, cycle_length)\n\
+- tree: numbered arrow chains (a.rs -> b.rs -> a.rs)\n\
+- table: #/Cycle/Length table\n\
+- dot/mermaid/graphml: only the edges that form cycles\n\n\
+Exits with status 1 when any cycle exists, so it can gate CI.\n\n\
+Example: mise deps --cycles-only --deps-format tree"
+        )]
+        cycles_only: bool,
+
+        /// Leave test code out of the graph.
+        #[arg(
+            long,
+            long_help = "Analyze production dependencies only. Test files are skipped entirely:\n\
+- files under tests/ or __tests__/ directories\n\
+- *_test.* (Go, Rust), test_*.py, and *.test.*/*.spec.* (JS/TS) files\n\n\
+In Rust files, imports inside items gated by #[cfg(test)] (typically `mod tests`) are\n\
+dropped as well. Cycles and reverse dependencies are computed on the reduced graph.\n\n\
+Example: mise deps --cycles-only --no-tests"
+        )]
+        no_tests: bool,
+
+        /// Output format for deps (jsonl/json/dot/mermaid/graphml/tree/table).
         #[arg(
             long = "deps-format",
             value_name = "FORMAT",
@@ -461,6 +1091,7 @@ Supported values:\n\
 - json: complete JSON array\n\
 - dot: Graphviz DOT format\n\
 - mermaid: Mermaid diagram syntax\n\
+- graphml: GraphML XML for Gephi/yEd (nodes carry label and path data)\n\
 - tree: ASCII tree (requires file argument)\n\
 - table: ASCII table summary"
         )]
@@ -495,6 +1126,8 @@ This is more powerful than plain `git diff` because:
 1. Shows direct impacts: files that depend on changed files
 2. Shows transitive impacts: files affected through dependency chains
 3. Lists affected anchors: code markers that may need attention
+4. Lists affected tests: inline #[cfg(test)] modules and *_test.rs, tests/,
+   test_*.py, *.test.ts (and similar) files next to the affected files
 
 Examples:
     mise impact                        # Analyze unstaged changes
@@ -502,6 +1135,8 @@ Examples:
     mise impact --commit abc123        # Analyze a specific commit
     mise impact --diff main..feature   # Compare branches
     mise impact --impact-format summary
+    mise impact --staged --impact-format junit --max-risk 20 > impact.xml
+    mise impact --staged | jq -r '.affected_tests[]'   # Tests to run
 "#
     )]
     Impact {
@@ -521,15 +1156,30 @@ Example: --commit abc123"
         )]
         commit: Option<String>,
 
-        /// Analyze diff between two refs (base..head).
+        /// Analyze diff between two refs (base..head or base...head).
         #[arg(
             long,
             value_name = "BASE..HEAD",
             long_help = "Analyze the diff between two git refs.\n\n\
-Example: --diff main..feature"
+BASE..HEAD compares the two trees directly. BASE...HEAD compares HEAD against the\n\
+merge base (git merge-base BASE HEAD), i.e. only the changes made on HEAD since it\n\
+forked, like a pull request diff. An empty side means HEAD. Unknown revisions are\n\
+reported as an INVALID_GIT_REF error item.\n\n\
+Examples: --diff main..feature, --diff main...HEAD"
         )]
         diff: Option<String>,
 
+        /// Only analyze changed files matching GLOB (can be repeated).
+        #[arg(
+            long,
+            value_name = "GLOB",
+            num_args = 1..,
+            long_help = "Restrict the changed-file set to paths matching the given globs\n\
+before dependency expansion. Impacted files outside the globs are still reported.\n\n\
+Examples: --paths 'src/backends/*' --paths '*.rs'"
+        )]
+        paths: Vec<String>,
+
         /// Maximum depth for transitive impact analysis.
         #[arg(
             long,
@@ -540,7 +1190,7 @@ Higher values find more distant impacts but may be slower."
         )]
         max_depth: usize,
 
-        /// Output format for impact (jsonl/json/summary/table).
+        /// Output format for impact (jsonl/json/summary/table/junit).
         #[arg(
             long = "impact-format",
             value_name = "FORMAT",
@@ -550,9 +1200,34 @@ Supported values:\n\
 - jsonl (default): single JSON line with full analysis\n\
 - json: pretty-printed JSON\n\
 - summary: human-readable summary\n\
-- table: ASCII table format"
+- table: ASCII table format\n\
+- junit: JUnit XML (one testcase per impacted file) for CI test reports"
         )]
         impact_format: String,
+
+        /// Fail the junit report when the risk score exceeds N.
+        #[arg(
+            long,
+            value_name = "N",
+            long_help = "Risk threshold for junit output.\n\n\
+The risk score is the number of downstream files (direct + transitive impacts).\n\
+When it exceeds N, a failing `impact.threshold` testcase is added to the report."
+        )]
+        max_risk: Option<usize>,
+
+        /// Fan-out thresholds for medium,high,critical severity (default 1,5,20).
+        #[arg(
+            long,
+            value_name = "M,H,C",
+            default_value = "1,5,20",
+            long_help = "Fan-out thresholds used to classify each changed file.\n\n\
+Fan-out is the number of files that transitively depend on the changed file.\n\
+A file is medium at >= M dependents, high at >= H and critical at >= C; anything\n\
+below M (e.g. a file nothing imports) is low. Severities appear in the jsonl\n\
+`severities` field, next to each changed file in the summary, and as a column in\n\
+the table (sorted most severe first)."
+        )]
+        severity_thresholds: crate::backends::impact::SeverityThresholds,
     },
 
     /// Higher-level workflows that combine multiple sources.
@@ -571,20 +1246,73 @@ Use these when you want prompt-ready evidence rather than raw matches."
         long_about = "Rebuild cached artifacts under .mise/ (e.g., files.jsonl, anchors.jsonl,\n\
 meta.json).\n\n\
 Use this to speed up repeated workflows or to snapshot workspace state.\n\n\
-Example:\n\
-  mise rebuild\n"
+Use --files-only or --anchors-only to regenerate a single artifact and leave the\n\
+other untouched; meta.json records per-artifact timestamps.\n\n\
+Use --incremental to re-parse anchors only in files whose mtime or size changed\n\
+since the last rebuild (meta.json keeps a per-file stamp map). A full rebuild\n\
+remains the default.\n\n\
+Examples:\n\
+  mise rebuild\n\
+  mise rebuild --anchors-only   # After editing anchor markers only\n\
+  mise rebuild --incremental    # Re-parse changed files only\n"
     )]
-    Rebuild,
+    Rebuild {
+        /// Only regenerate anchors.jsonl.
+        #[arg(long, conflicts_with = "files_only")]
+        anchors_only: bool,
+
+        /// Only regenerate files.jsonl.
+        #[arg(long)]
+        files_only: bool,
+
+        /// Reuse cached anchors of files whose mtime and size are unchanged.
+        #[arg(long)]
+        incremental: bool,
+    },
+
+    /// Inspect the .mise cache.
+    #[command(
+        long_about = "Inspect cached artifacts under .mise/ without rebuilding them.\n\n\
+Examples:\n\
+  mise cache status   # Is .mise/ up to date?\n"
+    )]
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommands,
+    },
 
     /// Check external dependencies and system status.
     #[command(
         long_about = "Check whether required/optional external tools are installed and\n\
 discoverable (e.g., rg, sg/ast-grep, watchexec).\n\n\
-Example:\n\
-  mise doctor\n"
+The first item summarizes readiness ({\"ready\":true,\"missing_required\":[],...});\n\
+each tool item carries {name, found, path, version, required} in `data`.\n\n\
+Examples:\n\
+  mise doctor\n\
+  mise doctor --format json   # For provisioning scripts\n"
     )]
     Doctor,
 
+    /// Print the JSON Schema of mise output.
+    #[command(
+        long_about = "Print a JSON Schema (draft 2020-12) describing mise output, derived from\n\
+the serde types, for validating output in CI or generating typed clients.\n\n\
+Targets:\n\
+- result-set (default): ResultSet/ResultItem, including every kind, confidence\n\
+  and source_mode value\n\
+- deps: the `data` payload of deps items (file, cycle and directory shapes)\n\
+- impact: impact --impact-format json\n\
+- stats: flow stats --stats-format json\n\n\
+Examples:\n\
+  mise schema > mise.schema.json\n\
+  mise schema impact\n"
+    )]
+    Schema {
+        /// Output to describe (result-set/deps/impact/stats).
+        #[arg(default_value = "result-set", value_name = "TARGET")]
+        target: String,
+    },
+
     /// Execute multiple commands concurrently with structured output.
     #[command(
         long_about = r#"Execute multiple independent misec commands (or external commands) in parallel.
@@ -596,7 +1324,7 @@ This is particularly useful for:
 - Batch anchor operations
 - Combined analysis workflows
 
-Task definition formats (JSON):
+Task definition formats (JSON, or YAML with the same shapes):
 - Single task: {"id": "name", "cmd": "command"}
 - Task array: [{"id": "t1", "cmd": "c1"}, {"id": "t2", "cmd": "c2"}]
 - Task set with groups for organization
@@ -606,9 +1334,23 @@ Task properties:
 - cmd: Shell command to execute (required)
 - cwd: Working directory (relative to root)
 - env: Environment variables {"KEY": "value"}
+- env_file: Dotenv file (KEY=VALUE lines, relative to cwd) merged under env; a
+  missing file fails the task
 - timeout: Timeout in seconds (default: 300)
-- depends_on: Array of task IDs to wait for
+- retries: Extra attempts after a failure (default: 0); results report `attempts`
+- retry_delay_ms: Delay between attempts in milliseconds (default: 0)
+- depends_on: Array of task IDs to wait for (any list order; a task is skipped only
+  if a dependency failed, and dependency cycles are reported as TASK_CYCLE)
 - tags: Array of tags for filtering
+- matrix: {"key": ["v1", "v2"], ...} expands the task into the cartesian product of
+  values, substituting ${matrix.key} into cmd/cwd (ids become id-v1-..., keys sorted);
+  every key needs at least one value
+
+Task set hooks (or --on-success/--on-failure):
+- on_success: command run once after all tasks if none failed or were skipped
+- on_failure: command run once after all tasks otherwise
+  Both see MISE_TOTAL, MISE_SUCCEEDED, MISE_FAILED and MISE_SKIPPED; a failing
+  hook makes misec exit non-zero, but is not counted in the summary.
 
 Output management:
 - Default output dir: <workspace>/rundata/
@@ -645,11 +1387,18 @@ Examples (misec parallel analysis):
       {"id":"stats","cmd":"misec flow stats","depends_on":["rebuild"]}
     ]'
 
+    # Matrix expansion: one definition, four tasks (find-src-TODO, ...)
+    misec run --json '{"id":"find","cmd":"misec match ${matrix.pattern} ${matrix.dir}",
+      "matrix":{"dir":["src","tests"],"pattern":["TODO","FIXME"]}}'
+
     # From task file with custom output directory
     misec run --file analysis-tasks.json --output ./analysis-results
 
     # Preview execution plan
     misec run --json '...' --dry-run
+
+    # Notify when everything is done
+    misec run --file tasks.json --on-failure 'echo "$MISE_FAILED of $MISE_TOTAL failed"'
 "#
     )]
     Run {
@@ -663,12 +1412,13 @@ Accepts single task, array of tasks, or full task set with groups."
         )]
         json: Option<String>,
 
-        /// Path to JSON file with task definitions.
+        /// Path to JSON or YAML file with task definitions.
         #[arg(
             long,
             value_name = "FILE",
             conflicts_with = "json",
-            long_help = "Path to a JSON file containing task definitions."
+            long_help = "Path to a JSON or YAML file containing task definitions.\n\n\
+.yml/.yaml files are parsed as YAML first, other files as JSON first."
         )]
         file: Option<std::path::PathBuf>,
 
@@ -679,7 +1429,7 @@ Accepts single task, array of tasks, or full task set with groups."
             default_value = "0",
             value_name = "N",
             long_help = "Maximum number of tasks to run in parallel.\n\n\
-Default (0) uses the number of CPU cores."
+Default (0) uses the global --jobs value, or the number of CPU cores if unset."
         )]
         parallel: usize,
 
@@ -735,6 +1485,25 @@ Tasks without any tags are excluded when filtering."
 Shows all tasks that would be executed and their configuration."
         )]
         dry_run: bool,
+
+        /// Command to run after all tasks if none failed.
+        #[arg(
+            long,
+            value_name = "CMD",
+            long_help = "Shell command run once after all tasks when none failed or were skipped.\n\n\
+Overrides the task set's on_success. The summary is exposed as MISE_TOTAL,\n\
+MISE_SUCCEEDED, MISE_FAILED and MISE_SKIPPED."
+        )]
+        on_success: Option<String>,
+
+        /// Command to run after all tasks if any failed.
+        #[arg(
+            long,
+            value_name = "CMD",
+            long_help = "Shell command run once after all tasks when any failed or were skipped.\n\n\
+Overrides the task set's on_failure. Receives the same MISE_* variables as --on-success."
+        )]
+        on_failure: Option<String>,
     },
 
     /// Watch for file changes and run commands (requires 'watch' feature)
@@ -825,13 +1594,19 @@ result per match.\n\n\
 Examples:\n\
   mise anchor list\n\
   mise anchor list --tag chapter\n\
-  mise anchor list --brief\n"
+  mise anchor list --brief\n\
+  mise anchor list --since main --tag chapter\n"
     )]
     List {
         /// Only include anchors containing this tag.
         #[arg(long, value_name = "TAG")]
         tag: Option<String>,
 
+        /// Only parse files changed relative to this git ref (`git diff --name-only <REF>`).
+        /// Fails if ROOT is not inside a git repository.
+        #[arg(long, value_name = "REF")]
+        since: Option<String>,
+
         /// Brief mode: only emit metadata (id, path, tags, range) without content.
         /// Use this for initial exploration to save tokens, then use `anchor get <id>` for details.
         #[arg(long)]
@@ -858,31 +1633,128 @@ Examples:\n\
             long_help = "Include up to N neighbor anchors that share tags with the target anchor.\n\
 These neighbors are returned with lower confidence to signal that they are contextual."
         )]
-        with_neighbors: Option<usize>,
+        with_neighbors: Option<usize>,
+
+        /// Strip anchor marker lines from the returned excerpts.
+        #[arg(
+            long,
+            long_help = "Remove Q:begin/Q:end marker lines (such as those of nested anchors) from the\n\
+excerpt of the target anchor and any neighbors, leaving only their content.\n\
+The range still covers the full marked region.\n\n\
+Example: mise anchor get intro --body-only --with-neighbors 2"
+        )]
+        body_only: bool,
+    },
+
+    /// Get every anchor carrying a tag, with full content.
+    #[command(
+        long_about = "Find all anchors whose tags include TAG and emit each one with its full\n\
+content, sorted by path then line (e.g. to gather every chapter).\n\n\
+This is an alias for `anchor list --tag TAG` (without --brief), named as the tag\n\
+counterpart of `anchor get`; both produce the same output.\n\n\
+Examples:\n\
+  mise anchor get-by-tag chapter\n\
+  mise anchor get-by-tag chapter --format md\n"
+    )]
+    GetByTag {
+        /// Tag to match.
+        #[arg(value_name = "TAG")]
+        tag: String,
+    },
+
+    /// Search for a regex only inside anchor bodies.
+    #[command(
+        long_about = "Parse anchors under ROOT and run a regex over each anchor body, ignoring\n\
+text between anchors. Each hit is emitted as a match item at its file line, with the\n\
+anchor id, tags and the line offset within the anchor body in data.\n\n\
+Examples:\n\
+  mise anchor grep unsafe --tag api\n\
+  mise anchor grep 'TODO|FIXME' --scope src\n"
+    )]
+    Grep {
+        /// Regex pattern to search for.
+        #[arg(value_name = "PATTERN")]
+        pattern: String,
+
+        /// Only search anchors containing this tag.
+        #[arg(long, value_name = "TAG")]
+        tag: Option<String>,
+
+        /// Only search anchors in files under this path.
+        #[arg(
+            long,
+            value_name = "PATH",
+            long_help = "Only search anchors in files under this path (relative to ROOT).\n\n\
+If omitted, all anchors under ROOT are searched."
+        )]
+        scope: Option<PathBuf>,
     },
 
     /// Lint anchor markers and report issues.
     #[command(
         long_about = "Validate anchor marker pairing, duplicate IDs, and suspicious anchors\n\
-(empty/oversized).\n\n\
+(empty/oversized, or partially overlapping: OVERLAPPING_ANCHORS).\n\n\
+Anchors whose begin marker records a hash (written by `anchor mark`) are checked\n\
+against their current body: ANCHOR_CONTENT_CHANGED flags edits made since marking.\n\
+Nested marker lines are not part of the hashed body, and `anchor mark`, `replace` and\n\
+`move` keep the hashes they affect current. Anchors without a hash attribute are not\n\
+checked; --update-hashes accepts reviewed edits as the new baseline.\n\n\
+Stray markers are always errors, reported with their line and ID (also in data):\n\
+  ORPHANED_END_MARKER    end marker with no preceding unmatched begin of the same ID\n\
+  DANGLING_BEGIN_MARKER  begin marker that is never closed\n\
+Each also lists its older code (UNPAIRED_END / UNPAIRED_BEGIN) as a second error.\n\n\
 This command emits issues as error result items, suitable for CI gating.\n\n\
+Use --rules to tune checks per project. The file (TOML, or JSON by .json extension)\n\
+maps rule names to a severity (off/info/warning/error) and parameters:\n\
+  duplicate-id = { severity = \"error\" }\n\
+  empty        = { severity = \"warning\" }\n\
+  oversized    = { severity = \"warning\", max_lines = 500 }\n\
+  overlapping  = { severity = \"error\" }\n\
+  id-pattern   = { severity = \"error\", pattern = \"^[a-z0-9_.-]+$\" }\n\
+  token-budget = { severity = \"warning\", max_tokens = 2000 }\n\
+  content-changed = { severity = \"warning\" }\n\
+Omitted rules keep their defaults (id-pattern and token-budget are off).\n\
+Any error-severity issue (default or --rules severity) exits with status 1.\n\n\
 Example:\n\
-  mise anchor lint\n"
+  mise anchor lint\n\
+  mise anchor lint --rules .mise-lint.toml\n\
+  mise anchor lint --update-hashes\n"
     )]
-    Lint,
+    Lint {
+        /// Lint rules file (TOML or JSON).
+        #[arg(long, value_name = "FILE")]
+        rules: Option<PathBuf>,
+
+        /// Re-record content hashes of changed anchors before linting.
+        #[arg(
+            long,
+            long_help = "Rewrite the `hash=` of every anchor whose body changed since it was marked,\n\
+then lint. Use it after reviewing the edits reported as ANCHOR_CONTENT_CHANGED:\n\
+the current bodies become the new baseline. Each rewritten anchor is listed as an\n\
+ANCHOR_HASH_UPDATED info item. Anchors marked without a hash are left alone."
+        )]
+        update_hashes: bool,
+    },
 
     /// Mark a text block with anchor markers (insert begin/end tags).
     #[command(
         long_about = "Insert anchor markers around a specified line range in a file.\n\
 This is useful for AI agents to quickly mark sections of code or documentation.\n\n\
 The markers follow the format:\n\
-  <!--Q:begin id=xxx tags=a,b v=1-->\n\
+  <!--Q:begin id=xxx tags=a,b v=1 hash=<xxh3>-->\n\
   ...content...\n\
   <!--Q:end id=xxx-->\n\n\
+The hash records the marked body so `anchor lint` can report later edits.\n\n\
+In code files the comment syntax follows the extension: `# Q:begin id=xxx v=1` for\n\
+Python/shell/Ruby/YAML/TOML, `// Q:begin id=xxx v=1` for Rust/JS/TS/Go/C-like files.\n\n\
+A range may wrap existing anchors (they become nested) or sit inside an anchor body.\n\
+A range that cuts through an existing anchor's markers fails with ANCHOR_CONFLICT\n\
+naming that anchor.\n\n\
 Examples:\n\
   mise anchor mark README.md --start 10 --end 25 --id intro\n\
   mise anchor mark src/main.rs --start 1 --end 50 --id main.entry --tags entry,core\n\
-  mise anchor mark doc.md --start 5 --end 10 --id sec1 --dry-run\n"
+  mise anchor mark doc.md --start 5 --end 10 --id sec1 --dry-run\n\
+  mise anchor mark src/main.rs --start 1 --end 50 --auto-id\n"
     )]
     Mark {
         /// File path to mark (relative to ROOT).
@@ -897,9 +1769,25 @@ Examples:\n\
         #[arg(long, value_name = "LINE")]
         end: u32,
 
-        /// Anchor ID.
-        #[arg(long, value_name = "ID")]
-        id: String,
+        /// Anchor ID (default: derived from path and line range).
+        #[arg(
+            long,
+            value_name = "ID",
+            long_help = "Anchor ID.\n\n\
+If omitted (and --auto-id is not set), a stable ID is derived from the normalized\n\
+path and line range, e.g. `src_main_rs.L10-25`, with a numeric suffix if that ID is\n\
+already used in the file. The generated ID is reported in the result."
+        )]
+        id: Option<String>,
+
+        /// Generate the anchor ID when --id is omitted.
+        #[arg(
+            long,
+            long_help = "Derive the anchor ID from the file stem plus a slug of the first non-empty\n\
+line of the marked range (e.g. `main.entry-point`). A numeric suffix is appended\n\
+if the ID is already used by an existing anchor. Ignored when --id is given."
+        )]
+        auto_id: bool,
 
         /// Tags for categorization (comma-separated).
         #[arg(long, value_name = "TAGS", value_delimiter = ',')]
@@ -929,11 +1817,15 @@ JSON input format (array or object with 'marks' field):\n\
   ]\n\n\
 Or:\n\
   {\"marks\": [{...}, {...}]}\n\n\
-Marks in the same file are processed from bottom to top to avoid line shifts.\n\n\
+Marks without an \"id\" get a stable `path.Lstart-end` ID (e.g. `src_main_rs.L5-20`).\n\
+Marks in the same file are processed from bottom to top to avoid line shifts.\n\
+Line numbers refer to the original file; a mark that encloses other marks from the\n\
+same batch is widened to nest them.\n\n\
 Examples:\n\
   mise anchor batch --json '[{\"path\":\"a.md\",\"start_line\":1,\"end_line\":5,\"id\":\"a\"}]'\n\
   mise anchor batch --file marks.json\n\
-  mise anchor batch --json '...' --dry-run\n"
+  mise anchor batch --json '...' --dry-run\n\
+  mise anchor batch --file marks.json --auto-id  # Generate missing ids\n"
     )]
     Batch {
         /// JSON string with mark specifications.
@@ -947,6 +1839,113 @@ Examples:\n\
         /// Preview changes without writing to files.
         #[arg(long)]
         dry_run: bool,
+
+        /// Generate IDs for marks that omit the "id" field.
+        #[arg(
+            long,
+            long_help = "For marks without an \"id\", derive one from the file stem plus a slug of the\n\
+first non-empty line of the range, unique against existing anchors and the batch."
+        )]
+        auto_id: bool,
+    },
+
+    /// Replace the content between an anchor's markers.
+    #[command(
+        long_about = "Swap the text between an anchor's begin and end markers in a file.\n\
+The marker lines (id, tags, version) are left untouched.\n\n\
+Examples:\n\
+  mise anchor replace README.md --id intro --body 'New intro text'\n\
+  mise anchor replace src/main.rs --id main.entry --body-file entry.rs --dry-run\n"
+    )]
+    Replace {
+        /// File path containing the anchor (relative to ROOT).
+        #[arg(value_name = "FILE")]
+        file: String,
+
+        /// Anchor ID whose content is replaced.
+        #[arg(long, value_name = "ID")]
+        id: String,
+
+        /// New content for the anchor body.
+        #[arg(
+            long,
+            value_name = "TEXT",
+            conflicts_with = "body_file",
+            required_unless_present = "body_file"
+        )]
+        body: Option<String>,
+
+        /// Read the new anchor body from a file.
+        #[arg(long, value_name = "PATH")]
+        body_file: Option<PathBuf>,
+
+        /// Preview changes without writing to file.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Rename an anchor ID across the whole workspace.
+    #[command(
+        long_about = "Rewrite the begin and end markers of anchor OLD_ID to NEW_ID in every file\n\
+under ROOT, preserving tags and version. Emits one result per file touched.\n\n\
+Refuses to run if NEW_ID is already used by another anchor, unless --force is given.\n\n\
+Examples:\n\
+  mise anchor rename sample.main core.entry\n\
+  mise anchor rename sample.main core.entry --dry-run\n"
+    )]
+    Rename {
+        /// Current anchor ID.
+        #[arg(value_name = "OLD_ID")]
+        old_id: String,
+
+        /// New anchor ID.
+        #[arg(value_name = "NEW_ID")]
+        new_id: String,
+
+        /// Rename even if NEW_ID already exists.
+        #[arg(long)]
+        force: bool,
+
+        /// Preview changes without writing files.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Move an anchored block from one file to another.
+    #[command(
+        long_about = "Cut anchor ID (begin marker, body and end marker) out of SRC_FILE and insert the\n\
+block into DEST_FILE, starting at line --at or appended at the end.\n\
+Anchors nested inside the block move with it; DEST_FILE is created if missing.\n\n\
+Both edits are computed before anything is written: a missing anchor, an ID already\n\
+present in DEST_FILE or an out-of-range --at leaves both files untouched.\n\
+Emits one result per file (data.role: source/destination).\n\n\
+Marker lines are rewritten in DEST_FILE's comment syntax (e.g. `// Q:begin` in a .rs\n\
+file becomes `<!--Q:begin-->` in .md), keeping id, tags, v and hash; the body is\n\
+moved unchanged.\n\n\
+Examples:\n\
+  mise anchor move docs/old.md --id intro --to docs/new.md\n\
+  mise anchor move docs/old.md --id intro --to docs/new.md --at 5 --dry-run\n"
+    )]
+    Move {
+        /// File containing the anchor (relative to ROOT).
+        #[arg(value_name = "SRC_FILE")]
+        file: String,
+
+        /// Anchor ID to move.
+        #[arg(long, value_name = "ID")]
+        id: String,
+
+        /// Destination file (relative to ROOT).
+        #[arg(long, value_name = "DEST_FILE")]
+        to: String,
+
+        /// Line in the destination where the block starts (default: append).
+        #[arg(long, value_name = "LINE")]
+        at: Option<u32>,
+
+        /// Preview changes without writing files.
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Remove anchor markers from a file (unmark).
@@ -972,6 +1971,44 @@ Examples:\n\
     },
 }
 
+#[derive(Subcommand, Debug)]
+pub enum CacheCommands {
+    /// Report cache age, sizes, and files modified since the last rebuild.
+    #[command(
+        long_about = "Load .mise/meta.json, count cached files and anchors, and scan the workspace\n\
+for files modified after the cache was built (reads would miss them).\n\n\
+Emits one item for .mise/meta.json with data {cache_version, generated_at,\n\
+files_generated_at, anchors_generated_at, age_ms, cached_files, cached_anchors,\n\
+workspace_files, newer_files, stale}. The reference time is the older of the two\n\
+artifacts. Without a cache, emits a CACHE_NOT_FOUND error item.\n\n\
+Example:\n\
+  mise cache status --format json\n"
+    )]
+    Status,
+
+    /// Compare two files.jsonl snapshots.
+    #[command(
+        long_about = "Load two copies of .mise/files.jsonl (e.g. saved before and after some work)\n\
+and report what changed between them, one item per file sorted by path, with data\n\
+{change, old, new, by}: change is `added`, `removed` or `modified`; old/new carry size\n\
+and mtime_ms. Files are compared by content hash when both records have one (from\n\
+meta.hash or `scan --hash`), otherwise by mtime, then size (reported in `by`).\n\
+Unchanged files are omitted.\n\n\
+Example:\n\
+  cp .mise/files.jsonl /tmp/before.jsonl && mise rebuild\n\
+  mise cache diff /tmp/before.jsonl .mise/files.jsonl\n"
+    )]
+    Diff {
+        /// Older snapshot.
+        #[arg(value_name = "OLD")]
+        old: PathBuf,
+
+        /// Newer snapshot.
+        #[arg(value_name = "NEW")]
+        new: PathBuf,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 pub enum FlowCommands {
     /// Gather prompt-ready evidence for a writing task.
@@ -980,8 +2017,10 @@ pub enum FlowCommands {
 2) related anchors by shared tags (medium confidence)\n\
 3) keyword-based ripgrep matches (low confidence)\n\n\
 Use this to quickly assemble citations and context for a doc/PR/issue response.\n\n\
-Example:\n\
-  mise flow writing --anchor intro --max-items 12\n")]
+Examples:\n\
+  mise flow writing --anchor intro --max-items 12\n\
+  mise flow writing --anchor ch01 --keywords 8 --min-word-len 3\n\
+  mise flow writing --anchor intro --no-search   # Anchor evidence only\n")]
     Writing {
         /// Primary anchor ID.
         #[arg(long, value_name = "ID")]
@@ -990,6 +2029,18 @@ Example:\n\
         /// Maximum number of items to return.
         #[arg(long, default_value = "10", value_name = "N")]
         max_items: usize,
+
+        /// Number of keywords to extract from the primary anchor for searching.
+        #[arg(long, default_value = "5", value_name = "N")]
+        keywords: usize,
+
+        /// Minimum length of English keywords.
+        #[arg(long, default_value = "4", value_name = "N")]
+        min_word_len: usize,
+
+        /// Skip the keyword search (step 3); use anchor evidence only.
+        #[arg(long)]
+        no_search: bool,
     },
 
     /// Pack anchors and files into a context bundle for AI.
@@ -999,11 +2050,13 @@ This is useful for preparing precise, controlled context for AI assistants.\n\n\
 Features:\n\
 - Combine multiple anchors and files\n\
 - Token budget control with --max-tokens\n\
-- Priority-based truncation (by confidence or order)\n\n\
+- Priority-based truncation (by confidence or order)\n\
+- Tag-driven pinning/deprioritization of anchors\n\n\
 Examples:\n\
   mise flow pack --anchors cli.scan,core.model\n\
   mise flow pack --anchors intro --files README.md Cargo.toml\n\
-  mise flow pack --anchors api.handler --max-tokens 8000\n"
+  mise flow pack --anchors api.handler --max-tokens 8000\n\
+  mise flow pack --anchors a,b,c --max-tokens 4000 --pin-tag critical\n"
     )]
     Pack {
         /// Anchor IDs to include (comma-separated).
@@ -1022,17 +2075,20 @@ Example: --anchors cli.scan,core.model,api.handler"
             value_name = "FILES",
             num_args = 0..,
             long_help = "File paths to include in the pack.\n\n\
-Example: --files README.md src/main.rs"
+Append :START-END to include only those lines (1-based, inclusive); the item's\n\
+range records the slice. Plain paths include the whole file.\n\n\
+Example: --files README.md src/main.rs:10-60"
         )]
         files: Vec<String>,
 
-        /// Maximum tokens to include (estimated as chars/4).
+        /// Maximum tokens to include (counted with --model).
         #[arg(
             long,
             value_name = "N",
             long_help = "Maximum number of tokens to include.\n\n\
-Tokens are estimated as characters / 4. When over budget, items are\n\
-truncated based on the priority mode."
+Tokens are counted with the BPE encoding selected by --model (cl100k by\n\
+default; `heuristic` for the fast chars/4 estimate). When over budget, items\n\
+are truncated based on the priority mode."
         )]
         max_tokens: Option<usize>,
 
@@ -1048,16 +2104,100 @@ Supported values:\n\
         )]
         priority: String,
 
+        /// Keep anchors with this tag when truncating (can be repeated).
+        #[arg(
+            long,
+            value_name = "TAG",
+            long_help = "Boost anchors carrying this tag to the highest priority so they\n\
+survive truncation when over budget.\n\n\
+Example: --pin-tag critical --pin-tag api"
+        )]
+        pin_tag: Vec<String>,
+
+        /// Drop anchors with this tag first when truncating (can be repeated).
+        #[arg(
+            long,
+            value_name = "TAG",
+            long_help = "Lower anchors carrying this tag to the lowest priority so they are\n\
+dropped first when over budget. --pin-tag wins if an anchor matches both.\n\n\
+Example: --deprioritize-tag draft"
+        )]
+        deprioritize_tag: Vec<String>,
+
+        /// Replace regex matches in emitted content with [REDACTED] (can be repeated).
+        #[arg(
+            long,
+            value_name = "REGEX",
+            long_help = "Scrub content matching this regex before it is emitted, replacing each\n\
+match with [REDACTED]. Redaction happens before token budgeting.\n\n\
+Example: --redact '[\\w.]+@corp\\.com' --redact 'https://internal\\.[^ ]+'"
+        )]
+        redact: Vec<String>,
+
+        /// Also redact built-in secret patterns (API keys, tokens, private keys).
+        #[arg(
+            long,
+            long_help = "Apply a built-in set of redaction patterns for common secrets:\n\
+private key blocks, AWS access keys, GitHub/Slack tokens, sk- API keys,\n\
+bearer tokens and key/secret/token/password assignments."
+        )]
+        redact_defaults: bool,
+
+        /// Remove anchors that are also inside a packed file (off/anchor/file).
+        #[arg(
+            long,
+            value_name = "MODE",
+            default_value = "off",
+            long_help = "De-duplicate anchors whose lines are fully contained in a file passed via\n\
+--files (same path, file range covering the anchor range).\n\n\
+Supported values:\n\
+- off (default): include both copies\n\
+- anchor: drop the anchor, keep the full file\n\
+- file: keep the anchor, replace its lines in the file with an omission note\n\n\
+De-duplication runs before token budgeting, so --stats and --max-tokens\n\
+count the de-duplicated content."
+        )]
+        dedup: String,
+
         /// Show pack statistics on stderr.
         #[arg(
             long,
-            long_help = "Print pack statistics (item count, token estimate) to stderr."
+            long_help = "Print pack statistics (item count, token estimate, redaction count) to stderr."
         )]
         stats: bool,
 
+        /// Wrap the packed output in a prompt template file.
+        #[arg(
+            long,
+            value_name = "PATH",
+            long_help = "Wrap the packed output in a prompt template and print the result to stdout.\n\n\
+Supported placeholders:\n\
+- {{context}}: the rendered pack body (in the selected --format)\n\
+- {{file_count}}: number of distinct files in the pack\n\
+- {{token_total}}: estimated token count of the pack\n\n\
+Any other {{placeholder}} is an error.\n\n\
+Example: --template prompts/review.md"
+        )]
+        template: Option<PathBuf>,
+
+        /// Pack output format (jsonl/markdown).
+        #[arg(
+            long = "pack-format",
+            value_name = "FORMAT",
+            default_value = "jsonl",
+            long_help = "Select the output format for the pack.\n\n\
+Supported values:\n\
+- jsonl (default): ResultSet output (respects --format)\n\
+- markdown (md): one Markdown document for pasting into a chat; each item is a\n\
+  `## path (anchor id)` heading with its content in a language-tagged code fence.\n\
+  Items cut by --max-tokens end with a `> [truncated]` note."
+        )]
+        pack_format: String,
+
         /// Token model for accurate counting (cl100k/o200k/gpt4/gpt4o/gpt35turbo/claude3/heuristic).
         #[arg(
             long,
+            visible_alias = "token-model",
             value_name = "MODEL",
             default_value = "cl100k",
             long_help = "Token model for accurate counting.\n\n\
@@ -1080,6 +2220,7 @@ Provides:\n\
 - Total characters, words, and lines\n\
 - CJK character count (for Chinese/Japanese/Korean)\n\
 - Estimated token count (smart algorithm for mixed content)\n\
+- Estimated reading time (--wpm/--cpm)\n\
 - Anchor statistics by tag\n\
 - Top files by size\n\n\
 Examples:\n\
@@ -1088,6 +2229,7 @@ Examples:\n\
   mise flow stats --stats-format json       # Full JSON output\n\
   mise flow stats --stats-format table      # Markdown table\n\
   mise flow stats --scope docs --exts md,txt\n\
+  mise flow stats --exclude 'drafts/*'      # Skip drafts\n\
   mise flow stats --top 20                  # Show top 20 files\n"
     )]
     Stats {
@@ -1100,6 +2242,9 @@ Example: --scope docs"
         )]
         scope: Option<std::path::PathBuf>,
 
+        #[command(flatten)]
+        filter: GlobFilterArgs,
+
         /// File extensions to include (comma-separated).
         #[arg(
             long,
@@ -1134,9 +2279,40 @@ Supported values:\n\
         )]
         top: usize,
 
+        /// Reading speed for English text, in words per minute.
+        #[arg(
+            long,
+            default_value = "200",
+            value_name = "N",
+            value_parser = clap::value_parser!(u32).range(1..),
+            long_help = "English reading speed (words per minute) used for the reading-time estimate."
+        )]
+        wpm: u32,
+
+        /// Reading speed for CJK text, in characters per minute.
+        #[arg(
+            long,
+            default_value = "400",
+            value_name = "N",
+            value_parser = clap::value_parser!(u32).range(1..),
+            long_help = "CJK reading speed (characters per minute) used for the reading-time estimate.\n\n\
+Example: --cpm 300"
+        )]
+        cpm: u32,
+
+        /// Count files that look binary instead of skipping them.
+        #[arg(
+            long,
+            long_help = "Files whose first 8 KB contain a NUL byte or are mostly invalid UTF-8 are\n\
+skipped as binary and listed in `binary_skipped` (json), as BINARY_SKIPPED notes\n\
+(standard) or as a count (summary/table). Set this flag to count them anyway."
+        )]
+        include_binary: bool,
+
         /// Token model for accurate counting (cl100k/o200k/gpt4/gpt4o/gpt35turbo/claude3/heuristic).
         #[arg(
             long,
+            visible_alias = "token-model",
             value_name = "MODEL",
             default_value = "cl100k",
             long_help = "Token model for accurate counting.\n\n\
@@ -1166,7 +2342,11 @@ Examples:\n\
   mise flow outline --tag chapter          # Filter by tag\n\
   mise flow outline --outline-format tree  # Tree view\n\
   mise flow outline --outline-format json  # JSON output\n\
-  mise flow outline --scope docs           # Limit to docs/\n"
+  mise flow outline --outline-format json-tree  # Nested JSON by containment\n\
+  mise flow outline --outline-format html  # HTML lists with line links\n\
+  mise flow outline --max-level 1          # Hide anchors nested deeper than level 1\n\
+  mise flow outline --scope docs           # Limit to docs/\n\
+  mise flow outline --include 'chapters/*' # Only files under chapters/\n"
     )]
     Outline {
         /// Limit outline to a subdirectory.
@@ -1178,6 +2358,9 @@ Example: --scope docs"
         )]
         scope: Option<std::path::PathBuf>,
 
+        #[command(flatten)]
+        filter: GlobFilterArgs,
+
         /// Filter anchors by tag.
         #[arg(
             long,
@@ -1198,7 +2381,7 @@ Example: --exts md,txt"
         )]
         exts: Vec<String>,
 
-        /// Output format (markdown/json/tree/standard).
+        /// Output format (markdown/json/json-tree/tree/html/standard).
         #[arg(
             long = "outline-format",
             value_name = "FORMAT",
@@ -1206,15 +2389,47 @@ Example: --exts md,txt"
             long_help = "Select the output format for outline.\n\n\
 Supported values:\n\
 - markdown (default): Markdown document\n\
-- json: full JSON object\n\
+- json: full JSON object (flat items list)\n\
+- json-tree: JSON object with contained anchors nested under `children`\n\
 - tree: ASCII tree view\n\
+- html: HTML fragment of nested <ul>/<li> lists linking to path#Lstart\n\
 - standard: ResultSet format"
         )]
         outline_format: String,
 
+        /// Hide anchors nested deeper than this level.
+        #[arg(
+            long,
+            value_name = "N",
+            long_help = "Only show outline items whose nesting level is at most N, like limiting\n\
+the heading depth of a table of contents. Level 0 is the top level.\n\n\
+Totals still count every anchor unless --totals-visible-only is set.\n\n\
+Example: --max-level 1"
+        )]
+        max_level: Option<usize>,
+
+        /// Compute totals from the shown items only (with --max-level).
+        #[arg(
+            long,
+            requires = "max_level",
+            long_help = "Make the chars/words/tokens totals cover only the items left after\n\
+--max-level instead of every anchor."
+        )]
+        totals_visible_only: bool,
+
+        /// Read files that look binary instead of skipping them.
+        #[arg(
+            long,
+            long_help = "Files whose first 8 KB contain a NUL byte or are mostly invalid UTF-8 are\n\
+skipped as binary and listed in `binary_skipped` (json) or as BINARY_SKIPPED notes\n\
+(standard); markdown/tree/html print a note on stderr. Set this flag to parse them anyway."
+        )]
+        include_binary: bool,
+
         /// Token model for accurate counting (cl100k/o200k/gpt4/gpt4o/gpt35turbo/claude3/heuristic).
         #[arg(
             long,
+            visible_alias = "token-model",
             value_name = "MODEL",
             default_value = "cl100k",
             long_help = "Token model for accurate counting.\n\n\
@@ -1233,12 +2448,65 @@ Supported values:\n\
 
 /// Run the CLI with parsed arguments
 pub fn run(cli: Cli) -> Result<()> {
+    // Start the watchdog first so the budget covers the whole command
+    if let Some(ms) = cli.time_budget {
+        crate::core::budget::start_watchdog(ms);
+    }
+
     // Parse output format
     let format: OutputFormat = cli.format.parse().unwrap_or_default();
-    let render_config = RenderConfig::with_pretty(format, cli.pretty);
+    let render_config = RenderConfig::with_pretty(format, cli.pretty)
+        .with_max_total_bytes(cli.max_total_bytes)
+        .with_color(!cli.no_color && std::io::stdout().is_terminal())
+        .with_md_flat(cli.md_flat)
+        .with_ascii(cli.ascii)
+        .with_min_confidence(cli.min_confidence)
+        .with_stream(cli.stream);
+
+    if !cli.json_errors {
+        return dispatch(cli, format, render_config);
+    }
+
+    if let Err(e) = dispatch(cli, format, render_config) {
+        print!("{}", render_failure(&e, render_config));
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Render a command failure as a single COMMAND_FAILED error item for --json-errors
+fn render_failure(error: &anyhow::Error, render_config: RenderConfig) -> String {
+    let mut result_set = crate::core::model::ResultSet::new();
+    result_set.push(crate::core::model::ResultItem::error(
+        crate::core::model::MiseError::new("COMMAND_FAILED", format!("{:#}", error)),
+    ));
+    crate::core::render::Renderer::with_config(render_config).render(&result_set)
+}
+
+/// Validate global options and run the selected command
+fn dispatch(cli: Cli, format: OutputFormat, render_config: RenderConfig) -> Result<()> {
+    // Grouped output only makes sense where results carry line context
+    if format == OutputFormat::Grouped
+        && !matches!(
+            cli.command,
+            Commands::Match { .. }
+                | Commands::Ast { .. }
+                | Commands::Search { .. }
+                | Commands::Anchor {
+                    action: AnchorCommands::Grep { .. }
+                }
+        )
+    {
+        anyhow::bail!("--format rg is only supported by match, ast, search and anchor grep");
+    }
 
     // Get absolute root path
     let root = cli.root.canonicalize().unwrap_or(cli.root);
+    crate::core::paths::set_resolve_symlinks(cli.resolve_symlinks);
+    crate::cache::reader::set_verbose(cli.verbose);
+    crate::core::policy::set_deny_patterns(&root, &cli.deny)?;
+    crate::core::file_reader::set_encoding_override(cli.encoding.as_deref())?;
+    let jobs = cli.jobs;
 
     match cli.command {
         Commands::Scan {
@@ -1246,48 +2514,156 @@ pub fn run(cli: Cli) -> Result<()> {
             max_depth,
             hidden,
             no_ignore,
+            follow_symlinks,
             r#type,
-            include,
-            exclude,
+            filter,
+            minimal,
+            min_size,
+            max_size,
+            modified_within,
+            modified_before,
+            find_duplicates,
+            summary,
+            hash,
         } => {
             let options = crate::backends::scan::ScanOptions {
                 scope,
                 max_depth,
                 hidden,
                 ignore: !no_ignore,
-                file_type: r#type,
-                include,
-                exclude,
+                // Duplicate detection and summaries only look at files
+                file_type: if find_duplicates || summary {
+                    Some("file".to_string())
+                } else {
+                    r#type
+                },
+                filter: filter.try_into()?,
+                minimal,
+                min_size,
+                max_size,
+                find_duplicates,
+                summary,
+                modified_within,
+                modified_before,
+                follow_links: follow_symlinks,
+                hash,
             };
             crate::backends::scan::run_scan(&root, options, render_config)
         }
 
-        Commands::Find { pattern, scope } => crate::backends::scan::run_find(
+        Commands::Find {
+            pattern,
+            scope,
+            glob,
+            fuzzy,
+            exists,
+        } => crate::backends::scan::run_find(
             &root,
             pattern.as_deref(),
             scope.as_deref(),
+            if fuzzy {
+                crate::backends::scan::FindMode::Fuzzy
+            } else if glob {
+                crate::backends::scan::FindMode::Glob
+            } else {
+                crate::backends::scan::FindMode::Substring
+            },
+            exists,
             render_config,
         ),
 
         Commands::Extract {
             path,
             lines,
+            bytes,
+            from,
+            to,
+            context,
+            before,
+            after,
             max_bytes,
-        } => crate::backends::extract::run_extract(&root, &path, &lines, max_bytes, render_config),
+            merge_with_anchors,
+            include_binary,
+        } => {
+            let options = crate::backends::extract::ExtractOptions {
+                max_bytes,
+                merge_with_anchors,
+                include_binary,
+                context_before: before.or(context).unwrap_or(0),
+                context_after: after.or(context).unwrap_or(0),
+            };
+            match (bytes, lines, from, to) {
+                (Some(bytes), ..) => crate::backends::extract::run_extract_bytes(
+                    &root,
+                    &path,
+                    &bytes,
+                    &options,
+                    render_config,
+                ),
+                (None, Some(lines), ..) => crate::backends::extract::run_extract(
+                    &root,
+                    &path,
+                    &lines,
+                    &options,
+                    render_config,
+                ),
+                (None, None, Some(from), Some(to)) => {
+                    crate::backends::extract::run_extract_pattern(
+                        &root,
+                        &path,
+                        &from,
+                        &to,
+                        &options,
+                        render_config,
+                    )
+                }
+                _ => unreachable!("clap requires --lines, --bytes or --from/--to"),
+            }
+        }
 
         Commands::Anchor { action } => match action {
-            AnchorCommands::List { tag, brief } => {
-                crate::anchors::api::run_list(&root, tag.as_deref(), brief, render_config)
-            }
-            AnchorCommands::Get { id, with_neighbors } => {
-                crate::anchors::api::run_get(&root, &id, with_neighbors, render_config)
+            AnchorCommands::List { tag, since, brief } => crate::anchors::api::run_list(
+                &root,
+                tag.as_deref(),
+                since.as_deref(),
+                brief,
+                render_config,
+            ),
+            AnchorCommands::Get {
+                id,
+                with_neighbors,
+                body_only,
+            } => crate::anchors::api::run_get(&root, &id, with_neighbors, body_only, render_config),
+            AnchorCommands::GetByTag { tag } => {
+                crate::anchors::api::run_get_by_tag(&root, &tag, render_config)
             }
-            AnchorCommands::Lint => crate::anchors::lint::run_lint(&root, render_config),
+            AnchorCommands::Grep {
+                pattern,
+                tag,
+                scope,
+            } => crate::anchors::api::run_grep(
+                &root,
+                &pattern,
+                tag.as_deref(),
+                scope.as_deref(),
+                cli.explain,
+                render_config,
+            ),
+            AnchorCommands::Lint {
+                rules,
+                update_hashes,
+            } => crate::anchors::lint::run_lint(
+                &root,
+                rules.as_deref(),
+                update_hashes,
+                render_config,
+            ),
             AnchorCommands::Mark {
                 file,
                 start,
                 end,
                 id,
+                auto_id,
                 tags,
                 version,
                 dry_run,
@@ -1296,30 +2672,73 @@ pub fn run(cli: Cli) -> Result<()> {
                     path: file,
                     start_line: start,
                     end_line: end,
-                    id,
+                    id: id.unwrap_or_default(),
                     tags,
                     version,
                 };
-                crate::anchors::mark::run_mark(&root, &spec, dry_run, render_config)
+                crate::anchors::mark::run_mark(&root, &spec, dry_run, auto_id, render_config)
             }
             AnchorCommands::Batch {
                 json,
                 file,
                 dry_run,
+                auto_id,
             } => {
                 if let Some(json_str) = json {
-                    crate::anchors::mark::run_batch_mark(&root, &json_str, dry_run, render_config)
+                    crate::anchors::mark::run_batch_mark(
+                        &root,
+                        &json_str,
+                        dry_run,
+                        auto_id,
+                        render_config,
+                    )
                 } else if let Some(file_path) = file {
                     crate::anchors::mark::run_batch_mark_from_file(
                         &root,
                         &file_path,
                         dry_run,
+                        auto_id,
                         render_config,
                     )
                 } else {
                     anyhow::bail!("Either --json or --file must be provided")
                 }
             }
+            AnchorCommands::Replace {
+                file,
+                id,
+                body,
+                body_file,
+                dry_run,
+            } => {
+                let body = match (body, body_file) {
+                    (Some(body), _) => body,
+                    (None, Some(path)) => std::fs::read_to_string(&path)
+                        .with_context(|| format!("Failed to read body file: {}", path.display()))?,
+                    (None, None) => unreachable!("clap requires --body or --body-file"),
+                };
+                crate::anchors::mark::run_replace(&root, &file, &id, &body, dry_run, render_config)
+            }
+            AnchorCommands::Rename {
+                old_id,
+                new_id,
+                force,
+                dry_run,
+            } => crate::anchors::mark::run_rename(
+                &root,
+                &old_id,
+                &new_id,
+                force,
+                dry_run,
+                render_config,
+            ),
+            AnchorCommands::Move {
+                file,
+                id,
+                to,
+                at,
+                dry_run,
+            } => crate::anchors::mark::run_move(&root, &file, &id, &to, at, dry_run, render_config),
             AnchorCommands::Unmark { file, id, dry_run } => {
                 crate::anchors::mark::run_unmark(&root, &file, &id, dry_run, render_config)
             }
@@ -1335,6 +2754,13 @@ pub fn run(cli: Cli) -> Result<()> {
             max_count,
             ignore_case,
             word_regexp,
+            fixed_strings,
+            replace,
+            apply,
+            per_submatch,
+            multiline,
+            rg_threads,
+            exists,
         } => {
             let options = crate::backends::rg::MatchOptions {
                 include,
@@ -1344,6 +2770,13 @@ pub fn run(cli: Cli) -> Result<()> {
                 max_count,
                 ignore_case,
                 word_regexp,
+                threads: if rg_threads > 0 { rg_threads } else { jobs },
+                exists,
+                fixed_strings,
+                replace,
+                apply,
+                per_submatch,
+                multiline,
             };
             crate::backends::rg::run_match(&root, &pattern, &scope, options, render_config)
         }
@@ -1351,30 +2784,80 @@ pub fn run(cli: Cli) -> Result<()> {
         Commands::Ast {
             pattern,
             scope,
+            filter,
+            lang,
+            rewrite,
+            apply,
+            rule,
+        } => {
+            // With --rule there is no pattern, so the first positional is a scope
+            let mut scope = scope;
+            let pattern = match (&rule, pattern) {
+                (Some(_), Some(first)) => {
+                    scope.insert(0, PathBuf::from(first));
+                    String::new()
+                }
+                (_, pattern) => pattern.unwrap_or_default(),
+            };
+            let options = crate::backends::ast_grep::AstOptions {
+                filter: filter.try_into()?,
+                lang,
+                threads: jobs,
+                rewrite,
+                apply,
+                rule,
+            };
+            crate::backends::ast_grep::run_ast(&root, &pattern, &scope, options, render_config)
+        }
+
+        Commands::Search {
+            pattern,
+            ast_pattern,
+            scope,
             include,
             exclude,
             lang,
+            ignore_case,
         } => {
-            let options = crate::backends::ast_grep::AstOptions {
+            let options = crate::backends::search::SearchOptions {
+                ast_pattern,
                 include,
                 exclude,
                 lang,
+                ignore_case,
+                threads: jobs,
             };
-            crate::backends::ast_grep::run_ast(&root, &pattern, &scope, options, render_config)
+            crate::backends::search::run_search(&root, &pattern, &scope, options, render_config)
         }
 
         Commands::Deps {
             file,
+            filter,
             reverse,
+            show_imports,
+            depth,
+            group_by_dir,
+            cycles_only,
+            no_tests,
             deps_format,
             output,
         } => {
             let deps_fmt: crate::backends::deps::DepsFormat =
                 deps_format.parse().unwrap_or_default();
+            let options = crate::backends::deps::DepsOptions {
+                reverse,
+                show_imports,
+                group_by_dir,
+                depth,
+                cycles_only,
+                filter: filter.try_into()?,
+                no_tests,
+                jobs,
+            };
             crate::backends::deps::run_deps(
                 &root,
                 file.as_deref(),
-                reverse,
+                &options,
                 deps_fmt,
                 output.as_deref(),
                 render_config,
@@ -1385,32 +2868,58 @@ pub fn run(cli: Cli) -> Result<()> {
             staged,
             commit,
             diff,
+            paths,
             max_depth,
             impact_format,
+            max_risk,
+            severity_thresholds,
         } => {
             let impact_fmt: crate::backends::impact::ImpactFormat =
                 impact_format.parse().unwrap_or_default();
-            crate::backends::impact::run_impact(
-                &root,
+            let options = crate::backends::impact::ImpactOptions {
                 staged,
-                commit.as_deref(),
-                diff.as_deref(),
+                commit,
+                diff,
                 max_depth,
-                impact_fmt,
-                render_config,
-            )
+                format: impact_fmt,
+                max_risk,
+                severity_thresholds,
+                paths,
+                jobs,
+            };
+            crate::backends::impact::run_impact(&root, options, render_config)
         }
 
         Commands::Flow { action } => match action {
-            FlowCommands::Writing { anchor, max_items } => {
-                crate::flows::writing::run_writing(&root, &anchor, max_items, render_config)
+            FlowCommands::Writing {
+                anchor,
+                max_items,
+                keywords,
+                min_word_len,
+                no_search,
+            } => {
+                let options = crate::flows::writing::WritingOptions {
+                    max_items,
+                    keywords,
+                    min_word_len,
+                    search: !no_search,
+                    explain: cli.explain,
+                };
+                crate::flows::writing::run_writing(&root, &anchor, &options, render_config)
             }
             FlowCommands::Pack {
                 anchors,
                 files,
                 max_tokens,
                 priority,
+                pin_tag,
+                deprioritize_tag,
+                redact,
+                redact_defaults,
+                dedup,
                 stats,
+                template,
+                pack_format,
                 model,
             } => {
                 let pack_priority: crate::flows::pack::PackPriority =
@@ -1422,50 +2931,77 @@ pub fn run(cli: Cli) -> Result<()> {
                     max_tokens,
                     priority: pack_priority,
                     token_model,
+                    pin_tags: pin_tag,
+                    deprioritize_tags: deprioritize_tag,
+                    redact,
+                    redact_defaults,
+                    dedup: dedup.parse().unwrap_or_default(),
+                    min_confidence: render_config.min_confidence,
+                    explain: cli.explain,
                 };
-                crate::flows::pack::run_pack(&root, opts, stats, render_config)
+                let pack_fmt: crate::flows::pack::PackFormat =
+                    pack_format.parse().unwrap_or_default();
+                crate::flows::pack::run_pack(
+                    &root,
+                    opts,
+                    stats,
+                    template.as_deref(),
+                    pack_fmt,
+                    render_config,
+                )
             }
             FlowCommands::Stats {
                 scope,
+                filter,
                 exts,
                 stats_format,
                 top,
+                wpm,
+                cpm,
+                include_binary,
                 model,
             } => {
                 let stats_fmt: crate::flows::stats::StatsFormat =
                     stats_format.parse().unwrap_or_default();
                 let extensions = if exts.is_empty() { None } else { Some(exts) };
                 let token_model: TokenModel = model.parse().unwrap_or_default();
-                crate::flows::stats::run_stats(
-                    &root,
-                    scope.as_deref(),
+                let options = crate::flows::stats::StatsOptions {
+                    scope,
                     extensions,
-                    stats_fmt,
-                    top,
+                    top_n: top,
                     token_model,
-                    render_config,
-                )
+                    reading_rates: crate::flows::stats::ReadingRates { wpm, cpm },
+                    include_binary,
+                    filter: filter.try_into()?,
+                };
+                crate::flows::stats::run_stats(&root, options, stats_fmt, render_config)
             }
             FlowCommands::Outline {
                 scope,
+                filter,
                 tag,
                 exts,
                 outline_format,
+                max_level,
+                totals_visible_only,
+                include_binary,
                 model,
             } => {
                 let outline_fmt: crate::flows::outline::OutlineFormat =
                     outline_format.parse().unwrap_or_default();
                 let extensions = if exts.is_empty() { None } else { Some(exts) };
                 let token_model: TokenModel = model.parse().unwrap_or_default();
-                crate::flows::outline::run_outline(
-                    &root,
-                    scope.as_deref(),
-                    tag.as_deref(),
+                let options = crate::flows::outline::OutlineOptions {
+                    scope,
+                    tag,
                     extensions,
-                    outline_fmt,
                     token_model,
-                    render_config,
-                )
+                    max_level,
+                    totals_visible_only,
+                    include_binary,
+                    filter: filter.try_into()?,
+                };
+                crate::flows::outline::run_outline(&root, options, outline_fmt, render_config)
             }
         },
 
@@ -1479,15 +3015,19 @@ pub fn run(cli: Cli) -> Result<()> {
             timeout,
             tag,
             dry_run,
+            on_success,
+            on_failure,
         } => {
             let options = crate::backends::run::RunOptions {
-                max_parallel: parallel,
+                max_parallel: if parallel > 0 { parallel } else { jobs },
                 output_dir: output,
                 save_outputs: !no_save,
                 continue_on_error,
                 timeout,
                 filter_tag: tag,
                 dry_run,
+                on_success,
+                on_failure,
             };
             crate::backends::run::run_run(
                 &root,
@@ -1498,10 +3038,37 @@ pub fn run(cli: Cli) -> Result<()> {
             )
         }
 
-        Commands::Rebuild => crate::cache::store::run_rebuild(&root, render_config),
+        Commands::Rebuild {
+            anchors_only,
+            files_only,
+            incremental,
+        } => {
+            use crate::cache::store::RebuildTarget;
+            let target = if anchors_only {
+                RebuildTarget::AnchorsOnly
+            } else if files_only {
+                RebuildTarget::FilesOnly
+            } else {
+                RebuildTarget::All
+            };
+            crate::cache::store::run_rebuild(&root, target, incremental, render_config)
+        }
+
+        Commands::Cache { action } => match action {
+            CacheCommands::Status => crate::cache::store::run_status(&root, render_config),
+            CacheCommands::Diff { old, new } => {
+                crate::cache::diff::run_diff(&old, &new, render_config)
+            }
+        },
 
         Commands::Doctor => crate::backends::doctor::run_doctor(render_config),
 
+        Commands::Schema { target } => {
+            let target: crate::core::schema::SchemaTarget =
+                target.parse().map_err(anyhow::Error::msg)?;
+            crate::core::schema::run_schema(target)
+        }
+
         #[cfg(feature = "watch")]
         Commands::Watch {
             cmd,
@@ -1526,3 +3093,59 @@ pub fn run(cli: Cli) -> Result<()> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::model::{Range, ResultItem};
+    use crate::core::render::JsonlStream;
+    use anyhow::anyhow;
+    use serde_json::Value;
+
+    #[test]
+    fn test_render_failure_envelope() {
+        let error = anyhow!("No such file").context("Failed to read src/a.rs");
+
+        let json = render_failure(&error, RenderConfig::new(OutputFormat::Json));
+        let items: Value = serde_json::from_str(&json).unwrap();
+        let items = items.as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["kind"], "error");
+        assert_eq!(items[0]["errors"][0]["code"], "COMMAND_FAILED");
+        assert_eq!(
+            items[0]["errors"][0]["message"],
+            "Failed to read src/a.rs: No such file"
+        );
+
+        let jsonl = render_failure(&error, RenderConfig::new(OutputFormat::Jsonl));
+        assert_eq!(jsonl.lines().count(), 1);
+        let item: Value = serde_json::from_str(jsonl.trim_end()).unwrap();
+        assert_eq!(item, items[0]);
+    }
+
+    #[test]
+    fn test_render_failure_after_streamed_items() {
+        // Items streamed before the failure stay, and the error follows as one more line
+        let mut buf = Vec::new();
+        let mut stream = JsonlStream::new(&mut buf);
+        for line in [1, 2] {
+            let item = ResultItem::match_result("a.rs", Range::lines(line, line), "hit");
+            stream.write_item(&item).unwrap();
+        }
+        let mut stdout = String::from_utf8(buf).unwrap();
+        stdout.push_str(&render_failure(
+            &anyhow!("walk failed"),
+            RenderConfig::new(OutputFormat::Jsonl),
+        ));
+
+        let items: Vec<Value> = stdout
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(items.len(), 3);
+        assert!(items[..2].iter().all(|i| i["kind"] == "match"));
+        assert_eq!(items[2]["kind"], "error");
+        assert_eq!(items[2]["errors"][0]["code"], "COMMAND_FAILED");
+        assert_eq!(items[2]["errors"][0]["message"], "walk failed");
+    }
+}
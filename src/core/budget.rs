@@ -0,0 +1,122 @@
+//! Wall-clock time budget for long-running commands
+//!
+//! A watchdog thread flips a flag once the budget elapses. Traversal loops poll
+//! `should_stop()` and return what they have collected so far; the renderer
+//! then appends a `TIME_BUDGET_EXCEEDED` notice so output stays well-formed.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crate::core::model::{MiseError, ResultItem};
+
+/// Error code for the notice emitted when a traversal was cut short
+pub const TIME_BUDGET_EXCEEDED: &str = "TIME_BUDGET_EXCEEDED";
+
+/// Configured budget in milliseconds (0 = none)
+static BUDGET_MS: AtomicU64 = AtomicU64::new(0);
+
+/// Set by the watchdog when the budget has elapsed
+static EXPIRED: AtomicBool = AtomicBool::new(false);
+
+/// Set when a traversal actually stopped early because of the budget
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Start the watchdog for a budget of `ms` milliseconds
+pub fn start_watchdog(ms: u64) {
+    BUDGET_MS.store(ms, Ordering::Relaxed);
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(ms));
+        EXPIRED.store(true, Ordering::Relaxed);
+    });
+}
+
+/// Whether a traversal should stop collecting results
+///
+/// Returning true records that results are incomplete.
+pub fn should_stop() -> bool {
+    if EXPIRED.load(Ordering::Relaxed) {
+        INTERRUPTED.store(true, Ordering::Relaxed);
+        true
+    } else {
+        false
+    }
+}
+
+/// Whether any traversal was cut short by the budget
+pub fn was_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+/// Build the notice item appended to interrupted output
+pub fn notice_item() -> ResultItem {
+    let mut item = ResultItem::error(MiseError::new(
+        TIME_BUDGET_EXCEEDED,
+        format!(
+            "Time budget of {}ms exceeded; results are partial",
+            BUDGET_MS.load(Ordering::Relaxed)
+        ),
+    ));
+    item.meta.truncated = true;
+    item
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notice_item() {
+        let item = notice_item();
+        assert_eq!(item.errors[0].code, TIME_BUDGET_EXCEEDED);
+        assert!(item.meta.truncated);
+    }
+
+    #[test]
+    fn test_expired_budget_returns_partial_results() {
+        // The budget is process-wide, so expire it in a child test process
+        // rather than under the other tests running in this one
+        if std::env::var_os("MISE_BUDGET_CHILD").is_none() {
+            let status = std::process::Command::new(std::env::current_exe().unwrap())
+                .args([
+                    "core::budget::tests::test_expired_budget_returns_partial_results",
+                    "--exact",
+                    "--quiet",
+                ])
+                .env("MISE_BUDGET_CHILD", "1")
+                .status()
+                .unwrap();
+            assert!(status.success());
+            return;
+        }
+
+        use crate::backends::deps::analyze_deps;
+        use crate::backends::scan::{scan_files, GlobFilter, ScanOptions};
+        use crate::core::render::{OutputFormat, Renderer};
+
+        let temp = tempfile::tempdir().unwrap();
+        for name in ["a.rs", "b.rs", "c.rs"] {
+            std::fs::write(temp.path().join(name), "use crate::b;\n").unwrap();
+        }
+
+        start_watchdog(0);
+        while !EXPIRED.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let options = ScanOptions {
+            file_type: Some("file".to_string()),
+            ..Default::default()
+        };
+        let files = scan_files(temp.path(), &options).unwrap();
+        assert!(files.items.len() < 3);
+        let graph = analyze_deps(temp.path(), None, &GlobFilter::default(), false, 0).unwrap();
+        assert!(graph.files.len() < 3);
+        assert!(was_interrupted());
+
+        let output = Renderer::new(OutputFormat::Jsonl).render(&files);
+        let last: serde_json::Value = serde_json::from_str(output.lines().last().unwrap()).unwrap();
+        assert_eq!(last["errors"][0]["code"], TIME_BUDGET_EXCEEDED);
+        assert_eq!(last["meta"]["truncated"], true);
+    }
+}
@@ -1,14 +1,18 @@
 //! Unified file reading strategies
 //!
 //! Provides consistent handling for:
-//! - Non-UTF-8 files
+//! - Non-UTF-8 files (legacy encodings are detected and converted)
 //! - Oversized files
 //! - Binary files
 
+use anyhow::{anyhow, Result};
+use chardetng::EncodingDetector;
+use encoding_rs::{CoderResult, Decoder, DecoderResult, Encoding, UTF_16BE, UTF_16LE, UTF_8};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io::Read;
+use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
+use std::sync::RwLock;
 
 use crate::core::model::{MiseError, ResultItem};
 
@@ -18,13 +22,189 @@ pub const DEFAULT_MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
 /// Default truncation size in bytes (64 KB)
 pub const DEFAULT_TRUNCATE_SIZE: usize = 64 * 1024;
 
+/// Bytes sniffed from the start of a file by `is_probably_binary`
+pub const BINARY_SNIFF_SIZE: usize = 8 * 1024;
+
+/// Share of invalid UTF-8 bytes above which a sample counts as binary
+const BINARY_INVALID_RATIO: f64 = 0.3;
+
+/// Code of the note emitted for binary files left out of text processing
+pub const BINARY_SKIPPED: &str = "BINARY_SKIPPED";
+
+/// Codec forced by `--encoding`; `None` means detect per file
+static ENCODING_OVERRIDE: RwLock<Option<&'static Encoding>> = RwLock::new(None);
+
+/// Look up a codec by its WHATWG label (`gbk`, `latin1`, `shift_jis`, `utf-16le`, ...)
+pub fn parse_encoding(label: &str) -> Result<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes()).ok_or_else(|| {
+        anyhow!(
+            "Unknown encoding '{}' (expected a label such as utf-8, gbk, big5, shift_jis or latin1)",
+            label
+        )
+    })
+}
+
+/// Force one codec for all text reads, or restore detection with `None`
+pub fn set_encoding_override(label: Option<&str>) -> Result<()> {
+    let encoding = label.map(parse_encoding).transpose()?;
+    *ENCODING_OVERRIDE.write().unwrap_or_else(|e| e.into_inner()) = encoding;
+    Ok(())
+}
+
+fn encoding_override() -> Option<&'static Encoding> {
+    *ENCODING_OVERRIDE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Guess the codec of raw text bytes
+///
+/// A BOM wins, then UTF-8 (a character cut off at the end still counts);
+/// anything else is left to `chardetng`.
+pub fn detect_encoding(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => UTF_8,
+        Err(e) if e.error_len().is_none() => UTF_8,
+        Err(_) => guess_legacy_encoding(bytes),
+    }
+}
+
+/// Let `chardetng` pick a codec for bytes that are not UTF-8 (never UTF-16)
+fn guess_legacy_encoding(bytes: &[u8]) -> &'static Encoding {
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    detector.guess(None, true)
+}
+
+/// Text decoded from raw file bytes
+#[derive(Debug, Clone)]
+pub struct DecodedText {
+    /// The content converted to UTF-8
+    pub text: String,
+    /// Codec the bytes were decoded with
+    pub encoding: &'static Encoding,
+    /// Whether malformed sequences were replaced with U+FFFD
+    pub had_errors: bool,
+}
+
+impl DecodedText {
+    /// Codec name for `meta.encoding`; `None` for plain UTF-8
+    pub fn legacy_encoding(&self) -> Option<String> {
+        (self.encoding != UTF_8).then(|| self.encoding.name().to_string())
+    }
+}
+
+/// Decode bytes with the `--encoding` codec, or the detected one
+pub fn decode_text(bytes: &[u8]) -> DecodedText {
+    decode_with(bytes, encoding_override())
+}
+
+fn decode_with(bytes: &[u8], forced: Option<&'static Encoding>) -> DecodedText {
+    let guess = forced.unwrap_or_else(|| detect_encoding(bytes));
+    let (text, encoding, had_errors) = guess.decode(bytes);
+    DecodedText {
+        text: text.into_owned(),
+        encoding,
+        had_errors,
+    }
+}
+
+/// Bytes inspected to pick the codec of a streamed read
+pub const ENCODING_SNIFF_SIZE: usize = 64 * 1024;
+
+/// Codec for a file: the `--encoding` one, or one detected from its first
+/// `ENCODING_SNIFF_SIZE` bytes (returned so the caller can keep reading them)
+fn sniff_encoding(file: &mut fs::File) -> std::io::Result<(&'static Encoding, Vec<u8>)> {
+    let mut prefix = Vec::with_capacity(ENCODING_SNIFF_SIZE);
+    file.take(ENCODING_SNIFF_SIZE as u64)
+        .read_to_end(&mut prefix)?;
+    let encoding = encoding_override().unwrap_or_else(|| detect_encoding(&prefix));
+    Ok((encoding, prefix))
+}
+
+/// Detect the codec of a file without reading past its prefix
+pub fn detect_file_encoding(path: &Path) -> std::io::Result<&'static Encoding> {
+    sniff_encoding(&mut fs::File::open(path)?).map(|(encoding, _)| encoding)
+}
+
+/// Open a text file for streaming reads, decoded to UTF-8
+///
+/// The codec is picked from a bounded prefix (see [`detect_file_encoding`]) and
+/// the rest is decoded as it is read, so callers that stop early never load the
+/// whole file. A BOM is honoured and stripped, as in [`decode_text`].
+pub fn open_decoded(path: &Path) -> std::io::Result<(impl BufRead, &'static Encoding)> {
+    let mut file = fs::File::open(path)?;
+    let (encoding, prefix) = sniff_encoding(&mut file)?;
+    let reader = DecodingReader::new(std::io::Cursor::new(prefix).chain(file), encoding);
+    Ok((BufReader::new(reader), encoding))
+}
+
+/// Reader yielding the UTF-8 decoding of `inner`
+struct DecodingReader<R> {
+    inner: R,
+    decoder: Decoder,
+    /// Raw bytes read from `inner`; `input[start..end]` is not decoded yet
+    input: Vec<u8>,
+    start: usize,
+    end: usize,
+    /// Decoded bytes; `output[pos..len]` has not been returned yet
+    output: Vec<u8>,
+    pos: usize,
+    len: usize,
+    eof: bool,
+    /// The final decode call was made; the decoder must not be used again
+    finished: bool,
+}
+
+impl<R: Read> DecodingReader<R> {
+    fn new(inner: R, encoding: &'static Encoding) -> Self {
+        Self {
+            inner,
+            decoder: encoding.new_decoder(),
+            input: vec![0; 8 * 1024],
+            start: 0,
+            end: 0,
+            output: vec![0; 8 * 1024],
+            pos: 0,
+            len: 0,
+            eof: false,
+            finished: false,
+        }
+    }
+}
+
+impl<R: Read> Read for DecodingReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.len && !self.finished {
+            if self.start == self.end && !self.eof {
+                self.end = self.inner.read(&mut self.input)?;
+                self.start = 0;
+                self.eof = self.end == 0;
+            }
+            let (result, read, written, _) = self.decoder.decode_to_utf8(
+                &self.input[self.start..self.end],
+                &mut self.output,
+                self.eof,
+            );
+            self.start += read;
+            (self.pos, self.len) = (0, written);
+            self.finished = self.eof && result == CoderResult::InputEmpty;
+        }
+        let n = out.len().min(self.len - self.pos);
+        out[..n].copy_from_slice(&self.output[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
 /// Strategy for handling non-UTF-8 content
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum EncodingStrategy {
-    /// Skip non-UTF-8 files entirely
+    /// Skip files that are not UTF-8 (or the `--encoding` codec) entirely
     Skip,
-    /// Use lossy conversion (replace invalid bytes with ?)
+    /// Convert from the detected encoding, replacing invalid bytes with U+FFFD
     #[default]
     Lossy,
     /// Treat as binary (skip content extraction)
@@ -92,6 +272,9 @@ pub struct FileReadResult {
 
     /// Reason for skipping (if skipped)
     pub skip_reason: Option<String>,
+
+    /// Codec the content was converted from, when not UTF-8
+    pub encoding: Option<String>,
 }
 
 impl FileReadResult {
@@ -104,6 +287,7 @@ impl FileReadResult {
             warnings: Vec::new(),
             skipped: false,
             skip_reason: None,
+            encoding: None,
         }
     }
 
@@ -116,6 +300,7 @@ impl FileReadResult {
             warnings: Vec::new(),
             skipped: true,
             skip_reason: Some(reason.into()),
+            encoding: None,
         }
     }
 
@@ -131,6 +316,12 @@ impl FileReadResult {
         self
     }
 
+    /// Record the codec the content was converted from
+    pub fn with_encoding(mut self, encoding: Option<String>) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
     /// Add a warning
     pub fn with_warning(mut self, warning: FileWarning) -> Self {
         self.warnings.push(warning);
@@ -155,6 +346,8 @@ pub enum WarningCode {
     CircularDependency,
     /// Raw format warning
     RawFormatUnstable,
+    /// Path excluded by the --deny policy
+    PathDenied,
     /// General warning
     General,
 }
@@ -169,6 +362,7 @@ impl WarningCode {
             WarningCode::BinaryFile => "BINARY_FILE",
             WarningCode::CircularDependency => "CIRCULAR_DEPENDENCY",
             WarningCode::RawFormatUnstable => "RAW_FORMAT_UNSTABLE",
+            WarningCode::PathDenied => crate::core::policy::PATH_DENIED,
             WarningCode::General => "WARNING",
         }
     }
@@ -234,6 +428,13 @@ impl FileWarning {
 
 /// Read a file with the given configuration
 pub fn read_file_with_config(path: &Path, config: &FileReadConfig) -> FileReadResult {
+    // Denied paths are never opened, regardless of other options
+    if crate::core::policy::is_path_denied(path) {
+        let warning = FileWarning::new(WarningCode::PathDenied, "Path is denied by --deny policy")
+            .with_path(path.display().to_string());
+        return FileReadResult::skipped("Path denied by policy").with_warning(warning);
+    }
+
     // Check file size first
     let metadata = match fs::metadata(path) {
         Ok(m) => m,
@@ -291,69 +492,148 @@ pub fn read_file_with_config(path: &Path, config: &FileReadConfig) -> FileReadRe
         }
     }
 
-    // Try to convert to UTF-8
-    match String::from_utf8(bytes.clone()) {
-        Ok(content) => {
-            let truncated = content.len() > config.truncate_size
-                && config.size_strategy == SizeStrategy::Truncate;
-
-            if truncated {
-                let truncated_content = truncate_at_char_boundary(&content, config.truncate_size);
-                let warning = FileWarning::new(
-                    WarningCode::FileTruncated,
-                    format!(
-                        "Content truncated from {} to {} bytes",
-                        content.len(),
-                        truncated_content.len()
-                    ),
-                )
-                .with_path(path.display().to_string());
-
-                FileReadResult::success(truncated_content)
-                    .with_truncated()
-                    .with_warning(warning)
-            } else {
-                FileReadResult::success(content)
-            }
-        }
-        Err(_) => {
-            // Handle non-UTF-8 based on strategy
-            match config.encoding_strategy {
-                EncodingStrategy::Skip => {
-                    let warning = FileWarning::new(
-                        WarningCode::FileSkippedEncoding,
-                        "File contains invalid UTF-8 sequences",
-                    )
-                    .with_path(path.display().to_string());
-                    FileReadResult::skipped("Invalid UTF-8").with_warning(warning)
-                }
-                EncodingStrategy::Lossy | EncodingStrategy::Binary => {
-                    let content = String::from_utf8_lossy(&bytes).into_owned();
-                    let warning = FileWarning::new(
-                        WarningCode::LossyConversion,
-                        "Lossy UTF-8 conversion applied (some characters replaced)",
-                    )
-                    .with_path(path.display().to_string());
-
-                    let truncated = content.len() > config.truncate_size
-                        && config.size_strategy == SizeStrategy::Truncate;
-
-                    if truncated {
-                        let truncated_content =
-                            truncate_at_char_boundary(&content, config.truncate_size);
-                        FileReadResult::success(truncated_content)
-                            .with_truncated()
-                            .with_lossy()
-                            .with_warning(warning)
-                    } else {
-                        FileReadResult::success(content)
-                            .with_lossy()
-                            .with_warning(warning)
-                    }
-                }
-            }
-        }
+    let forced = encoding_override();
+    let decoded = decode_with(&bytes, forced);
+    let converted = decoded.encoding != UTF_8 && forced.is_none();
+    if config.encoding_strategy == EncodingStrategy::Skip && (decoded.had_errors || converted) {
+        let warning = FileWarning::new(
+            WarningCode::FileSkippedEncoding,
+            "File contains invalid UTF-8 sequences",
+        )
+        .with_path(path.display().to_string());
+        return FileReadResult::skipped("Invalid UTF-8").with_warning(warning);
     }
+
+    let encoding = decoded.legacy_encoding();
+    let content = decoded.text;
+    let mut result =
+        if content.len() > config.truncate_size && config.size_strategy == SizeStrategy::Truncate {
+            let truncated_content = truncate_at_char_boundary(&content, config.truncate_size);
+            let warning = FileWarning::new(
+                WarningCode::FileTruncated,
+                format!(
+                    "Content truncated from {} to {} bytes",
+                    content.len(),
+                    truncated_content.len()
+                ),
+            )
+            .with_path(path.display().to_string());
+            FileReadResult::success(truncated_content)
+                .with_truncated()
+                .with_warning(warning)
+        } else {
+            FileReadResult::success(content)
+        };
+
+    if decoded.had_errors {
+        let warning = FileWarning::new(
+            WarningCode::LossyConversion,
+            "Lossy UTF-8 conversion applied (some characters replaced)",
+        )
+        .with_path(path.display().to_string());
+        result = result.with_lossy().with_warning(warning);
+    }
+    result.with_encoding(encoding)
+}
+
+/// Whether a sample from the start of a file looks like binary data
+///
+/// A sample with a BOM, or read with a UTF-16 `--encoding`, is judged by
+/// whether it decodes in that codec: UTF-16 text has NUL bytes in nearly every
+/// character. Otherwise binary means a NUL byte, or more than 30% of the sample
+/// being invalid UTF-8 that does not decode as legacy text either (see
+/// `decodes_as_legacy_text`). A character cut off by the end of the sample is
+/// not held against it.
+pub fn looks_binary(sample: &[u8]) -> bool {
+    looks_binary_with(sample, encoding_override())
+}
+
+fn looks_binary_with(sample: &[u8], forced: Option<&'static Encoding>) -> bool {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(sample) {
+        return !decodes_as(encoding, &sample[bom_len..]);
+    }
+    if let Some(forced) = forced.filter(|e| *e == UTF_16LE || *e == UTF_16BE) {
+        return !decodes_as(forced, sample);
+    }
+
+    if sample.contains(&0) {
+        return true;
+    }
+    if sample.is_empty() {
+        return false;
+    }
+
+    let mut invalid = 0;
+    let mut trailing = 0;
+    for chunk in sample.utf8_chunks() {
+        invalid += chunk.invalid().len();
+        trailing = chunk.invalid().len();
+    }
+    // The last chunk's invalid bytes end the sample and may be a cut-off character
+    if trailing < 4 {
+        invalid -= trailing;
+    }
+    invalid as f64 / sample.len() as f64 > BINARY_INVALID_RATIO
+        && !decodes_as_legacy_text(sample, forced)
+}
+
+/// Whether a sample decodes cleanly in the `--encoding` codec or a detected multi-byte one
+///
+/// Detected single-byte codecs (windows-125x, KOI8, ...) accept nearly any byte
+/// sequence, so they cannot vouch for a sample; such files need `--encoding`.
+fn decodes_as_legacy_text(sample: &[u8], forced: Option<&'static Encoding>) -> bool {
+    let encoding = match forced {
+        Some(forced) => forced,
+        None => match guess_legacy_encoding(sample) {
+            e if e == UTF_8 || e.is_single_byte() => return false,
+            e => e,
+        },
+    };
+    decodes_as(encoding, sample)
+}
+
+/// Whether `sample` decodes in `encoding` without malformed sequences or NUL characters
+fn decodes_as(encoding: &'static Encoding, sample: &[u8]) -> bool {
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let capacity = decoder
+        .max_utf8_buffer_length_without_replacement(sample.len())
+        .unwrap_or(sample.len() * 3);
+    let mut text = String::with_capacity(capacity);
+    // Not the last chunk: a character cut off by the sample end is fine
+    let (result, _) = decoder.decode_to_string_without_replacement(sample, &mut text, false);
+    !matches!(result, DecoderResult::Malformed(..)) && !text.contains('\0')
+}
+
+/// Sniff the first `BINARY_SNIFF_SIZE` bytes of a file for binary content
+///
+/// Unreadable files are not considered binary; reading them fails later anyway.
+pub fn is_probably_binary(path: &Path) -> bool {
+    let Ok(file) = fs::File::open(path) else {
+        return false;
+    };
+    let mut sample = Vec::with_capacity(BINARY_SNIFF_SIZE);
+    if file
+        .take(BINARY_SNIFF_SIZE as u64)
+        .read_to_end(&mut sample)
+        .is_err()
+    {
+        return false;
+    }
+    looks_binary(&sample)
+}
+
+/// Note item for a binary file that was skipped
+pub fn binary_skipped_item(path: impl Into<String>) -> ResultItem {
+    let path = path.into();
+    let mut item = ResultItem::error(MiseError::new(
+        BINARY_SKIPPED,
+        format!(
+            "Skipped binary file '{}' (use --include-binary to read it)",
+            path
+        ),
+    ));
+    item.path = Some(path);
+    item
 }
 
 /// Read file bytes with size handling
@@ -598,6 +878,119 @@ mod tests {
         assert_eq!(SizeStrategy::default(), SizeStrategy::Truncate);
     }
 
+    #[test]
+    fn test_looks_binary() {
+        assert!(!looks_binary(b""));
+        assert!(!looks_binary("plain text, 中文".as_bytes()));
+        assert!(looks_binary(b"PK\x03\x04\x00\x00"));
+        assert!(looks_binary(&[0xFE, 0xFA, 0xC0, 0xFF, b'a']));
+        // A few Latin-1 bytes in text are not enough to count as binary
+        assert!(!looks_binary(b"caf\xE9 au lait, cr\xE8me br\xFBl\xE9e"));
+        // A multi-byte character cut off by the sample end is not invalid
+        let cut = &"abc中".as_bytes()[..5];
+        assert!(!looks_binary(cut));
+    }
+
+    #[test]
+    fn test_decode_legacy_encodings() {
+        let (gbk, _, _) = encoding_rs::GBK.encode("中文文档，旧的编码格式。");
+        let decoded = decode_with(&gbk, None);
+        assert_eq!(decoded.text, "中文文档，旧的编码格式。");
+        assert_eq!(decoded.legacy_encoding().as_deref(), Some("GBK"));
+        assert!(!decoded.had_errors);
+        assert!(!looks_binary(&gbk));
+
+        let decoded = decode_with(b"caf\xE9", Some(parse_encoding("latin1").unwrap()));
+        assert_eq!(decoded.text, "café");
+        assert_eq!(decoded.legacy_encoding().as_deref(), Some("windows-1252"));
+
+        let decoded = decode_with("plain 中文".as_bytes(), None);
+        assert_eq!(decoded.legacy_encoding(), None);
+        assert_eq!(detect_encoding(&"abc中".as_bytes()[..5]), UTF_8);
+
+        assert_eq!(parse_encoding(" Shift_JIS ").unwrap().name(), "Shift_JIS");
+        assert!(parse_encoding("klingon").is_err());
+    }
+
+    #[test]
+    fn test_read_file_legacy_encoding() {
+        let dir = TempDir::new().unwrap();
+        let file_path = dir.path().join("legacy.md");
+        let (gbk, _, _) = encoding_rs::GBK.encode("# 标题\n正文内容\n");
+        fs::write(&file_path, &gbk).unwrap();
+
+        let result = read_file_safe(&file_path);
+        assert_eq!(result.content.as_deref(), Some("# 标题\n正文内容\n"));
+        assert_eq!(result.encoding.as_deref(), Some("GBK"));
+        assert!(!result.lossy_conversion);
+        assert!(result.warnings.is_empty());
+
+        let config = FileReadConfig {
+            encoding_strategy: EncodingStrategy::Skip,
+            ..Default::default()
+        };
+        assert!(read_file_with_config(&file_path, &config).skipped);
+    }
+
+    #[test]
+    fn test_open_decoded_streams_past_sniff_prefix() {
+        let dir = TempDir::new().unwrap();
+        let file_path = dir.path().join("long.txt");
+        // Odd-length lines put two-byte GBK characters across every buffer boundary
+        let text: String = (0..20_000)
+            .map(|i| format!("第{}行中文内容\n", i))
+            .collect();
+        assert!(text.len() > ENCODING_SNIFF_SIZE);
+        let (gbk, _, _) = encoding_rs::GBK.encode(&text);
+        fs::write(&file_path, &gbk).unwrap();
+
+        let (mut reader, encoding) = open_decoded(&file_path).unwrap();
+        assert_eq!(encoding, encoding_rs::GBK);
+        let mut decoded = String::new();
+        reader.read_to_string(&mut decoded).unwrap();
+        assert_eq!(decoded, text);
+        assert_eq!(detect_file_encoding(&file_path).unwrap(), encoding_rs::GBK);
+
+        fs::write(&file_path, "\u{feff}one\ntwo").unwrap();
+        let (reader, encoding) = open_decoded(&file_path).unwrap();
+        assert_eq!(encoding, UTF_8);
+        let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
+        assert_eq!(lines, ["one", "two"]);
+    }
+
+    #[test]
+    fn test_is_probably_binary() {
+        let temp = TempDir::new().unwrap();
+        let text = temp.path().join("notes.md");
+        let image = temp.path().join("logo.png");
+        std::fs::write(&text, "# Title\n").unwrap();
+        std::fs::write(&image, b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR").unwrap();
+
+        assert!(!is_probably_binary(&text));
+        assert!(is_probably_binary(&image));
+        assert!(!is_probably_binary(&temp.path().join("missing")));
+
+        // UTF-16 text is full of NUL bytes but is not binary with a BOM
+        let utf16 = temp.path().join("utf16.txt");
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(
+            "line one\n第二行\n"
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes),
+        );
+        std::fs::write(&utf16, &bytes).unwrap();
+        assert!(!is_probably_binary(&utf16));
+        // ... or without one, when read with a UTF-16 --encoding
+        assert!(looks_binary_with(&bytes[2..], None));
+        assert!(!looks_binary_with(&bytes[2..], Some(UTF_16LE)));
+        // A BOM does not vouch for bytes that decode to NULs
+        assert!(looks_binary(&[0xFF, 0xFE, 0x00, 0x00, 0x41, 0x00]));
+
+        let item = binary_skipped_item("logo.png");
+        assert_eq!(item.errors[0].code, BINARY_SKIPPED);
+        assert_eq!(item.path.as_deref(), Some("logo.png"));
+    }
+
     #[test]
     fn test_read_nonexistent_file() {
         let result = read_file_safe(Path::new("/nonexistent/file.txt"));
@@ -6,11 +6,17 @@
 //! - Path normalization utilities
 //! - Common utilities
 //! - File reading strategies
+//! - Path deny policy
 //! - Token counting for LLM context budgeting
+//! - Wall-clock time budget
+//! - JSON Schema export for the output model
 
+pub mod budget;
 pub mod file_reader;
 pub mod model;
 pub mod paths;
+pub mod policy;
 pub mod render;
+pub mod schema;
 pub mod tokenizer;
 pub mod util;
@@ -3,10 +3,11 @@
 //! All commands (internal or external tools) must map to this unified Result Model
 //! before rendering output.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// The kind of result item
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Kind {
     File,
@@ -18,7 +19,7 @@ pub enum Kind {
 }
 
 /// Confidence level of a result
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Confidence {
     High,
@@ -26,8 +27,40 @@ pub enum Confidence {
     Low,
 }
 
+impl Confidence {
+    /// Ordering weight: Low < Medium < High
+    fn rank(self) -> u8 {
+        match self {
+            Confidence::Low => 0,
+            Confidence::Medium => 1,
+            Confidence::High => 2,
+        }
+    }
+
+    /// Whether this confidence meets the `min` threshold
+    pub fn at_least(self, min: Confidence) -> bool {
+        self.rank() >= min.rank()
+    }
+}
+
+impl std::str::FromStr for Confidence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Confidence::Low),
+            "medium" => Ok(Confidence::Medium),
+            "high" => Ok(Confidence::High),
+            _ => Err(format!(
+                "Unknown confidence: {} (expected low, medium or high)",
+                s
+            )),
+        }
+    }
+}
+
 /// Source mode indicating how the result was obtained
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum SourceMode {
     Scan,
@@ -38,21 +71,27 @@ pub enum SourceMode {
 }
 
 /// Line-based range
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct RangeLine {
     pub start: u32,
     pub end: u32,
+    /// 1-based character column where the match starts on `start`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_col: Option<u32>,
+    /// 1-based character column just past the match on `end` (exclusive)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_col: Option<u32>,
 }
 
 /// Byte-based range
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct RangeByte {
     pub start: u64,
     pub end: u64,
 }
 
 /// Range can be either line-based or byte-based
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum Range {
     Line(RangeLine),
@@ -62,18 +101,34 @@ pub enum Range {
 impl Range {
     /// Create a new line range
     pub fn lines(start: u32, end: u32) -> Self {
-        Range::Line(RangeLine { start, end })
+        Range::Line(RangeLine {
+            start,
+            end,
+            start_col: None,
+            end_col: None,
+        })
+    }
+
+    /// Attach character columns to a line range (byte ranges are unchanged)
+    pub fn with_columns(self, start_col: u32, end_col: u32) -> Self {
+        match self {
+            Range::Line(line) => Range::Line(RangeLine {
+                start_col: Some(start_col),
+                end_col: Some(end_col),
+                ..line
+            }),
+            Range::Byte(_) => self,
+        }
     }
 
     /// Create a new byte range
-    #[allow(dead_code)]
     pub fn bytes(start: u64, end: u64) -> Self {
         Range::Byte(RangeByte { start, end })
     }
 }
 
 /// Metadata for a result item
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct Meta {
     /// Modification time in milliseconds since epoch
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -90,10 +145,18 @@ pub struct Meta {
     /// Whether the content was truncated
     #[serde(default)]
     pub truncated: bool,
+
+    /// Whether a requested byte range was widened to UTF-8 character boundaries
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub adjusted: bool,
+
+    /// Source encoding the content was converted from (absent for UTF-8)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
 }
 
 /// Error information for a result
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MiseError {
     pub code: String,
     pub message: String,
@@ -109,7 +172,7 @@ impl MiseError {
 }
 
 /// The unified result item that all commands must produce
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ResultItem {
     /// The kind of this result
     pub kind: Kind,
@@ -248,6 +311,37 @@ impl ResultItem {
         self
     }
 
+    /// Whether this item passes a `--min-confidence` threshold (errors always do)
+    pub fn meets_confidence(&self, min: Confidence) -> bool {
+        self.kind == Kind::Error || self.confidence.at_least(min)
+    }
+
+    /// Record why this item was included when `enabled` (`--explain`); error items are left as-is
+    pub fn explain(&mut self, enabled: bool, reason: impl Into<String>) {
+        if enabled && self.kind != Kind::Error {
+            self.set_reason(reason);
+        }
+    }
+
+    /// Attach a human-readable `reason` to the data payload
+    ///
+    /// Existing object payloads keep their fields; any other payload is moved under `value`.
+    pub fn set_reason(&mut self, reason: impl Into<String>) {
+        let reason = serde_json::Value::String(reason.into());
+        match self.data.take() {
+            Some(serde_json::Value::Object(mut map)) => {
+                map.insert("reason".to_string(), reason);
+                self.data = Some(serde_json::Value::Object(map));
+            }
+            Some(other) => {
+                self.data = Some(serde_json::json!({ "value": other, "reason": reason }));
+            }
+            None => {
+                self.data = Some(serde_json::json!({ "reason": reason }));
+            }
+        }
+    }
+
     /// Add an error
     #[allow(dead_code)]
     pub fn with_error(mut self, error: MiseError) -> Self {
@@ -257,7 +351,7 @@ impl ResultItem {
 }
 
 /// Result set containing multiple result items
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ResultSet {
     pub items: Vec<ResultItem>,
 }
@@ -287,7 +381,10 @@ impl ResultSet {
                     }
                     // Compare by range start if paths are equal
                     match (&a.range, &b.range) {
-                        (Some(Range::Line(ra)), Some(Range::Line(rb))) => ra.start.cmp(&rb.start),
+                        (Some(Range::Line(ra)), Some(Range::Line(rb))) => ra
+                            .start
+                            .cmp(&rb.start)
+                            .then(ra.start_col.cmp(&rb.start_col)),
                         (Some(Range::Byte(ra)), Some(Range::Byte(rb))) => ra.start.cmp(&rb.start),
                         (Some(_), None) => std::cmp::Ordering::Less,
                         (None, Some(_)) => std::cmp::Ordering::Greater,
@@ -301,6 +398,13 @@ impl ResultSet {
         });
     }
 
+    /// Drop items whose confidence is below `min`
+    ///
+    /// Error items are always kept so failures are never filtered away.
+    pub fn retain_min_confidence(&mut self, min: Confidence) {
+        self.items.retain(|item| item.meets_confidence(min));
+    }
+
     #[allow(dead_code)]
     pub fn len(&self) -> usize {
         self.items.len()
@@ -333,6 +437,25 @@ impl FromIterator<ResultItem> for ResultSet {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_retain_min_confidence() {
+        assert_eq!("Medium".parse::<Confidence>(), Ok(Confidence::Medium));
+        assert!("certain".parse::<Confidence>().is_err());
+
+        let mut result_set: ResultSet = [
+            ResultItem::file("high.rs"),
+            ResultItem::file("medium.rs").with_confidence(Confidence::Medium),
+            ResultItem::file("low.rs").with_confidence(Confidence::Low),
+            ResultItem::error(MiseError::new("E", "kept")).with_confidence(Confidence::Low),
+        ]
+        .into_iter()
+        .collect();
+        result_set.retain_min_confidence(Confidence::Medium);
+
+        let paths: Vec<_> = result_set.items.iter().map(|i| i.path.as_deref()).collect();
+        assert_eq!(paths, [Some("high.rs"), Some("medium.rs"), None]);
+    }
+
     #[test]
     fn test_result_item_file() {
         let item = ResultItem::file("src/main.rs");
@@ -391,6 +514,8 @@ mod tests {
             size: Some(1024),
             hash: Some("abc123".to_string()),
             truncated: true,
+            adjusted: false,
+            encoding: None,
         };
         let item = ResultItem::file("test.rs").with_meta(meta);
         assert_eq!(item.meta.mtime_ms, Some(12345));
@@ -717,4 +842,36 @@ mod tests {
         assert!(set.items[0].path.is_some());
         assert!(set.items[1].path.is_none());
     }
+
+    #[test]
+    fn test_set_reason_merges_into_data() {
+        let mut item = ResultItem::file("a.md");
+        item.set_reason("primary anchor");
+        assert_eq!(item.data.as_ref().unwrap()["reason"], "primary anchor");
+
+        let mut item = ResultItem::file("a.md").with_data(serde_json::json!({ "id": "x" }));
+        item.set_reason("shares tag: rust");
+        let data = item.data.unwrap();
+        assert_eq!(data["id"], "x");
+        assert_eq!(data["reason"], "shares tag: rust");
+
+        let mut item = ResultItem::file("a.md").with_data(serde_json::json!([1, 2]));
+        item.set_reason("keyword match: function");
+        let data = item.data.unwrap();
+        assert_eq!(data["value"], serde_json::json!([1, 2]));
+        assert_eq!(data["reason"], "keyword match: function");
+    }
+
+    #[test]
+    fn test_explain_only_when_enabled() {
+        let mut item = ResultItem::file("a.md");
+        item.explain(false, "requested file");
+        assert!(item.data.is_none());
+        item.explain(true, "requested file");
+        assert_eq!(item.data.unwrap()["reason"], "requested file");
+
+        let mut item = ResultItem::error(MiseError::new("IO_ERROR", "boom"));
+        item.explain(true, "requested file");
+        assert!(item.data.is_none());
+    }
 }
@@ -3,6 +3,17 @@
 //! Ensures all paths are normalized to use '/' as separator and are relative to root.
 
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether emitted paths should have symlinks resolved (set once from the CLI)
+static RESOLVE_SYMLINKS: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable symlink resolution for all emitted paths
+///
+/// By default symlinks are left as encountered during traversal.
+pub fn set_resolve_symlinks(enabled: bool) {
+    RESOLVE_SYMLINKS.store(enabled, Ordering::Relaxed);
+}
 
 /// Normalize a path to use '/' as separator (for cross-platform consistency)
 pub fn normalize_path(path: &Path) -> String {
@@ -10,7 +21,27 @@ pub fn normalize_path(path: &Path) -> String {
 }
 
 /// Make a path relative to the root directory
+///
+/// Honors the global `--resolve-symlinks` setting.
 pub fn make_relative(path: &Path, root: &Path) -> Option<String> {
+    make_relative_with(path, root, RESOLVE_SYMLINKS.load(Ordering::Relaxed))
+}
+
+/// Make a path relative to the root directory, optionally resolving symlinks
+///
+/// When `resolve` is set, the path is canonicalized first. A resolved path that
+/// escapes the root is emitted as an absolute path; paths that cannot be
+/// canonicalized (e.g. no longer exist) fall back to the unresolved form.
+pub fn make_relative_with(path: &Path, root: &Path, resolve: bool) -> Option<String> {
+    if resolve {
+        if let Ok(real) = path.canonicalize() {
+            let real_root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+            return Some(match real.strip_prefix(&real_root) {
+                Ok(relative) => normalize_path(relative),
+                Err(_) => normalize_path(&real),
+            });
+        }
+    }
     path.strip_prefix(root).ok().map(normalize_path)
 }
 
@@ -88,6 +119,36 @@ mod tests {
         assert_eq!(make_relative(path, root), Some("".to_string()));
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_make_relative_with_symlinked_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join("real")).unwrap();
+        std::fs::write(root.join("real/file.txt"), "x").unwrap();
+        std::os::unix::fs::symlink(root.join("real"), root.join("link")).unwrap();
+
+        let via_link = root.join("link/file.txt");
+        assert_eq!(
+            make_relative_with(&via_link, &root, false),
+            Some("link/file.txt".to_string())
+        );
+        assert_eq!(
+            make_relative_with(&via_link, &root, true),
+            Some("real/file.txt".to_string())
+        );
+    }
+
+    #[test]
+    fn test_make_relative_with_missing_path_falls_back() {
+        let root = Path::new("/project");
+        let path = Path::new("/project/missing.rs");
+        assert_eq!(
+            make_relative_with(path, root, true),
+            Some("missing.rs".to_string())
+        );
+    }
+
     #[test]
     fn test_join_normalized() {
         let base = Path::new("/project");
@@ -0,0 +1,245 @@
+//! Path access policy - hard deny list enforced before any read
+//!
+//! Unlike ignore rules (which `--hidden`/`--no-ignore` style options or explicit
+//! paths can bypass), denied paths are never scanned, read or emitted.
+//!
+//! Patterns use gitignore-like globs:
+//! - `secrets/` or `secrets`: any file or directory named `secrets`, at any depth
+//! - `.env`, `*.pem`: matched against every path component
+//! - `config/prod/*.json`: patterns containing `/` are anchored at ROOT and
+//!   also deny everything below a matching directory
+//! - `*` matches within a component, `**` across components, `?` one character
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::core::model::{MiseError, ResultItem};
+use crate::core::paths::{make_relative_with, normalize_path};
+
+/// Error code for requests that target a denied path
+pub const PATH_DENIED: &str = "PATH_DENIED";
+
+/// A single compiled deny rule
+#[derive(Debug, Clone)]
+struct DenyRule {
+    /// Normalized source glob
+    glob: String,
+    regex: Regex,
+    /// Whether the pattern contains `/` and is matched against root-relative prefixes
+    anchored: bool,
+}
+
+/// A set of deny rules
+#[derive(Debug, Clone, Default)]
+pub struct PathPolicy {
+    root: Option<PathBuf>,
+    rules: Vec<DenyRule>,
+}
+
+/// Translate a glob into an anchored regex
+fn glob_to_regex(glob: &str) -> String {
+    let mut re = String::from("^");
+    let mut chars = glob.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // `**/` also matches zero directories
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    re.push_str("(?:.*/)?");
+                } else {
+                    re.push_str(".*");
+                }
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            _ => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    re.push('$');
+    re
+}
+
+impl PathPolicy {
+    /// Compile deny patterns for the given root
+    pub fn new(root: Option<&Path>, patterns: &[String]) -> Result<Self> {
+        let rules = patterns
+            .iter()
+            .map(|pattern| {
+                let trimmed = pattern.trim_start_matches("./").trim_end_matches('/');
+                let regex = Regex::new(&glob_to_regex(trimmed))
+                    .with_context(|| format!("Invalid --deny pattern: {}", pattern))?;
+                Ok(DenyRule {
+                    glob: trimmed.to_string(),
+                    regex,
+                    anchored: trimmed.contains('/'),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            root: root.map(|r| r.to_path_buf()),
+            rules,
+        })
+    }
+
+    /// Whether no paths are denied
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Exclusion globs in ripgrep/gitignore syntax (`!secrets`, `!/config/prod`)
+    pub fn exclude_globs(&self) -> Vec<String> {
+        self.rules
+            .iter()
+            .map(|rule| {
+                if rule.anchored {
+                    format!("!/{}", rule.glob)
+                } else {
+                    format!("!{}", rule.glob)
+                }
+            })
+            .collect()
+    }
+
+    /// Check a root-relative path ('/' separated) against the deny rules
+    pub fn is_denied(&self, relative: &str) -> bool {
+        let relative = relative.trim_start_matches("./");
+        let components: Vec<&str> = relative.split('/').filter(|c| !c.is_empty()).collect();
+
+        self.rules.iter().any(|rule| {
+            if rule.anchored {
+                // Match the path itself or any ancestor directory
+                (1..=components.len()).any(|n| rule.regex.is_match(&components[..n].join("/")))
+            } else {
+                components.iter().any(|c| rule.regex.is_match(c))
+            }
+        })
+    }
+
+    /// Check a filesystem path (absolute, or relative to ROOT) against the deny rules
+    pub fn is_path_denied(&self, path: &Path) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+
+        let relative = match (&self.root, path.is_absolute()) {
+            (Some(root), true) => make_relative_with(path, root, false)
+                .or_else(|| make_relative_with(path, root, true)),
+            _ => Some(normalize_path(path)),
+        };
+
+        match relative {
+            Some(rel) => self.is_denied(&rel),
+            // Outside ROOT: deny rules only apply to the component names
+            None => self.is_denied(&normalize_path(path)),
+        }
+    }
+}
+
+/// Process-wide deny policy (set once from the CLI)
+static POLICY: Lazy<RwLock<PathPolicy>> = Lazy::new(|| RwLock::new(PathPolicy::default()));
+
+/// Install the deny patterns for all scan/read operations
+pub fn set_deny_patterns(root: &Path, patterns: &[String]) -> Result<()> {
+    let policy = PathPolicy::new(Some(root), patterns)?;
+    if let Ok(mut guard) = POLICY.write() {
+        *guard = policy;
+    }
+    Ok(())
+}
+
+/// Check a root-relative path against the active policy
+pub fn is_denied(relative: &str) -> bool {
+    POLICY
+        .read()
+        .map(|p| !p.is_empty() && p.is_denied(relative))
+        .unwrap_or(false)
+}
+
+/// Check a filesystem path against the active policy
+pub fn is_path_denied(path: &Path) -> bool {
+    POLICY
+        .read()
+        .map(|p| p.is_path_denied(path))
+        .unwrap_or(false)
+}
+
+/// Build the error item reported when a denied path is explicitly requested
+pub fn denied_item(path: impl Into<String>) -> ResultItem {
+    let path = path.into();
+    let mut item = ResultItem::error(MiseError::new(
+        PATH_DENIED,
+        format!("Access to '{}' is denied by --deny policy", path),
+    ));
+    item.path = Some(path);
+    item
+}
+
+/// `!glob` exclusions for the active policy, for external search tools
+pub fn deny_globs() -> Vec<String> {
+    POLICY.read().map(|p| p.exclude_globs()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(patterns: &[&str]) -> PathPolicy {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        PathPolicy::new(Some(Path::new("/repo")), &patterns).unwrap()
+    }
+
+    #[test]
+    fn test_glob_to_regex() {
+        assert_eq!(glob_to_regex("*.pem"), r"^[^/]*\.pem$");
+        assert_eq!(glob_to_regex("a/**/b"), "^a/(?:.*/)?b$");
+    }
+
+    #[test]
+    fn test_is_denied_component_patterns() {
+        let p = policy(&["secrets/", ".env", "*.pem"]);
+        assert!(p.is_denied("secrets/api.txt"));
+        assert!(p.is_denied("config/secrets/db.json"));
+        assert!(p.is_denied(".env"));
+        assert!(p.is_denied("app/.env"));
+        assert!(p.is_denied("certs/server.pem"));
+        assert!(!p.is_denied("src/secrets.rs"));
+        assert!(!p.is_denied(".env.example"));
+    }
+
+    #[test]
+    fn test_is_denied_anchored_patterns() {
+        let p = policy(&["config/prod", "docs/**/*.key"]);
+        assert!(p.is_denied("config/prod"));
+        assert!(p.is_denied("config/prod/db.json"));
+        assert!(!p.is_denied("other/config/prod/db.json"));
+        assert!(p.is_denied("docs/a/b/c.key"));
+        assert!(p.is_denied("docs/c.key"));
+    }
+
+    #[test]
+    fn test_is_path_denied_absolute() {
+        let p = policy(&["secrets"]);
+        assert!(p.is_path_denied(Path::new("/repo/secrets/x")));
+        assert!(!p.is_path_denied(Path::new("/repo/src/x")));
+        assert!(p.is_path_denied(Path::new("secrets/x")));
+        assert!(!PathPolicy::default().is_path_denied(Path::new("/repo/secrets/x")));
+    }
+
+    #[test]
+    fn test_exclude_globs_and_denied_item() {
+        let p = policy(&["secrets/", "config/prod"]);
+        assert_eq!(p.exclude_globs(), vec!["!secrets", "!/config/prod"]);
+
+        let item = denied_item("secrets/x");
+        assert_eq!(item.errors[0].code, PATH_DENIED);
+        assert_eq!(item.path.as_deref(), Some("secrets/x"));
+    }
+}
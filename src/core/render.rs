@@ -1,10 +1,62 @@
 //! Renderer module
 //!
-//! Renders ResultSet to different output formats: jsonl, json, md, raw
+//! Renders ResultSet to different output formats: jsonl, json, md, raw, rg
 
-use crate::core::model::{Kind, Range, ResultItem, ResultSet};
+use crate::core::model::{Confidence, Kind, MiseError, Range, ResultItem, ResultSet};
+use crate::core::util::escape_xml;
+use std::collections::HashMap;
 use std::io::Write;
 
+/// Line-drawing style for human-readable trees and tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BoxStyle {
+    #[default]
+    Unicode,
+    Ascii,
+}
+
+impl BoxStyle {
+    /// Prefix for a tree entry that has following siblings
+    pub fn tee(self) -> &'static str {
+        match self {
+            BoxStyle::Unicode => "├── ",
+            BoxStyle::Ascii => "|-- ",
+        }
+    }
+
+    /// Prefix for the last tree entry
+    pub fn elbow(self) -> &'static str {
+        match self {
+            BoxStyle::Unicode => "└── ",
+            BoxStyle::Ascii => "`-- ",
+        }
+    }
+
+    /// Indent continuing a parent's vertical line
+    pub fn pipe(self) -> &'static str {
+        match self {
+            BoxStyle::Unicode => "│   ",
+            BoxStyle::Ascii => "|   ",
+        }
+    }
+
+    /// Convert table borders drawn with box characters to this style
+    pub fn table(self, text: String) -> String {
+        match self {
+            BoxStyle::Unicode => text,
+            BoxStyle::Ascii => text
+                .chars()
+                .map(|c| match c {
+                    '─' => '-',
+                    '│' => '|',
+                    '┌' | '┐' | '└' | '┘' | '├' | '┤' | '┬' | '┴' | '┼' => '+',
+                    other => other,
+                })
+                .collect(),
+        }
+    }
+}
+
 /// Output format
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum OutputFormat {
@@ -13,6 +65,12 @@ pub enum OutputFormat {
     Json,
     Markdown,
     Raw,
+    /// ripgrep-style human output: a heading per file followed by `line:excerpt` rows
+    Grouped,
+    /// RFC 4180 CSV with a header row, for spreadsheets
+    Csv,
+    /// A single YAML document holding the item sequence
+    Yaml,
 }
 
 impl std::str::FromStr for OutputFormat {
@@ -24,6 +82,9 @@ impl std::str::FromStr for OutputFormat {
             "json" => Ok(OutputFormat::Json),
             "md" | "markdown" => Ok(OutputFormat::Markdown),
             "raw" => Ok(OutputFormat::Raw),
+            "rg" | "grouped" => Ok(OutputFormat::Grouped),
+            "csv" => Ok(OutputFormat::Csv),
+            "yaml" | "yml" => Ok(OutputFormat::Yaml),
             _ => Err(format!("Unknown format: {}", s)),
         }
     }
@@ -34,21 +95,79 @@ impl std::str::FromStr for OutputFormat {
 pub struct RenderConfig {
     pub format: OutputFormat,
     pub pretty: bool,
+    /// Hard cap on rendered output size in bytes (including the trailing newline)
+    pub max_total_bytes: Option<usize>,
+    /// Emit ANSI colors (only used by human-oriented formats)
+    pub color: bool,
+    /// Markdown: list items flat instead of in collapsible per-file sections
+    pub md_flat: bool,
+    /// Drop items below this confidence before rendering (errors are kept)
+    pub min_confidence: Option<Confidence>,
+    /// Write jsonl items as they are produced, in production order (opt-in)
+    pub stream: bool,
+    /// Draw trees and tables with ASCII instead of box-drawing characters
+    pub ascii: bool,
 }
 
 impl RenderConfig {
     /// Create a new render config with default options
     #[allow(dead_code)]
     pub fn new(format: OutputFormat) -> Self {
+        Self::with_pretty(format, false)
+    }
+
+    /// Create a new render config with pretty option
+    pub fn with_pretty(format: OutputFormat, pretty: bool) -> Self {
         Self {
             format,
-            pretty: false,
+            pretty,
+            ..Default::default()
         }
     }
 
-    /// Create a new render config with pretty option
-    pub fn with_pretty(format: OutputFormat, pretty: bool) -> Self {
-        Self { format, pretty }
+    /// Set the total output byte budget
+    pub fn with_max_total_bytes(mut self, max_total_bytes: Option<usize>) -> Self {
+        self.max_total_bytes = max_total_bytes;
+        self
+    }
+
+    /// Enable or disable ANSI colors
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Render Markdown as a flat list instead of collapsible per-file sections
+    pub fn with_md_flat(mut self, md_flat: bool) -> Self {
+        self.md_flat = md_flat;
+        self
+    }
+
+    /// Opt into streaming jsonl output in production order
+    pub fn with_stream(mut self, stream: bool) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    /// Set the minimum confidence an item needs to be rendered
+    pub fn with_min_confidence(mut self, min_confidence: Option<Confidence>) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Draw trees and tables with ASCII only
+    pub fn with_ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// Line-drawing style for trees and tables
+    pub fn box_style(&self) -> BoxStyle {
+        if self.ascii {
+            BoxStyle::Ascii
+        } else {
+            BoxStyle::Unicode
+        }
     }
 }
 
@@ -71,13 +190,82 @@ impl Renderer {
     }
 
     /// Render a result set to a string
+    ///
+    /// Non-empty output ends with a newline, so callers write it with `print!`;
+    /// output capped to nothing by `--max-total-bytes` stays empty. If a
+    /// traversal was cut short by `--time-budget`, a notice item is appended.
     pub fn render(&self, result_set: &ResultSet) -> String {
+        let interrupted = crate::core::budget::was_interrupted();
+        if !interrupted && self.config.min_confidence.is_none() {
+            return self.render_budgeted(result_set);
+        }
+
+        let mut filtered = result_set.clone();
+        if let Some(min) = self.config.min_confidence {
+            filtered.retain_min_confidence(min);
+        }
+        if interrupted {
+            filtered.push(crate::core::budget::notice_item());
+        }
+        self.render_budgeted(&filtered)
+    }
+
+    fn render_budgeted(&self, result_set: &ResultSet) -> String {
+        match self.config.max_total_bytes {
+            Some(cap) => self.render_capped(result_set, cap),
+            None => self.render_document(result_set),
+        }
+    }
+
+    /// Render every item, ending non-empty output with a newline
+    fn render_document(&self, result_set: &ResultSet) -> String {
+        let mut output = self.render_all(result_set);
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output
+    }
+
+    fn render_all(&self, result_set: &ResultSet) -> String {
         match self.config.format {
             OutputFormat::Jsonl => self.render_jsonl(result_set),
             OutputFormat::Json => self.render_json(result_set),
             OutputFormat::Markdown => self.render_markdown(result_set),
             OutputFormat::Raw => self.render_raw(result_set),
+            OutputFormat::Grouped => self.render_grouped(result_set),
+            OutputFormat::Csv => self.render_csv(result_set),
+            OutputFormat::Yaml => self.render_yaml(result_set),
+        }
+    }
+
+    /// Whether items can be written as they are produced
+    ///
+    /// Streaming is opt-in (`--stream`) because it gives up the sorted order
+    /// of `ResultSet::sort`. Only compact jsonl streams: pretty output, the
+    /// other formats, the `--max-total-bytes` cap and `--min-confidence` need
+    /// the whole result set.
+    pub fn can_stream(&self) -> bool {
+        self.config.stream
+            && self.config.format == OutputFormat::Jsonl
+            && !self.config.pretty
+            && self.config.max_total_bytes.is_none()
+            && self.config.min_confidence.is_none()
+    }
+
+    /// Write items as jsonl lines while the iterator produces them
+    ///
+    /// Items are emitted in production order (no sorting). Returns the number
+    /// of items written.
+    pub fn render_streaming<W: Write>(
+        &self,
+        items: impl IntoIterator<Item = ResultItem>,
+        writer: W,
+    ) -> std::io::Result<usize> {
+        let mut stream = JsonlStream::new(writer);
+        for item in items {
+            stream.write_item(&item)?;
         }
+        stream.finish()
     }
 
     /// Render to a writer
@@ -91,6 +279,52 @@ impl Renderer {
         writer.write_all(output.as_bytes())
     }
 
+    /// Render at most `cap` bytes, trailing newline included, dropping
+    /// trailing items whole
+    ///
+    /// When items are dropped, a truncation notice item is appended.
+    fn render_capped(&self, result_set: &ResultSet, cap: usize) -> String {
+        let full = self.render_document(result_set);
+        if full.len() <= cap {
+            return full;
+        }
+
+        let total = result_set.items.len();
+        let render_prefix = |count: usize| {
+            let mut truncated = ResultSet::new();
+            truncated.extend(result_set.items[..count].iter().cloned());
+            let mut notice = ResultItem::error(MiseError::new(
+                "OUTPUT_TRUNCATED",
+                format!(
+                    "Output truncated to {} bytes: {} of {} items omitted",
+                    cap,
+                    total - count,
+                    total
+                ),
+            ));
+            notice.meta.truncated = true;
+            truncated.push(notice);
+            self.render_document(&truncated)
+        };
+
+        // Binary search for the largest prefix that fits alongside the notice
+        let (mut lo, mut hi) = (0, total);
+        let mut best: Option<String> = None;
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let output = render_prefix(mid);
+            if output.len() <= cap {
+                best = Some(output);
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+
+        // Not even the notice fits: emit nothing rather than exceed the cap
+        best.unwrap_or_default()
+    }
+
     /// Render as JSON Lines (one JSON object per line)
     fn render_jsonl(&self, result_set: &ResultSet) -> String {
         result_set
@@ -116,6 +350,13 @@ impl Renderer {
         }
     }
 
+    /// Render as a YAML sequence with the same fields as the JSON renderer
+    fn render_yaml(&self, result_set: &ResultSet) -> String {
+        serde_norway::to_string(&result_set.items)
+            .map(|yaml| yaml.trim_end().to_string())
+            .unwrap_or_else(|_| "[]".to_string())
+    }
+
     /// Render as Markdown
     fn render_markdown(&self, result_set: &ResultSet) -> String {
         let mut output = String::new();
@@ -152,6 +393,13 @@ impl Renderer {
 
         if !files.is_empty() {
             output.push_str("## Files\n\n");
+            let collapse = !self.config.md_flat;
+            if collapse {
+                output.push_str(&format!(
+                    "<details><summary>{}</summary>\n\n",
+                    count_label(files.len(), "file", "files")
+                ));
+            }
             for item in files {
                 if let Some(path) = &item.path {
                     output.push_str(&format!("- `{}`", path));
@@ -161,56 +409,105 @@ impl Renderer {
                     output.push('\n');
                 }
             }
+            if collapse {
+                output.push_str("\n</details>\n");
+            }
             output.push('\n');
         }
 
-        if !matches.is_empty() {
-            output.push_str("## Matches\n\n");
-            for item in matches {
-                self.render_item_md(&mut output, item);
+        self.render_section_md(&mut output, "Matches", &matches, ("match", "matches"));
+        self.render_section_md(&mut output, "Extracts", &extracts, ("extract", "extracts"));
+        self.render_section_md(&mut output, "Anchors", &anchors, ("anchor", "anchors"));
+        self.render_section_md(&mut output, "Flow Results", &flows, ("result", "results"));
+
+        output
+    }
+
+    /// Render one Markdown section of items
+    ///
+    /// Items are grouped by path, in order of first appearance, into
+    /// collapsible `<details>` blocks whose summary carries the item count;
+    /// with `md_flat` each item gets its own heading instead.
+    fn render_section_md(
+        &self,
+        output: &mut String,
+        title: &str,
+        items: &[&ResultItem],
+        (singular, plural): (&str, &str),
+    ) {
+        if items.is_empty() {
+            return;
+        }
+        output.push_str(&format!("## {}\n\n", title));
+
+        if self.config.md_flat {
+            for item in items {
+                self.render_item_md(output, item);
             }
             output.push('\n');
+            return;
         }
 
-        if !extracts.is_empty() {
-            output.push_str("## Extracts\n\n");
-            for item in extracts {
-                self.render_item_md(&mut output, item);
+        // Group by path in first-seen order; items without a path stay ungrouped
+        let mut groups: Vec<(&str, Vec<&ResultItem>)> = Vec::new();
+        let mut group_index: HashMap<&str, usize> = HashMap::new();
+        let mut ungrouped = Vec::new();
+        for &item in items {
+            let Some(path) = item.path.as_deref() else {
+                ungrouped.push(item);
+                continue;
+            };
+            match group_index.get(path) {
+                Some(&idx) => groups[idx].1.push(item),
+                None => {
+                    group_index.insert(path, groups.len());
+                    groups.push((path, vec![item]));
+                }
             }
-            output.push('\n');
         }
 
-        if !anchors.is_empty() {
-            output.push_str("## Anchors\n\n");
-            for item in anchors {
-                self.render_item_md(&mut output, item);
+        for (path, group) in groups {
+            output.push_str(&format!(
+                "<details><summary><code>{}</code> ({})</summary>\n\n",
+                escape_xml(path),
+                count_label(group.len(), singular, plural)
+            ));
+            for item in group {
+                self.render_item_body_md(output, item);
             }
-            output.push('\n');
+            output.push_str("</details>\n\n");
         }
 
-        if !flows.is_empty() {
-            output.push_str("## Flow Results\n\n");
-            for item in flows {
-                self.render_item_md(&mut output, item);
+        if !ungrouped.is_empty() {
+            for item in ungrouped {
+                self.render_item_md(output, item);
             }
             output.push('\n');
         }
-
-        output
     }
 
     fn render_item_md(&self, output: &mut String, item: &ResultItem) {
         if let Some(path) = &item.path {
             output.push_str(&format!("### `{}`", path));
             if let Some(range) = &item.range {
-                match range {
-                    Range::Line(r) => output.push_str(&format!(" (lines {}-{})", r.start, r.end)),
-                    Range::Byte(r) => output.push_str(&format!(" (bytes {}-{})", r.start, r.end)),
-                }
+                output.push_str(&format!(" ({})", range_label(range)));
             }
             output.push('\n');
         }
 
+        self.render_excerpt_md(output, item);
+    }
+
+    /// Render an item inside its file's group: a range heading, then the excerpt
+    fn render_item_body_md(&self, output: &mut String, item: &ResultItem) {
+        if let Some(range) = &item.range {
+            output.push_str(&format!("#### {}\n", range_label(range)));
+        }
+
+        self.render_excerpt_md(output, item);
+    }
+
+    fn render_excerpt_md(&self, output: &mut String, item: &ResultItem) {
         if let Some(excerpt) = &item.excerpt {
             output.push_str("\n```\n");
             output.push_str(excerpt);
@@ -227,6 +524,105 @@ impl Renderer {
         output.push('\n');
     }
 
+    /// Wrap text in an ANSI SGR sequence when colors are enabled
+    fn paint(&self, text: &str, sgr: &str) -> String {
+        if self.config.color {
+            format!("\x1b[{}m{}\x1b[0m", sgr, text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Render grouped by file, mimicking ripgrep's default terminal layout
+    ///
+    /// Consecutive items on the same path share one heading; each excerpt line
+    /// is prefixed with its line number. Errors are listed first.
+    fn render_grouped(&self, result_set: &ResultSet) -> String {
+        let mut lines: Vec<String> = Vec::new();
+
+        for item in result_set.items.iter().filter(|i| i.kind == Kind::Error) {
+            for error in &item.errors {
+                lines.push(format!(
+                    "{}: {}",
+                    self.paint(&format!("error[{}]", error.code), "1;31"),
+                    error.message
+                ));
+            }
+        }
+
+        let mut current_path: Option<&str> = None;
+        for item in result_set.items.iter().filter(|i| i.kind != Kind::Error) {
+            let path = item.path.as_deref().unwrap_or("<stdin>");
+            if current_path != Some(path) {
+                if !lines.is_empty() {
+                    lines.push(String::new());
+                }
+                lines.push(self.paint(path, "35"));
+                current_path = Some(path);
+            }
+
+            let start = match &item.range {
+                Some(Range::Line(r)) => Some(r.start),
+                _ => None,
+            };
+            let excerpt = item.excerpt.as_deref().unwrap_or("");
+            for (offset, text) in excerpt.lines().enumerate() {
+                match start {
+                    Some(start) => {
+                        let number = (start as usize + offset).to_string();
+                        lines.push(format!("{}:{}", self.paint(&number, "32"), text));
+                    }
+                    None => lines.push(text.to_string()),
+                }
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    /// Render as CSV: a header row, then one row per item
+    ///
+    /// Rows end with CRLF per RFC 4180. Error items carry `CODE: message` in
+    /// the excerpt column; byte ranges leave the line columns empty.
+    fn render_csv(&self, result_set: &ResultSet) -> String {
+        let label = |value: serde_json::Value| value.as_str().unwrap_or_default().to_string();
+
+        let mut rows = vec!["kind,path,start_line,end_line,confidence,excerpt".to_string()];
+        for item in &result_set.items {
+            let (start, end) = match &item.range {
+                Some(Range::Line(r)) => (r.start.to_string(), r.end.to_string()),
+                _ => (String::new(), String::new()),
+            };
+            let excerpt = if item.kind == Kind::Error {
+                item.errors
+                    .iter()
+                    .map(|e| format!("{}: {}", e.code, e.message))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            } else {
+                item.excerpt.clone().unwrap_or_default()
+            };
+
+            let fields = [
+                label(serde_json::json!(item.kind)),
+                item.path.clone().unwrap_or_default(),
+                start,
+                end,
+                label(serde_json::json!(item.confidence)),
+                excerpt,
+            ];
+            rows.push(
+                fields
+                    .iter()
+                    .map(|f| csv_field(f))
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+        }
+
+        rows.join("\r\n")
+    }
+
     /// Render as raw output (for debugging)
     fn render_raw(&self, result_set: &ResultSet) -> String {
         // Raw mode: just output excerpts directly
@@ -239,6 +635,119 @@ impl Renderer {
     }
 }
 
+/// Incremental jsonl writer for producers that push items (e.g. a walk callback)
+pub struct JsonlStream<W: Write> {
+    writer: W,
+    written: usize,
+}
+
+impl<W: Write> JsonlStream<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, written: 0 }
+    }
+
+    /// Write one item as a compact JSON line
+    pub fn write_item(&mut self, item: &ResultItem) -> std::io::Result<()> {
+        serde_json::to_writer(&mut self.writer, item)?;
+        self.writer.write_all(b"\n")?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Append the time budget notice if the run was interrupted, then flush
+    pub fn finish(mut self) -> std::io::Result<usize> {
+        if crate::core::budget::was_interrupted() {
+            self.write_item(&crate::core::budget::notice_item())?;
+        }
+        self.writer.flush()?;
+        Ok(self.written)
+    }
+}
+
+/// Treat a closed pipe (e.g. `| head`) as a normal end of a streamed output
+pub fn finish_stream(result: std::io::Result<usize>) -> anyhow::Result<()> {
+    match result {
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        Err(e) => Err(e.into()),
+        Ok(_) => Ok(()),
+    }
+}
+
+/// Quote a CSV field if it contains a comma, quote or line break (RFC 4180)
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Human-readable range for Markdown headings, e.g. `lines 3-9`
+fn range_label(range: &Range) -> String {
+    match range {
+        Range::Line(r) => format!("lines {}-{}", r.start, r.end),
+        Range::Byte(r) => format!("bytes {}-{}", r.start, r.end),
+    }
+}
+
+/// `1 match`, `3 matches`
+fn count_label(count: usize, singular: &str, plural: &str) -> String {
+    format!("{} {}", count, if count == 1 { singular } else { plural })
+}
+
+/// Marker appended to every excerpt cut short by a byte budget
+pub const TRUNCATION_MARKER: &str = "…[truncated]";
+
+/// How far back (in bytes) truncation looks for whitespace to avoid splitting a word
+const WORD_BACKOFF: usize = 32;
+
+/// Cut `text` to at most `max_bytes`, returning (excerpt, was_truncated)
+///
+/// The cut lands on a char boundary and, when whitespace is found within the
+/// last few bytes, before the partial word; `TRUNCATION_MARKER` is appended and
+/// counts toward `max_bytes`. A budget too small for the marker gets a bare cut.
+pub fn truncate_excerpt(text: &str, max_bytes: usize) -> (String, bool) {
+    if text.len() <= max_bytes {
+        return (text.to_string(), false);
+    }
+
+    let with_marker = max_bytes >= TRUNCATION_MARKER.len();
+    let budget = if with_marker {
+        max_bytes - TRUNCATION_MARKER.len()
+    } else {
+        max_bytes
+    };
+
+    let mut end = budget;
+    while end > 0 && !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    // Back off to the last whitespace if the cut falls inside a word
+    let mid_word = end > 0
+        && !text[end..].starts_with(char::is_whitespace)
+        && !text[..end].ends_with(char::is_whitespace);
+    if mid_word {
+        let window_start = end.saturating_sub(WORD_BACKOFF);
+        if let Some((i, _)) = text[..end]
+            .char_indices()
+            .rev()
+            .take_while(|(i, _)| *i >= window_start)
+            .find(|(_, c)| c.is_whitespace())
+        {
+            if i > 0 {
+                end = i;
+            }
+        }
+    }
+
+    let mut excerpt = text[..end].trim_end().to_string();
+    if with_marker {
+        excerpt.push_str(TRUNCATION_MARKER);
+    }
+    (excerpt, true)
+}
+
 /// Write raw mode warning to stderr
 #[allow(dead_code)]
 pub fn write_raw_warning() {
@@ -273,7 +782,7 @@ mod tests {
         let output = renderer.render(&result_set);
 
         assert!(output.starts_with('['));
-        assert!(output.ends_with(']'));
+        assert!(output.ends_with("]\n"));
     }
 
     #[test]
@@ -288,6 +797,90 @@ mod tests {
             OutputFormat::Markdown
         );
         assert_eq!("raw".parse::<OutputFormat>().unwrap(), OutputFormat::Raw);
+        assert_eq!("csv".parse::<OutputFormat>().unwrap(), OutputFormat::Csv);
+        assert_eq!("yml".parse::<OutputFormat>().unwrap(), OutputFormat::Yaml);
+    }
+
+    #[test]
+    fn test_render_yaml_round_trip() {
+        let mut result_set = ResultSet::new();
+        let mut item = ResultItem::match_result("src/a.rs", Range::lines(3, 4), "line: one\ntwo");
+        item.data = Some(serde_json::json!({ "id": "x", "tags": ["a"] }));
+        result_set.push(item);
+        result_set.push(ResultItem::error(MiseError::new("E_X", "bad")));
+
+        let yaml = Renderer::new(OutputFormat::Yaml).render(&result_set);
+        let parsed: Vec<ResultItem> = serde_norway::from_str(&yaml).unwrap();
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::to_value(&result_set.items).unwrap()
+        );
+
+        assert_eq!(
+            Renderer::new(OutputFormat::Yaml).render(&ResultSet::new()),
+            "[]\n"
+        );
+    }
+
+    #[test]
+    fn test_render_streaming_matches_buffered_jsonl() {
+        let mut result_set = ResultSet::new();
+        result_set.push(ResultItem::file("b.rs"));
+        result_set.push(ResultItem::match_result("a.rs", Range::lines(1, 1), "x"));
+
+        assert!(!Renderer::new(OutputFormat::Jsonl).can_stream());
+        let renderer =
+            Renderer::with_config(RenderConfig::new(OutputFormat::Jsonl).with_stream(true));
+        assert!(renderer.can_stream());
+        let mut out = Vec::new();
+        let written = renderer
+            .render_streaming(result_set.items.clone(), &mut out)
+            .unwrap();
+        assert_eq!(written, 2);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            renderer.render(&result_set)
+        );
+
+        let streaming = |format| RenderConfig::new(format).with_stream(true);
+        assert!(!Renderer::with_config(streaming(OutputFormat::Json)).can_stream());
+        let mut pretty = streaming(OutputFormat::Jsonl);
+        pretty.pretty = true;
+        assert!(!Renderer::with_config(pretty).can_stream());
+        assert!(!Renderer::with_config(
+            streaming(OutputFormat::Jsonl).with_max_total_bytes(Some(10))
+        )
+        .can_stream());
+    }
+
+    #[test]
+    fn test_finish_stream_ignores_broken_pipe() {
+        let broken = std::io::Error::from(std::io::ErrorKind::BrokenPipe);
+        assert!(finish_stream(Err(broken)).is_ok());
+        let other = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert!(finish_stream(Err(other)).is_err());
+    }
+
+    #[test]
+    fn test_render_csv() {
+        let mut result_set = ResultSet::new();
+        result_set.push(ResultItem::match_result(
+            "src/a.rs",
+            Range::lines(3, 4),
+            "let (a, b) = \"x\";\nnext",
+        ));
+        result_set.push(ResultItem::file("plain.txt"));
+        result_set.push(ResultItem::error(MiseError::new("E_X", "bad, worse")));
+
+        let output = Renderer::new(OutputFormat::Csv).render(&result_set);
+        let rows: Vec<&str> = output.split("\r\n").collect();
+        assert_eq!(rows[0], "kind,path,start_line,end_line,confidence,excerpt");
+        assert_eq!(
+            rows[1],
+            "match,src/a.rs,3,4,high,\"let (a, b) = \"\"x\"\";\nnext\""
+        );
+        assert_eq!(rows[2], "file,plain.txt,,,high,");
+        assert_eq!(rows[3], "error,,,,high,\"E_X: bad, worse\"\n");
     }
 
     #[test]
@@ -322,6 +915,64 @@ mod tests {
         assert!(!config.pretty);
     }
 
+    #[test]
+    fn test_render_max_total_bytes_never_exceeded() {
+        let mut result_set = ResultSet::new();
+        for i in 0..50 {
+            let mut item = ResultItem::file(format!("src/file_{}.rs", i));
+            item.excerpt = Some("x".repeat(i * 7));
+            result_set.push(item);
+        }
+
+        for format in [
+            OutputFormat::Jsonl,
+            OutputFormat::Json,
+            OutputFormat::Markdown,
+            OutputFormat::Raw,
+        ] {
+            for pretty in [false, true] {
+                for cap in [0, 1, 10, 200, 500, 1000, 4096, 100_000] {
+                    let config =
+                        RenderConfig::with_pretty(format, pretty).with_max_total_bytes(Some(cap));
+                    // The output is printed as-is, trailing newline included
+                    let output = Renderer::with_config(config).render(&result_set);
+                    assert!(
+                        output.len() <= cap && (output.is_empty() || output.ends_with('\n')),
+                        "{:?} pretty={} cap={} produced {} bytes",
+                        format,
+                        pretty,
+                        cap,
+                        output.len()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_max_total_bytes_truncates_at_item_boundary() {
+        let mut result_set = ResultSet::new();
+        for i in 0..20 {
+            result_set.push(ResultItem::file(format!("src/file_{}.rs", i)));
+        }
+
+        let config = RenderConfig::new(OutputFormat::Jsonl).with_max_total_bytes(Some(1000));
+        let output = Renderer::with_config(config).render(&result_set);
+
+        let lines: Vec<&str> = output.lines().collect();
+        for line in &lines {
+            assert!(serde_json::from_str::<serde_json::Value>(line).is_ok());
+        }
+        let last: serde_json::Value = serde_json::from_str(lines.last().unwrap()).unwrap();
+        assert_eq!(last["errors"][0]["code"], "OUTPUT_TRUNCATED");
+        assert_eq!(last["meta"]["truncated"], true);
+        assert!(lines.len() < 21);
+
+        let config = RenderConfig::new(OutputFormat::Jsonl).with_max_total_bytes(Some(100_000));
+        let output = Renderer::with_config(config).render(&result_set);
+        assert_eq!(output.lines().count(), 20);
+    }
+
     #[test]
     fn test_render_config_with_pretty() {
         let config = RenderConfig::with_pretty(OutputFormat::Jsonl, true);
@@ -336,6 +987,13 @@ mod tests {
         assert!(!config.pretty);
     }
 
+    #[test]
+    fn test_render_config_box_style() {
+        let config = RenderConfig::new(OutputFormat::Jsonl);
+        assert_eq!(config.box_style(), BoxStyle::Unicode);
+        assert_eq!(config.with_ascii(true).box_style(), BoxStyle::Ascii);
+    }
+
     #[test]
     fn test_render_jsonl_pretty() {
         let mut result_set = ResultSet::new();
@@ -474,6 +1132,96 @@ mod tests {
         assert!(output.contains("bytes 100-200"));
     }
 
+    #[test]
+    fn test_render_min_confidence() {
+        let mut result_set = ResultSet::new();
+        result_set.push(ResultItem::file("sure.rs"));
+        result_set.push(ResultItem::file("guess.rs").with_confidence(Confidence::Low));
+
+        let config =
+            RenderConfig::new(OutputFormat::Jsonl).with_min_confidence(Some(Confidence::Medium));
+        let renderer = Renderer::with_config(config.with_stream(true));
+        assert!(!renderer.can_stream());
+        let output = renderer.render(&result_set);
+        assert!(output.contains("sure.rs"));
+        assert!(!output.contains("guess.rs"));
+    }
+
+    #[test]
+    fn test_render_markdown_collapsible_groups() {
+        let mut result_set = ResultSet::new();
+        result_set.push(ResultItem::match_result(
+            "src/a.rs",
+            Range::lines(3, 4),
+            "a1",
+        ));
+        result_set.push(ResultItem::match_result(
+            "src/b.rs",
+            Range::lines(1, 1),
+            "b1",
+        ));
+        result_set.push(ResultItem::match_result(
+            "src/a.rs",
+            Range::lines(9, 9),
+            "a2",
+        ));
+
+        let output = Renderer::new(OutputFormat::Markdown).render(&result_set);
+        assert!(output.contains("<details><summary><code>src/a.rs</code> (2 matches)</summary>"));
+        assert!(output.contains("<details><summary><code>src/b.rs</code> (1 match)</summary>"));
+        assert_eq!(output.matches("</details>").count(), 2);
+        assert!(output.contains("#### lines 9-9"));
+        assert!(output.find("a2").unwrap() < output.find("src/b.rs").unwrap());
+
+        // Items without a path are rendered as-is, outside any group
+        let mut piped = ResultItem::match_result("x", Range::lines(1, 1), "from a pipe");
+        piped.path = None;
+        result_set.push(piped);
+        let output = Renderer::new(OutputFormat::Markdown).render(&result_set);
+        assert!(output.contains("from a pipe"));
+        assert!(!output.contains("<stdin>"));
+        assert_eq!(output.matches("</details>").count(), 2);
+
+        let flat =
+            Renderer::with_config(RenderConfig::new(OutputFormat::Markdown).with_md_flat(true))
+                .render(&result_set);
+        assert!(!flat.contains("<details>"));
+        assert!(flat.contains("### `src/a.rs` (lines 9-9)"));
+    }
+
+    #[test]
+    fn test_render_grouped() {
+        let mut result_set = ResultSet::new();
+        result_set.push(ResultItem::match_result(
+            "src/a.rs",
+            Range::lines(3, 4),
+            "fn a() {\n}",
+        ));
+        result_set.push(ResultItem::match_result(
+            "src/a.rs",
+            Range::lines(9, 9),
+            "a();",
+        ));
+        result_set.push(ResultItem::match_result(
+            "src/b.rs",
+            Range::lines(1, 1),
+            "b();",
+        ));
+
+        let renderer = Renderer::new(OutputFormat::Grouped);
+        assert_eq!(
+            renderer.render(&result_set),
+            "src/a.rs\n3:fn a() {\n4:}\n9:a();\n\nsrc/b.rs\n1:b();\n"
+        );
+
+        let colored =
+            Renderer::with_config(RenderConfig::new(OutputFormat::Grouped).with_color(true));
+        assert!(colored
+            .render(&result_set)
+            .contains("\x1b[35msrc/a.rs\x1b[0m\n\x1b[32m3\x1b[0m:fn a() {"));
+        assert_eq!("rg".parse::<OutputFormat>().unwrap(), OutputFormat::Grouped);
+    }
+
     #[test]
     fn test_render_raw() {
         let mut result_set = ResultSet::new();
@@ -530,6 +1278,27 @@ mod tests {
         assert!(output.contains("## Flow Results"));
     }
 
+    #[test]
+    fn test_truncate_excerpt() {
+        assert_eq!(truncate_excerpt("short", 64), ("short".to_string(), false));
+
+        // Backs off to the last space instead of splitting "truncated"
+        let (excerpt, truncated) = truncate_excerpt("a line that should be truncated here", 30);
+        assert!(truncated);
+        assert_eq!(excerpt, "a line that…[truncated]");
+        assert!(excerpt.len() <= 30);
+
+        // No whitespace nearby: hard cut on a char boundary
+        let (excerpt, _) = truncate_excerpt(&"你".repeat(20), 20);
+        assert_eq!(excerpt, "你你…[truncated]");
+
+        // Budget smaller than the marker: bare cut
+        assert_eq!(
+            truncate_excerpt("hello world", 5),
+            ("hello".to_string(), true)
+        );
+    }
+
     #[test]
     fn test_output_format_default() {
         let format: OutputFormat = Default::default();
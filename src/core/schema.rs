@@ -0,0 +1,113 @@
+//! JSON Schema export - Machine-readable contract for mise output
+//!
+//! Schemas are derived from the serde types, so they track the emitted JSON:
+//! - `result-set`: the ResultSet/ResultItem model shared by all commands
+//! - `deps`: the `data` payload of `deps` items
+//! - `impact`: `impact --impact-format json`
+//! - `stats`: `flow stats --stats-format json`
+
+use anyhow::Result;
+use schemars::{schema_for, Schema};
+
+use crate::backends::deps::DepsData;
+use crate::backends::impact::ImpactAnalysis;
+use crate::core::model::ResultSet;
+use crate::flows::stats::ProjectStats;
+
+/// Which output the schema describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaTarget {
+    /// ResultSet with its items (default)
+    #[default]
+    ResultSet,
+    /// `data` payload of deps items
+    Deps,
+    /// Impact analysis JSON
+    Impact,
+    /// Project statistics JSON
+    Stats,
+}
+
+impl std::str::FromStr for SchemaTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "result-set" | "resultset" | "result" => Ok(SchemaTarget::ResultSet),
+            "deps" => Ok(SchemaTarget::Deps),
+            "impact" => Ok(SchemaTarget::Impact),
+            "stats" => Ok(SchemaTarget::Stats),
+            _ => Err(format!("Unknown schema target: {}", s)),
+        }
+    }
+}
+
+/// Build the JSON Schema for a target
+pub fn schema_for_target(target: SchemaTarget) -> Schema {
+    match target {
+        SchemaTarget::ResultSet => schema_for!(ResultSet),
+        SchemaTarget::Deps => schema_for!(DepsData),
+        SchemaTarget::Impact => schema_for!(ImpactAnalysis),
+        SchemaTarget::Stats => schema_for!(ProjectStats),
+    }
+}
+
+/// Run the schema command
+pub fn run_schema(target: SchemaTarget) -> Result<()> {
+    let schema = schema_for_target(target);
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_target_parse() {
+        assert_eq!(
+            "resultset".parse::<SchemaTarget>().unwrap(),
+            SchemaTarget::ResultSet
+        );
+        assert_eq!(
+            "stats".parse::<SchemaTarget>().unwrap(),
+            SchemaTarget::Stats
+        );
+        assert!("model".parse::<SchemaTarget>().is_err());
+    }
+
+    #[test]
+    fn test_result_set_schema_covers_enums() {
+        let schema = schema_for_target(SchemaTarget::ResultSet);
+        let json = serde_json::to_value(&schema).unwrap();
+        let defs = &json["$defs"];
+
+        let values = |name: &str| -> Vec<String> {
+            serde_json::from_value(defs[name]["enum"].clone()).unwrap()
+        };
+        assert_eq!(
+            values("Kind"),
+            ["file", "match", "extract", "anchor", "flow", "error"]
+        );
+        assert_eq!(values("Confidence"), ["high", "medium", "low"]);
+        assert_eq!(
+            values("SourceMode"),
+            ["scan", "rg", "astgrep", "anchor", "mixed"]
+        );
+        assert!(defs["ResultItem"]["properties"]["data"].is_object());
+    }
+
+    #[test]
+    fn test_payload_schemas() {
+        for target in [
+            SchemaTarget::Deps,
+            SchemaTarget::Impact,
+            SchemaTarget::Stats,
+        ] {
+            let json = serde_json::to_value(schema_for_target(target)).unwrap();
+            assert!(json["$schema"].is_string());
+        }
+        let impact = serde_json::to_value(schema_for_target(SchemaTarget::Impact)).unwrap();
+        assert!(impact["properties"]["severities"].is_object());
+    }
+}
@@ -4,27 +4,57 @@ use sha1::{Digest, Sha1};
 use std::fs::File;
 use std::io::{BufReader, Read};
 use std::path::Path;
-use std::time::SystemTime;
-use xxhash_rust::xxh3::xxh3_64;
+use std::time::{Duration, SystemTime};
+use xxhash_rust::xxh3::{xxh3_64, Xxh3};
 
 /// Hash algorithm selection
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum HashAlgorithm {
     #[default]
     Xxh3,
-    #[allow(dead_code)]
     Sha1,
 }
 
-/// Compute hash of file content
-#[allow(dead_code)]
+impl std::str::FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "xxhash" | "xxh3" => Ok(HashAlgorithm::Xxh3),
+            "sha1" => Ok(HashAlgorithm::Sha1),
+            _ => Err(format!(
+                "Unknown hash algorithm: {} (expected xxhash or sha1)",
+                s
+            )),
+        }
+    }
+}
+
+/// Compute hash of file content, reading it in chunks
 pub fn hash_file(path: &Path, algorithm: HashAlgorithm) -> std::io::Result<String> {
-    let file = File::open(path)?;
-    let mut reader = BufReader::new(file);
-    let mut buffer = Vec::new();
-    reader.read_to_end(&mut buffer)?;
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut feed = |update: &mut dyn FnMut(&[u8])| -> std::io::Result<()> {
+        loop {
+            match reader.read(&mut buffer)? {
+                0 => return Ok(()),
+                n => update(&buffer[..n]),
+            }
+        }
+    };
 
-    Ok(hash_bytes(&buffer, algorithm))
+    Ok(match algorithm {
+        HashAlgorithm::Xxh3 => {
+            let mut hasher = Xxh3::new();
+            feed(&mut |chunk| hasher.update(chunk))?;
+            format!("{:016x}", hasher.digest())
+        }
+        HashAlgorithm::Sha1 => {
+            let mut hasher = Sha1::new();
+            feed(&mut |chunk| hasher.update(chunk))?;
+            format!("{:x}", hasher.finalize())
+        }
+    })
 }
 
 /// Compute hash of bytes
@@ -55,19 +85,73 @@ pub fn get_file_size(path: &Path) -> std::io::Result<u64> {
     Ok(metadata.len())
 }
 
-/// Truncate string to max bytes, returning (truncated_string, was_truncated)
-pub fn truncate_string(s: &str, max_bytes: usize) -> (String, bool) {
-    if s.len() <= max_bytes {
-        return (s.to_string(), false);
-    }
+/// Parse a byte size with an optional binary suffix: `512`, `10k`, `2M`, `1G`, `4KB`
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let trimmed = s.trim();
+    let lower = trimmed.to_ascii_lowercase();
+    let lower = lower.strip_suffix('b').unwrap_or(&lower);
+    let (digits, multiplier) = match lower.char_indices().last() {
+        Some((i, 'k')) => (&lower[..i], 1u64 << 10),
+        Some((i, 'm')) => (&lower[..i], 1 << 20),
+        Some((i, 'g')) => (&lower[..i], 1 << 30),
+        _ => (lower, 1),
+    };
+
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| format!("Invalid size: {} (expected e.g. 512, 10k, 2M, 1G)", trimmed))
+}
 
-    // Find a valid UTF-8 boundary
-    let mut end = max_bytes;
-    while end > 0 && !s.is_char_boundary(end) {
-        end -= 1;
-    }
+/// Parse a duration like `30s`, `30m`, `12h`, `7d` or `2w`
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let trimmed = s.trim();
+    let invalid = || {
+        format!(
+            "Invalid duration: {} (expected e.g. 30m, 12h, 7d, 2w)",
+            trimmed
+        )
+    };
+
+    let unit = trimmed.chars().last().ok_or_else(invalid)?;
+    let seconds = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        'w' => 7 * 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+
+    trimmed[..trimmed.len() - 1]
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(seconds))
+        .map(Duration::from_secs)
+        .ok_or_else(invalid)
+}
 
-    (s[..end].to_string(), true)
+/// Format milliseconds since the epoch as an RFC 3339 UTC timestamp
+pub fn format_rfc3339_ms(ms: i64) -> Option<String> {
+    chrono::DateTime::from_timestamp_millis(ms)
+        .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+}
+
+/// Escape text for inclusion in XML/HTML content or attribute values
+pub fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
 }
 
 /// Check if a command is available in PATH
@@ -79,6 +163,17 @@ pub fn command_exists(cmd: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// Resolve a command to its full path on PATH (via `which`)
+pub fn command_path(cmd: &str) -> Option<String> {
+    let output = std::process::Command::new("which").arg(cmd).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let path = stdout.lines().next()?.trim();
+    (!path.is_empty()).then(|| path.to_string())
+}
+
 /// Get current timestamp in milliseconds
 pub fn now_ms() -> i64 {
     SystemTime::now()
@@ -103,22 +198,22 @@ mod tests {
     }
 
     #[test]
-    fn test_truncate_string() {
-        let s = "hello world";
-        let (truncated, was_truncated) = truncate_string(s, 5);
-        assert_eq!(truncated, "hello");
-        assert!(was_truncated);
-
-        let (not_truncated, was_truncated) = truncate_string(s, 100);
-        assert_eq!(not_truncated, s);
-        assert!(!was_truncated);
-    }
-
-    #[test]
-    fn test_truncate_string_utf8() {
-        let s = "你好世界";
-        let (truncated, _) = truncate_string(s, 6);
-        assert_eq!(truncated, "你好"); // Each Chinese char is 3 bytes
+    fn test_hash_file_matches_hash_bytes() {
+        let temp = tempfile::tempdir().unwrap();
+        let file_path = temp.path().join("big.bin");
+        // Spans several read chunks
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&file_path, &data).unwrap();
+
+        for algorithm in [HashAlgorithm::Xxh3, HashAlgorithm::Sha1] {
+            assert_eq!(
+                hash_file(&file_path, algorithm).unwrap(),
+                hash_bytes(&data, algorithm)
+            );
+        }
+        assert_eq!("xxhash".parse(), Ok(HashAlgorithm::Xxh3));
+        assert_eq!("SHA1".parse(), Ok(HashAlgorithm::Sha1));
+        assert!("md5".parse::<HashAlgorithm>().is_err());
     }
 
     #[test]
@@ -169,6 +264,15 @@ mod tests {
         assert_eq!(size, 11);
     }
 
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(escape_xml("plain"), "plain");
+        assert_eq!(
+            escape_xml(r#"<a href="x">&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&apos;&lt;/a&gt;"
+        );
+    }
+
     #[test]
     fn test_command_exists() {
         // 'ls' should exist on Unix systems
@@ -185,22 +289,6 @@ mod tests {
         assert!(now > 1577836800000); // 2020-01-01 00:00:00 UTC
     }
 
-    #[test]
-    fn test_truncate_string_exact_boundary() {
-        let s = "hello";
-        let (truncated, was_truncated) = truncate_string(s, 5);
-        assert_eq!(truncated, "hello");
-        assert!(!was_truncated);
-    }
-
-    #[test]
-    fn test_truncate_string_empty() {
-        let s = "";
-        let (truncated, was_truncated) = truncate_string(s, 10);
-        assert_eq!(truncated, "");
-        assert!(!was_truncated);
-    }
-
     #[test]
     fn test_hash_algorithm_default() {
         let algo: HashAlgorithm = Default::default();
@@ -208,33 +296,37 @@ mod tests {
     }
 
     #[test]
-    fn test_truncate_string_unicode_boundary() {
-        // Each Chinese character is 3 bytes in UTF-8
-        // "你好" = 6 bytes (你=3, 好=3)
-        // Truncating at 4 bytes would land in the middle of "好"
-        // Should find previous valid boundary at 3 bytes
-        let s = "你好";
-        let (truncated, was_truncated) = truncate_string(s, 4);
-        assert_eq!(truncated, "你"); // Only first char fits
-        assert!(was_truncated);
+    fn test_parse_size() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("10k"), Ok(10 * 1024));
+        assert_eq!(parse_size("2M"), Ok(2 * 1024 * 1024));
+        assert_eq!(parse_size("1G"), Ok(1 << 30));
+        assert_eq!(parse_size("4KB"), Ok(4096));
+        assert_eq!(parse_size("0"), Ok(0));
+        assert!(parse_size("").is_err());
+        assert!(parse_size("k").is_err());
+        assert!(parse_size("1.5M").is_err());
+        assert!(parse_size("-1").is_err());
     }
 
     #[test]
-    fn test_truncate_string_mid_char() {
-        // 🎉 is 4 bytes in UTF-8
-        // Truncating at byte 2 should go back to 0
-        let s = "🎉test";
-        let (truncated, was_truncated) = truncate_string(s, 2);
-        assert_eq!(truncated, ""); // Can't fit even one char
-        assert!(was_truncated);
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("12h"), Ok(Duration::from_secs(12 * 3600)));
+        assert_eq!(parse_duration("7d"), Ok(Duration::from_secs(7 * 86400)));
+        assert_eq!(parse_duration("2w"), Ok(Duration::from_secs(14 * 86400)));
+        assert!(parse_duration("7").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("1.5h").is_err());
+        assert!(parse_duration("").is_err());
     }
 
     #[test]
-    fn test_truncate_string_at_char_boundary() {
-        // "abc" are each 1 byte
-        let s = "abc";
-        let (truncated, was_truncated) = truncate_string(s, 2);
-        assert_eq!(truncated, "ab");
-        assert!(was_truncated);
+    fn test_format_rfc3339_ms() {
+        assert_eq!(
+            format_rfc3339_ms(1_700_000_000_123).as_deref(),
+            Some("2023-11-14T22:13:20Z")
+        );
     }
 }
@@ -6,13 +6,15 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::anchors::parse::{parse_file, Anchor};
-use crate::backends::scan::{scan_files, ScanOptions};
+use crate::backends::scan::{scan_files, GlobFilter, ScanOptions};
+use crate::core::file_reader::{binary_skipped_item, is_probably_binary};
 use crate::core::model::{Confidence, Kind, ResultItem, ResultSet, SourceMode};
-use crate::core::render::{RenderConfig, Renderer};
+use crate::core::render::{BoxStyle, RenderConfig, Renderer};
 use crate::core::tokenizer::{count_tokens, TokenModel};
+use crate::core::util::escape_xml;
 
 /// Outline item representing an anchor with its content stats
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +58,61 @@ pub struct ProjectOutline {
     pub total_tokens: usize,
     /// Anchors grouped by tag
     pub by_tag: HashMap<String, Vec<String>>,
+    /// Files left out because they look binary
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub binary_skipped: Vec<String>,
+}
+
+impl ProjectOutline {
+    /// Drop items nested deeper than `max_level` (0 = top level only)
+    ///
+    /// Totals keep describing every anchor unless `visible_totals` is set, in
+    /// which case they are recomputed from the remaining items.
+    pub fn limit_level(&mut self, max_level: usize, visible_totals: bool) {
+        self.items.retain(|item| item.level <= max_level);
+        for ids in self.by_tag.values_mut() {
+            ids.retain(|id| self.items.iter().any(|item| &item.id == id));
+        }
+        self.by_tag.retain(|_, ids| !ids.is_empty());
+
+        if visible_totals {
+            self.total_chars = self.items.iter().map(|i| i.chars).sum();
+            self.total_words = self.items.iter().map(|i| i.words).sum();
+            self.total_cjk_chars = self.items.iter().map(|i| i.cjk_chars).sum();
+            self.total_tokens = self.items.iter().map(|i| i.tokens).sum();
+        }
+    }
+}
+
+/// Options for the outline command
+#[derive(Debug, Clone, Default)]
+pub struct OutlineOptions {
+    /// Limit outline to this subdirectory
+    pub scope: Option<PathBuf>,
+    /// Only include anchors with this tag
+    pub tag: Option<String>,
+    /// Extensions to include (default: common text formats)
+    pub extensions: Option<Vec<String>>,
+    /// Token model for counting
+    pub token_model: TokenModel,
+    /// Hide items nested deeper than this level
+    pub max_level: Option<usize>,
+    /// Compute totals from shown items only (with `max_level`)
+    pub totals_visible_only: bool,
+    /// Parse files that look binary instead of skipping them
+    pub include_binary: bool,
+    /// Include/exclude globs over root-relative paths
+    pub filter: GlobFilter,
+}
+
+/// Outline item with the anchors it contains nested beneath it
+#[derive(Debug, Clone, Serialize)]
+pub struct OutlineNode {
+    #[serde(flatten)]
+    pub item: OutlineItem,
+    /// Anchors contained within this anchor's range
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<OutlineNode>,
 }
 
 /// Check if a character is CJK
@@ -155,13 +212,15 @@ fn anchor_to_outline_item(
 pub fn generate_outline(
     root: &Path,
     scope: Option<&Path>,
+    filter: &GlobFilter,
     tag_filter: Option<&str>,
     extensions: Option<&[&str]>,
     token_model: TokenModel,
+    include_binary: bool,
 ) -> Result<ProjectOutline> {
     use crate::cache::reader::get_files_cached;
 
-    let files = if scope.is_some() {
+    let mut files = if scope.is_some() {
         // If scope is specified, do a direct scan (scope is specific)
         let options = ScanOptions {
             scope: scope.map(|p| p.to_path_buf()),
@@ -174,11 +233,13 @@ pub fn generate_outline(
         // Use cached files when no scope
         get_files_cached(root)?
     };
+    filter.retain(&mut files);
 
     let default_exts = ["md", "txt", "rst", "adoc", "org", "tex", "html", "xml"];
     let exts: &[&str] = extensions.unwrap_or(&default_exts);
 
     let mut all_anchors: Vec<Anchor> = Vec::new();
+    let mut binary_skipped = Vec::new();
 
     // Collect all anchors
     for file_item in &files.items {
@@ -189,6 +250,10 @@ pub fn generate_outline(
             }
 
             let full_path = root.join(path);
+            if !include_binary && is_probably_binary(&full_path) {
+                binary_skipped.push(path.clone());
+                continue;
+            }
             let anchors = parse_file(&full_path, path);
             all_anchors.extend(anchors);
         }
@@ -233,6 +298,7 @@ pub fn generate_outline(
         total_cjk_chars,
         total_tokens,
         by_tag,
+        binary_skipped,
     })
 }
 
@@ -244,8 +310,12 @@ pub enum OutlineFormat {
     Markdown,
     /// JSON output
     Json,
+    /// JSON output with contained anchors nested under their parents
+    JsonTree,
     /// Tree view
     Tree,
+    /// HTML fragment with nested lists and line links
+    Html,
     /// Standard ResultSet
     Standard,
 }
@@ -257,13 +327,59 @@ impl std::str::FromStr for OutlineFormat {
         match s.to_lowercase().as_str() {
             "md" | "markdown" => Ok(OutlineFormat::Markdown),
             "json" => Ok(OutlineFormat::Json),
+            "json-tree" | "jsontree" | "nested" => Ok(OutlineFormat::JsonTree),
             "tree" => Ok(OutlineFormat::Tree),
+            "html" => Ok(OutlineFormat::Html),
             "standard" | "default" => Ok(OutlineFormat::Standard),
             _ => Err(format!("Unknown outline format: {}", s)),
         }
     }
 }
 
+/// Nest outline items under the innermost anchor that contains them in the same file
+///
+/// Expects items sorted by path, then start line (as produced by `generate_outline`).
+pub fn build_outline_tree(items: &[OutlineItem]) -> Vec<OutlineNode> {
+    let contains = |outer: &OutlineItem, inner: &OutlineItem| {
+        outer.path == inner.path
+            && outer.start_line <= inner.start_line
+            && outer.end_line >= inner.end_line
+    };
+
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); items.len()];
+    let mut roots = Vec::new();
+    let mut stack: Vec<usize> = Vec::new();
+
+    for (idx, item) in items.iter().enumerate() {
+        while let Some(&top) = stack.last() {
+            if contains(&items[top], item) {
+                break;
+            }
+            stack.pop();
+        }
+        match stack.last() {
+            Some(&parent) => children[parent].push(idx),
+            None => roots.push(idx),
+        }
+        stack.push(idx);
+    }
+
+    fn build(idx: usize, items: &[OutlineItem], children: &[Vec<usize>]) -> OutlineNode {
+        OutlineNode {
+            item: items[idx].clone(),
+            children: children[idx]
+                .iter()
+                .map(|&child| build(child, items, children))
+                .collect(),
+        }
+    }
+
+    roots
+        .into_iter()
+        .map(|idx| build(idx, items, &children))
+        .collect()
+}
+
 /// Render outline as Markdown
 fn render_markdown(outline: &ProjectOutline) -> String {
     let mut output = String::new();
@@ -323,7 +439,7 @@ fn render_markdown(outline: &ProjectOutline) -> String {
 }
 
 /// Render outline as tree
-fn render_tree(outline: &ProjectOutline) -> String {
+fn render_tree(outline: &ProjectOutline, style: BoxStyle) -> String {
     let mut output = String::new();
 
     output.push_str("📑 Document Outline\n");
@@ -348,11 +464,11 @@ fn render_tree(outline: &ProjectOutline) -> String {
         }
 
         let prefix = if is_last_in_file {
-            "└── "
+            style.elbow()
         } else {
-            "├── "
+            style.tee()
         };
-        let level_indent = "│   ".repeat(item.level);
+        let level_indent = style.pipe().repeat(item.level);
 
         output.push_str(&format!(
             "{}{}[{}] {} chars ({} words)\n",
@@ -363,6 +479,74 @@ fn render_tree(outline: &ProjectOutline) -> String {
     output
 }
 
+/// Render outline as an HTML fragment
+///
+/// Items are nested `<ul>`/`<li>` lists grouped by file (children by
+/// containment), each linking to `path#Lstart` with count badges.
+fn render_html(outline: &ProjectOutline) -> String {
+    fn push_node(output: &mut String, node: &OutlineNode, depth: usize) {
+        let item = &node.item;
+        let indent = "  ".repeat(depth);
+        output.push_str(&format!(
+            "{}<li><a href=\"{}#L{}\">{}</a> \
+             <span class=\"badge\">{} chars</span> \
+             <span class=\"badge\">{} words</span> \
+             <span class=\"badge\">~{} tokens</span>",
+            indent,
+            escape_xml(&item.path),
+            item.start_line,
+            escape_xml(&item.id),
+            item.chars,
+            item.words,
+            item.tokens
+        ));
+        if let Some(preview) = &item.preview {
+            output.push_str(&format!(
+                " <span class=\"preview\">{}</span>",
+                escape_xml(preview)
+            ));
+        }
+        if node.children.is_empty() {
+            output.push_str("</li>\n");
+            return;
+        }
+        output.push_str(&format!("\n{}  <ul>\n", indent));
+        for child in &node.children {
+            push_node(output, child, depth + 2);
+        }
+        output.push_str(&format!("{}  </ul>\n{}</li>\n", indent, indent));
+    }
+
+    let mut output = String::new();
+    output.push_str("<nav class=\"outline\">\n");
+    output.push_str(&format!(
+        "<p class=\"outline-summary\">{} anchors | {} chars | {} words | ~{} tokens</p>\n",
+        outline.items.len(),
+        outline.total_chars,
+        outline.total_words,
+        outline.total_tokens
+    ));
+    output.push_str("<ul>\n");
+
+    let tree = build_outline_tree(&outline.items);
+    for (idx, node) in tree.iter().enumerate() {
+        let path = &node.item.path;
+        if idx == 0 || tree[idx - 1].item.path != *path {
+            output.push_str(&format!(
+                "  <li class=\"file\"><code>{}</code>\n    <ul>\n",
+                escape_xml(path)
+            ));
+        }
+        push_node(&mut output, node, 3);
+        if tree.get(idx + 1).map(|n| &n.item.path) != Some(path) {
+            output.push_str("    </ul>\n  </li>\n");
+        }
+    }
+
+    output.push_str("</ul>\n</nav>");
+    output
+}
+
 /// Convert outline to ResultSet
 fn outline_to_result_set(outline: &ProjectOutline) -> ResultSet {
     let mut result_set = ResultSet::new();
@@ -411,44 +595,77 @@ fn outline_to_result_set(outline: &ProjectOutline) -> ResultSet {
         result_set.push(result_item);
     }
 
+    for path in &outline.binary_skipped {
+        result_set.push(binary_skipped_item(path.clone()));
+    }
+
     result_set
 }
 
 /// Run the outline command
 pub fn run_outline(
     root: &Path,
-    scope: Option<&Path>,
-    tag_filter: Option<&str>,
-    extensions: Option<Vec<String>>,
+    options: OutlineOptions,
     outline_format: OutlineFormat,
-    token_model: TokenModel,
     config: RenderConfig,
 ) -> Result<()> {
-    let ext_refs: Option<Vec<&str>> = extensions
+    let ext_refs: Option<Vec<&str>> = options
+        .extensions
         .as_ref()
         .map(|v| v.iter().map(|s| s.as_str()).collect());
     let ext_slice: Option<&[&str]> = ext_refs.as_deref();
 
-    let outline = generate_outline(root, scope, tag_filter, ext_slice, token_model)?;
+    let mut outline = generate_outline(
+        root,
+        options.scope.as_deref(),
+        &options.filter,
+        options.tag.as_deref(),
+        ext_slice,
+        options.token_model,
+        options.include_binary,
+    )?;
+    if let Some(max_level) = options.max_level {
+        outline.limit_level(max_level, options.totals_visible_only);
+    }
 
     match outline_format {
         OutlineFormat::Json => {
             let json = serde_json::to_string_pretty(&outline)?;
             println!("{}", json);
         }
+        OutlineFormat::JsonTree => {
+            let mut value = serde_json::to_value(&outline)?;
+            value["items"] = serde_json::to_value(build_outline_tree(&outline.items))?;
+            println!("{}", serde_json::to_string_pretty(&value)?);
+        }
         OutlineFormat::Markdown => {
             println!("{}", render_markdown(&outline));
         }
         OutlineFormat::Tree => {
-            println!("{}", render_tree(&outline));
+            println!("{}", render_tree(&outline, config.box_style()));
+        }
+        OutlineFormat::Html => {
+            println!("{}", render_html(&outline));
         }
         OutlineFormat::Standard => {
             let result_set = outline_to_result_set(&outline);
             let renderer = Renderer::with_config(config);
-            println!("{}", renderer.render(&result_set));
+            print!("{}", renderer.render(&result_set));
         }
     }
 
+    // Markdown, tree and HTML output have no room for the note items
+    if matches!(
+        outline_format,
+        OutlineFormat::Markdown | OutlineFormat::Tree | OutlineFormat::Html
+    ) && !outline.binary_skipped.is_empty()
+    {
+        eprintln!(
+            "note: skipped {} binary file(s); use --include-binary to parse them",
+            outline.binary_skipped.len()
+        );
+    }
+
     Ok(())
 }
 
@@ -584,6 +801,7 @@ mod tests {
             total_tokens: 150,
             items: vec![],
             by_tag: std::collections::HashMap::new(),
+            binary_skipped: vec![],
         };
         assert_eq!(outline.total_chars, 500);
         assert!(outline.items.is_empty());
@@ -626,6 +844,10 @@ mod tests {
             "default".parse::<OutlineFormat>().unwrap(),
             OutlineFormat::Standard
         );
+        assert_eq!(
+            "html".parse::<OutlineFormat>().unwrap(),
+            OutlineFormat::Html
+        );
     }
 
     #[test]
@@ -637,6 +859,7 @@ mod tests {
             total_cjk_chars: 0,
             total_tokens: 0,
             by_tag: HashMap::new(),
+            binary_skipped: vec![],
         };
         let md = render_markdown(&outline);
         assert!(md.contains("Document Outline"));
@@ -667,6 +890,7 @@ mod tests {
             total_cjk_chars: 0,
             total_tokens: 25,
             by_tag,
+            binary_skipped: vec![],
         };
         let md = render_markdown(&outline);
         assert!(md.contains("📄 test.md"));
@@ -684,8 +908,9 @@ mod tests {
             total_cjk_chars: 0,
             total_tokens: 0,
             by_tag: HashMap::new(),
+            binary_skipped: vec![],
         };
-        let tree = render_tree(&outline);
+        let tree = render_tree(&outline, BoxStyle::Unicode);
         assert!(tree.contains("Document Outline"));
         assert!(tree.contains("0 anchors"));
     }
@@ -726,12 +951,19 @@ mod tests {
             total_cjk_chars: 0,
             total_tokens: 37,
             by_tag: HashMap::new(),
+            binary_skipped: vec![],
         };
-        let tree = render_tree(&outline);
+        let tree = render_tree(&outline, BoxStyle::Unicode);
         assert!(tree.contains("📄 test.md"));
         assert!(tree.contains("[item1]"));
         assert!(tree.contains("[item2]"));
         assert!(tree.contains("├──") || tree.contains("└──"));
+
+        let ascii = render_tree(&outline, BoxStyle::Ascii);
+        assert!(ascii.contains("|-- [item1]"));
+        assert!(!ascii
+            .chars()
+            .any(|c| ('\u{2500}'..='\u{257F}').contains(&c)));
     }
 
     #[test]
@@ -755,6 +987,7 @@ mod tests {
             total_cjk_chars: 10,
             total_tokens: 50,
             by_tag: HashMap::new(),
+            binary_skipped: vec![],
         };
 
         let result_set = outline_to_result_set(&outline);
@@ -852,12 +1085,187 @@ mod tests {
             total_cjk_chars: 0,
             total_tokens: 75,
             by_tag: HashMap::new(),
+            binary_skipped: vec![],
         };
         let md = render_markdown(&outline);
         assert!(md.contains("[parent]"));
         assert!(md.contains("[child]"));
     }
 
+    #[test]
+    fn test_render_html_nested_and_escaped() {
+        let item = |id: &str, path: &str, start: u32, end: u32, level: usize| OutlineItem {
+            id: id.to_string(),
+            path: path.to_string(),
+            tags: vec![],
+            start_line: start,
+            end_line: end,
+            chars: 10,
+            words: 2,
+            cjk_chars: 0,
+            tokens: 3,
+            preview: None,
+            level,
+        };
+        let mut parent = item("ch<1>", "a.md", 1, 20, 0);
+        parent.preview = Some("Tom & \"Jerry\"".to_string());
+        let outline = ProjectOutline {
+            items: vec![
+                parent,
+                item("s1", "a.md", 5, 10, 1),
+                item("other", "b.md", 1, 3, 0),
+            ],
+            total_chars: 30,
+            total_words: 6,
+            total_cjk_chars: 0,
+            total_tokens: 9,
+            by_tag: HashMap::new(),
+            binary_skipped: vec![],
+        };
+
+        let html = render_html(&outline);
+        assert!(html.contains("<a href=\"a.md#L1\">ch&lt;1&gt;</a>"));
+        assert!(html.contains("<span class=\"preview\">Tom &amp; &quot;Jerry&quot;</span>"));
+        assert!(html.contains("<span class=\"badge\">~3 tokens</span>"));
+        assert_eq!(html.matches("<li class=\"file\">").count(), 2);
+        // The child is nested inside its parent's list item
+        let parent_at = html.find("a.md#L1").unwrap();
+        let child_at = html.find("a.md#L5").unwrap();
+        let parent_close = parent_at + html[parent_at..].find("</ul>").unwrap();
+        assert!(parent_at < child_at && child_at < parent_close);
+        assert_eq!(html.matches("<ul>").count(), html.matches("</ul>").count());
+        assert_eq!(html.matches("<li").count(), html.matches("</li>").count());
+    }
+
+    #[test]
+    fn test_limit_level() {
+        let item = |id: &str, level: usize, chars: usize| OutlineItem {
+            id: id.to_string(),
+            path: "a.md".to_string(),
+            tags: vec![format!("l{}", level)],
+            start_line: 1,
+            end_line: 1,
+            chars,
+            words: 1,
+            cjk_chars: 0,
+            tokens: chars / 4,
+            preview: None,
+            level,
+        };
+        let items = vec![item("ch1", 0, 400), item("s1", 1, 200), item("p1", 2, 100)];
+        let mut by_tag = HashMap::new();
+        for i in &items {
+            by_tag.insert(i.tags[0].clone(), vec![i.id.clone()]);
+        }
+        let outline = ProjectOutline {
+            items,
+            total_chars: 700,
+            total_words: 3,
+            total_cjk_chars: 0,
+            total_tokens: 175,
+            by_tag,
+            binary_skipped: vec![],
+        };
+
+        let mut full_totals = outline.clone();
+        full_totals.limit_level(1, false);
+        let ids: Vec<&str> = full_totals.items.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, ["ch1", "s1"]);
+        assert!(!full_totals.by_tag.contains_key("l2"));
+        assert_eq!(full_totals.total_chars, 700);
+
+        let mut visible_totals = outline;
+        visible_totals.limit_level(0, true);
+        assert_eq!(visible_totals.items.len(), 1);
+        assert_eq!(visible_totals.total_chars, 400);
+        assert_eq!(visible_totals.total_words, 1);
+        assert_eq!(visible_totals.total_tokens, 100);
+    }
+
+    #[test]
+    fn test_build_outline_tree_nests_by_containment() {
+        let item = |id: &str, path: &str, start: u32, end: u32| OutlineItem {
+            id: id.to_string(),
+            path: path.to_string(),
+            tags: vec![],
+            start_line: start,
+            end_line: end,
+            chars: 0,
+            words: 0,
+            cjk_chars: 0,
+            tokens: 0,
+            preview: None,
+            level: 0,
+        };
+        let items = vec![
+            item("ch1", "a.md", 1, 20),
+            item("ch1.s1", "a.md", 2, 8),
+            item("ch1.s1.p1", "a.md", 3, 5),
+            item("ch1.s2", "a.md", 10, 19),
+            item("ch2", "a.md", 21, 30),
+            item("other", "b.md", 1, 40),
+        ];
+
+        let tree = build_outline_tree(&items);
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree[0].item.id, "ch1");
+        assert_eq!(tree[0].children.len(), 2);
+        assert_eq!(tree[0].children[0].children[0].item.id, "ch1.s1.p1");
+        assert_eq!(tree[0].children[1].item.id, "ch1.s2");
+        assert!(tree[1].children.is_empty());
+        assert_eq!(tree[2].item.path, "b.md");
+
+        let json = serde_json::to_value(&tree).unwrap();
+        assert_eq!(json[0]["id"], "ch1");
+        assert_eq!(json[0]["children"][1]["id"], "ch1.s2");
+        assert!(json[1].get("children").is_none());
+    }
+
+    #[test]
+    fn test_generate_outline_skips_binary() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp.path().join("doc.md"),
+            "<!--Q:begin id=doc-->\ntext\n<!--Q:end id=doc-->\n",
+        )
+        .unwrap();
+        std::fs::write(temp.path().join("blob.md"), b"\x00\x00<!--Q:begin id=x-->").unwrap();
+
+        let outline = generate_outline(
+            temp.path(),
+            Some(temp.path()),
+            &GlobFilter::default(),
+            None,
+            None,
+            TokenModel::default(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(outline.binary_skipped, ["blob.md"]);
+        assert_eq!(outline.items.len(), 1);
+
+        let result_set = outline_to_result_set(&outline);
+        let notes: Vec<_> = result_set
+            .items
+            .iter()
+            .filter(|i| !i.errors.is_empty())
+            .collect();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].errors[0].code, "BINARY_SKIPPED");
+
+        let outline = generate_outline(
+            temp.path(),
+            Some(temp.path()),
+            &GlobFilter::default(),
+            None,
+            None,
+            TokenModel::default(),
+            true,
+        )
+        .unwrap();
+        assert!(outline.binary_skipped.is_empty());
+    }
+
     #[test]
     fn test_different_token_models() {
         let text = "Hello world, 你好世界!";
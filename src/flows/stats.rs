@@ -4,19 +4,21 @@
 //! Uses tiktoken for accurate token counting.
 
 use anyhow::Result;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::anchors::parse::parse_file;
-use crate::backends::scan::{scan_files, ScanOptions};
+use crate::backends::scan::{scan_files, GlobFilter, ScanOptions};
+use crate::core::file_reader::{binary_skipped_item, decode_text, is_probably_binary};
 use crate::core::model::{Confidence, Kind, ResultItem, ResultSet, SourceMode};
 use crate::core::render::{RenderConfig, Renderer};
 use crate::core::tokenizer::{count_tokens, TokenModel};
 
 /// Statistics for a single file
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct FileStats {
     /// File path relative to root
     pub path: String,
@@ -34,10 +36,62 @@ pub struct FileStats {
     pub tokens: usize,
     /// Number of anchors in this file
     pub anchors: usize,
+    /// Source encoding the file was converted from (absent for UTF-8)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+}
+
+/// Totals for all files sharing one extension
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ExtensionStats {
+    /// Files with this extension
+    pub files: usize,
+    /// Total characters
+    pub chars: usize,
+    /// Total words (English)
+    pub words: usize,
+    /// Total lines
+    pub lines: usize,
+    /// Estimated total tokens
+    pub tokens: usize,
+}
+
+impl ExtensionStats {
+    fn add(&mut self, file: &FileStats) {
+        self.files += 1;
+        self.chars += file.chars;
+        self.words += file.words;
+        self.lines += file.lines;
+        self.tokens += file.tokens;
+    }
+}
+
+/// Reading speeds used for the reading-time estimate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadingRates {
+    /// English words per minute
+    pub wpm: u32,
+    /// CJK characters per minute
+    pub cpm: u32,
+}
+
+impl Default for ReadingRates {
+    fn default() -> Self {
+        Self { wpm: 200, cpm: 400 }
+    }
+}
+
+impl ReadingRates {
+    /// Estimated minutes to read, rounded to one decimal place
+    pub fn minutes(&self, words: usize, cjk_chars: usize) -> f64 {
+        let minutes =
+            words as f64 / self.wpm.max(1) as f64 + cjk_chars as f64 / self.cpm.max(1) as f64;
+        (minutes * 10.0).round() / 10.0
+    }
 }
 
 /// Project-wide statistics
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ProjectStats {
     /// Total files scanned
     pub total_files: usize,
@@ -53,12 +107,40 @@ pub struct ProjectStats {
     pub total_lines: usize,
     /// Estimated total tokens
     pub total_tokens: usize,
+    /// Estimated reading time (words at `wpm` plus CJK characters at `cpm`)
+    #[serde(default)]
+    pub reading_time_minutes: f64,
     /// Total anchors
     pub total_anchors: usize,
     /// Anchor count by tag
     pub anchors_by_tag: HashMap<String, usize>,
+    /// Totals keyed by file extension (without the dot)
+    #[serde(default)]
+    pub by_extension: BTreeMap<String, ExtensionStats>,
     /// Per-file statistics (top files by size)
     pub file_stats: Vec<FileStats>,
+    /// Files left out because they look binary
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub binary_skipped: Vec<String>,
+}
+
+impl ProjectStats {
+    /// Reading time for display, e.g. `~12 min read`
+    pub fn reading_time_label(&self) -> String {
+        let minutes = self.reading_time_minutes.ceil() as u64;
+        if minutes == 0 {
+            "0 min read".to_string()
+        } else {
+            format!("~{} min read", minutes)
+        }
+    }
+
+    /// Extension totals, largest token count first
+    pub fn extensions_by_tokens(&self) -> Vec<(&String, &ExtensionStats)> {
+        let mut exts: Vec<_> = self.by_extension.iter().collect();
+        exts.sort_by(|a, b| b.1.tokens.cmp(&a.1.tokens).then_with(|| a.0.cmp(b.0)));
+        exts
+    }
 }
 
 /// Check if a character is CJK (Chinese/Japanese/Korean)
@@ -76,7 +158,9 @@ fn is_cjk_char(c: char) -> bool {
 
 /// Calculate statistics for a single file
 fn calculate_file_stats(path: &Path, relative_path: &str, model: TokenModel) -> Option<FileStats> {
-    let content = fs::read_to_string(path).ok()?;
+    let bytes = fs::read(path).ok()?;
+    let decoded = decode_text(&bytes);
+    let content = decoded.text.as_str();
 
     let chars = content.chars().count();
     let chars_no_space = content.chars().filter(|c| !c.is_whitespace()).count();
@@ -92,7 +176,7 @@ fn calculate_file_stats(path: &Path, relative_path: &str, model: TokenModel) ->
     let cjk_chars = content.chars().filter(|c| is_cjk_char(*c)).count();
 
     // Count tokens using tiktoken
-    let tokens = count_tokens(&content, model);
+    let tokens = count_tokens(content, model);
 
     // Count anchors
     let anchors = parse_file(path, relative_path);
@@ -107,32 +191,29 @@ fn calculate_file_stats(path: &Path, relative_path: &str, model: TokenModel) ->
         lines,
         tokens,
         anchors: anchor_count,
+        encoding: decoded.legacy_encoding(),
     })
 }
 
 /// Calculate project-wide statistics
-pub fn calculate_project_stats(
-    root: &Path,
-    scope: Option<&Path>,
-    extensions: Option<&[&str]>,
-    top_n: usize,
-    token_model: TokenModel,
-) -> Result<ProjectStats> {
+pub fn calculate_project_stats(root: &Path, options: &StatsOptions) -> Result<ProjectStats> {
     use crate::cache::reader::get_files_cached;
 
-    let files = if scope.is_some() {
+    let token_model = options.token_model;
+    let mut files = if options.scope.is_some() {
         // If scope is specified, do a direct scan
-        let options = ScanOptions {
-            scope: scope.map(|p| p.to_path_buf()),
+        let scan_options = ScanOptions {
+            scope: options.scope.clone(),
             file_type: Some("file".to_string()),
             ignore: true,
             ..Default::default()
         };
-        scan_files(root, &options)?
+        scan_files(root, &scan_options)?
     } else {
         // Use cached files when no scope
         get_files_cached(root)?
     };
+    options.filter.retain(&mut files);
 
     let mut stats = ProjectStats::default();
     let mut all_file_stats = Vec::new();
@@ -140,7 +221,11 @@ pub fn calculate_project_stats(
 
     // Default text extensions if not specified
     let default_exts = ["md", "txt", "rst", "adoc", "org", "tex", "html", "xml"];
-    let exts: &[&str] = extensions.unwrap_or(&default_exts);
+    let ext_refs: Option<Vec<&str>> = options
+        .extensions
+        .as_ref()
+        .map(|v| v.iter().map(|s| s.as_str()).collect());
+    let exts: &[&str] = ext_refs.as_deref().unwrap_or(&default_exts);
 
     for file_item in files.items {
         if let Some(path) = &file_item.path {
@@ -151,6 +236,10 @@ pub fn calculate_project_stats(
             }
 
             let full_path = root.join(path);
+            if !options.include_binary && is_probably_binary(&full_path) {
+                stats.binary_skipped.push(path.clone());
+                continue;
+            }
             if let Some(file_stats) = calculate_file_stats(&full_path, path, token_model) {
                 stats.total_files += 1;
                 stats.total_chars += file_stats.chars;
@@ -161,6 +250,12 @@ pub fn calculate_project_stats(
                 stats.total_tokens += file_stats.tokens;
                 stats.total_anchors += file_stats.anchors;
 
+                let ext = Path::new(path)
+                    .extension()
+                    .map(|e| e.to_string_lossy().to_lowercase())
+                    .unwrap_or_default();
+                stats.by_extension.entry(ext).or_default().add(&file_stats);
+
                 // Collect anchor tags
                 let anchors = parse_file(&full_path, path);
                 for anchor in anchors {
@@ -176,8 +271,11 @@ pub fn calculate_project_stats(
 
     // Sort by chars descending and take top N
     all_file_stats.sort_by(|a, b| b.chars.cmp(&a.chars));
-    stats.file_stats = all_file_stats.into_iter().take(top_n).collect();
+    stats.file_stats = all_file_stats.into_iter().take(options.top_n).collect();
     stats.anchors_by_tag = anchors_by_tag;
+    stats.reading_time_minutes = options
+        .reading_rates
+        .minutes(stats.total_words, stats.total_cjk_chars);
 
     Ok(stats)
 }
@@ -196,6 +294,7 @@ fn stats_to_result_set(stats: &ProjectStats) -> ResultSet {
          Words:        {} (English)\n\
          CJK Chars:    {}\n\
          Est. Tokens:  {}\n\
+         Reading Time: {}\n\
          Anchors:      {}\n\
          ─────────────────────────────────────",
         stats.total_files,
@@ -205,6 +304,7 @@ fn stats_to_result_set(stats: &ProjectStats) -> ResultSet {
         stats.total_words,
         stats.total_cjk_chars,
         stats.total_tokens,
+        stats.reading_time_label(),
         stats.total_anchors,
     );
 
@@ -234,6 +334,27 @@ fn stats_to_result_set(stats: &ProjectStats) -> ResultSet {
         result_set.push(tags_item);
     }
 
+    // Add per-extension breakdown
+    if !stats.by_extension.is_empty() {
+        let ext_lines: Vec<String> = stats
+            .extensions_by_tokens()
+            .into_iter()
+            .map(|(ext, e)| {
+                format!(
+                    "  .{} - {} files, {} chars, {} words, {} lines, ~{} tokens",
+                    ext, e.files, e.chars, e.words, e.lines, e.tokens
+                )
+            })
+            .collect();
+
+        let mut ext_item = ResultItem::file("by_extension");
+        ext_item.kind = Kind::Flow;
+        ext_item.excerpt = Some(format!("🗂️ By Extension\n{}", ext_lines.join("\n")));
+        ext_item.confidence = Confidence::High;
+        ext_item.source_mode = SourceMode::Scan;
+        result_set.push(ext_item);
+    }
+
     // Add top files
     if !stats.file_stats.is_empty() {
         let files_summary: Vec<String> = stats
@@ -261,6 +382,10 @@ fn stats_to_result_set(stats: &ProjectStats) -> ResultSet {
         result_set.push(files_item);
     }
 
+    for path in &stats.binary_skipped {
+        result_set.push(binary_skipped_item(path.clone()));
+    }
+
     result_set
 }
 
@@ -292,22 +417,34 @@ impl std::str::FromStr for StatsFormat {
     }
 }
 
+/// Options for the stats command
+#[derive(Debug, Clone, Default)]
+pub struct StatsOptions {
+    /// Limit stats to this subdirectory
+    pub scope: Option<PathBuf>,
+    /// Extensions to include (default: common text formats)
+    pub extensions: Option<Vec<String>>,
+    /// Number of top files to report
+    pub top_n: usize,
+    /// Token model for counting
+    pub token_model: TokenModel,
+    /// Reading speeds for the reading-time estimate
+    pub reading_rates: ReadingRates,
+    /// Count files that look binary instead of skipping them
+    pub include_binary: bool,
+    /// Include/exclude globs over root-relative paths
+    pub filter: GlobFilter,
+}
+
 /// Run the stats command
 pub fn run_stats(
     root: &Path,
-    scope: Option<&Path>,
-    extensions: Option<Vec<String>>,
+    options: StatsOptions,
     stats_format: StatsFormat,
-    top_n: usize,
-    token_model: TokenModel,
     config: RenderConfig,
 ) -> Result<()> {
-    let ext_refs: Option<Vec<&str>> = extensions
-        .as_ref()
-        .map(|v| v.iter().map(|s| s.as_str()).collect());
-    let ext_slice: Option<&[&str]> = ext_refs.as_deref();
-
-    let stats = calculate_project_stats(root, scope, ext_slice, top_n, token_model)?;
+    let token_model = options.token_model;
+    let stats = calculate_project_stats(root, &options)?;
 
     match stats_format {
         StatsFormat::Json => {
@@ -324,7 +461,11 @@ pub fn run_stats(
             println!("  Words (EN):   {}", stats.total_words);
             println!("  CJK Chars:    {}", stats.total_cjk_chars);
             println!("  Tokens:       {}", stats.total_tokens);
+            println!("  Reading:      {}", stats.reading_time_label());
             println!("  Anchors:      {}", stats.total_anchors);
+            if !stats.binary_skipped.is_empty() {
+                println!("  Binary:       {} skipped", stats.binary_skipped.len());
+            }
             println!("═══════════════════════════════════════");
 
             if !stats.anchors_by_tag.is_empty() {
@@ -336,6 +477,21 @@ pub fn run_stats(
                 }
             }
 
+            if !stats.by_extension.is_empty() {
+                println!("\n🗂️ By Extension:");
+                for (ext, e) in stats.extensions_by_tokens() {
+                    println!(
+                        "  {:10} {:>5} files  {:>8} chars  {:>6} words  {:>6} lines  ~{:>6} tokens",
+                        format!(".{}", ext),
+                        e.files,
+                        e.chars,
+                        e.words,
+                        e.lines,
+                        e.tokens
+                    );
+                }
+            }
+
             if !stats.file_stats.is_empty() {
                 println!("\n📄 Top {} Files:", stats.file_stats.len());
                 for f in &stats.file_stats {
@@ -357,7 +513,23 @@ pub fn run_stats(
             println!("| Words (English) | {} |", stats.total_words);
             println!("| CJK Characters | {} |", stats.total_cjk_chars);
             println!("| Estimated Tokens | {} |", stats.total_tokens);
+            println!("| Reading Time | {} |", stats.reading_time_label());
             println!("| Anchors | {} |", stats.total_anchors);
+            if !stats.binary_skipped.is_empty() {
+                println!("| Binary Files Skipped | {} |", stats.binary_skipped.len());
+            }
+
+            if !stats.by_extension.is_empty() {
+                println!("\n## By Extension\n");
+                println!("| Extension | Files | Chars | Words | Lines | Tokens |");
+                println!("|-----------|-------|-------|-------|-------|--------|");
+                for (ext, e) in stats.extensions_by_tokens() {
+                    println!(
+                        "| .{} | {} | {} | {} | {} | {} |",
+                        ext, e.files, e.chars, e.words, e.lines, e.tokens
+                    );
+                }
+            }
 
             if !stats.file_stats.is_empty() {
                 println!("\n## Top Files\n");
@@ -374,7 +546,7 @@ pub fn run_stats(
         StatsFormat::Standard => {
             let result_set = stats_to_result_set(&stats);
             let renderer = Renderer::with_config(config);
-            println!("{}", renderer.render(&result_set));
+            print!("{}", renderer.render(&result_set));
         }
     }
 
@@ -462,6 +634,7 @@ mod tests {
             cjk_chars: 5,
             tokens: 30,
             anchors: 2,
+            encoding: None,
         };
         assert_eq!(file_stats.path, "test.md");
         assert_eq!(file_stats.lines, 10);
@@ -535,12 +708,128 @@ mod tests {
         std::fs::write(temp.path().join("file1.md"), "Hello world").unwrap();
         std::fs::write(temp.path().join("file2.txt"), "Test content").unwrap();
 
-        let stats =
-            calculate_project_stats(temp.path(), None, None, 10, TokenModel::default()).unwrap();
+        let stats = calculate_project_stats(
+            temp.path(),
+            &StatsOptions {
+                top_n: 10,
+                ..Default::default()
+            },
+        )
+        .unwrap();
         assert!(stats.total_files >= 2);
         assert!(stats.total_chars > 0);
     }
 
+    #[test]
+    fn test_calculate_project_stats_by_extension() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(temp.path().join("src")).unwrap();
+        std::fs::write(temp.path().join("a.md"), "Some prose here\n").unwrap();
+        std::fs::write(temp.path().join("b.md"), "More prose\nand more\n").unwrap();
+        std::fs::write(temp.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+
+        let stats = calculate_project_stats(
+            temp.path(),
+            &StatsOptions {
+                scope: Some(temp.path().to_path_buf()),
+                extensions: Some(vec!["md".to_string(), "rs".to_string()]),
+                top_n: 10,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let md = &stats.by_extension["md"];
+        assert_eq!(md.files, 2);
+        assert_eq!(md.lines, 3);
+        assert_eq!(stats.by_extension["rs"].files, 1);
+        assert_eq!(
+            stats.by_extension.values().map(|e| e.tokens).sum::<usize>(),
+            stats.total_tokens
+        );
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["by_extension"]["rs"]["lines"], 1);
+    }
+
+    #[test]
+    fn test_calculate_project_stats_glob_filter() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(temp.path().join("docs/drafts")).unwrap();
+        std::fs::write(temp.path().join("docs/intro.md"), "Intro\n").unwrap();
+        std::fs::write(temp.path().join("docs/drafts/wip.md"), "Draft\n").unwrap();
+        std::fs::write(temp.path().join("notes.md"), "Notes\n").unwrap();
+
+        let stats = calculate_project_stats(
+            temp.path(),
+            &StatsOptions {
+                scope: Some(temp.path().to_path_buf()),
+                top_n: 10,
+                filter: GlobFilter::new(
+                    vec!["docs/*".to_string()],
+                    vec!["**/drafts/**".to_string()],
+                )
+                .unwrap(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let paths: Vec<_> = stats.file_stats.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["docs/intro.md"]);
+    }
+
+    #[test]
+    fn test_calculate_project_stats_skips_binary() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("a.md"), "Some prose here\n").unwrap();
+        std::fs::write(temp.path().join("blob.md"), b"\x89PNG\r\n\x1a\n\x00\x00").unwrap();
+
+        let stats = calculate_project_stats(
+            temp.path(),
+            &StatsOptions {
+                scope: Some(temp.path().to_path_buf()),
+                top_n: 10,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(stats.total_files, 1);
+        assert_eq!(stats.binary_skipped, ["blob.md"]);
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["binary_skipped"][0], "blob.md");
+
+        let stats = calculate_project_stats(
+            temp.path(),
+            &StatsOptions {
+                scope: Some(temp.path().to_path_buf()),
+                top_n: 10,
+                include_binary: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(stats.total_files, 2);
+        assert!(stats.binary_skipped.is_empty());
+    }
+
+    #[test]
+    fn test_reading_rates_minutes() {
+        let rates = ReadingRates::default();
+        assert_eq!(rates.minutes(2000, 0), 10.0);
+        assert_eq!(rates.minutes(1000, 800), 7.0);
+        assert_eq!(rates.minutes(0, 0), 0.0);
+
+        let slow = ReadingRates { wpm: 100, cpm: 300 };
+        assert_eq!(slow.minutes(150, 100), 1.8);
+
+        let stats = ProjectStats {
+            reading_time_minutes: 11.2,
+            ..Default::default()
+        };
+        assert_eq!(stats.reading_time_label(), "~12 min read");
+        assert_eq!(ProjectStats::default().reading_time_label(), "0 min read");
+    }
+
     #[test]
     fn test_stats_format_default() {
         let format: StatsFormat = Default::default();
@@ -15,17 +15,44 @@ use crate::cache::reader::{find_anchor_by_id, get_all_anchors_parsed};
 use crate::core::model::{Confidence, ResultSet};
 use crate::core::render::{RenderConfig, Renderer};
 
+/// Options for the writing flow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WritingOptions {
+    /// Maximum number of items to return
+    pub max_items: usize,
+    /// Number of keywords extracted from the primary anchor for step 3
+    pub keywords: usize,
+    /// Minimum length of English keywords
+    pub min_word_len: usize,
+    /// Run the ripgrep keyword search (step 3)
+    pub search: bool,
+    /// Record why each item was included (`--explain`)
+    pub explain: bool,
+}
+
+impl Default for WritingOptions {
+    fn default() -> Self {
+        Self {
+            max_items: 10,
+            keywords: 5,
+            min_word_len: 4,
+            search: true,
+            explain: false,
+        }
+    }
+}
+
 /// Run the writing flow
 pub fn run_writing(
     root: &Path,
     anchor_id: &str,
-    max_items: usize,
+    options: &WritingOptions,
     config: RenderConfig,
 ) -> Result<()> {
-    let result_set = gather_writing_evidence(root, anchor_id, max_items)?;
+    let result_set = gather_writing_evidence(root, anchor_id, options)?;
 
     let renderer = Renderer::with_config(config);
-    println!("{}", renderer.render(&result_set));
+    print!("{}", renderer.render(&result_set));
 
     Ok(())
 }
@@ -34,8 +61,10 @@ pub fn run_writing(
 pub fn gather_writing_evidence(
     root: &Path,
     anchor_id: &str,
-    max_items: usize,
+    options: &WritingOptions,
 ) -> Result<ResultSet> {
+    let max_items = options.max_items;
+    let explain = options.explain;
     let mut result_set = ResultSet::new();
     let mut seen_paths: HashSet<String> = HashSet::new();
 
@@ -45,11 +74,12 @@ pub fn gather_writing_evidence(
     let mut primary_tags: Vec<String> = Vec::new();
     let mut primary_content: Option<String> = None;
 
-    for item in primary.items {
+    for mut item in primary.items {
         if let Some(path) = &item.path {
             seen_paths.insert(path.clone());
         }
         primary_content = item.excerpt.clone();
+        item.explain(explain, "primary anchor");
         result_set.push(item);
     }
 
@@ -82,6 +112,17 @@ pub fn gather_writing_evidence(
             if !shared_tags.is_empty() {
                 let mut item = anchor.to_result_item();
                 item.confidence = Confidence::Medium;
+                item.explain(
+                    explain,
+                    format!(
+                        "shares tag: {}",
+                        shared_tags
+                            .iter()
+                            .map(|t| t.as_str())
+                            .collect::<Vec<_>>()
+                            .join(",")
+                    ),
+                );
                 seen_paths.insert(path);
                 result_set.push(item);
                 related_count += 1;
@@ -95,14 +136,18 @@ pub fn gather_writing_evidence(
 
     // Step 3: Search for additional content using ripgrep (low confidence)
     // Extract keywords from primary content for searching
-    if let Some(content) = primary_content {
+    if let Some(content) = primary_content.filter(|_| options.search) {
         // Smart keyword extraction: supports both English and Chinese
-        let keywords = extract_keywords(&content, 5);
+        let keywords = extract_keywords(&content, options.keywords, options.min_word_len);
 
         if !keywords.is_empty() {
-            let pattern = keywords.join("|");
+            let (pattern, fixed_strings) = keyword_pattern(&keywords);
             // No include/exclude filters for writing flow
-            let search_results = run_rg(root, &pattern, &[] as &[&Path], &MatchOptions::default())?;
+            let options = MatchOptions {
+                fixed_strings,
+                ..Default::default()
+            };
+            let search_results = run_rg(root, &pattern, &[] as &[&Path], &options)?;
 
             let mut search_count = 0;
             for mut item in search_results.items {
@@ -114,6 +159,15 @@ pub fn gather_writing_evidence(
                 }
 
                 item.confidence = Confidence::Low;
+                let excerpt = item.excerpt.as_deref().unwrap_or("").to_lowercase();
+                let matched = keywords.iter().find(|k| excerpt.contains(k.as_str()));
+                item.explain(
+                    explain,
+                    format!(
+                        "keyword match: {}",
+                        matched.cloned().unwrap_or_else(|| keywords.join(","))
+                    ),
+                );
                 result_set.push(item);
                 search_count += 1;
 
@@ -167,15 +221,34 @@ fn is_cjk_stop_char(c: char) -> bool {
     CJK_STOPS.contains(&c)
 }
 
+/// Build the ripgrep pattern for a keyword search
+///
+/// Plain words are joined into a regex alternation. If any keyword carries
+/// punctuation (which regex would misread), all keywords are searched as
+/// newline-separated literals instead; the bool selects fixed-string mode.
+fn keyword_pattern(keywords: &[String]) -> (String, bool) {
+    let has_punctuation = keywords
+        .iter()
+        .any(|k| k.chars().any(|c| !c.is_alphanumeric()));
+    if has_punctuation {
+        (keywords.join("\n"), true)
+    } else {
+        (keywords.join("|"), false)
+    }
+}
+
 /// Extract keywords from text, supporting both English and CJK content
-pub fn extract_keywords(text: &str, max_keywords: usize) -> Vec<String> {
+///
+/// English words shorter than `min_word_len` are skipped; CJK text yields
+/// 2-4 character n-grams regardless.
+pub fn extract_keywords(text: &str, max_keywords: usize, min_word_len: usize) -> Vec<String> {
     let mut keywords = Vec::new();
     let mut seen = std::collections::HashSet::new();
 
-    // Extract English words (at least 4 chars, not common)
+    // Extract English words (at least `min_word_len` chars, not common)
     let english_words: Vec<&str> = text
         .split(|c: char| !c.is_ascii_alphanumeric())
-        .filter(|w| w.len() >= 4)
+        .filter(|w| !w.is_empty() && w.len() >= min_word_len)
         .filter(|w| !is_common_word(w))
         .collect();
 
@@ -230,7 +303,7 @@ mod tests {
     #[test]
     fn test_extract_keywords_english() {
         let text = "This is a function that implements the core logic";
-        let keywords = extract_keywords(text, 3);
+        let keywords = extract_keywords(text, 3, 4);
         assert!(keywords.contains(&"function".to_string()));
         assert!(keywords.contains(&"implements".to_string()));
         assert!(keywords.contains(&"logic".to_string()) || keywords.contains(&"core".to_string()));
@@ -239,16 +312,31 @@ mod tests {
     #[test]
     fn test_extract_keywords_chinese() {
         let text = "这是一个关于上下文准备工具的说明文档";
-        let keywords = extract_keywords(text, 5);
+        let keywords = extract_keywords(text, 5, 4);
         // Should extract n-grams like "上下文", "准备工具", "说明文档"
         assert!(!keywords.is_empty());
         assert!(keywords.iter().any(|k| k.chars().all(|c| is_cjk_char(c))));
     }
 
+    #[test]
+    fn test_keyword_pattern() {
+        let words = vec!["config".to_string(), "解析器".to_string()];
+        assert_eq!(
+            keyword_pattern(&words),
+            ("config|解析器".to_string(), false)
+        );
+
+        let fragments = vec!["foo(bar)".to_string(), "a.b.c".to_string()];
+        assert_eq!(
+            keyword_pattern(&fragments),
+            ("foo(bar)\na.b.c".to_string(), true)
+        );
+    }
+
     #[test]
     fn test_extract_keywords_mixed() {
         let text = "mise 是一个上下文准备工具 for AI agents";
-        let keywords = extract_keywords(text, 5);
+        let keywords = extract_keywords(text, 5, 4);
         // Should have both English and Chinese keywords
         assert!(!keywords.is_empty());
     }
@@ -277,7 +365,7 @@ mod tests {
 
     #[test]
     fn test_extract_keywords_empty() {
-        let keywords = extract_keywords("", 5);
+        let keywords = extract_keywords("", 5, 4);
         assert!(keywords.is_empty());
     }
 
@@ -285,21 +373,21 @@ mod tests {
     fn test_extract_keywords_short_words() {
         // Words shorter than 4 chars should be ignored
         let text = "a an the is are";
-        let keywords = extract_keywords(text, 5);
+        let keywords = extract_keywords(text, 5, 4);
         assert!(keywords.is_empty());
     }
 
     #[test]
     fn test_extract_keywords_max_limit() {
         let text = "function method class interface struct enum module package namespace";
-        let keywords = extract_keywords(text, 3);
+        let keywords = extract_keywords(text, 3, 4);
         assert_eq!(keywords.len(), 3);
     }
 
     #[test]
     fn test_extract_keywords_deduplication() {
         let text = "function function function method method";
-        let keywords = extract_keywords(text, 10);
+        let keywords = extract_keywords(text, 10, 4);
         // Should only have unique keywords
         let unique_count = keywords
             .iter()
@@ -482,14 +570,14 @@ mod tests {
     #[test]
     fn test_extract_keywords_only_common_words() {
         let text = "the and that this with from have been were will";
-        let keywords = extract_keywords(text, 10);
+        let keywords = extract_keywords(text, 10, 4);
         assert!(keywords.is_empty());
     }
 
     #[test]
     fn test_extract_keywords_special_chars() {
         let text = "function!@#$%method^&*()class";
-        let keywords = extract_keywords(text, 3);
+        let keywords = extract_keywords(text, 3, 4);
         assert!(keywords.contains(&"function".to_string()));
         assert!(keywords.contains(&"method".to_string()));
         assert!(keywords.contains(&"class".to_string()));
@@ -498,7 +586,7 @@ mod tests {
     #[test]
     fn test_extract_keywords_numbers() {
         let text = "func123 test456 abcd1234";
-        let keywords = extract_keywords(text, 5);
+        let keywords = extract_keywords(text, 5, 4);
         // Should include alphanumeric words
         assert!(!keywords.is_empty());
     }
@@ -506,7 +594,7 @@ mod tests {
     #[test]
     fn test_extract_keywords_cjk_ngrams() {
         let text = "上下文准备工具测试";
-        let keywords = extract_keywords(text, 5);
+        let keywords = extract_keywords(text, 5, 4);
         // Should extract 2-grams, 3-grams, 4-grams
         for kw in &keywords {
             let len = kw.chars().count();
@@ -517,7 +605,7 @@ mod tests {
     #[test]
     fn test_extract_keywords_case_insensitive_dedup() {
         let text = "Function function FUNCTION method Method METHOD";
-        let keywords = extract_keywords(text, 10);
+        let keywords = extract_keywords(text, 10, 4);
         // Should deduplicate case-insensitively
         let lower_keywords: Vec<String> = keywords.iter().map(|k| k.to_lowercase()).collect();
         let unique: std::collections::HashSet<_> = lower_keywords.iter().collect();
@@ -527,7 +615,7 @@ mod tests {
     #[test]
     fn test_extract_keywords_preserves_original_case() {
         let text = "Function METHOD";
-        let keywords = extract_keywords(text, 2);
+        let keywords = extract_keywords(text, 2, 4);
         // Should preserve original case
         assert!(
             keywords.contains(&"Function".to_string()) || keywords.contains(&"METHOD".to_string())
@@ -537,10 +625,40 @@ mod tests {
     #[test]
     fn test_extract_keywords_long_chinese_text() {
         let text = "这是一个非常长的中文文本用于测试关键词提取功能的正确性和效率";
-        let keywords = extract_keywords(text, 10);
+        let keywords = extract_keywords(text, 10, 4);
         assert!(keywords.len() <= 10);
     }
 
+    #[test]
+    fn test_extract_keywords_min_word_len() {
+        let text = "Use the API key for the app";
+        assert!(extract_keywords(text, 5, 4).is_empty());
+        assert_eq!(
+            extract_keywords(text, 5, 3),
+            vec!["Use", "API", "key", "for", "app"]
+        );
+    }
+
+    #[test]
+    fn test_gather_writing_evidence_no_search() {
+        use tempfile::tempdir;
+        let temp = tempdir().unwrap();
+        std::fs::write(
+            temp.path().join("a.md"),
+            "<!--Q:begin id=primary v=1-->\nThe scheduler rebalances workers.\n<!--Q:end id=primary-->\n",
+        )
+        .unwrap();
+        std::fs::write(temp.path().join("b.md"), "Notes on the scheduler.\n").unwrap();
+
+        let options = WritingOptions {
+            search: false,
+            ..Default::default()
+        };
+        let result = gather_writing_evidence(temp.path(), "primary", &options).unwrap();
+        assert_eq!(result.items.len(), 1);
+        assert_eq!(result.items[0].path.as_deref(), Some("a.md"));
+    }
+
     #[test]
     fn test_gather_writing_evidence_with_anchor() {
         use tempfile::tempdir;
@@ -557,7 +675,14 @@ Some other content.
 
         // Note: This test depends on external tools (rg), so we test the flow
         // and accept various outcomes
-        let result = gather_writing_evidence(temp.path(), "test-anchor", 10);
+        let result = gather_writing_evidence(
+            temp.path(),
+            "test-anchor",
+            &WritingOptions {
+                max_items: 10,
+                ..Default::default()
+            },
+        );
         // The result may fail if anchor isn't found, or succeed with items
         match result {
             Ok(result_set) => {
@@ -579,7 +704,14 @@ Some other content.
         let content = "# Test Document\nSome content without anchors.\n";
         std::fs::write(temp.path().join("test.md"), content).unwrap();
 
-        let result = gather_writing_evidence(temp.path(), "nonexistent-anchor", 10);
+        let result = gather_writing_evidence(
+            temp.path(),
+            "nonexistent-anchor",
+            &WritingOptions {
+                max_items: 10,
+                ..Default::default()
+            },
+        );
         // The function may return an error or an empty result set
         // depending on implementation details
         match result {
@@ -599,12 +731,15 @@ Some other content.
         let temp = tempdir().unwrap();
         std::fs::write(temp.path().join("test.md"), "no anchors here").unwrap();
 
-        let config = crate::core::render::RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config =
+            crate::core::render::RenderConfig::new(crate::core::render::OutputFormat::Json);
 
-        let result = run_writing(temp.path(), "nonexistent", 10, config);
+        let result = run_writing(
+            temp.path(),
+            "nonexistent",
+            &WritingOptions::default(),
+            config,
+        );
         // The function may succeed with empty results or fail
         // depending on how get_anchor handles missing anchors
         let _ = result;
@@ -621,13 +756,16 @@ Test content with function and implementation keywords.
 "#;
         std::fs::write(temp.path().join("test.md"), content).unwrap();
 
-        let config = crate::core::render::RenderConfig {
-            format: crate::core::render::OutputFormat::Json,
-            pretty: false,
-        };
+        let config =
+            crate::core::render::RenderConfig::new(crate::core::render::OutputFormat::Json);
 
         // This may succeed or fail depending on environment
-        let result = run_writing(temp.path(), "writing-test", 10, config);
+        let result = run_writing(
+            temp.path(),
+            "writing-test",
+            &WritingOptions::default(),
+            config,
+        );
         // We just verify it runs without panic
         let _ = result;
     }
@@ -650,7 +788,14 @@ Related content sharing the shared tag.
         std::fs::write(temp.path().join("file2.md"), file2).unwrap();
 
         // This tests tag-based relation finding
-        let result = gather_writing_evidence(temp.path(), "primary", 10);
+        let result = gather_writing_evidence(
+            temp.path(),
+            "primary",
+            &WritingOptions {
+                max_items: 10,
+                ..Default::default()
+            },
+        );
         match result {
             Ok(result_set) => {
                 // Should find items related by tags
@@ -674,7 +819,14 @@ Content for testing max_items limit with multiple keywords and matching.
         std::fs::write(temp.path().join("test.md"), content).unwrap();
 
         // Request only 2 items max
-        let result = gather_writing_evidence(temp.path(), "limit-test", 2);
+        let result = gather_writing_evidence(
+            temp.path(),
+            "limit-test",
+            &WritingOptions {
+                max_items: 2,
+                ..Default::default()
+            },
+        );
         match result {
             Ok(result_set) => {
                 // Should respect max_items to some degree
@@ -691,7 +843,14 @@ Content for testing max_items limit with multiple keywords and matching.
         let temp = tempdir().unwrap();
 
         // Empty directory with no files
-        let result = gather_writing_evidence(temp.path(), "any-anchor", 10);
+        let result = gather_writing_evidence(
+            temp.path(),
+            "any-anchor",
+            &WritingOptions {
+                max_items: 10,
+                ..Default::default()
+            },
+        );
         // The function may succeed with empty results or fail
         // depending on implementation
         let _ = result;
@@ -709,7 +868,14 @@ Content without any tags for testing.
 "#;
         std::fs::write(temp.path().join("test.md"), content).unwrap();
 
-        let result = gather_writing_evidence(temp.path(), "no-tags", 10);
+        let result = gather_writing_evidence(
+            temp.path(),
+            "no-tags",
+            &WritingOptions {
+                max_items: 10,
+                ..Default::default()
+            },
+        );
         match result {
             Ok(result_set) => {
                 // Should still work, just won't find related by tags
@@ -722,7 +888,7 @@ Content without any tags for testing.
     #[test]
     fn test_extract_keywords_with_code_content() {
         let text = "fn calculate_total(items: Vec<Item>) -> Result<u64, Error>";
-        let keywords = extract_keywords(text, 5);
+        let keywords = extract_keywords(text, 5, 4);
         assert!(
             keywords.contains(&"calculate_total".to_string())
                 || keywords.contains(&"items".to_string())
@@ -733,7 +899,7 @@ Content without any tags for testing.
     #[test]
     fn test_extract_keywords_markdown_content() {
         let text = "## Implementation Details\n\nThis module implements the core functionality.";
-        let keywords = extract_keywords(text, 5);
+        let keywords = extract_keywords(text, 5, 4);
         assert!(
             keywords.contains(&"Implementation".to_string())
                 || keywords.contains(&"Details".to_string())